@@ -4,8 +4,9 @@ extern crate criterion;
 use criterion::Criterion;
 use footile::*;
 use pix::matte::Matte8;
+use pix::rgb::Rgba8p;
 use pix::Raster;
-use pointy::Transform;
+use pointy::{Pt, Transform};
 
 fn fill_16(c: &mut Criterion) {
     c.bench_function("fill_16", |b| b.iter(|| fill(16)));
@@ -15,8 +16,68 @@ fn fill_256(c: &mut Criterion) {
     c.bench_function("fill_256", |b| b.iter(|| fill(256)));
 }
 
+fn fill_256_soft(c: &mut Criterion) {
+    c.bench_function("fill_256_soft", |b| {
+        b.iter(|| {
+            make_plotter(256).fill(
+                FillRule::NonZero,
+                make_fishy(),
+                Matte8::new(128),
+            );
+        })
+    });
+}
+
+fn fill_256_rgba(c: &mut Criterion) {
+    let clr = Rgba8p::new(64, 32, 0, 128);
+    c.bench_function("fill_256_rgba", |b| {
+        b.iter(|| {
+            let mut p = Plotter::new(Raster::with_clear(256, 256));
+            p.set_transform(Transform::with_scale(2.0, 2.0));
+            p.fill(FillRule::NonZero, make_fishy(), clr);
+        })
+    });
+}
+
+fn fill_256_gradient(c: &mut Criterion) {
+    let stops = [
+        (0.0, Rgba8p::new(255, 128, 0, 255)),
+        (1.0, Rgba8p::new(0, 64, 255, 255)),
+    ];
+    let stops = ink::GradientStops::new(&stops).unwrap();
+    let mut ramp = ink::LinearGradient::new((0.0, 0.0), (256.0, 64.0), stops);
+    let mut p = Plotter::new(Raster::with_clear(256, 256));
+    p.set_transform(Transform::with_scale(2.0, 2.0));
+    c.bench_function("fill_256_gradient", |b| {
+        b.iter(|| {
+            p.fill_with(FillRule::NonZero, make_fishy(), &mut ramp);
+        })
+    });
+}
+
+fn fill_256_ink(c: &mut Criterion) {
+    let mut ink = ink::ColorInk::new(Rgba8p::new(64, 32, 0, 255));
+    let mut p = Plotter::new(Raster::with_clear(256, 256));
+    p.set_transform(Transform::with_scale(2.0, 2.0));
+    c.bench_function("fill_256_ink", |b| {
+        b.iter(|| {
+            p.fill_with(FillRule::NonZero, make_fishy(), &mut ink);
+        })
+    });
+}
+
+fn fill_2048(c: &mut Criterion) {
+    let mut p = Plotter::new(Raster::with_clear(2048, 2048));
+    p.set_transform(Transform::with_scale(16.0, 16.0));
+    c.bench_function("fill_2048", |b| {
+        b.iter(|| {
+            p.fill(FillRule::NonZero, make_fishy(), Matte8::new(255));
+        })
+    });
+}
+
 fn fill(i: u32) {
-    make_plotter(i).fill(FillRule::NonZero, &make_fishy(), Matte8::new(255));
+    make_plotter(i).fill(FillRule::NonZero, make_fishy(), Matte8::new(255));
 }
 
 fn stroke_16(c: &mut Criterion) {
@@ -28,7 +89,223 @@ fn stroke_256(c: &mut Criterion) {
 }
 
 fn gray_stroke(i: u32) {
-    make_plotter(i).stroke(&make_fishy(), Matte8::new(255));
+    make_plotter(i).stroke(make_fishy(), Matte8::new(255));
+}
+
+fn fill_stroke_16(c: &mut Criterion) {
+    c.bench_function("fill_stroke_16", |b| b.iter(|| fill_stroke(16)));
+}
+
+fn fill_stroke_256(c: &mut Criterion) {
+    c.bench_function("fill_stroke_256", |b| b.iter(|| fill_stroke(256)));
+}
+
+fn fill_stroke(i: u32) {
+    make_plotter(i).fill_stroke(
+        FillRule::NonZero,
+        make_fishy(),
+        Matte8::new(128),
+        Matte8::new(255),
+    );
+}
+
+fn frame_clear_256(c: &mut Criterion) {
+    let mut p = make_plotter(256);
+    c.bench_function("frame_clear_256", |b| {
+        b.iter(|| {
+            p.clear()
+                .fill(FillRule::NonZero, make_fishy(), Matte8::new(255));
+        })
+    });
+}
+
+fn frame_new_256(c: &mut Criterion) {
+    c.bench_function("frame_new_256", |b| b.iter(|| fill(256)));
+}
+
+fn tiles_16x16(c: &mut Criterion) {
+    let mut p = make_plotter(16);
+    let fishy = make_fishy();
+    c.bench_function("tiles_16x16", |b| {
+        b.iter(|| {
+            for y in 0..16 {
+                for x in 0..16 {
+                    p.clear().set_viewport(x as f32 * 16.0, y as f32 * 16.0);
+                    p.fill(FillRule::NonZero, &fishy, Matte8::new(255));
+                }
+            }
+        })
+    });
+}
+
+fn glyphs_loop_500(c: &mut Criterion) {
+    let glyph = make_glyph();
+    c.bench_function("glyphs_loop_500", |b| {
+        b.iter(|| {
+            let mut p = make_plotter(256);
+            for t in glyph_transforms() {
+                p.set_transform(t);
+                p.fill(FillRule::NonZero, &glyph, Matte8::new(255));
+            }
+        })
+    });
+}
+
+fn glyphs_batch_500(c: &mut Criterion) {
+    let glyph = make_glyph();
+    c.bench_function("glyphs_batch_500", |b| {
+        b.iter(|| {
+            let paths = glyph_transforms().map(|t| (&glyph[..], t));
+            let mut p = make_plotter(256);
+            p.set_transform(Transform::default());
+            p.fill_batch(FillRule::NonZero, paths, Matte8::new(255));
+        })
+    });
+}
+
+fn glyph_wide_4096(c: &mut Criterion) {
+    let glyph = make_glyph();
+    let mut p = Plotter::new(Raster::<Matte8>::with_clear(4096, 16));
+    c.bench_function("glyph_wide_4096", |b| {
+        b.iter(|| {
+            p.fill(FillRule::NonZero, &glyph, Matte8::new(255));
+        })
+    });
+}
+
+fn small_fills_1000(c: &mut Criterion) {
+    let glyph = make_glyph();
+    let mut p = make_plotter(32);
+    c.bench_function("small_fills_1000", |b| {
+        b.iter(|| {
+            for _ in 0..1000 {
+                p.fill(FillRule::NonZero, &glyph, Matte8::new(255));
+            }
+        })
+    });
+}
+
+fn fishy_fills_1000(c: &mut Criterion) {
+    let fishy = make_fishy();
+    let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+    p.set_transform(Transform::with_scale(0.5, 0.5));
+    c.bench_function("fishy_fills_1000", |b| {
+        b.iter(|| {
+            for i in 0..1000 {
+                p.fill(FillRule::NonZero, &fishy, Matte8::new(i as u8));
+            }
+        })
+    });
+}
+
+fn fishy_prepared_1000(c: &mut Criterion) {
+    let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+    p.set_transform(Transform::with_scale(0.5, 0.5));
+    let fishy = p.prepare(make_fishy());
+    c.bench_function("fishy_prepared_1000", |b| {
+        b.iter(|| {
+            for i in 0..1000 {
+                p.fill_prepared(
+                    FillRule::NonZero,
+                    &fishy,
+                    Matte8::new(i as u8),
+                );
+            }
+        })
+    });
+}
+
+fn fill_100k_points(c: &mut Criterion) {
+    let n = 100_000;
+    let circle: Vec<_> = (0..n)
+        .map(|i| {
+            let a = i as f32 / n as f32 * std::f32::consts::TAU;
+            PathOp::Line(Pt::new(
+                128.0 + 120.0 * a.cos(),
+                128.0 + 120.0 * a.sin(),
+            ))
+        })
+        .collect();
+    let mut p = Plotter::new(Raster::<Matte8>::with_clear(256, 256));
+    c.bench_function("fill_100k_points", |b| {
+        b.iter(|| {
+            p.fill(FillRule::NonZero, &circle, Matte8::new(255));
+        })
+    });
+}
+
+fn fill_2000_strips(c: &mut Criterion) {
+    // Wavy strips, with vertices every few rows, like flattened hatching
+    let wave = |y: f32| (y / 16.0).sin() * 4.0;
+    let strips: Vec<_> = (0..2000)
+        .flat_map(|i| {
+            let x = i as f32;
+            let down = (0..=64).map(move |j| {
+                let y = j as f32 * 4.0;
+                PathOp::Line(Pt::new(x + wave(y), y))
+            });
+            let up = (0..=64).rev().map(move |j| {
+                let y = j as f32 * 4.0;
+                PathOp::Line(Pt::new(x + 0.5 + wave(y), y))
+            });
+            down.chain(up).chain([PathOp::Close()])
+        })
+        .collect();
+    let mut p = Plotter::new(Raster::<Matte8>::with_clear(2048, 256));
+    c.bench_function("fill_2000_strips", |b| {
+        b.iter(|| {
+            p.fill(FillRule::NonZero, &strips, Matte8::new(255));
+        })
+    });
+}
+
+fn fill_sparse_100(c: &mut Criterion) {
+    let squares: Vec<_> = (0..100)
+        .flat_map(|i| {
+            let (x, y) = ((i % 8) as f32 * 8.0, i as f32 * 40.0);
+            [
+                PathOp::Move(Pt::new(x, y)),
+                PathOp::Line(Pt::new(x + 4.0, y)),
+                PathOp::Line(Pt::new(x + 4.0, y + 4.0)),
+                PathOp::Line(Pt::new(x, y + 4.0)),
+                PathOp::Close(),
+            ]
+        })
+        .collect();
+    let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 4096));
+    c.bench_function("fill_sparse_100", |b| {
+        b.iter(|| {
+            p.fill(FillRule::NonZero, &squares, Matte8::new(255));
+        })
+    });
+}
+
+fn fill_traced_50k(c: &mut Criterion) {
+    let traced = make_traced(50_000);
+    let mut p = Plotter::new(Raster::<Matte8>::with_clear(256, 256));
+    c.bench_function("fill_traced_50k", |b| {
+        b.iter(|| {
+            p.fill(FillRule::NonZero, &traced, Matte8::new(255));
+        })
+    });
+}
+
+fn glyph_transforms() -> impl Iterator<Item = Transform<f32>> {
+    (0..500).map(|i| {
+        let x = (i % 25) as f32 * 10.0;
+        let y = (i / 25) as f32 * 12.0;
+        Transform::with_translate(x, y)
+    })
+}
+
+fn make_glyph() -> Vec<PathOp> {
+    Path2D::default()
+        .absolute()
+        .move_to(1.0, 0.0)
+        .cubic_to(8.0, 0.0, 8.0, 10.0, 1.0, 10.0)
+        .line_to(4.0, 5.0)
+        .close()
+        .finish()
 }
 
 fn make_plotter(i: u32) -> Plotter<Matte8> {
@@ -38,6 +315,17 @@ fn make_plotter(i: u32) -> Plotter<Matte8> {
     p
 }
 
+/// Make a traced outline, wobbling like a contour traced from an image
+fn make_traced(n: usize) -> Vec<PathOp> {
+    (0..n)
+        .map(|i| {
+            let a = i as f32 / n as f32 * std::f32::consts::TAU;
+            let r = 100.0 + 20.0 * (a * 5.0).sin() + 2.0 * (a * 997.0).sin();
+            PathOp::Line(Pt::new(128.0 + r * a.cos(), 128.0 + r * a.sin()))
+        })
+        .collect()
+}
+
 fn make_fishy() -> Vec<PathOp> {
     Path2D::default()
         .relative()
@@ -50,5 +338,31 @@ fn make_fishy() -> Vec<PathOp> {
         .finish()
 }
 
-criterion_group!(benches, fill_16, fill_256, stroke_16, stroke_256);
+criterion_group!(
+    benches,
+    fill_16,
+    fill_256,
+    fill_256_soft,
+    fill_256_rgba,
+    fill_256_gradient,
+    fill_256_ink,
+    fill_2048,
+    stroke_16,
+    stroke_256,
+    fill_stroke_16,
+    fill_stroke_256,
+    frame_clear_256,
+    frame_new_256,
+    tiles_16x16,
+    glyphs_loop_500,
+    glyphs_batch_500,
+    glyph_wide_4096,
+    small_fills_1000,
+    fishy_fills_1000,
+    fishy_prepared_1000,
+    fill_100k_points,
+    fill_2000_strips,
+    fill_sparse_100,
+    fill_traced_50k
+);
 criterion_main!(benches);
@@ -15,5 +15,5 @@ fn main() -> Result<(), std::io::Error> {
         .finish();
     let r = Raster::with_clear(128, 128);
     let mut p = Plotter::new(r);
-    png::write_matte(p.stroke(&path, Matte8::new(255)), "./curve.png")
+    png::write_matte(&p.stroke(&path, Matte8::new(255)), "./curve.png")
 }
@@ -19,6 +19,6 @@ fn main() -> Result<(), std::io::Error> {
     p.fill(FillRule::NonZero, &path, Graya8p::new(128, 255));
     p.stroke(&path, Graya8p::new(255, 255));
 
-    let r = Raster::<SGray8>::with_raster(&p.raster());
+    let r = Raster::<SGray8>::with_raster(p.raster());
     png::write(&r, "./drop.png")
 }
@@ -21,5 +21,5 @@ fn main() -> Result<(), std::io::Error> {
     let r = Raster::with_clear(64, 64);
     let mut p = Plotter::new(r);
     p.fill(FillRule::NonZero, &path, Matte8::new(255));
-    png::write_matte(&p.raster(), "./figure.png")
+    png::write_matte(p.raster(), "./figure.png")
 }
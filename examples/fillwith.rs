@@ -0,0 +1,42 @@
+// fillwith.rs
+use footile::{FillRule, Path2D, Plotter};
+use pix::chan::{Ch8, Channel};
+use pix::el::Pixel;
+use pix::rgb::{Rgba8p, SRgba8};
+use pix::Raster;
+
+mod png;
+
+fn main() -> Result<(), std::io::Error> {
+    let fish = Path2D::default()
+        .relative()
+        .pen_width(3.0)
+        .move_to(112.0, 24.0)
+        .line_to(-32.0, 24.0)
+        .cubic_to(-96.0, -48.0, -96.0, 80.0, 0.0, 32.0)
+        .line_to(32.0, 24.0)
+        .line_to(-16.0, -40.0)
+        .close()
+        .finish();
+    let raster = Raster::<Rgba8p>::with_clear(128, 128);
+    let mut p = Plotter::new(raster);
+    // Scales, with a count of covered pixels
+    let mut count = 0;
+    p.fill_fn(FillRule::NonZero, &fish, |d, x, y, alpha: Ch8| {
+        let (fx, fy) = (x as f32 / 6.0, y as f32 / 6.0);
+        let scale = ((fx + fy).sin() * (fx - fy).sin()).abs();
+        let v = (64.0 + 160.0 * scale) as u8;
+        let clr = Rgba8p::new(v / 4, v, v / 2, 255);
+        // Composite with coverage alpha
+        let chans = d.channels_mut().iter_mut().zip(clr.channels());
+        for (dc, &sc) in chans {
+            *dc = sc * alpha + *dc * (Ch8::MAX - alpha);
+        }
+        count += 1;
+    });
+    println!("covered pixels: {count}");
+    p.stroke(&fish, Rgba8p::new(0, 0, 0, 255));
+
+    let r = Raster::<SRgba8>::with_raster(p.raster());
+    png::write(&r, "./fillwith.png")
+}
@@ -26,10 +26,14 @@ fn main() -> Result<(), std::io::Error> {
         .finish();
     let raster = Raster::with_clear(128, 128);
     let mut p = Plotter::new(raster);
-    p.fill(FillRule::NonZero, &fish, Rgba8p::new(127, 96, 96, 255));
-    p.stroke(&fish, Rgba8p::new(255, 208, 208, 255));
+    p.draw(
+        FillRule::NonZero,
+        &fish,
+        Some(Rgba8p::new(127, 96, 96, 255)),
+        Some(Rgba8p::new(255, 208, 208, 255)),
+    );
     p.stroke(&eye, Rgba8p::new(0, 0, 0, 255));
 
-    let r = Raster::<SRgba8>::with_raster(&p.raster());
+    let r = Raster::<SRgba8>::with_raster(p.raster());
     png::write(&r, "./fishy.png")
 }
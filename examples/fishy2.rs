@@ -31,6 +31,6 @@ fn main() -> Result<(), std::io::Error> {
     p.stroke(&fish, Rgba8p::new(255, 208, 208, 255));
     p.stroke(&eye, Rgba8p::new(0, 0, 0, 255));
 
-    let r = Raster::<SRgba8>::with_raster(&p.raster());
+    let r = Raster::<SRgba8>::with_raster(p.raster());
     png::write(&r, "./fishy2.png")
 }
@@ -22,5 +22,5 @@ fn main() -> Result<(), std::io::Error> {
     }
     let path = pb.close().finish();
     p.fill(FillRule::EvenOdd, &path, Matte8::new(255));
-    png::write_matte(&p.raster(), "./heptagram.png")
+    png::write_matte(p.raster(), "./heptagram.png")
 }
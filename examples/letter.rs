@@ -31,5 +31,5 @@ fn main() -> Result<(), std::io::Error> {
     let r = Raster::with_clear(165, 256);
     let mut p = Plotter::new(r);
     p.fill(FillRule::NonZero, &path, Matte8::new(255));
-    png::write_matte(&p.raster(), "./letter.png")
+    png::write_matte(p.raster(), "./letter.png")
 }
@@ -0,0 +1,23 @@
+// nib.rs
+use footile::{Path2D, Plotter};
+use pix::matte::Matte8;
+use pix::Raster;
+
+mod png;
+
+fn main() -> Result<(), std::io::Error> {
+    let path = Path2D::default()
+        .absolute()
+        .move_to(48.0, 12.0)
+        .cubic_to(32.0, 0.0, 8.0, 12.0, 20.0, 28.0)
+        .cubic_to(28.0, 38.0, 52.0, 30.0, 44.0, 50.0)
+        .cubic_to(36.0, 64.0, 12.0, 56.0, 12.0, 48.0)
+        .finish();
+    let r = Raster::with_clear(64, 64);
+    let mut p = Plotter::new(r);
+    let angle = std::f32::consts::FRAC_PI_4;
+    png::write_matte(
+        &p.stroke_nib(&path, 8.0, angle, Matte8::new(255)),
+        "./nib.png",
+    )
+}
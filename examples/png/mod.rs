@@ -3,8 +3,9 @@
 use pix::el::Pixel;
 use pix::gray::SGray8;
 use pix::matte::Matte8;
+use pix::rgb::SRgba8;
 use pix::Raster;
-use png_pong::Encoder;
+use png_pong::{Decoder, Encoder, PngRaster, Step};
 use std::fs::File;
 use std::io;
 
@@ -25,3 +26,29 @@ pub fn write_matte(raster: &Raster<Matte8>, filename: &str) -> io::Result<()> {
         Raster::<SGray8>::with_u8_buffer(raster.width(), raster.height(), pix);
     write(&raster, filename)
 }
+
+/// Read a file into an `SRgba8` raster.
+pub fn read(filename: &str) -> io::Result<Raster<SRgba8>> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let file = File::open(filename)?;
+    let decoder = Decoder::new(file)
+        .map_err(|e| invalid(e.to_string()))?
+        .into_steps();
+    let Step { raster, .. } = decoder
+        .last()
+        .ok_or_else(|| invalid("no image".to_string()))?
+        .map_err(|e| invalid(e.to_string()))?;
+    Ok(match raster {
+        PngRaster::Gray8(r) => Raster::with_raster(&r),
+        PngRaster::Gray16(r) => Raster::with_raster(&r),
+        PngRaster::Rgb8(r) => Raster::with_raster(&r),
+        PngRaster::Rgb16(r) => Raster::with_raster(&r),
+        PngRaster::Graya8(r) => Raster::with_raster(&r),
+        PngRaster::Graya16(r) => Raster::with_raster(&r),
+        PngRaster::Rgba8(r) => r,
+        PngRaster::Rgba16(r) => Raster::with_raster(&r),
+        PngRaster::Palette(..) => {
+            return Err(invalid("palette images not supported".to_string()))
+        }
+    })
+}
@@ -14,5 +14,5 @@ fn main() -> Result<(), std::io::Error> {
         .finish();
     let r = Raster::with_clear(64, 64);
     let mut p = Plotter::new(r);
-    png::write_matte(p.stroke(&path, Matte8::new(255)), "./quad.png")
+    png::write_matte(&p.stroke(&path, Matte8::new(255)), "./quad.png")
 }
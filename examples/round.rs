@@ -15,5 +15,5 @@ fn main() -> Result<(), std::io::Error> {
         .finish();
     let mut p = Plotter::new(Raster::with_clear(100, 100));
     p.set_join(JoinStyle::Round);
-    png::write_matte(p.stroke(&path, Matte8::new(255)), "./round.png")
+    png::write_matte(&p.stroke(&path, Matte8::new(255)), "./round.png")
 }
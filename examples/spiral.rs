@@ -0,0 +1,36 @@
+// spiral.rs
+use footile::ink::{GradientStops, InterpSpace};
+use footile::{Path2D, Plotter};
+use pix::rgb::{Rgba8p, SRgba8};
+use pix::Raster;
+
+mod png;
+
+fn main() -> Result<(), std::io::Error> {
+    // Archimedean spiral, from the center out
+    let mut path = Path2D::default().absolute().pen_width(6.0);
+    for i in 0..=400 {
+        let a = i as f32 * 0.05;
+        let r = 4.0 + a * 5.0;
+        let (x, y) = (128.0 + r * a.cos(), 128.0 + r * a.sin());
+        path = if i == 0 {
+            path.move_to(x, y)
+        } else {
+            path.line_to(x, y)
+        };
+    }
+    let path = path.finish();
+    let stops = [
+        (0.0, Rgba8p::new(0, 0, 255, 255)),
+        (1.0, Rgba8p::new(255, 0, 0, 255)),
+    ];
+    let stops = GradientStops::new(&stops)
+        .unwrap()
+        .with_interp(InterpSpace::Oklab);
+    let raster = Raster::with_color(256, 256, Rgba8p::new(255, 255, 255, 255));
+    let mut p = Plotter::new(raster);
+    p.stroke_gradient(&path, &stops);
+
+    let r = Raster::<SRgba8>::with_raster(p.raster());
+    png::write(&r, "./spiral.png")
+}
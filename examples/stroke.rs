@@ -15,5 +15,5 @@ fn main() -> Result<(), std::io::Error> {
         .close()
         .finish();
     let mut p = Plotter::new(Raster::with_clear(64, 64));
-    png::write_matte(p.stroke(&path, Matte8::new(255)), "./stroke.png")
+    png::write_matte(&p.stroke(&path, Matte8::new(255)), "./stroke.png")
 }
@@ -20,6 +20,6 @@ fn main() -> Result<(), std::io::Error> {
     let clr = Rgba8p::new(64, 128, 64, 255);
     let mut p = Plotter::new(Raster::with_color(64, 64, clr));
     p.stroke(&path, Rgba8p::new(255, 255, 0, 255));
-    let r = Raster::<SRgba8>::with_raster(&p.raster());
+    let r = Raster::<SRgba8>::with_raster(p.raster());
     png::write(&r, "./stroke2.png")
 }
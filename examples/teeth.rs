@@ -27,5 +27,5 @@ fn main() -> Result<(), std::io::Error> {
         .line_to(8.0, -8.0)
         .finish();
     let mut p = Plotter::new(Raster::with_clear(64, 64));
-    png::write_matte(p.stroke(&path, Matte8::new(255)), "./teeth.png")
+    png::write_matte(&p.stroke(&path, Matte8::new(255)), "./teeth.png")
 }
@@ -0,0 +1,57 @@
+// texture.rs
+use footile::ink::{Filter, Texture, Wrap};
+use footile::{FillRule, Path2D, Plotter};
+use pix::rgb::{Rgba8p, SRgba8};
+use pix::Raster;
+use pointy::Transform;
+
+mod png;
+
+/// Make a photo-like texture, if no image file is given
+fn make_texture() -> Raster<SRgba8> {
+    let mut r = Raster::with_clear(64, 64);
+    for y in 0..64 {
+        for x in 0..64 {
+            let (fx, fy) = (x as f32 / 63.0, y as f32 / 63.0);
+            let ripple = ((fx * 12.0).sin() * (fy * 9.0).cos() + 1.0) / 2.0;
+            let red = (255.0 * fx) as u8;
+            let green = (255.0 * ripple) as u8;
+            let blue = (255.0 * (1.0 - fy)) as u8;
+            *r.pixel_mut(x, y) = SRgba8::new(red, green, blue, 255);
+        }
+    }
+    r
+}
+
+fn main() -> Result<(), std::io::Error> {
+    let photo = match std::env::args().nth(1) {
+        Some(filename) => png::read(&filename)?,
+        None => make_texture(),
+    };
+    let photo = Raster::<Rgba8p>::with_raster(&photo);
+    let fish = Path2D::default()
+        .relative()
+        .pen_width(3.0)
+        .move_to(112.0, 24.0)
+        .line_to(-32.0, 24.0)
+        .cubic_to(-96.0, -48.0, -96.0, 80.0, 0.0, 32.0)
+        .line_to(32.0, 24.0)
+        .line_to(-16.0, -40.0)
+        .close()
+        .finish();
+    // Fit the photo width to the fish
+    let scale = photo.width() as f32 / 96.0;
+    let ink = Texture::new(&photo)
+        .with_transform(
+            Transform::with_translate(-16.0, -24.0).scale(scale, scale),
+        )
+        .with_filter(Filter::Bilinear)
+        .with_wrap(Wrap::Mirror);
+    let raster = Raster::with_clear(128, 128);
+    let mut p = Plotter::new(raster);
+    p.fill_ink(FillRule::NonZero, &fish, ink);
+    p.stroke(&fish, Rgba8p::new(0, 0, 0, 255));
+
+    let r = Raster::<SRgba8>::with_raster(p.raster());
+    png::write(&r, "./texture.png")
+}
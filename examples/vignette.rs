@@ -0,0 +1,38 @@
+// vignette.rs
+use footile::ink::{GradientStops, LinearGradient, Modulate};
+use footile::{FillRule, Path2D, Plotter};
+use pix::rgb::{Rgba8p, SRgba8};
+use pix::Raster;
+
+mod png;
+
+fn main() -> Result<(), std::io::Error> {
+    let rect = Path2D::default()
+        .absolute()
+        .move_to(8.0, 8.0)
+        .line_to(120.0, 8.0)
+        .line_to(120.0, 120.0)
+        .line_to(8.0, 120.0)
+        .close()
+        .finish();
+    let stops = [
+        (0.0, Rgba8p::new(255, 160, 0, 255)),
+        (0.5, Rgba8p::new(200, 0, 80, 255)),
+        (1.0, Rgba8p::new(40, 0, 160, 255)),
+    ];
+    let stops = GradientStops::new(&stops).unwrap();
+    let ramp = LinearGradient::new((8.0, 8.0), (120.0, 120.0), stops)
+        .with_dither(true);
+    // Fade out with distance from the center
+    let ink = Modulate::new(ramp, |x, y| {
+        let (dx, dy) = (x as f32 - 63.5, y as f32 - 63.5);
+        let dist = (dx * dx + dy * dy).sqrt() / 80.0;
+        1.0 - dist * dist
+    });
+    let raster = Raster::with_color(128, 128, Rgba8p::new(255, 255, 255, 255));
+    let mut p = Plotter::new(raster);
+    p.fill_ink(FillRule::NonZero, &rect, ink);
+
+    let r = Raster::<SRgba8>::with_raster(p.raster());
+    png::write(&r, "./vignette.png")
+}
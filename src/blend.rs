@@ -0,0 +1,174 @@
+// blend.rs     Blend modes.
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+use crate::imgbuf::{div_255, mul_coverage};
+use pix::chan::{Ch16, Ch32, Ch8, Channel, Linear, Premultiplied};
+use pix::el::Pixel;
+use pix::ops::{DestOut, SrcOver};
+
+/// Sealed trait to prevent implementing `BlendChannel` outside this crate
+mod sealed {
+    pub trait Sealed {}
+    impl Sealed for pix::chan::Ch8 {}
+    impl Sealed for pix::chan::Ch16 {}
+    impl Sealed for pix::chan::Ch32 {}
+}
+
+/// [Channel] which can be blended by a [Plotter].
+///
+/// This trait is *sealed*, and is implemented for [Ch8], [Ch16] and [Ch32].
+///
+/// [Channel]: ../pix/chan/trait.Channel.html
+/// [Ch8]: ../pix/chan/struct.Ch8.html
+/// [Ch16]: ../pix/chan/struct.Ch16.html
+/// [Ch32]: ../pix/chan/struct.Ch32.html
+/// [Plotter]: struct.Plotter.html
+pub trait BlendChannel:
+    Channel + From<Ch8> + From<Ch16> + sealed::Sealed
+{
+    /// Difference between adjacent channel values (zero if not quantized)
+    const STEP: f32;
+
+    /// Multiply by coverage, rounding to nearest.
+    fn mul_coverage(self, cov: Self) -> Self;
+
+    /// Multiply blend: `s·d + s·(1 - da) + d·(1 - sa)`
+    fn multiply(s: Self, sa: Self, d: Self, da: Self) -> Self;
+
+    /// Screen blend: `s + d - s·d`
+    fn screen(s: Self, d: Self) -> Self;
+}
+
+impl BlendChannel for Ch8 {
+    const STEP: f32 = 1.0 / 255.0;
+
+    fn mul_coverage(self, cov: Self) -> Self {
+        mul_coverage(self, cov)
+    }
+
+    fn multiply(s: Self, sa: Self, d: Self, da: Self) -> Self {
+        let (s, sa) = (u32::from(u8::from(s)), u32::from(u8::from(sa)));
+        let (d, da) = (u32::from(u8::from(d)), u32::from(u8::from(da)));
+        Ch8::new(div_255(s * d + s * (255 - da) + d * (255 - sa)))
+    }
+
+    fn screen(s: Self, d: Self) -> Self {
+        let (s, d) = (u32::from(u8::from(s)), u32::from(u8::from(d)));
+        Ch8::new(div_255(s * 255 + d * 255 - s * d))
+    }
+}
+
+impl BlendChannel for Ch16 {
+    const STEP: f32 = 1.0 / 65535.0;
+
+    fn mul_coverage(self, cov: Self) -> Self {
+        let v = u64::from(u16::from(self)) * u64::from(u16::from(cov));
+        Ch16::new(div_65535(v))
+    }
+
+    fn multiply(s: Self, sa: Self, d: Self, da: Self) -> Self {
+        let (s, sa) = (u64::from(u16::from(s)), u64::from(u16::from(sa)));
+        let (d, da) = (u64::from(u16::from(d)), u64::from(u16::from(da)));
+        Ch16::new(div_65535(s * d + s * (65535 - da) + d * (65535 - sa)))
+    }
+
+    fn screen(s: Self, d: Self) -> Self {
+        let (s, d) = (u64::from(u16::from(s)), u64::from(u16::from(d)));
+        Ch16::new(div_65535(s * 65535 + d * 65535 - s * d))
+    }
+}
+
+impl BlendChannel for Ch32 {
+    const STEP: f32 = 0.0;
+
+    fn mul_coverage(self, cov: Self) -> Self {
+        self * cov
+    }
+
+    fn multiply(s: Self, sa: Self, d: Self, da: Self) -> Self {
+        let (s, sa) = (f32::from(s), f32::from(sa));
+        let (d, da) = (f32::from(d), f32::from(da));
+        Ch32::new(s * d + s * (1.0 - da) + d * (1.0 - sa))
+    }
+
+    fn screen(s: Self, d: Self) -> Self {
+        let (s, d) = (f32::from(s), f32::from(d));
+        Ch32::new(s + d - s * d)
+    }
+}
+
+/// Divide by 65535, rounding to nearest and saturating.
+fn div_65535(v: u64) -> u16 {
+    ((v + 32767) / 65535).min(65535) as u16
+}
+
+/// Blend mode for compositing fills and strokes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BlendMode {
+    /// Source over destination
+    #[default]
+    SrcOver,
+    /// Multiply source and destination
+    Multiply,
+    /// Screen source and destination (inverse of multiply)
+    Screen,
+    /// Add source to destination
+    Plus,
+    /// Clear destination where covered
+    Clear,
+}
+
+impl BlendMode {
+    /// Composite a source color onto a destination pixel.
+    ///
+    /// * `dst` Destination pixel.
+    /// * `src` Source color.
+    /// * `alpha` Coverage alpha.
+    pub(crate) fn composite<P>(self, dst: &mut P, src: &P, alpha: P::Chan)
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+        P::Chan: BlendChannel,
+    {
+        // Compositing with zero alpha would round down small values
+        if alpha == P::Chan::MIN {
+            return;
+        }
+        match self {
+            BlendMode::SrcOver => {
+                dst.composite_channels_alpha(src, SrcOver, &alpha)
+            }
+            BlendMode::Plus => {
+                blend_channels(dst, src, alpha, |s, _sa, d, _da| s + d)
+            }
+            BlendMode::Clear => {
+                dst.composite_channels_alpha(src, DestOut, &alpha)
+            }
+            BlendMode::Multiply => {
+                blend_channels(dst, src, alpha, BlendChannel::multiply)
+            }
+            BlendMode::Screen => {
+                blend_channels(dst, src, alpha, |s, _sa, d, _da| {
+                    BlendChannel::screen(s, d)
+                })
+            }
+        }
+    }
+}
+
+/// Blend all channels of a pixel, including alpha.
+///
+/// Channels are premultiplied, so the same function applies to color and
+/// alpha channels.
+fn blend_channels<P, F>(dst: &mut P, src: &P, alpha: P::Chan, f: F)
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+    F: Fn(P::Chan, P::Chan, P::Chan, P::Chan) -> P::Chan,
+{
+    let sa = src.alpha().mul_coverage(alpha);
+    let da = dst.alpha();
+    for (d, s) in dst.channels_mut().iter_mut().zip(src.channels()) {
+        *d = f(s.mul_coverage(alpha), sa, *d, da);
+    }
+}
@@ -0,0 +1,53 @@
+// error.rs     Error type.
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+use std::fmt;
+
+/// Largest coordinate magnitude, in pixels, which can be plotted
+#[cfg(not(feature = "wide-coords"))]
+pub const COORD_MAX: f32 = 16_384.0;
+
+/// Largest coordinate magnitude, in pixels, which can be plotted
+#[cfg(feature = "wide-coords")]
+pub const COORD_MAX: f32 = 16_777_216.0;
+
+/// Errors from fallible plotting and invalid inks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Raster has zero width or height
+    EmptyRaster,
+    /// Coordinate or pen width is NaN or infinite
+    NonFinite,
+    /// Coordinate or pen width is too large, after transform
+    OutOfRange,
+    /// Path has too many points (over 16 million)
+    TooManyPoints,
+    /// Gradient stops are empty, out of order or outside of 0 to 1
+    InvalidStops,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::EmptyRaster => write!(f, "Raster has zero width or height"),
+            Error::NonFinite => write!(f, "Non-finite coordinate"),
+            Error::OutOfRange => write!(f, "Coordinate out of range"),
+            Error::TooManyPoints => write!(f, "Too many points"),
+            Error::InvalidStops => write!(f, "Invalid gradient stops"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Check that a coordinate (in pixels) can be plotted
+pub fn check_coord(v: f32) -> Result<(), Error> {
+    if !v.is_finite() {
+        Err(Error::NonFinite)
+    } else if v.abs() > COORD_MAX {
+        Err(Error::OutOfRange)
+    } else {
+        Ok(())
+    }
+}
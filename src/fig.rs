@@ -2,37 +2,78 @@
 //
 // Copyright (c) 2017-2021  Douglas P Lau
 //
-use crate::fixed::Fixed;
-use crate::imgbuf::{matte_src_over_even_odd, matte_src_over_non_zero};
+use crate::blend::{BlendChannel, BlendMode};
+use crate::error::Error;
+use crate::fixed::{Coord, Fixed};
+use crate::geom::{flatten_cubic, flatten_quad, tol_sq, WidePt};
+use crate::imgbuf::{
+    matte_src_over_even_odd, matte_src_over_non_zero, rgba8p_src_over_even_odd,
+    rgba8p_src_over_non_zero,
+};
 use crate::path::FillRule;
 use crate::vid::Vid;
-use pix::chan::{Ch8, Linear, Premultiplied};
+use pix::chan::{Ch16, Ch8, Channel, Linear, Premultiplied};
 use pix::el::Pixel;
 use pix::matte::Matte8;
-use pix::ops::SrcOver;
-use pix::{Raster, RowsMut};
+use pix::rgb::Rgba8p;
+use pix::{Raster, Region};
 use pointy::Pt;
 use std::any::TypeId;
 use std::cmp::Ordering;
 use std::cmp::Ordering::*;
 use std::fmt;
-use std::ops::Sub;
+use std::ops::{Range, Sub};
+use std::slice::{Chunks, ChunksMut};
 
 /// A 2D point with fixed-point values
-#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
 struct FxPt {
-    x: Fixed,
-    y: Fixed,
+    x: Coord,
+    y: Coord,
 }
 
+/// Minimum number of rows in a band filled in parallel
+#[cfg(feature = "rayon")]
+const BAND_ROWS: usize = 64;
+
+/// Minimum number of pixels in a raster to fill bands in parallel
+#[cfg(feature = "rayon")]
+const BAND_PIXELS: usize = 1 << 18;
+
+/// Number of rows between calls to progress callback
+const PROGRESS_ROWS: i32 = 16;
+
+/// Minimum number of points in a figure to scan with an edge table, instead
+/// of sorting vertex IDs
+const TABLE_POINTS: usize = 512;
+
+/// Maximum number of starting edges collected for a band of a streamed
+/// figure, unless a single row has more
+const STREAM_EDGES: usize = 1 << 16;
+
+/// Largest coordinate magnitude of figure points, in pixels.
+///
+/// Points are clipped to this, so that the difference between any two fits
+/// in a `Coord` value.
+#[cfg(not(feature = "wide-coords"))]
+const CLIP_MAX: f32 = 16_383.0;
+
+/// Largest coordinate magnitude of figure points, in pixels.
+///
+/// Points are clipped to this, so that whole pixels are exact as `f32`.
+#[cfg(feature = "wide-coords")]
+const CLIP_MAX: f32 = 16_777_215.0;
+
 /// Figure direction enum
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 enum FigDir {
+    #[default]
     Forward,
     Reverse,
 }
 
 /// Sub-figure structure
+#[derive(Clone, Copy)]
 struct SubFig {
     /// Starting point
     start: Vid,
@@ -42,56 +83,369 @@ struct SubFig {
     done: bool,
 }
 
+/// Sorted vertex IDs state
+#[derive(Clone, Copy, Debug)]
+struct Sorted {
+    /// Area size used to cull sub-figures, or `None` if nothing was culled
+    area: Option<(u32, u32)>,
+    /// Figure direction
+    dir: FigDir,
+}
+
+/// Clipping state for one side of the figure bounds
+#[derive(Clone, Copy, Debug, Default)]
+struct ClipSide {
+    /// First point of sub-figure, if any
+    first: Option<Pt<f32>>,
+    /// Previous point
+    prev: Pt<f32>,
+}
+
+/// Clipping state of a sub-figure, for all sides of the figure bounds
+#[derive(Clone, Copy, Debug, Default)]
+struct Clipper {
+    /// Clipping state for each side of bounds
+    sides: [ClipSide; 4],
+    /// Flag indicating current sub-figure has points outside bounds
+    clipping: bool,
+}
+
 /// Edge structure
 #[derive(Debug)]
 struct Edge {
-    /// Lower vertex ID
-    v1: Vid,
     /// Upper vertex Y
-    y_upper: Fixed,
+    y_upper: Coord,
     /// Lower vertex Y
-    y_lower: Fixed,
+    y_lower: Coord,
     /// Figure direction from upper to lower
     dir: FigDir,
-    /// Change in cov per pix on current row
-    step_pix: Fixed,
+    /// Lower vertex X
+    x_lower: Coord,
     /// Inverse slope (delta_x / delta_y)
-    inv_slope: Fixed,
+    inv_slope: Coord,
+    /// X at top of current row (or upper vertex, on starting row)
+    x_top: Coord,
     /// X at bottom of current row
-    x_bot: Fixed,
+    x_bot: Coord,
     /// Minimum X on current row
-    min_x: Fixed,
+    min_x: Coord,
     /// Maximum X on current row
-    max_x: Fixed,
+    max_x: Coord,
+}
+
+/// Span of pixels scanned on a row
+#[derive(Clone, Copy, Debug, Default)]
+struct Span {
+    /// Range of scanned pixels
+    range: Option<(i32, i32)>,
+    /// Sum of signed area
+    sum: i32,
 }
 
 /// A Fig is a series of 2D points which can be rendered to an image raster.
+///
+/// This is the rasterizer used by [Plotter], for filling paths from another
+/// representation.  Points are in raster pixels, with no transform.
+///
+/// Each fill needs a signed area buffer, at least as long as the raster is
+/// wide.  It must be zeroed before the first fill, and is left zeroed after
+/// each fill, so it can be reused.  With 8 bits of coverage, an `i16` buffer
+/// is fastest, but a non-zero fill needs `i32` when [max_winding] is over
+/// 127.
+///
+/// ```rust
+/// use footile::{FillRule, Fig, Paint};
+/// use pix::{matte::Matte8, Raster};
+///
+/// let mut raster = Raster::<Matte8>::with_clear(3, 3);
+/// let mut sgn_area = vec![0i16; 3];
+/// let mut fig = Fig::new();
+/// fig.add_point((1.0, 2.0));
+/// fig.add_point((1.0, 3.0));
+/// fig.add_point((2.0, 3.0));
+/// fig.add_point((2.0, 2.0));
+/// fig.close();
+/// let paint = Paint::new(Matte8::new(255));
+/// fig.fill(FillRule::NonZero, &mut raster, paint, &mut sgn_area);
+/// assert_eq!(raster.pixel(1, 2), Matte8::new(255));
+/// ```
+///
+/// [max_winding]: struct.Fig.html#method.max_winding
+/// [Plotter]: struct.Plotter.html
 pub struct Fig {
     /// All pionts
     points: Vec<FxPt>,
     /// All sub-figures
     subs: Vec<SubFig>,
+    /// Upper bound of winding number magnitude, for done sub-figures
+    max_winding: u32,
+    /// Number of points dropped after the figure was full
+    dropped: usize,
+    /// Clipping state of current sub-figure
+    clip: Clipper,
+    /// Sorted vertex IDs buffer, reused between fills
+    vids: Vec<Vid>,
+    /// State of sorted vertex IDs, while still valid
+    sorted: Option<Sorted>,
+    /// Active edges buffer, reused between fills
+    edges: Vec<Edge>,
+    /// Edge table buffer, reused between fills
+    table: Vec<RowEdge>,
+    /// Edge counts buffer for building edge table
+    counts: Vec<usize>,
+}
+
+/// Order to scan edges of a figure
+enum ScanOrder {
+    /// Figure direction, and vertex IDs sorted by position
+    Vertices(FigDir, Vec<Vid>),
+    /// Table of edges, ordered by starting row
+    Edges(Vec<RowEdge>),
+}
+
+/// Edge in a table ordered by starting row
+#[derive(Clone, Copy, Debug, Default)]
+struct RowEdge {
+    /// Upper point
+    upper: FxPt,
+    /// Lower point
+    lower: FxPt,
+    /// Direction from upper to lower point
+    dir: FigDir,
+}
+
+/// A figure streamed from points, without storing them.
+///
+/// Points are streamed once to count the edges starting on each row, and
+/// again for each band of rows, collecting only edges starting within it.
+/// Memory depends on the number of rows, the edges collected for a band and
+/// the active edges, rather than the total number of points.
+pub(crate) struct EdgeStream {
+    /// Offset added to clipped points
+    origin: FxPt,
+    /// Height of area to fill
+    height: i32,
+    /// Top row where edges can start
+    row_min: i32,
+    /// Clipping state of current sub-figure
+    clip: Clipper,
+    /// First point of current sub-figure
+    first: Option<FxPt>,
+    /// Previous point of current sub-figure
+    prev: FxPt,
+    /// Downward flag of first non-horizontal edge of current sub-figure
+    first_down: Option<bool>,
+    /// Downward flag of previous non-horizontal edge
+    down: bool,
+    /// Upper bound of winding number magnitude
+    max_winding: u32,
+    /// Number of points streamed
+    n_points: usize,
+    /// Number of sub-figures streamed
+    n_subs: usize,
+    /// Number of edges starting on each row, from `row_min`
+    counts: Vec<u32>,
+    /// Rows of current band, or `None` while counting
+    band: Option<Range<i32>>,
+    /// Next index into `starts` for each row of current band
+    offsets: Vec<usize>,
+    /// Edges starting within current band, ordered by row
+    starts: Vec<RowEdge>,
+    /// Active edges, continuing between bands
+    edges: Vec<Edge>,
+}
+
+/// Signed area accumulator for pixel coverage
+///
+/// This is implemented for `i16` (up to 8 bits of coverage) and `i32` (up to
+/// 16 bits).  Sums wrap on overflow, like the SIMD fast paths, so they are
+/// correct as long as accumulated coverage fits.
+pub trait SgnArea: Copy + Default + Send + Sync + 'static {
+    /// Maximum coverage bits which can be accumulated
+    const MAX_BITS: u32;
+
+    /// Maximum winding number magnitude, with `MAX_BITS` of coverage
+    const MAX_WINDING: u32;
+
+    /// Convert from a coverage value
+    fn from_cov(cov: i32) -> Self;
+
+    /// Convert to an i32
+    fn to_i32(self) -> i32;
+
+    /// Add, wrapping on overflow
+    fn wrapping_add(self, rhs: Self) -> Self;
+
+    /// Get a slice as i16, for SIMD fast paths
+    fn as_i16(area: &mut [Self]) -> Option<&mut [i16]>;
+}
+
+/// Paint for filling a [Fig]
+///
+/// [Fig]: struct.Fig.html
+pub struct Paint<'a, P> {
+    /// Color to fill
+    pub clr: P,
+    /// Blend mode
+    pub blend: BlendMode,
+    /// Opacity (0 to 1), multiplied with fill coverage
+    pub opacity: f32,
+    /// Anti-aliasing enabled (otherwise coverage is thresholded)
+    pub antialias: bool,
+    /// Clip coverage, multiplied with fill coverage
+    pub clip: Option<&'a Raster<Matte8>>,
+    /// Coverage precision in bits (4, 8 or 16)
+    pub coverage_bits: u8,
+}
+
+/// Destination of scanned rows
+enum Rows<'a, P: Pixel> {
+    /// Raster rows to composite
+    Pixels {
+        /// Full raster rows
+        rows: ChunksMut<'a, P>,
+        /// Columns within each row
+        columns: Range<usize>,
+    },
+    /// Callback for runs of equal coverage
+    Spans {
+        /// Width of each row
+        width: i32,
+        /// Bottom row (exclusive)
+        y_bottom: i32,
+        /// Maximum coverage reported
+        max_cov: u16,
+        /// Span callback
+        spans: &'a mut dyn FnMut(i32, i32, u32, u16),
+    },
+    /// Callback for rows of coverage
+    Coverage {
+        /// Row buffer (taken while rasterizing)
+        row: Option<Raster<P>>,
+        /// Bottom row (exclusive)
+        y_bottom: i32,
+        /// Row callback
+        sink: &'a mut dyn FnMut(u32, &[u8]),
+    },
+}
+
+/// Metrics collected while filling a figure.
+///
+/// With metrics enabled, figures are filled in a single band of rows.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Metrics {
+    /// Number of points in the figure
+    pub points: usize,
+    /// Number of sub-figures
+    pub sub_figs: usize,
+    /// Number of edges added while scanning
+    pub edges_added: usize,
+    /// Maximum number of edges active on one row
+    pub max_active_edges: usize,
+    /// Number of raster rows scanned
+    pub rows_scanned: usize,
+    /// Number of pixels composited
+    pub pixels_composited: usize,
+    /// Number of points dropped after the figure was full
+    pub points_dropped: usize,
+}
+
+/// Band of raster rows to fill
+struct Band<'a, P: Pixel> {
+    /// Top row of band
+    y_top: i32,
+    /// Destination rows
+    rows: Rows<'a, P>,
+    /// Clip coverage rows
+    clip: Option<Chunks<'a, Matte8>>,
+    /// Progress callback
+    progress: Option<&'a mut dyn FnMut(u32) -> bool>,
+    /// Metrics counters
+    metrics: Option<&'a mut Metrics>,
+    /// Active edges buffer
+    edges: &'a mut Vec<Edge>,
 }
 
 /// Figure scanner structure
-struct Scanner<'a, P>
+struct Scanner<'a, P, A>
 where
-    P: Pixel<Chan = Ch8, Alpha = Premultiplied, Gamma = Linear>,
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
 {
-    /// The figure
-    fig: &'a Fig,
     /// Fill rule
     rule: FillRule,
     /// Figure direction
     dir: FigDir,
-    /// Destination raster rows
-    rows: RowsMut<'a, P>,
+    /// Top row of band
+    y_top: i32,
+    /// Destination rows
+    rows: Rows<'a, P>,
     /// Color to fill
     clr: P,
+    /// Blend mode
+    blend: BlendMode,
+    /// Opacity
+    opacity: P::Chan,
+    /// Anti-aliasing enabled
+    antialias: bool,
+    /// Coverage precision in bits
+    bits: u32,
+    /// Clip coverage rows
+    clip: Option<Chunks<'a, Matte8>>,
+    /// Progress callback
+    progress: Option<&'a mut dyn FnMut(u32) -> bool>,
+    /// Metrics counters
+    metrics: Option<&'a mut Metrics>,
     /// Signed area buffer
-    sgn_area: &'a mut [i16],
+    sgn_area: &'a mut [A],
     /// Active edges
-    edges: Vec<Edge>,
+    edges: &'a mut Vec<Edge>,
+    /// Span scanned on current row
+    span: Span,
+    /// Region of pixels touched
+    dirty: Option<Region>,
+}
+
+impl SgnArea for i16 {
+    const MAX_BITS: u32 = 8;
+    const MAX_WINDING: u32 = (i16::MAX >> 8) as u32;
+
+    fn from_cov(cov: i32) -> Self {
+        cov as i16
+    }
+
+    fn to_i32(self) -> i32 {
+        i32::from(self)
+    }
+
+    fn wrapping_add(self, rhs: Self) -> Self {
+        i16::wrapping_add(self, rhs)
+    }
+
+    fn as_i16(area: &mut [Self]) -> Option<&mut [i16]> {
+        Some(area)
+    }
+}
+
+impl SgnArea for i32 {
+    const MAX_BITS: u32 = 16;
+    const MAX_WINDING: u32 = (i32::MAX >> 16) as u32;
+
+    fn from_cov(cov: i32) -> Self {
+        cov
+    }
+
+    fn to_i32(self) -> i32 {
+        self
+    }
+
+    fn wrapping_add(self, rhs: Self) -> Self {
+        i32::wrapping_add(self, rhs)
+    }
+
+    fn as_i16(_area: &mut [Self]) -> Option<&mut [i16]> {
+        None
+    }
 }
 
 impl Sub for FxPt {
@@ -104,7 +458,7 @@ impl Sub for FxPt {
 
 impl FxPt {
     /// Create a new point.
-    fn new(x: Fixed, y: Fixed) -> Self {
+    fn new(x: Coord, y: Coord) -> Self {
         FxPt { x, y }
     }
 
@@ -119,13 +473,24 @@ impl FxPt {
     }
 }
 
-impl FigDir {
-    /// Get the opposite direction
-    fn opposite(self) -> Self {
-        match self {
-            FigDir::Forward => FigDir::Reverse,
-            FigDir::Reverse => FigDir::Forward,
-        }
+impl Span {
+    /// Add a range of scanned pixels
+    fn add(&mut self, x0: i32, x1: i32, sum: i32) {
+        self.range = match self.range {
+            Some((r0, r1)) => Some((r0.min(x0), r1.max(x1))),
+            None => Some((x0, x1)),
+        };
+        self.sum += sum;
+    }
+
+    /// Get range of pixels which may be covered.
+    ///
+    /// Coverage continues to the right edge if the signed area does not sum
+    /// to zero (when edges are beyond the right edge).
+    fn pixels(self, width: i32) -> Option<(i32, i32)> {
+        let (x0, x1) = self.range?;
+        let x1 = if self.sum != 0 { width - 1 } else { x1 };
+        Some((x0, x1.min(width - 1)))
     }
 }
 
@@ -163,65 +528,195 @@ impl SubFig {
     }
 }
 
+/// Count peaks of a closed polygon (vertices above both neighbors)
+fn count_peaks(pts: &[FxPt]) -> u32 {
+    let dy = |i: usize| pts[(i + 1) % pts.len()].y - pts[i].y;
+    // Direction of the last non-horizontal edge
+    let mut down =
+        match (0..pts.len()).rev().map(dy).find(|d| *d != Coord::ZERO) {
+            Some(d) => d > Coord::ZERO,
+            None => return 0,
+        };
+    let mut peaks = 0;
+    for d in (0..pts.len()).map(dy).filter(|d| *d != Coord::ZERO) {
+        let d = d > Coord::ZERO;
+        if d && !down {
+            peaks += 1;
+        }
+        down = d;
+    }
+    peaks
+}
+
+/// Make a point for curve flattening
+fn wide_pt<P: Into<Pt<f32>>>(pt: P) -> WidePt {
+    WidePt(pt.into(), 1.0, 0.0)
+}
+
+/// Check if a point is inside one side of the figure bounds
+fn is_inside(side: usize, pt: Pt<f32>) -> bool {
+    match side {
+        0 => pt.x() >= -CLIP_MAX,
+        1 => pt.x() <= CLIP_MAX,
+        2 => pt.y() >= -CLIP_MAX,
+        _ => pt.y() <= CLIP_MAX,
+    }
+}
+
+/// Get intersection of a segment with one side of the figure bounds
+fn intersect_side(side: usize, p0: Pt<f32>, p1: Pt<f32>) -> Pt<f32> {
+    let lim = if side == 0 || side == 2 {
+        -CLIP_MAX
+    } else {
+        CLIP_MAX
+    };
+    if side < 2 {
+        let t = (lim - p0.x()) / (p1.x() - p0.x());
+        Pt::new(lim, p0.y() + (p1.y() - p0.y()) * t)
+    } else {
+        let t = (lim - p0.y()) / (p1.y() - p0.y());
+        Pt::new(p0.x() + (p1.x() - p0.x()) * t, lim)
+    }
+}
+
+impl Clipper {
+    /// Pass a point which needs no clipping.
+    ///
+    /// Returns `false` if the point must be clipped with `clip_point`.
+    fn pass(&mut self, pt: Pt<f32>) -> bool {
+        if !self.clipping {
+            if pt.x().abs() <= CLIP_MAX && pt.y().abs() <= CLIP_MAX {
+                let clip = &mut self.sides[0];
+                clip.first.get_or_insert(pt);
+                clip.prev = pt;
+                return true;
+            }
+            // Until now, all sides have the same state
+            self.sides = [self.sides[0]; 4];
+            self.clipping = true;
+        }
+        false
+    }
+
+    /// Clip a point against one side of the bounds, and the following sides.
+    ///
+    /// This is Sutherland-Hodgman clipping, one point at a time.
+    ///
+    /// * `side` Side index (0 to 3), or 4 to push the point unclipped.
+    /// * `pt` Point to clip.
+    /// * `push` Callback to push a clipped point.
+    fn clip_point(
+        &mut self,
+        side: usize,
+        pt: Pt<f32>,
+        push: &mut impl FnMut(Pt<f32>),
+    ) {
+        if side >= self.sides.len() {
+            push(pt);
+            return;
+        }
+        let clip = self.sides[side];
+        if clip.first.is_some() {
+            if is_inside(side, clip.prev) != is_inside(side, pt) {
+                let p = intersect_side(side, clip.prev, pt);
+                self.clip_point(side + 1, p, push);
+            }
+        } else {
+            self.sides[side].first = Some(pt);
+        }
+        self.sides[side].prev = pt;
+        if is_inside(side, pt) {
+            self.clip_point(side + 1, pt, push);
+        }
+    }
+
+    /// Clip the closing segment of a sub-figure.
+    ///
+    /// * `push` Callback to push a clipped point.
+    fn close(&mut self, push: &mut impl FnMut(Pt<f32>)) {
+        self.clipping = false;
+        for side in 0..self.sides.len() {
+            if let Some(first) = self.sides[side].first.take() {
+                let prev = self.sides[side].prev;
+                if is_inside(side, prev) != is_inside(side, first) {
+                    let p = intersect_side(side, prev, first);
+                    self.clip_point(side + 1, p, push);
+                }
+            }
+        }
+    }
+}
+
 /// Get the row of a Y value
-fn row_of(y: Fixed) -> i32 {
-    y.into()
+fn row_of(y: Coord) -> i32 {
+    y.to_i32()
+}
+
+impl RowEdge {
+    /// Create an edge between two points, in figure order.
+    ///
+    /// Returns `None` for horizontal edges.
+    fn new(p0: FxPt, p1: FxPt) -> Option<Self> {
+        let (upper, lower, dir) = match p0.y.cmp(&p1.y) {
+            Less => (p0, p1, FigDir::Forward),
+            Greater => (p1, p0, FigDir::Reverse),
+            Equal => return None,
+        };
+        Some(RowEdge { upper, lower, dir })
+    }
+
+    /// Get the starting row
+    fn row(&self) -> i32 {
+        row_of(self.upper.y)
+    }
+
+    /// Check if the edge can cover any row of an area, from the top.
+    ///
+    /// Edges ending at or above the top have no area within it.
+    fn covers(&self, height: i32) -> bool {
+        self.lower.y > Coord::ZERO && self.row() < height
+    }
 }
 
 impl Edge {
     /// Create a new edge
     ///
-    /// * `v0` Upper vertex.
-    /// * `v1` Lower vertex.
+    /// The lower point must be below the upper point; horizontal edges are
+    /// skipped when updating edges, after points are converted to `Coord`.
+    ///
     /// * `p0` Upper point.
     /// * `p1` Lower point.
     /// * `dir` Direction from upper to lower vertex.
-    fn new(v0: Vid, v1: Vid, p0: FxPt, p1: FxPt, dir: FigDir) -> Edge {
-        debug_assert_ne!(v0, v1);
-        let delta_x = p1.x - p0.x;
-        let delta_y = p1.y - p0.y;
-        debug_assert!(delta_y > Fixed::ZERO);
-        let step_pix = Edge::calculate_step(delta_x, delta_y);
-        let inv_slope = delta_x / delta_y;
+    fn new(p0: FxPt, p1: FxPt, dir: FigDir) -> Edge {
+        // Offset points can be far apart, so saturate instead of wrapping
+        let delta_x = p1.x.saturating_sub(p0.x);
+        let delta_y = p1.y.saturating_sub(p0.y);
+        debug_assert!(delta_y > Coord::ZERO);
+        // Nearly horizontal edges can have slopes beyond the fixed range.
+        // Rounding keeps the error from adding up in one direction, as the
+        // slope is added on each row.
+        let inv_slope = delta_x.div_round(delta_y);
         let y_upper = p0.y;
         let y_lower = p1.y;
-        let y_bot = (y_upper + Fixed::ONE).floor() - y_upper;
-        let x_bot = p0.x + inv_slope * y_bot;
+        // Limit to lower vertex, so nearly horizontal edges cannot overflow
+        let y_bot = ((y_upper + Coord::ONE).floor() - y_upper).min(delta_y);
+        let x_bot = p0.x.saturating_add(delta_x.mul_div_round(y_bot, delta_y));
         Edge {
-            v1,
             y_upper,
             y_lower,
             dir,
-            step_pix,
+            x_lower: p1.x,
             inv_slope,
+            x_top: p0.x,
             x_bot,
-            min_x: Fixed::ZERO,
-            max_x: Fixed::ZERO,
-        }
-    }
-
-    /// Calculate the step for each pixel on an edge
-    fn calculate_step(delta_x: Fixed, delta_y: Fixed) -> Fixed {
-        if delta_x != Fixed::ZERO {
-            (delta_y / delta_x).abs().min(Fixed::ONE)
-        } else {
-            Fixed::ZERO
+            min_x: Coord::ZERO,
+            max_x: Coord::ZERO,
         }
     }
 
     /// Get the minimum X pixel
     fn min_pix(&self) -> i32 {
-        self.min_x.into()
-    }
-
-    /// Get the maximum X pixel
-    fn max_pix(&self) -> i32 {
-        self.max_x.into()
-    }
-
-    /// Get the X midpoint for the current row.
-    fn mid_x(&self) -> Fixed {
-        self.max_x.avg(self.min_x)
+        self.min_x.to_i32()
     }
 
     /// Check for the edge starting row.
@@ -235,41 +730,43 @@ impl Edge {
     }
 
     /// Get pixel coverage of starting row.
-    fn starting_cov(&self) -> i16 {
+    ///
+    /// * `bits` Coverage precision.
+    fn starting_cov(&self, bits: u32) -> i32 {
         let y_row = row_of(self.y_upper);
-        self.continuing_cov(y_row) - pixel_cov(self.y_upper.fract())
+        self.continuing_cov(y_row, bits)
+            - self.y_upper.fract().round_scaled(bits)
     }
 
     /// Calculate X limits for the starting row.
     fn calculate_x_limits_starting(&mut self) {
         let y_row = row_of(self.y_upper);
-        let y0 = Fixed::ONE - self.y_upper.fract();
-        let x0 = self.x_bot - self.inv_slope * y0;
-        self.set_x_limits(x0, y_row);
+        self.set_x_limits(y_row);
     }
 
     /// Get pixel coverage of continuing row.
-    fn continuing_cov(&self, y_row: i32) -> i16 {
+    ///
+    /// * `bits` Coverage precision.
+    fn continuing_cov(&self, y_row: i32, bits: u32) -> i32 {
         debug_assert!(y_row <= row_of(self.y_lower));
         if self.is_ending(y_row) {
-            pixel_cov(self.y_lower.fract())
+            self.y_lower.fract().round_scaled(bits)
         } else {
-            256
+            1 << bits
         }
     }
 
     /// Calculate X limits for a continuing row.
     fn calculate_x_limits_continuing(&mut self, y_row: i32) {
         debug_assert!(!self.is_starting(y_row));
-        let x0 = self.x_bot - self.inv_slope;
-        self.set_x_limits(x0, y_row);
+        self.set_x_limits(y_row);
     }
 
     /// Set X limits
-    fn set_x_limits(&mut self, x0: Fixed, y_row: i32) {
+    fn set_x_limits(&mut self, y_row: i32) {
+        let x0 = self.x_top;
         let x1 = if self.is_ending(y_row) {
-            let y1 = self.y_lower.ceil() - self.y_lower;
-            self.x_bot - self.inv_slope * y1
+            self.x_lower
         } else {
             self.x_bot
         };
@@ -279,45 +776,111 @@ impl Edge {
 
     /// Scan signed area of current row.
     ///
+    /// Coverage left of the raster is summed at column -1, and added to
+    /// column 0, so the winding entering column 0 is exact.
+    ///
     /// * `dir` Direction of edge.
-    /// * `cov` Pixel coverage of current row (1 - 256).
+    /// * `cov` Pixel coverage of current row (1 to `1 << bits`).
+    /// * `bits` Coverage precision.
     /// * `area` Signed area buffer.
-    fn scan_area(&self, dir: FigDir, cov: i16, area: &mut [i16]) {
+    /// * `span` Span of scanned pixels.
+    fn scan_area<A: SgnArea>(
+        &self,
+        dir: FigDir,
+        cov: i32,
+        bits: u32,
+        area: &mut [A],
+        span: &mut Span,
+    ) {
         let ed = if self.dir == dir { 1 } else { -1 };
-        let full_cov = Fixed::from(cov as f32 / 256.0);
-        let mut x_cov = self.first_cov(full_cov); // total coverage at X
-        let step_cov = self.step_cov(Fixed::ONE); // coverage change per step
-        debug_assert!(step_cov > Fixed::ZERO);
+        let full_cov = Coord::from(cov as f32 / (1 << bits) as f32);
+        let x_start = self.min_pix().max(-1);
+        let mut before = self.height_before(x_start, full_cov);
         let mut sum_pix = 0; // cumulative sum of pixel coverage
-        for x in self.min_pix()..area.len() as i32 {
-            let x_pix = pixel_cov(x_cov).min(cov);
+        let mut x0 = None;
+        let mut x1 = 0;
+        for x in x_start..area.len() as i32 {
+            let x_cov = self.cumulative_cov(x, full_cov, &mut before);
+            let x_pix = x_cov.round_scaled(bits).min(cov);
             let p = x_pix - sum_pix; // pixel coverage at X
-            area[x.max(0) as usize] += p * ed;
+            let xp = x.max(0);
+            let a = &mut area[xp as usize];
+            *a = a.wrapping_add(A::from_cov(p * ed));
+            if p != 0 {
+                x0.get_or_insert(xp);
+                x1 = xp;
+            }
             sum_pix += p;
             if sum_pix >= cov {
                 break;
             }
-            x_cov = (x_cov + step_cov).min(Fixed::ONE);
+        }
+        if let Some(x0) = x0 {
+            span.add(x0, x1, sum_pix * ed);
         }
     }
 
-    /// Get coverage of first pixel on edge.
-    fn first_cov(&self, full_cov: Fixed) -> Fixed {
-        let r = if self.min_pix() == self.max_pix() {
-            (Fixed::ONE - self.mid_x().fract()) * full_cov
+    /// Get height of the edge left of a pixel column.
+    ///
+    /// * `x` Pixel column.
+    /// * `full_cov` Height of the edge on the current row.
+    fn height_before(&self, x: i32, full_cov: Coord) -> Coord {
+        let left = Coord::from(x);
+        if left <= self.min_x {
+            Coord::ZERO
+        } else if left >= self.max_x {
+            full_cov
         } else {
-            (Fixed::ONE - self.min_x.fract()) * Fixed::HALF
-        };
-        self.step_cov(r)
+            let width = self.max_x - self.min_x;
+            full_cov.mul_div_round(left - self.min_x, width)
+        }
     }
 
-    /// Get pixel coverage.
-    fn step_cov(&self, r: Fixed) -> Fixed {
-        if self.step_pix > Fixed::ZERO {
-            r * self.step_pix
-        } else {
-            r
+    /// Get cumulative coverage of the edge through a pixel column.
+    ///
+    /// This is the height of the edge left of the column, plus the area right
+    /// of the edge within the column.  The edge is straight within the row,
+    /// so its height is spread evenly between `min_x` and `max_x`, and the
+    /// area within each column is an exact trapezoid.
+    ///
+    /// * `x` Pixel column, increasing on each call.
+    /// * `full_cov` Height of the edge on the current row.
+    /// * `before` Height of the edge left of the column (updated).
+    fn cumulative_cov(
+        &self,
+        x: i32,
+        full_cov: Coord,
+        before: &mut Coord,
+    ) -> Coord {
+        let right = Coord::from(x + 1);
+        let l = self.min_x.max(Coord::from(x));
+        let r = self.max_x.min(right);
+        if r < l {
+            return full_cov;
         }
+        let width = self.max_x - self.min_x;
+        let after = if r < self.max_x {
+            full_cov.mul_div_round(r - self.min_x, width)
+        } else {
+            full_cov
+        };
+        let height = after - *before;
+        // Area right of the edge is height times the width right of its
+        // midpoint.  The smaller side is rounded, and the other side is the
+        // remainder, so a mirrored edge has exactly complementary coverage.
+        // When centered, an odd height is split by the sign of the slope,
+        // which is also opposite for a mirrored edge.
+        let two = Coord::from(2);
+        let w2 = (right - l) + (right - r);
+        let area = match w2.cmp(&Coord::ONE) {
+            Less => height.mul_div_round(w2, two),
+            Greater => height - height.mul_div_round(two - w2, two),
+            Equal if self.inv_slope < Coord::ZERO => height - (height >> 1),
+            Equal => height >> 1,
+        };
+        let cov = *before + area;
+        *before = after;
+        cov
     }
 }
 
@@ -334,13 +897,70 @@ impl fmt::Debug for Fig {
     }
 }
 
+impl Default for Fig {
+    fn default() -> Self {
+        Fig::new()
+    }
+}
+
 impl Fig {
     /// Create a figure rasterizer
     pub fn new() -> Fig {
         let points = Vec::with_capacity(1024);
         let mut subs = Vec::with_capacity(16);
         subs.push(SubFig::new(Vid(0)));
-        Fig { points, subs }
+        Fig {
+            points,
+            subs,
+            max_winding: 0,
+            dropped: 0,
+            clip: Clipper::default(),
+            vids: vec![],
+            sorted: None,
+            edges: vec![],
+            table: vec![],
+            counts: vec![],
+        }
+    }
+
+    /// Reset to an empty figure, keeping allocated buffers.
+    pub fn reset(&mut self) {
+        self.points.clear();
+        self.subs.clear();
+        self.max_winding = 0;
+        self.dropped = 0;
+        self.clip = Clipper::default();
+        self.sorted = None;
+        self.subs.push(SubFig::new(Vid(0)));
+    }
+
+    /// Copy another figure, keeping allocated buffers.
+    ///
+    /// Sorted vertex IDs are copied too, so filling the copy within the same
+    /// area does not sort them again.
+    pub(crate) fn copy_from(&mut self, other: &Fig) {
+        self.points.clone_from(&other.points);
+        self.subs.clone_from(&other.subs);
+        self.max_winding = other.max_winding;
+        self.dropped = other.dropped;
+        self.clip = other.clip;
+        self.vids.clone_from(&other.vids);
+        self.sorted = other.sorted;
+    }
+
+    /// Sort vertex IDs for filling, ahead of time.
+    ///
+    /// No sub-figures are culled, so the order stays valid for any area, and
+    /// after translating.  The last sub-figure is closed, if it was left open.
+    pub(crate) fn sort_vids(&mut self) {
+        self.close();
+        let mut vids = std::mem::take(&mut self.vids);
+        self.visible_vids(None, &mut vids);
+        if !vids.is_empty() {
+            let dir = self.sort_by_position(&mut vids);
+            self.sorted = Some(Sorted { area: None, dir });
+        }
+        self.vids = vids;
     }
 
     /// Get the current sub-figure
@@ -372,25 +992,38 @@ impl Fig {
     /// Mark sub-figure done.
     fn sub_set_done(&mut self) {
         let sub = self.sub_current();
-        if sub.n_points > 0 {
+        if sub.n_points > 0 && !sub.done {
             let pt = self.point(sub.start);
             if self.is_coincident(pt) {
                 self.points.pop();
                 self.sub_current_mut().n_points -= 1;
             }
             self.sub_current_mut().done = true;
+            self.sorted = None;
+            let sub = self.sub_current();
+            let start = usize::from(sub.start);
+            let pts = &self.points[start..start + sub.n_points];
+            self.max_winding += count_peaks(pts);
         }
     }
 
+    /// Get an upper bound of winding number magnitude at any point.
+    ///
+    /// This is the total number of peaks (vertices above both neighbors) in
+    /// all sub-figures.  Each peak starts one rising and one falling chain of
+    /// edges, and a horizontal line crosses each chain at most once.
+    pub fn max_winding(&self) -> u32 {
+        self.max_winding
+    }
+
     /// Get the sub-figure at a specified vertex ID.
     fn sub_at(&self, vid: Vid) -> &SubFig {
-        for sub in self.subs.iter() {
-            if vid < sub.start + sub.n_points {
-                return sub;
-            }
-        }
+        // Sub-figures are ordered by starting vid
+        let i = self
+            .subs
+            .partition_point(|sub| sub.start + sub.n_points <= vid);
         // Invalid vid indicates bug
-        unreachable!();
+        &self.subs[i]
     }
 
     /// Get the next vertex.
@@ -418,29 +1051,207 @@ impl Fig {
     }
 
     /// Get Y value at a vertex.
-    fn get_y(&self, vid: Vid) -> Fixed {
+    fn get_y(&self, vid: Vid) -> Coord {
         self.point(vid).y
     }
 
     /// Add a point.
     ///
+    /// Points are clipped to 16,383 pixels from the origin (16,777,215 with
+    /// the `wide-coords` feature), keeping the winding within those bounds.
+    /// Geometry far off the raster is filled correctly where it is visible.
+    ///
     /// * `pt` Point to add.
     pub fn add_point<P: Into<Pt<f32>>>(&mut self, pt: P) {
+        let pt = pt.into();
+        if self.clip.pass(pt) {
+            self.push_point(pt);
+        } else {
+            let mut clip = self.clip;
+            clip.clip_point(0, pt, &mut |p| self.push_point(p));
+            self.clip = clip;
+        }
+    }
+
+    /// Add a point, or return an error if the figure is full.
+    ///
+    /// Points past the limit of about 16 million are dropped, and counted by
+    /// [dropped_points].  This returns [Error::TooManyPoints] if the point
+    /// (or any point added by clipping it) was dropped.
+    ///
+    /// * `pt` Point to add.
+    ///
+    /// ```rust
+    /// use footile::Fig;
+    ///
+    /// let mut fig = Fig::new();
+    /// assert!(fig.try_add_point((1.0, 2.0)).is_ok());
+    /// assert_eq!(fig.dropped_points(), 0);
+    /// ```
+    ///
+    /// [dropped_points]: struct.Fig.html#method.dropped_points
+    /// [Error::TooManyPoints]: enum.Error.html#variant.TooManyPoints
+    pub fn try_add_point<P: Into<Pt<f32>>>(
+        &mut self,
+        pt: P,
+    ) -> Result<(), Error> {
+        let dropped = self.dropped;
+        self.add_point(pt);
+        if self.dropped == dropped {
+            Ok(())
+        } else {
+            Err(Error::TooManyPoints)
+        }
+    }
+
+    /// Add a point with fixed-point coordinates.
+    ///
+    /// The point is added exactly, without converting to `f32`, so filling
+    /// is deterministic across platforms.  Points beyond 16,383 pixels from
+    /// the origin are clipped the same as [add_point].
+    ///
+    /// * `x` X coordinate, in pixels.
+    /// * `y` Y coordinate, in pixels.
+    ///
+    /// ```rust
+    /// use footile::{FillRule, Fig, Fixed, Paint};
+    /// use pix::{matte::Matte8, Raster};
+    ///
+    /// let mut raster = Raster::<Matte8>::with_clear(4, 4);
+    /// let mut sgn_area = vec![0i16; 4];
+    /// let mut fig = Fig::new();
+    /// let (a, b) = (Fixed::from(1), Fixed::from_bits(0x0003_4000));
+    /// fig.add_point_fixed(a, a);
+    /// fig.add_point_fixed(a, b);
+    /// fig.add_point_fixed(b, b);
+    /// fig.add_point_fixed(b, a);
+    /// let paint = Paint::new(Matte8::new(255));
+    /// fig.fill(FillRule::NonZero, &mut raster, paint, &mut sgn_area);
+    /// assert_eq!(raster.pixel(3, 2), Matte8::new(64));
+    /// ```
+    ///
+    /// [add_point]: struct.Fig.html#method.add_point
+    pub fn add_point_fixed(&mut self, x: Fixed, y: Fixed) {
+        let pt = Pt::new(f32::from(x), f32::from(y));
+        if self.clip.pass(pt) {
+            // Coord is Fixed, unless the wide-coords feature is enabled
+            #[allow(clippy::useless_conversion)]
+            self.push_fixed(FxPt::new(x.into(), y.into()));
+        } else {
+            let mut clip = self.clip;
+            clip.clip_point(0, pt, &mut |p| self.push_point(p));
+            self.clip = clip;
+        }
+    }
+
+    /// Clip the closing segment of the current sub-figure.
+    fn clip_close(&mut self) {
+        let mut clip = self.clip;
+        clip.close(&mut |p| self.push_point(p));
+        self.clip = clip;
+    }
+
+    /// Push a clipped point.
+    ///
+    /// * `pt` Point to push.
+    fn push_point(&mut self, pt: Pt<f32>) {
+        self.push_fixed(FxPt::new(Coord::from(pt.x()), Coord::from(pt.y())));
+    }
+
+    /// Push a clipped fixed-point point.
+    ///
+    /// * `pt` Point to push.
+    fn push_fixed(&mut self, pt: FxPt) {
         let n_pts = self.points.len();
         if n_pts < usize::from(Vid::MAX) {
             let done = self.sub_is_done();
             if done {
                 self.sub_add();
             }
-            let pt = pt.into();
-            let pt = FxPt::new(Fixed::from(pt.x()), Fixed::from(pt.y()));
             if done || !self.is_coincident(pt) {
                 self.points.push(pt);
                 self.sub_add_point();
+                self.sorted = None;
             }
+        } else {
+            self.dropped += 1;
         }
     }
 
+    /// Add a quadratic bézier spline, decomposed into a series of lines.
+    ///
+    /// * `a` Start point.
+    /// * `b` Control point.
+    /// * `c` End point.
+    /// * `tolerance` Maximum distance from the curve, in pixels.
+    pub fn add_quad<P: Into<Pt<f32>>>(
+        &mut self,
+        a: P,
+        b: P,
+        c: P,
+        tolerance: f32,
+    ) {
+        let (a, b, c) = (wide_pt(a), wide_pt(b), wide_pt(c));
+        self.add_point(a.0);
+        flatten_quad(a, b, c, tol_sq(tolerance), |p| self.add_point(p.0));
+    }
+
+    /// Add a cubic bézier spline, decomposed into a series of lines.
+    ///
+    /// * `a` Start point.
+    /// * `b` First control point.
+    /// * `c` Second control point.
+    /// * `d` End point.
+    /// * `tolerance` Maximum distance from the curve, in pixels.
+    ///
+    /// ```rust
+    /// use footile::{FillRule, Fig, Paint};
+    /// use pix::{matte::Matte8, Raster};
+    ///
+    /// let mut raster = Raster::<Matte8>::with_clear(32, 32);
+    /// let mut sgn_area = vec![0i16; 32];
+    /// let (left, right) = ((2.0, 16.0), (30.0, 16.0));
+    /// let mut fig = Fig::new();
+    /// fig.add_cubic(left, (2.0, -4.0), (30.0, -4.0), right, 0.25);
+    /// fig.add_cubic(right, (30.0, 36.0), (2.0, 36.0), left, 0.25);
+    /// fig.close();
+    /// let paint = Paint::new(Matte8::new(255));
+    /// fig.fill(FillRule::NonZero, &mut raster, paint, &mut sgn_area);
+    /// assert_eq!(raster.pixel(16, 16), Matte8::new(255));
+    /// ```
+    pub fn add_cubic<P: Into<Pt<f32>>>(
+        &mut self,
+        a: P,
+        b: P,
+        c: P,
+        d: P,
+        tolerance: f32,
+    ) {
+        let (a, b, c, d) = (wide_pt(a), wide_pt(b), wide_pt(c), wide_pt(d));
+        self.add_point(a.0);
+        flatten_cubic(a, b, c, d, tol_sq(tolerance), |p| self.add_point(p.0));
+    }
+
+    /// Check if the figure is over half full of points.
+    pub fn is_half_full(&self) -> bool {
+        self.points.len() > usize::from(Vid::MAX) / 2
+    }
+
+    /// Check if the figure has no room for more points.
+    pub fn is_full(&self) -> bool {
+        self.points.len() >= usize::from(Vid::MAX)
+    }
+
+    /// Get the number of points dropped because the figure was full.
+    ///
+    /// A figure holds about 16 million points; any added after that are
+    /// dropped, so the fill is truncated.  The count is cleared by [reset].
+    ///
+    /// [reset]: struct.Fig.html#method.reset
+    pub fn dropped_points(&self) -> usize {
+        self.dropped
+    }
+
     /// Check if a point is coincident with previous point.
     fn is_coincident(&self, pt: FxPt) -> bool {
         if let Some(p) = self.points.last() {
@@ -452,22 +1263,163 @@ impl Fig {
 
     /// Close the current sub-figure.
     ///
-    /// NOTE: This must be called before filling in order to handle coincident
-    ///       start/end points.
+    /// Points added after this start a new sub-figure.  Filling always treats
+    /// sub-figures as closed, and closes the last one if needed, trimming an
+    /// end point coincident with the start.
     pub fn close(&mut self) {
+        self.clip_close();
         if !self.points.is_empty() {
             self.sub_set_done();
         }
     }
 
-    /// Compare two figure vertex IDs
+    /// Translate all points by a whole number of pixels.
+    ///
+    /// * `dx` Amount to translate X.
+    /// * `dy` Amount to translate Y.
+    pub fn translate(&mut self, dx: i32, dy: i32) {
+        self.translate_fixed(Coord::from(dx), Coord::from(dy));
+    }
+
+    /// Translate all points by any number of pixels, including fractions.
+    ///
+    /// * `dx` Amount to translate X.
+    /// * `dy` Amount to translate Y.
+    pub(crate) fn offset(&mut self, dx: f32, dy: f32) {
+        self.translate_fixed(Coord::from(dx), Coord::from(dy));
+    }
+
+    /// Translate all points by fixed-point amounts.
+    ///
+    /// Translating does not change the order of vertices, but it can change
+    /// which sub-figures are culled, so only an unculled order is kept.
+    fn translate_fixed(&mut self, dx: Coord, dy: Coord) {
+        if let Some(Sorted { area: Some(_), .. }) = self.sorted {
+            self.sorted = None;
+        }
+        for p in self.points.iter_mut() {
+            p.x = p.x.saturating_add(dx);
+            p.y = p.y.saturating_add(dy);
+        }
+    }
+
+    /// Draw the figure as an SVG image, for debugging.
+    ///
+    /// Each sub-figure is outlined in a different color, with its vertex IDs
+    /// as labels; an open sub-figure is dashed.  The raster bounds are drawn
+    /// as a gray rectangle, and the view includes all points.
+    ///
+    /// * `width` Width of raster.
+    /// * `height` Height of raster.
+    pub fn to_debug_svg(&self, width: u32, height: u32) -> String {
+        let mut svg = String::new();
+        self.write_debug_svg(&mut svg, width, height)
+            .expect("write to string");
+        svg
+    }
+
+    /// Write an SVG image of the figure.
+    fn write_debug_svg(
+        &self,
+        svg: &mut String,
+        width: u32,
+        height: u32,
+    ) -> fmt::Result {
+        use fmt::Write;
+        const COLORS: [&str; 6] = [
+            "#d62728", "#1f77b4", "#2ca02c", "#9467bd", "#ff7f0e", "#17becf",
+        ];
+        let (w, h) = (width as f32, height as f32);
+        let (mut x0, mut y0, mut x1, mut y1) = (0.0f32, 0.0f32, w, h);
+        for p in &self.points {
+            let (x, y) = (f32::from(p.x), f32::from(p.y));
+            (x0, x1) = (x0.min(x), x1.max(x));
+            (y0, y1) = (y0.min(y), y1.max(y));
+        }
+        // Labels, lines and margins scale with the view, to stay legible
+        let size = (x1 - x0).max(y1 - y0).max(1.0) / 64.0;
+        let line = size / 8.0;
+        let (x0, y0) = (x0 - size * 2.0, y0 - size * 2.0);
+        let (vw, vh) = (x1 - x0 + size * 2.0, y1 - y0 + size * 2.0);
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+             viewBox=\"{x0} {y0} {vw} {vh}\" font-size=\"{size}\" \
+             fill=\"none\" stroke-width=\"{line}\">"
+        )?;
+        writeln!(svg, "<rect width=\"{w}\" height=\"{h}\" stroke=\"#888\"/>")?;
+        for (i, sub) in self.subs.iter().enumerate() {
+            let color = COLORS[i % COLORS.len()];
+            let start = usize::from(sub.start);
+            let pts = &self.points[start..start + sub.n_points];
+            let (elem, dash) = if sub.done {
+                ("polygon", String::new())
+            } else {
+                let dash = size / 2.0;
+                ("polyline", format!(" stroke-dasharray=\"{size} {dash}\""))
+            };
+            write!(svg, "<{elem} points=\"")?;
+            for (j, p) in pts.iter().enumerate() {
+                let sep = if j > 0 { " " } else { "" };
+                write!(svg, "{sep}{},{}", f32::from(p.x), f32::from(p.y))?;
+            }
+            writeln!(svg, "\" stroke=\"{color}\"{dash}/>")?;
+            for (j, p) in pts.iter().enumerate() {
+                let (x, y) = (f32::from(p.x), f32::from(p.y));
+                writeln!(
+                    svg,
+                    "<text x=\"{x}\" y=\"{y}\" fill=\"{color}\">{}</text>",
+                    start + j
+                )?;
+            }
+        }
+        writeln!(svg, "</svg>")
+    }
+
+    /// Compare two figure vertex IDs.
+    ///
+    /// Vertices are ordered by Y, then X, with ties broken by vertex ID, so
+    /// this is a total order.
     fn compare_vids(&self, v0: Vid, v1: Vid) -> Ordering {
         let p0 = self.point(v0);
         let p1 = self.point(v1);
         match p0.y.cmp(&p1.y) {
             Less => Less,
             Greater => Greater,
-            Equal => p0.x.cmp(&p1.x),
+            Equal => {
+                p0.x.cmp(&p1.x).then(usize::from(v0).cmp(&usize::from(v1)))
+            }
+        }
+    }
+
+    /// Get vertex IDs of all sub-figures visible within an area.
+    ///
+    /// With no area, vertex IDs of all sub-figures are included, except
+    /// those with fewer than 3 points.
+    ///
+    /// Sub-figures entirely outside the raster are culled.  Those to the left
+    /// can be culled too, since their signed area sums to zero.  So can those
+    /// with fewer than 3 points, which enclose no area.
+    fn visible_vids(&self, area: Option<(u32, u32)>, vids: &mut Vec<Vid>) {
+        vids.clear();
+        for sub in self.subs.iter().filter(|sub| sub.n_points >= 3) {
+            let start = usize::from(sub.start);
+            let end = start + sub.n_points;
+            let pts = &self.points[start..end];
+            let visible = match area {
+                Some((width, height)) => {
+                    let w = Coord::from(width as i32);
+                    let h = Coord::from(height as i32);
+                    pts.iter().any(|p| p.x < w)
+                        && pts.iter().any(|p| p.x > Coord::ZERO)
+                        && pts.iter().any(|p| p.y < h)
+                        && pts.iter().any(|p| p.y > Coord::ZERO)
+                }
+                None => true,
+            };
+            if visible {
+                vids.extend((start..end).map(Vid::from));
+            }
         }
     }
 
@@ -475,92 +1427,1005 @@ impl Fig {
     ///
     /// * `rule` Fill rule.
     /// * `raster` Output raster.
-    /// * `clr` Color to fill.
-    /// * `sgn_area` Signed area buffer.
-    pub fn fill<P>(
-        &self,
+    /// * `paint` Paint to fill.
+    /// * `sgn_area` Signed area buffer, at least the raster width (zeroed).
+    ///
+    /// Returns the region of pixels touched, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sgn_area` is too short, or too narrow for the paint's
+    /// coverage bits.
+    pub fn fill<P, A>(
+        &mut self,
         rule: FillRule,
         raster: &mut Raster<P>,
-        clr: P,
-        sgn_area: &mut [i16],
-    ) where
-        P: Pixel<Chan = Ch8, Alpha = Premultiplied, Gamma = Linear>,
+        paint: Paint<P>,
+        sgn_area: &mut [A],
+    ) -> Option<Region>
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+        P::Chan: BlendChannel,
+        A: SgnArea,
     {
-        assert!(raster.width() <= sgn_area.len() as u32);
-        let n_points = self.points.len();
-        if n_points > 0 {
-            assert!(self.sub_is_done());
-            let mut vids: Vec<Vid> = (0..n_points).map(Vid::from).collect();
-            vids.sort_by(|a, b| self.compare_vids(*a, *b));
-            let dir = self.get_dir(vids[0]);
-            let top_row = row_of(self.point(vids[0]).y);
-            let region = (0, top_row.max(0), raster.width(), raster.height());
-            let rows = raster.rows_mut(region);
-            let mut scan = Scanner::new(self, rule, dir, rows, clr, sgn_area);
-            scan.scan_vertices(vids, top_row);
-        }
+        let reg = raster.region();
+        let band_rows = band_rows(raster.width(), raster.height());
+        self.fill_bands(rule, raster, reg, paint, sgn_area, band_rows)
+    }
+
+    /// Fill the figure to a region of an image raster.
+    ///
+    /// Figure points are relative to the region origin, and anything outside
+    /// the region is clipped.
+    ///
+    /// * `rule` Fill rule.
+    /// * `raster` Output raster.
+    /// * `reg` Region of raster to fill.
+    /// * `paint` Paint to fill.
+    /// * `sgn_area` Signed area buffer, at least the region width (zeroed).
+    ///
+    /// Returns the region of pixels touched, if any.
+    pub fn fill_region<P, A>(
+        &mut self,
+        rule: FillRule,
+        raster: &mut Raster<P>,
+        reg: Region,
+        paint: Paint<P>,
+        sgn_area: &mut [A],
+    ) -> Option<Region>
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+        P::Chan: BlendChannel,
+        A: SgnArea,
+    {
+        let band_rows = band_rows(reg.width(), reg.height());
+        self.fill_bands(rule, raster, reg, paint, sgn_area, band_rows)
+    }
+
+    /// Fill the figure to an image raster in bands of rows.
+    ///
+    /// Every band scans vertices from the top of the figure, so results do not
+    /// depend on the number of rows.  With the `rayon` feature, bands are
+    /// filled in parallel, each with its own signed area buffer.
+    ///
+    /// * `rule` Fill rule.
+    /// * `raster` Output raster.
+    /// * `reg` Region of raster to fill.
+    /// * `paint` Paint to fill.
+    /// * `sgn_area` Signed area buffer.
+    /// * `band_rows` Number of rows in each band.
+    pub(crate) fn fill_bands<P, A>(
+        &mut self,
+        rule: FillRule,
+        raster: &mut Raster<P>,
+        reg: Region,
+        paint: Paint<P>,
+        sgn_area: &mut [A],
+        band_rows: usize,
+    ) -> Option<Region>
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+        P::Chan: BlendChannel,
+        A: SgnArea,
+    {
+        let (width, top, columns) = region_rows(raster, reg, paint.clip);
+        assert!(columns.len() <= sgn_area.len(), "signed area too short");
+        let sgn_area = &mut sgn_area[..columns.len()];
+        let order = self.scan_order(reg.width(), reg.height())?;
+        let y_top = self.top_row(&order).max(0) as usize;
+        let start = (top + y_top) * width;
+        let end = (top + reg.height() as usize) * width;
+        let clip = paint.clip.map(|c| &c.pixels()[start..end]);
+        let pixels = &mut raster.pixels_mut()[start..end];
+        #[cfg(feature = "rayon")]
+        if band_rows * width < pixels.len() {
+            use rayon::prelude::*;
+            let fig = &*self;
+            let dirty = pixels
+                .par_chunks_mut(band_rows * width)
+                .enumerate()
+                .map(|(i, pixels)| {
+                    let y = i * band_rows;
+                    let mut edges = vec![];
+                    let band = Band {
+                        y_top: (y_top + y) as i32,
+                        rows: Rows::Pixels {
+                            rows: pixels.chunks_mut(width),
+                            columns: columns.clone(),
+                        },
+                        clip: clip.map(|c| c[y * width..].chunks(width)),
+                        progress: None,
+                        metrics: None,
+                        edges: &mut edges,
+                    };
+                    let mut sgn_area = vec![A::default(); columns.len()];
+                    fig.fill_band(rule, &order, &paint, band, &mut sgn_area)
+                })
+                .reduce(|| None, dirty_union);
+            self.restore_order(order);
+            return dirty.map(|d| offset_region(d, reg));
+        }
+        let mut edges = std::mem::take(&mut self.edges);
+        let dirty = pixels
+            .chunks_mut(band_rows * width)
+            .enumerate()
+            .map(|(i, pixels)| {
+                let y = i * band_rows;
+                let band = Band {
+                    y_top: (y_top + y) as i32,
+                    rows: Rows::Pixels {
+                        rows: pixels.chunks_mut(width),
+                        columns: columns.clone(),
+                    },
+                    clip: clip.map(|c| c[y * width..].chunks(width)),
+                    progress: None,
+                    metrics: None,
+                    edges: &mut edges,
+                };
+                self.fill_band(rule, &order, &paint, band, sgn_area)
+            })
+            .fold(None, dirty_union);
+        self.restore_order(order);
+        self.edges = edges;
+        dirty.map(|d| offset_region(d, reg))
+    }
+
+    /// Fill the figure to an image raster in a single band of rows.
+    ///
+    /// * `rule` Fill rule.
+    /// * `raster` Output raster.
+    /// * `reg` Region of raster to fill.
+    /// * `paint` Paint to fill.
+    /// * `sgn_area` Signed area buffer, at least the region width (zeroed).
+    /// * `progress` Callback with current row, returning `false` to stop.
+    /// * `metrics` Metrics to collect.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fill_serial<P, A>(
+        &mut self,
+        rule: FillRule,
+        raster: &mut Raster<P>,
+        reg: Region,
+        paint: Paint<P>,
+        sgn_area: &mut [A],
+        progress: Option<&mut dyn FnMut(u32) -> bool>,
+        mut metrics: Option<&mut Metrics>,
+    ) -> Option<Region>
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+        P::Chan: BlendChannel,
+        A: SgnArea,
+    {
+        let (width, top, columns) = region_rows(raster, reg, paint.clip);
+        assert!(columns.len() <= sgn_area.len(), "signed area too short");
+        let sgn_area = &mut sgn_area[..columns.len()];
+        if let Some(m) = &mut metrics {
+            **m = Metrics {
+                points: self.points.len(),
+                sub_figs: self.subs.len(),
+                points_dropped: self.dropped,
+                ..Metrics::default()
+            };
+        }
+        let order = self.scan_order(reg.width(), reg.height())?;
+        let y_top = self.top_row(&order).max(0) as usize;
+        let start = (top + y_top) * width;
+        let end = (top + reg.height() as usize) * width;
+        let mut edges = std::mem::take(&mut self.edges);
+        let band = Band {
+            y_top: y_top as i32,
+            rows: Rows::Pixels {
+                rows: raster.pixels_mut()[start..end].chunks_mut(width),
+                columns,
+            },
+            clip: paint.clip.map(|c| c.pixels()[start..end].chunks(width)),
+            progress: progress.map(|p| p as &mut dyn FnMut(u32) -> bool),
+            metrics,
+            edges: &mut edges,
+        };
+        let dirty = self.fill_band(rule, &order, &paint, band, sgn_area);
+        self.restore_order(order);
+        self.edges = edges;
+        dirty.map(|d| offset_region(d, reg))
+    }
+
+    /// Get runs of equal coverage, without filling a raster.
+    ///
+    /// Coverage is unquantized, from 0 to 256 (full).  With `max_cov` of
+    /// 255, it is the same as filling a `Matte8` raster with full alpha.
+    ///
+    /// * `rule` Fill rule.
+    /// * `width` Width of area to scan.
+    /// * `height` Height of area to scan.
+    /// * `antialias` Anti-aliasing enabled.
+    /// * `max_cov` Maximum coverage reported (up to 256).
+    /// * `sgn_area` Signed area buffer, at least `width` long (zeroed).
+    /// * `spans` Callback with row, column, length and coverage of each run.
+    ///
+    /// Returns the region of pixels scanned.
+    #[allow(clippy::too_many_arguments)]
+    pub fn fill_spans<A: SgnArea>(
+        &mut self,
+        rule: FillRule,
+        width: u32,
+        height: u32,
+        antialias: bool,
+        max_cov: u16,
+        sgn_area: &mut [A],
+        spans: &mut dyn FnMut(i32, i32, u32, u16),
+    ) -> Option<Region> {
+        self.fill_spans_progress(
+            rule, width, height, antialias, 8, max_cov, sgn_area, None, spans,
+        )
+    }
+
+    /// Get runs of equal coverage, with a progress callback.
+    ///
+    /// This is the same as [fill_spans](#method.fill_spans), but with
+    /// coverage quantized to `coverage_bits`, and scanning stops if the
+    /// callback returns `false`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn fill_spans_progress<A: SgnArea>(
+        &mut self,
+        rule: FillRule,
+        width: u32,
+        height: u32,
+        antialias: bool,
+        coverage_bits: u8,
+        max_cov: u16,
+        sgn_area: &mut [A],
+        progress: Option<&mut dyn FnMut(u32) -> bool>,
+        spans: &mut dyn FnMut(i32, i32, u32, u16),
+    ) -> Option<Region> {
+        assert!(width <= sgn_area.len() as u32);
+        let mut dirty = None;
+        if let Some(order) = self.scan_order(width, height) {
+            let y_top = self.top_row(&order).max(0);
+            let mut edges = std::mem::take(&mut self.edges);
+            let band = Band {
+                y_top,
+                rows: Rows::Spans {
+                    width: width as i32,
+                    y_bottom: height as i32,
+                    max_cov,
+                    spans,
+                },
+                clip: None,
+                progress: progress.map(|p| p as &mut dyn FnMut(u32) -> bool),
+                metrics: None,
+                edges: &mut edges,
+            };
+            let mut paint = Paint::new(Matte8::new(255));
+            paint.antialias = antialias;
+            paint.coverage_bits = coverage_bits;
+            dirty = self.fill_band(rule, &order, &paint, band, sgn_area);
+            self.restore_order(order);
+            self.edges = edges;
+        }
+        dirty
+    }
+
+    /// Get rows of coverage, without filling a raster.
+    ///
+    /// Each row is the same as filling a `Matte8` raster with full alpha, one
+    /// byte per pixel.  Rows are passed to the callback from top to bottom,
+    /// and those with no coverage are skipped.
+    ///
+    /// * `rule` Fill rule.
+    /// * `width` Width of area to scan.
+    /// * `height` Height of area to scan.
+    /// * `sgn_area` Signed area buffer, at least `width` long (zeroed).
+    /// * `sink` Callback with row number and coverage of each row.
+    ///
+    /// Returns the region of pixels scanned.
+    pub fn fill_coverage<A: SgnArea>(
+        &mut self,
+        rule: FillRule,
+        width: u32,
+        height: u32,
+        sgn_area: &mut [A],
+        mut sink: impl FnMut(u32, &[u8]),
+    ) -> Option<Region> {
+        assert!(width <= sgn_area.len() as u32);
+        let mut dirty = None;
+        if let Some(order) = self.scan_order(width, height) {
+            let y_top = self.top_row(&order).max(0);
+            let mut edges = std::mem::take(&mut self.edges);
+            let band = Band {
+                y_top,
+                rows: Rows::Coverage {
+                    row: Some(Raster::with_clear(width, 1)),
+                    y_bottom: height as i32,
+                    sink: &mut sink,
+                },
+                clip: None,
+                progress: None,
+                metrics: None,
+                edges: &mut edges,
+            };
+            let paint = Paint::new(Matte8::new(255));
+            dirty = self.fill_band(rule, &order, &paint, band, sgn_area);
+            self.restore_order(order);
+            self.edges = edges;
+        }
+        dirty
+    }
+
+    /// Get the order to scan the figure within an area.
+    ///
+    /// The last sub-figure is closed, if it was left open.
+    ///
+    /// Vertex IDs are sorted, unless the figure has at least `TABLE_POINTS`
+    /// points; then edges are bucketed by starting row instead, which takes
+    /// linear time.  Buffers are taken from the figure, and should be
+    /// restored after filling.  Vertex IDs are only sorted again if the
+    /// figure or area has changed since the last fill.
+    fn scan_order(&mut self, width: u32, height: u32) -> Option<ScanOrder> {
+        self.close();
+        if self.points.is_empty() {
+            return None;
+        }
+        if let Some(sorted) = self.sorted {
+            let valid = match sorted.area {
+                Some(area) => area == (width, height),
+                None => true,
+            };
+            if valid && !self.vids.is_empty() {
+                let vids = std::mem::take(&mut self.vids);
+                return Some(ScanOrder::Vertices(sorted.dir, vids));
+            }
+        }
+        if self.points.len() >= TABLE_POINTS {
+            let table = self.edge_table(height);
+            if table.is_empty() {
+                self.table = table;
+                return None;
+            }
+            return Some(ScanOrder::Edges(table));
+        }
+        let mut vids = std::mem::take(&mut self.vids);
+        let area = Some((width, height));
+        self.visible_vids(area, &mut vids);
+        if vids.is_empty() {
+            self.vids = vids;
+            return None;
+        }
+        let dir = self.sort_by_position(&mut vids);
+        self.sorted = Some(Sorted { area, dir });
+        Some(ScanOrder::Vertices(dir, vids))
+    }
+
+    /// Restore buffers taken for a scan order.
+    fn restore_order(&mut self, order: ScanOrder) {
+        match order {
+            ScanOrder::Vertices(_, vids) => self.vids = vids,
+            ScanOrder::Edges(table) => self.table = table,
+        }
+    }
+
+    /// Get the top row of a scan order.
+    fn top_row(&self, order: &ScanOrder) -> i32 {
+        match order {
+            ScanOrder::Vertices(_, vids) => row_of(self.point(vids[0]).y),
+            ScanOrder::Edges(table) => table[0].row(),
+        }
+    }
+
+    /// Get edges of all sub-figures which can cover rows of an area.
+    ///
+    /// Sub-figures with fewer than 3 points are skipped, as when sorting
+    /// vertex IDs.
+    fn row_edges(&self, height: i32) -> impl Iterator<Item = RowEdge> + '_ {
+        self.subs
+            .iter()
+            .filter(|sub| sub.n_points >= 3)
+            .flat_map(move |sub| {
+                let start = usize::from(sub.start);
+                let pts = &self.points[start..start + sub.n_points];
+                let next = pts[1..].iter().chain(&pts[..1]);
+                pts.iter()
+                    .zip(next)
+                    .filter_map(|(p0, p1)| RowEdge::new(*p0, *p1))
+                    .filter(move |e| e.covers(height))
+            })
+    }
+
+    /// Build a table of edges ordered by starting row, within an area.
+    ///
+    /// Edges are bucketed with a counting sort, so there is no need to sort
+    /// vertices.
+    fn edge_table(&mut self, height: u32) -> Vec<RowEdge> {
+        let height = height as i32;
+        let mut table = std::mem::take(&mut self.table);
+        table.clear();
+        let row_min = match self.row_edges(height).map(|e| e.row()).min() {
+            Some(row) => row,
+            None => return table,
+        };
+        // Count edges starting on each row, offset by one
+        let mut counts = std::mem::take(&mut self.counts);
+        counts.clear();
+        counts.resize((height - row_min) as usize + 1, 0);
+        for e in self.row_edges(height) {
+            counts[(e.row() - row_min) as usize + 1] += 1;
+        }
+        // Convert to index of first edge on each row
+        for i in 1..counts.len() {
+            counts[i] += counts[i - 1];
+        }
+        table.resize(counts[counts.len() - 1], RowEdge::default());
+        for e in self.row_edges(height) {
+            let i = &mut counts[(e.row() - row_min) as usize];
+            table[*i] = e;
+            *i += 1;
+        }
+        self.counts = counts;
+        table
+    }
+
+    /// Sort vertex IDs by position, and get the figure direction.
+    fn sort_by_position(&self, vids: &mut [Vid]) -> FigDir {
+        // Unstable sort does not allocate; the order is total, so it is
+        // still deterministic
+        vids.sort_unstable_by(|a, b| self.compare_vids(*a, *b));
+        // Direction is from top vertex of whole figure, even if culled
+        let top = (0..self.points.len())
+            .map(Vid::from)
+            .min_by(|a, b| self.compare_vids(*a, *b))
+            .unwrap();
+        self.get_dir(top)
+    }
+
+    /// Fill the figure to a band of raster rows.
+    fn fill_band<'a, P, A>(
+        &'a self,
+        rule: FillRule,
+        order: &ScanOrder,
+        paint: &Paint<P>,
+        band: Band<'a, P>,
+        sgn_area: &'a mut [A],
+    ) -> Option<Region>
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+        P::Chan: BlendChannel,
+        A: SgnArea,
+    {
+        // Edges may remain from a previous fill which was stopped
+        band.edges.clear();
+        let dir = match order {
+            ScanOrder::Vertices(dir, _) => *dir,
+            ScanOrder::Edges(_) => FigDir::Forward,
+        };
+        let mut scan = Scanner::new(rule, dir, paint, band, sgn_area);
+        let top_row = self.top_row(order);
+        match order {
+            ScanOrder::Vertices(_, vids) => {
+                scan.scan_vertices(self, vids, top_row)
+            }
+            ScanOrder::Edges(table) => {
+                scan.scan_edges(table, top_row..i32::MAX)
+            }
+        }
+        scan.dirty
     }
 }
 
-impl<'a, P> Scanner<'a, P>
+impl EdgeStream {
+    /// Create a figure stream, counting edges on the first pass.
+    ///
+    /// * `height` Height of area to fill.
+    /// * `dx` Offset X added to clipped points, in pixels.
+    /// * `dy` Offset Y added to clipped points, in pixels.
+    pub(crate) fn new(height: u32, dx: i32, dy: i32) -> Self {
+        let height = height as i32;
+        // Clipped points are no more than CLIP_MAX above the origin
+        let row_min = dy - CLIP_MAX as i32 - 1;
+        let counts = vec![0; (height - row_min).max(0) as usize];
+        EdgeStream {
+            origin: FxPt::new(Coord::from(dx), Coord::from(dy)),
+            height,
+            row_min,
+            clip: Clipper::default(),
+            first: None,
+            prev: FxPt::default(),
+            first_down: None,
+            down: false,
+            max_winding: 0,
+            n_points: 0,
+            n_subs: 0,
+            counts,
+            band: None,
+            offsets: vec![],
+            starts: vec![],
+            edges: vec![],
+        }
+    }
+
+    /// Get an upper bound of winding number magnitude.
+    ///
+    /// This is only valid after the first pass.
+    pub(crate) fn max_winding(&self) -> u32 {
+        self.max_winding
+    }
+
+    /// Get metrics counted on the first pass, before any band is filled.
+    pub(crate) fn metrics(&self) -> Metrics {
+        Metrics {
+            points: self.n_points,
+            sub_figs: self.n_subs,
+            ..Metrics::default()
+        }
+    }
+
+    /// Check if points are being counted on the first pass.
+    fn is_counting(&self) -> bool {
+        self.band.is_none()
+    }
+
+    /// Add a point.
+    ///
+    /// Points are clipped the same as [Fig.add_point].
+    ///
+    /// [Fig.add_point]: struct.Fig.html#method.add_point
+    pub(crate) fn add_point(&mut self, pt: Pt<f32>) {
+        if self.clip.pass(pt) {
+            self.push_point(pt);
+        } else {
+            let mut clip = self.clip;
+            clip.clip_point(0, pt, &mut |p| self.push_point(p));
+            self.clip = clip;
+        }
+    }
+
+    /// Push a clipped point, adding an edge from the previous point.
+    fn push_point(&mut self, pt: Pt<f32>) {
+        let pt = FxPt::new(Coord::from(pt.x()), Coord::from(pt.y()));
+        let pt = FxPt::new(
+            pt.x.saturating_add(self.origin.x),
+            pt.y.saturating_add(self.origin.y),
+        );
+        match self.first {
+            Some(_) if pt == self.prev => return,
+            Some(_) => self.add_edge(self.prev, pt),
+            None => {
+                self.first = Some(pt);
+                if self.is_counting() {
+                    self.n_subs += 1;
+                }
+            }
+        }
+        if self.is_counting() {
+            self.n_points += 1;
+        }
+        self.prev = pt;
+    }
+
+    /// Close the current sub-figure.
+    pub(crate) fn close(&mut self) {
+        let mut clip = self.clip;
+        clip.close(&mut |p| self.push_point(p));
+        self.clip = clip;
+        if let Some(first) = self.first.take() {
+            self.add_edge(self.prev, first);
+            if let Some(first_down) = self.first_down.take() {
+                // Closing a sub-figure may join one more peak
+                if first_down && !self.down && self.is_counting() {
+                    self.max_winding += 1;
+                }
+            }
+        }
+    }
+
+    /// Add an edge between two points, in figure order.
+    ///
+    /// Horizontal edges, and edges which cannot cover any row of the area,
+    /// are skipped.
+    fn add_edge(&mut self, p0: FxPt, p1: FxPt) {
+        let edge = match RowEdge::new(p0, p1) {
+            Some(edge) => edge,
+            None => return,
+        };
+        let down = edge.dir == FigDir::Forward;
+        if self.first_down.is_none() {
+            self.first_down = Some(down);
+        } else if down && !self.down && self.is_counting() {
+            self.max_winding += 1;
+        }
+        self.down = down;
+        if !edge.covers(self.height) {
+            return;
+        }
+        let row = edge.row();
+        debug_assert!(row >= self.row_min);
+        match &self.band {
+            None => self.counts[(row - self.row_min) as usize] += 1,
+            Some(band) if band.contains(&row) => {
+                let i = (row - band.start) as usize;
+                let j = self.offsets[i];
+                self.offsets[i] += 1;
+                self.starts[j] = edge;
+            }
+            Some(_) => (),
+        }
+    }
+
+    /// Begin the next band of rows, to stream points again.
+    ///
+    /// Bands are at least one row, with up to `STREAM_EDGES` starting edges.
+    /// The last band extends to the bottom of the area, so that active edges
+    /// are scanned until they end.
+    ///
+    /// Returns `false` after the last band.
+    pub(crate) fn next_band(&mut self) -> bool {
+        let row_end = self.height;
+        let row = match &self.band {
+            Some(band) => band.end,
+            None => {
+                // Rows above the first starting edge are empty
+                match self.counts.iter().position(|c| *c > 0) {
+                    Some(i) => self.row_min + i as i32,
+                    None => row_end,
+                }
+            }
+        };
+        if row >= row_end {
+            self.band = Some(row_end..row_end);
+            return false;
+        }
+        let mut n_edges = 0;
+        let mut end = row;
+        self.offsets.clear();
+        for count in &self.counts[(row - self.row_min) as usize..] {
+            let count = *count as usize;
+            if n_edges > 0 && n_edges + count > STREAM_EDGES {
+                break;
+            }
+            self.offsets.push(n_edges);
+            n_edges += count;
+            end += 1;
+        }
+        if self.counts[(end - self.row_min) as usize..]
+            .iter()
+            .all(|c| *c == 0)
+        {
+            end = row_end;
+            self.offsets.resize((end - row) as usize, n_edges);
+        }
+        self.starts.clear();
+        self.starts.resize(n_edges, RowEdge::default());
+        self.band = Some(row..end);
+        true
+    }
+
+    /// Fill the current band of rows to a region of an image raster.
+    ///
+    /// * `rule` Fill rule.
+    /// * `raster` Output raster.
+    /// * `reg` Region of raster to fill.
+    /// * `paint` Paint to fill.
+    /// * `sgn_area` Signed area buffer, at least the region width (zeroed).
+    /// * `metrics` Metrics to collect, added to previous bands.
+    ///
+    /// Returns the region of pixels touched, if any.
+    pub(crate) fn fill_band<P, A>(
+        &mut self,
+        rule: FillRule,
+        raster: &mut Raster<P>,
+        reg: Region,
+        paint: Paint<P>,
+        sgn_area: &mut [A],
+        metrics: Option<&mut Metrics>,
+    ) -> Option<Region>
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+        P::Chan: BlendChannel,
+        A: SgnArea,
+    {
+        let rows = self.band.clone()?;
+        let (width, top, columns) = region_rows(raster, reg, paint.clip);
+        assert!(columns.len() <= sgn_area.len(), "signed area too short");
+        let sgn_area = &mut sgn_area[..columns.len()];
+        let y_top = rows.start.max(0);
+        let start = (top + y_top as usize) * width;
+        let end = (top + rows.end.max(0) as usize) * width;
+        let band = Band {
+            y_top,
+            rows: Rows::Pixels {
+                rows: raster.pixels_mut()[start..end].chunks_mut(width),
+                columns,
+            },
+            clip: paint.clip.map(|c| c.pixels()[start..end].chunks(width)),
+            progress: None,
+            metrics,
+            edges: &mut self.edges,
+        };
+        let starts = &self.starts;
+        let mut scan =
+            Scanner::new(rule, FigDir::Forward, &paint, band, sgn_area);
+        scan.scan_edges(starts, rows);
+        scan.dirty.map(|d| offset_region(d, reg))
+    }
+}
+
+/// Get the number of rows in each band to fill.
+///
+/// Small rasters are filled in one band, since threading overhead would
+/// dominate.
+#[cfg(feature = "rayon")]
+fn band_rows(width: u32, height: u32) -> usize {
+    let (width, height) = (width as usize, height as usize);
+    if height >= BAND_ROWS * 2 && width * height >= BAND_PIXELS {
+        let n_threads = rayon::current_num_threads();
+        BAND_ROWS.max(height.div_ceil(n_threads))
+    } else {
+        height.max(1)
+    }
+}
+
+/// Get the number of rows in each band to fill.
+#[cfg(not(feature = "rayon"))]
+fn band_rows(_width: u32, height: u32) -> usize {
+    (height as usize).max(1)
+}
+
+impl<'a, P> Paint<'a, P> {
+    /// Create a new paint with a solid color.
+    pub fn new(clr: P) -> Self {
+        Paint {
+            clr,
+            blend: BlendMode::SrcOver,
+            opacity: 1.0,
+            antialias: true,
+            clip: None,
+            coverage_bits: 8,
+        }
+    }
+}
+
+impl<'a, P, A> Scanner<'a, P, A>
 where
-    P: Pixel<Chan = Ch8, Alpha = Premultiplied, Gamma = Linear>,
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+    A: SgnArea,
 {
-    /// Create a new figure scanner.
+    /// Create a new scanner for a band of rows.
     fn new(
-        fig: &'a Fig,
         rule: FillRule,
         dir: FigDir,
-        rows: RowsMut<'a, P>,
-        clr: P,
-        sgn_area: &'a mut [i16],
-    ) -> Scanner<'a, P> {
-        let edges = Vec::with_capacity(16);
+        paint: &Paint<P>,
+        band: Band<'a, P>,
+        sgn_area: &'a mut [A],
+    ) -> Self {
+        let bits = u32::from(paint.coverage_bits);
+        assert!(bits <= A::MAX_BITS, "signed area too narrow");
         Scanner {
-            fig,
             rule,
             dir,
-            rows,
-            clr,
+            y_top: band.y_top,
+            rows: band.rows,
+            clr: paint.clr,
+            blend: paint.blend,
+            opacity: P::Chan::from(paint.opacity),
+            antialias: paint.antialias,
+            bits,
+            clip: band.clip,
+            progress: band.progress,
+            metrics: band.metrics,
             sgn_area,
-            edges,
+            edges: band.edges,
+            span: Span::default(),
+            dirty: None,
         }
     }
 
-    /// Get Y value at a vertex.
-    fn get_y(&self, vid: Vid) -> Fixed {
-        self.fig.get_y(vid)
-    }
-
-    /// Scan all vertices in order.
-    fn scan_vertices(&mut self, vids: Vec<Vid>, top_row: i32) {
+    /// Scan all vertices of a figure in order.
+    ///
+    /// Rows above the band are not scanned, but edges are still updated.
+    fn scan_vertices(&mut self, fig: &Fig, vids: &[Vid], top_row: i32) {
         let mut vids = vids.iter().peekable();
         let mut y_row = top_row;
-        while let Some(row_buf) = self.rows.next() {
-            self.scan_continuing_edges(y_row);
+        while vids.peek().is_some() || !self.edges.is_empty() {
+            if self.edges.is_empty() {
+                if let Some(vid) = vids.peek() {
+                    let y_vtx = row_of(fig.get_y(**vid));
+                    y_row = self.skip_rows(y_row, y_vtx);
+                }
+            }
+            let row = if y_row < self.y_top {
+                None
+            } else {
+                match self.next_row(y_row) {
+                    Some(row) => Some(row),
+                    None => break,
+                }
+            };
+            let scan = row.is_some();
+            // Signed area is zeroed at start of each row, so it's safe to stop
+            if scan && !self.check_progress(y_row) {
+                break;
+            }
+            if scan {
+                self.count(|m| m.rows_scanned += 1);
+                self.scan_continuing_edges(y_row);
+            }
+            let mut updated = false;
             while let Some(vid) = vids.peek() {
-                let y_vtx = self.get_y(**vid);
+                let y_vtx = fig.get_y(**vid);
                 if row_of(y_vtx) > y_row {
                     break;
                 }
                 let vid = *vids.next().unwrap();
-                self.update_edges(vid, FigDir::Forward);
-                self.update_edges(vid, FigDir::Reverse);
+                self.update_edges(fig, vid, FigDir::Forward, scan);
+                self.update_edges(fig, vid, FigDir::Reverse, scan);
+                updated = true;
             }
-            self.rasterize_row(row_buf);
-            self.advance_edges();
+            // Edges can only end on a row with vertices
+            if updated {
+                self.remove_ending_edges(y_row);
+            }
+            self.finish_row(y_row, row);
             y_row += 1;
         }
     }
 
+    /// Scan rows with a table of edges, ordered by starting row.
+    ///
+    /// Active edges are kept after the last row, to continue in the next
+    /// band of a streamed figure.  Rows above the band are not scanned, but
+    /// edges are still advanced.
+    ///
+    /// * `starts` Edges starting within the rows, ordered by row.
+    /// * `rows` Rows to scan.
+    fn scan_edges(&mut self, starts: &[RowEdge], rows: Range<i32>) {
+        let mut starts = starts.iter().peekable();
+        let mut y_row = rows.start;
+        while y_row < rows.end {
+            if self.edges.is_empty() {
+                match starts.peek() {
+                    Some(e) => y_row = self.skip_rows(y_row, e.row()),
+                    None => break,
+                }
+            }
+            let row = if y_row < self.y_top {
+                None
+            } else {
+                match self.next_row(y_row) {
+                    Some(row) => Some(row),
+                    None => break,
+                }
+            };
+            let scan = row.is_some();
+            // Signed area is zeroed at start of each row, so it's safe to stop
+            if scan && !self.check_progress(y_row) {
+                break;
+            }
+            if scan {
+                self.count(|m| m.rows_scanned += 1);
+                self.scan_continuing_edges(y_row);
+            }
+            while let Some(e) = starts.next_if(|e| e.row() <= y_row) {
+                self.add_edge(e.upper, e.lower, e.dir, scan);
+            }
+            // Edges can end on rows where none start, at bottom vertices
+            self.remove_ending_edges(y_row);
+            self.finish_row(y_row, row);
+            y_row += 1;
+        }
+    }
+
+    /// Finish a row after updating edges, and advance to the next row.
+    ///
+    /// * `y_row` Current row.
+    /// * `row` Destination row and width, if scanned.
+    fn finish_row(
+        &mut self,
+        y_row: i32,
+        row: Option<(Option<&'a mut [P]>, i32)>,
+    ) {
+        let n_edges = self.edges.len();
+        self.count(|m| m.max_active_edges = m.max_active_edges.max(n_edges));
+        if let Some((row_buf, width)) = row {
+            let span = self.span.pixels(width);
+            if let Some((x0, x1)) = span {
+                self.add_dirty(x0, x1, y_row);
+            }
+            match row_buf {
+                Some(row_buf) => self.rasterize_row(row_buf, span),
+                None if matches!(self.rows, Rows::Coverage { .. }) => {
+                    self.emit_coverage(y_row, span)
+                }
+                None => self.emit_spans(y_row, span),
+            }
+        }
+        self.span = Span::default();
+        self.advance_edges();
+    }
+
+    /// Skip empty rows, with no active edges.
+    ///
+    /// Rows with a progress callback are not skipped.
+    ///
+    /// * `y_row` Current row.
+    /// * `y_vtx` Row of next vertex.
+    ///
+    /// Returns the next row to scan.
+    fn skip_rows(&mut self, y_row: i32, y_vtx: i32) -> i32 {
+        let y_next = match self.progress {
+            Some(_) => {
+                let y_progress =
+                    (y_row.div_euclid(PROGRESS_ROWS) + 1) * PROGRESS_ROWS;
+                y_vtx.min(y_progress)
+            }
+            None => y_vtx,
+        };
+        // Only rows within the band have destination rows to drop
+        let n_rows = y_next - y_row.max(self.y_top);
+        if n_rows > 0 {
+            let n = n_rows as usize - 1;
+            if let Rows::Pixels { rows, .. } = &mut self.rows {
+                rows.nth(n);
+            }
+            if let Some(clip) = &mut self.clip {
+                clip.nth(n);
+            }
+        }
+        y_row.max(y_next)
+    }
+
+    /// Get the next destination row, with its width.
+    ///
+    /// The row buffer is `None` when emitting spans or coverage rows.
+    fn next_row(&mut self, y_row: i32) -> Option<(Option<&'a mut [P]>, i32)> {
+        match &mut self.rows {
+            Rows::Pixels { rows, columns } => {
+                let row_buf = &mut rows.next()?[columns.clone()];
+                let width = row_buf.len() as i32;
+                Some((Some(row_buf), width))
+            }
+            Rows::Spans {
+                width, y_bottom, ..
+            } => (y_row < *y_bottom).then_some((None, *width)),
+            Rows::Coverage { row, y_bottom, .. } => {
+                let width = row.as_ref().map_or(0, |r| r.width() as i32);
+                (y_row < *y_bottom).then_some((None, width))
+            }
+        }
+    }
+
+    /// Check progress callback, returning `false` to stop.
+    fn check_progress(&mut self, y_row: i32) -> bool {
+        match &mut self.progress {
+            Some(progress) if y_row % PROGRESS_ROWS == 0 => {
+                progress(y_row as u32)
+            }
+            _ => true,
+        }
+    }
+
+    /// Update metrics counters, if enabled.
+    fn count(&mut self, f: impl FnOnce(&mut Metrics)) {
+        if let Some(metrics) = &mut self.metrics {
+            f(metrics);
+        }
+    }
+
+    /// Add pixels on a row to the dirty region.
+    fn add_dirty(&mut self, x0: i32, x1: i32, y_row: i32) {
+        let width = (x1 - x0 + 1) as u32;
+        let dirty = Region::new(x0, y_row, width, 1);
+        self.dirty = Some(match self.dirty {
+            Some(d) => region_union(d, dirty),
+            None => dirty,
+        });
+    }
+
     /// Scan edges continuing on this row.
     fn scan_continuing_edges(&mut self, y_row: i32) {
         let area = &mut self.sgn_area;
         for e in self.edges.iter_mut() {
-            let cov = e.continuing_cov(y_row);
+            let cov = e.continuing_cov(y_row, self.bits);
             if cov > 0 {
                 e.calculate_x_limits_continuing(y_row);
-                e.scan_area(self.dir, cov, area);
+                e.scan_area(self.dir, cov, self.bits, area, &mut self.span);
             }
         }
     }
@@ -568,149 +2433,392 @@ where
     /// Advance all edges to the next row.
     fn advance_edges(&mut self) {
         for e in self.edges.iter_mut() {
-            e.x_bot = e.x_bot + e.inv_slope;
+            e.x_top = e.x_bot;
+            e.x_bot = e.x_bot.saturating_add(e.inv_slope);
         }
     }
 
     /// Update edges at a given vertex.
-    fn update_edges(&mut self, vid: Vid, dir: FigDir) {
-        let v = self.fig.next(vid, dir);
-        if v != vid {
-            let y = self.get_y(vid);
-            match self.get_y(v).cmp(&y) {
-                Greater => self.add_edge(vid, v, dir),
-                Less => self.remove_edge(vid, dir.opposite()),
-                _ => (),
-            }
+    ///
+    /// Edges ending at the vertex are removed after all vertices on the row
+    /// have been updated.  Horizontal edges are skipped, since they enclose
+    /// no signed area; partial rows at horizontal boundaries are covered by
+    /// the starting and ending coverage of adjacent edges.
+    ///
+    /// * `scan` Scan area of added edges on current row.
+    fn update_edges(&mut self, fig: &Fig, vid: Vid, dir: FigDir, scan: bool) {
+        let v = fig.next(vid, dir);
+        if v != vid && fig.get_y(v) > fig.get_y(vid) {
+            self.add_edge(fig.point(vid), fig.point(v), dir, scan);
         }
     }
 
     /// Add an edge.
-    fn add_edge(&mut self, v0: Vid, v1: Vid, dir: FigDir) {
-        let fig = &self.fig;
-        let p0 = fig.point(v0); // Upper point
-        let p1 = fig.point(v1); // Lower point
-        let mut e = Edge::new(v0, v1, p0, p1, dir);
-        let cov = e.starting_cov();
-        if cov > 0 {
+    ///
+    /// * `p0` Upper point.
+    /// * `p1` Lower point.
+    /// * `dir` Direction from upper to lower point.
+    /// * `scan` Scan area of the edge on current row.
+    fn add_edge(&mut self, p0: FxPt, p1: FxPt, dir: FigDir, scan: bool) {
+        let mut e = Edge::new(p0, p1, dir);
+        let cov = e.starting_cov(self.bits);
+        if scan && cov > 0 {
             e.calculate_x_limits_starting();
-            e.scan_area(self.dir, cov, self.sgn_area);
+            let area = &mut self.sgn_area;
+            e.scan_area(self.dir, cov, self.bits, area, &mut self.span);
         }
         self.edges.push(e);
+        self.count(|m| m.edges_added += 1);
+    }
+
+    /// Remove edges ending on a row, after their area has been scanned.
+    fn remove_ending_edges(&mut self, y_row: i32) {
+        self.edges.retain(|e| !e.is_ending(y_row));
     }
 
-    /// Remove an edge.
-    fn remove_edge(&mut self, v1: Vid, dir: FigDir) {
-        if let Some(i) = self.find_edge(v1, dir) {
-            self.edges.swap_remove(i);
+    /// Rasterize the current row.
+    /// Signed area is zeroed upon return.
+    ///
+    /// * `row_buf` Destination row.
+    /// * `span` Range of pixels which may be covered.
+    fn rasterize_row(&mut self, row_buf: &mut [P], span: Option<(i32, i32)>) {
+        let clip = self.clip.as_mut().and_then(|rows| rows.next());
+        // Signed area outside of span is always zero
+        let (x0, x1) = match span {
+            Some((x0, x1)) => (x0 as usize, x1 as usize + 1),
+            None => return,
+        };
+        self.count(|m| m.pixels_composited += x1 - x0);
+        let dst = &mut row_buf[x0..x1];
+        // Clip rows are full width, so offset to the region columns
+        let x_off = match &self.rows {
+            Rows::Pixels { columns, .. } => columns.start,
+            Rows::Spans { .. } | Rows::Coverage { .. } => 0,
+        };
+        let clip = clip.map(|c| &c[x_off + x0..x_off + x1]);
+        match self.rule {
+            FillRule::NonZero => self.scan_non_zero(dst, x0, clip),
+            FillRule::EvenOdd => self.scan_even_odd(dst, x0, clip),
         }
     }
 
-    /// Find an active edge
-    fn find_edge(&self, v1: Vid, dir: FigDir) -> Option<usize> {
-        for (i, e) in self.edges.iter().enumerate() {
-            if v1 == e.v1 && dir == e.dir {
-                return Some(i);
+    /// Emit runs of equal coverage on the current row.
+    /// Signed area is zeroed upon return.
+    ///
+    /// * `y_row` Current row.
+    /// * `span` Range of pixels which may be covered.
+    fn emit_spans(&mut self, y_row: i32, span: Option<(i32, i32)>) {
+        let (x0, x1) = match span {
+            Some((x0, x1)) => (x0 as usize, x1 as usize + 1),
+            None => return,
+        };
+        let (max_cov, spans) = match &mut self.rows {
+            Rows::Spans { max_cov, spans, .. } => (*max_cov, spans),
+            _ => unreachable!(),
+        };
+        let mut emit = |x: usize, len: usize, cov: u16| {
+            if cov > 0 {
+                spans(y_row, x as i32, len as u32, cov);
+            }
+        };
+        let mut start = x0;
+        let mut run_cov = 0;
+        let mut sum = A::default();
+        for (x, s) in self.sgn_area[x0..x1].iter_mut().enumerate() {
+            let x = x0 + x;
+            sum = sum.wrapping_add(*s);
+            *s = A::default();
+            let cov = sum_cov(self.rule, sum.to_i32(), self.bits);
+            let cov = raw_coverage(cov, self.bits, self.antialias).min(max_cov);
+            if cov != run_cov {
+                emit(start, x - start, run_cov);
+                start = x;
+                run_cov = cov;
             }
         }
-        None
+        emit(start, x1 - start, run_cov);
     }
 
-    /// Rasterize the current row.
-    /// Signed area is zeroed upon return.
-    fn rasterize_row(&mut self, row_buf: &mut [P]) {
-        match self.rule {
-            FillRule::NonZero => self.scan_non_zero(row_buf),
-            FillRule::EvenOdd => self.scan_even_odd(row_buf),
+    /// Rasterize the current row to the coverage buffer, and pass it to the
+    /// row callback.
+    ///
+    /// * `y_row` Current row.
+    /// * `span` Range of pixels which may be covered.
+    fn emit_coverage(&mut self, y_row: i32, span: Option<(i32, i32)>) {
+        let (x0, x1) = match span {
+            Some((x0, x1)) => (x0 as usize, x1 as usize + 1),
+            None => return,
+        };
+        let mut row = match &mut self.rows {
+            Rows::Coverage { row, .. } => row.take().expect("row buffer"),
+            _ => unreachable!(),
+        };
+        self.rasterize_row(row.pixels_mut(), span);
+        if let Rows::Coverage { row: buf, sink, .. } = &mut self.rows {
+            sink(y_row as u32, row.as_u8_slice());
+            row.pixels_mut()[x0..x1].fill(P::default());
+            *buf = Some(row);
         }
     }
 
+    /// Check if a solid color fast path can be used.
+    fn is_fast(&self, clip: Option<&[Matte8]>) -> bool {
+        self.bits == 8
+            && clip.is_none()
+            && self.blend == BlendMode::SrcOver
+            && self.opacity == P::Chan::MAX
+            && self.antialias
+    }
+
     /// Accumulate scan area with non-zero fill rule.
-    fn scan_non_zero(&mut self, dst: &mut [P]) {
-        let clr = self.clr;
-        let sgn_area = &mut self.sgn_area;
-        if TypeId::of::<P>() == TypeId::of::<Matte8>() {
-            // FIXME: only if clr is Matte8::new(255)
-            matte_src_over_non_zero(dst, sgn_area);
-            return;
+    ///
+    /// * `dst` Destination pixels.
+    /// * `x0` Column of first pixel in `dst`.
+    /// * `clip` Clip coverage, aligned with `dst`.
+    fn scan_non_zero(
+        &mut self,
+        dst: &mut [P],
+        x0: usize,
+        clip: Option<&[Matte8]>,
+    ) {
+        let fast = self.is_fast(clip);
+        let sgn_area = &mut self.sgn_area[x0..];
+        if let Some(sgn_area) = A::as_i16(sgn_area).filter(|_| fast) {
+            if TypeId::of::<P>() == TypeId::of::<Matte8>() {
+                let alpha = Ch8::from(self.clr.alpha().to_f32());
+                matte_src_over_non_zero(dst, sgn_area, alpha);
+                return;
+            }
+            if TypeId::of::<P>() == TypeId::of::<Rgba8p>() {
+                rgba8p_src_over_non_zero(dst, sgn_area, self.clr);
+                return;
+            }
         }
-        let mut sum = 0;
-        for (d, s) in dst.iter_mut().zip(sgn_area.iter_mut()) {
-            sum += *s;
-            *s = 0;
-            let alpha = Ch8::from(saturating_cast_i16_u8(sum));
-            d.composite_channels_alpha(&clr, SrcOver, &alpha);
+        let clr = self.clr;
+        let mut sum = A::default();
+        for (i, (d, s)) in dst.iter_mut().zip(sgn_area.iter_mut()).enumerate() {
+            sum = sum.wrapping_add(*s);
+            *s = A::default();
+            let cov = sum_cov(FillRule::NonZero, sum.to_i32(), self.bits);
+            let alpha: P::Chan = coverage(cov, self.bits, self.antialias);
+            let alpha = alpha.mul_coverage(self.opacity);
+            let alpha = clip_alpha(alpha, clip, i);
+            self.blend.composite(d, &clr, alpha);
         }
     }
 
     /// Accumulate scan area with even-odd fill rule.
-    fn scan_even_odd(&mut self, dst: &mut [P]) {
-        let clr = self.clr;
-        let sgn_area = &mut self.sgn_area;
-        if TypeId::of::<P>() == TypeId::of::<Matte8>() {
-            // FIXME: only if clr is Matte8::new(255)
-            matte_src_over_even_odd(dst, sgn_area);
-            return;
+    ///
+    /// * `dst` Destination pixels.
+    /// * `x0` Column of first pixel in `dst`.
+    /// * `clip` Clip coverage, aligned with `dst`.
+    fn scan_even_odd(
+        &mut self,
+        dst: &mut [P],
+        x0: usize,
+        clip: Option<&[Matte8]>,
+    ) {
+        let fast = self.is_fast(clip);
+        let sgn_area = &mut self.sgn_area[x0..];
+        if let Some(sgn_area) = A::as_i16(sgn_area).filter(|_| fast) {
+            if TypeId::of::<P>() == TypeId::of::<Matte8>() {
+                let alpha = Ch8::from(self.clr.alpha().to_f32());
+                matte_src_over_even_odd(dst, sgn_area, alpha);
+                return;
+            }
+            if TypeId::of::<P>() == TypeId::of::<Rgba8p>() {
+                rgba8p_src_over_even_odd(dst, sgn_area, self.clr);
+                return;
+            }
         }
-        let mut sum = 0;
-        for (d, s) in dst.iter_mut().zip(sgn_area.iter_mut()) {
-            sum += *s;
-            *s = 0;
-            let v = sum & 0xFF;
-            let odd = sum & 0x100;
-            let c = (v - odd).abs();
-            let alpha = Ch8::from(saturating_cast_i16_u8(c));
-            d.composite_channels_alpha(&clr, SrcOver, &alpha);
+        let clr = self.clr;
+        let mut sum = A::default();
+        for (i, (d, s)) in dst.iter_mut().zip(sgn_area.iter_mut()).enumerate() {
+            sum = sum.wrapping_add(*s);
+            *s = A::default();
+            let cov = sum_cov(FillRule::EvenOdd, sum.to_i32(), self.bits);
+            let alpha: P::Chan = coverage(cov, self.bits, self.antialias);
+            let alpha = alpha.mul_coverage(self.opacity);
+            let alpha = clip_alpha(alpha, clip, i);
+            self.blend.composite(d, &clr, alpha);
         }
     }
 }
 
-/// Cast an i16 to a u8 with saturation
-fn saturating_cast_i16_u8(v: i16) -> u8 {
-    v.max(0).min(255) as u8
+/// Check a region to fill within a raster.
+///
+/// Returns the raster width, top row and columns of the region.
+fn region_rows<P: Pixel>(
+    raster: &Raster<P>,
+    reg: Region,
+    clip: Option<&Raster<Matte8>>,
+) -> (usize, usize, Range<usize>) {
+    assert_eq!(raster.intersection(reg), reg, "region outside raster");
+    if let Some(clip) = clip {
+        assert_eq!(clip.region(), raster.region());
+    }
+    let (left, top) = (reg.left() as usize, reg.top() as usize);
+    let columns = left..left + reg.width() as usize;
+    (raster.width() as usize, top, columns)
+}
+
+/// Offset a region by the origin of another region
+fn offset_region(a: Region, origin: Region) -> Region {
+    let (x, y) = (a.left() + origin.left(), a.top() + origin.top());
+    Region::new(x, y, a.width(), a.height())
 }
 
-/// Calculate pixel coverage
+/// Get the union of two dirty regions
+pub(crate) fn dirty_union(
+    a: Option<Region>,
+    b: Option<Region>,
+) -> Option<Region> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(region_union(a, b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Get the union of two regions
+pub fn region_union(a: Region, b: Region) -> Region {
+    let left = a.left().min(b.left());
+    let top = a.top().min(b.top());
+    let right = a.right().max(b.right());
+    let bottom = a.bottom().max(b.bottom());
+    Region::new(left, top, (right - left) as u32, (bottom - top) as u32)
+}
+
+/// Get coverage alpha, thresholded if anti-aliasing is disabled
 ///
-/// fcov Total coverage (0 to 1 fixed-point).
-/// return Total pixel coverage (0 to 256).
-fn pixel_cov(fcov: Fixed) -> i16 {
-    debug_assert!(fcov >= Fixed::ZERO && fcov <= Fixed::ONE);
-    // Round to nearest pixel cov value
-    let n: i32 = (fcov << 8).round().into();
-    n as i16
+/// * `cov` Pixel coverage (0 to `1 << bits`).
+/// * `bits` Coverage precision.
+fn coverage<C: BlendChannel>(cov: i32, bits: u32, antialias: bool) -> C {
+    let full = 1 << bits;
+    if !antialias {
+        return if cov >= full / 2 { C::MAX } else { C::MIN };
+    }
+    if bits > 8 && C::STEP > Ch16::STEP {
+        // Round to 8-bit channels the same as raw coverage
+        let cov = raw_coverage(cov, bits, antialias);
+        C::from(Ch8::new(cov.min(255) as u8))
+    } else if bits > 8 {
+        let cov = cov << (16 - bits);
+        C::from(Ch16::new(cov.min(u16::MAX.into()) as u16))
+    } else {
+        let cov = cov << (8 - bits);
+        C::from(Ch8::new(cov.min(u8::MAX.into()) as u8))
+    }
+}
+
+/// Get raw coverage (0 to 256), thresholded if anti-aliasing is disabled
+///
+/// * `cov` Pixel coverage (0 to `1 << bits`).
+/// * `bits` Coverage precision.
+fn raw_coverage(cov: i32, bits: u32, antialias: bool) -> u16 {
+    let full = 1 << bits;
+    if !antialias {
+        return if cov >= full / 2 { 256 } else { 0 };
+    }
+    if bits > 8 {
+        // Round to nearest, with ties to even
+        let shift = bits - 8;
+        let (q, r) = (cov >> shift, cov & ((1 << shift) - 1));
+        let half = 1 << (shift - 1);
+        let up = r > half || (r == half && q & 1 == 1);
+        (q + i32::from(up)) as u16
+    } else {
+        (cov << (8 - bits)) as u16
+    }
+}
+
+/// Multiply alpha by clip coverage at a column
+fn clip_alpha<C>(alpha: C, clip: Option<&[Matte8]>, i: usize) -> C
+where
+    C: BlendChannel,
+{
+    match clip {
+        Some(clip) => alpha.mul_coverage(C::from(clip[i].alpha())),
+        None => alpha,
+    }
+}
+
+/// Get pixel coverage of an accumulated signed area
+///
+/// * `rule` Fill rule.
+/// * `sum` Accumulated signed area.
+/// * `bits` Coverage precision.
+///
+/// Returns pixel coverage (0 to `1 << bits`).
+fn sum_cov(rule: FillRule, sum: i32, bits: u32) -> i32 {
+    let full = 1 << bits;
+    match rule {
+        FillRule::NonZero => sum.unsigned_abs().min(full as u32) as i32,
+        FillRule::EvenOdd => {
+            let v = sum & (full - 1);
+            let odd = sum & full;
+            (v - odd).abs()
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use pix::chan::Ch16;
     use pix::matte::Matte8;
-    use pix::rgb::Rgba8p;
+    use pix::rgb::{Rgba16p, Rgba8p};
     use pix::Raster;
 
     #[test]
     fn fixed_pt() {
         let a = FxPt::new(2.0.into(), 1.0.into());
         let b = FxPt::new(3.0.into(), 4.0.into());
-        let c = FxPt::new(Fixed::from(-1.0), 1.0.into());
+        let c = FxPt::new(Coord::from(-1.0), 1.0.into());
         assert_eq!(b - a, FxPt::new(1.0.into(), 3.0.into()));
         assert!(a.widdershins(b));
         assert!(!b.widdershins(a));
         assert!(b.widdershins(c));
     }
 
+    #[test]
+    #[cfg(not(feature = "wide-coords"))]
+    fn edge_overflow() {
+        let eps = Coord::EPSILON;
+        let pt = |x: i32, y| FxPt::new(Coord::from(x), y);
+        // Nearly horizontal, with points farther apart than the fixed range
+        let e = Edge::new(
+            pt(-20_000, Coord::ZERO),
+            pt(20_000, eps),
+            FigDir::Forward,
+        );
+        assert_eq!(e.inv_slope, Coord::MAX);
+        assert!(e.x_bot > e.x_top);
+        let e = Edge::new(
+            pt(20_000, Coord::ZERO),
+            pt(-20_000, eps),
+            FigDir::Forward,
+        );
+        // Rounded division saturates like `/`, to -MAX
+        assert_eq!(e.inv_slope, -Coord::MAX);
+        assert!(e.x_bot < e.x_top);
+        let e =
+            Edge::new(pt(0, Coord::ZERO), pt(1, Coord::ONE), FigDir::Reverse);
+        assert_eq!(e.inv_slope, Coord::ONE);
+        assert_eq!(e.x_bot, Coord::ONE);
+    }
+
     #[test]
     fn fig_3x3() {
         let clr = Rgba8p::new(99, 99, 99, 255);
         let mut m = Raster::with_clear(3, 3);
-        let mut s = vec![0; 3];
+        let mut s = vec![0i16; 3];
         let mut f = Fig::new();
         f.add_point((1.0, 2.0));
         f.add_point((1.0, 3.0));
         f.add_point((2.0, 3.0));
         f.add_point((2.0, 2.0));
         f.close();
-        f.fill(FillRule::NonZero, &mut m, clr, &mut s);
+        f.fill(FillRule::NonZero, &mut m, Paint::new(clr), &mut s);
         #[rustfmt::skip]
         let v = [
             Rgba8p::default(), Rgba8p::default(), Rgba8p::default(),
@@ -720,46 +2828,199 @@ mod test {
         assert_eq!(m.pixels(), &v);
     }
 
+    #[test]
+    fn max_winding() {
+        let mut f = Fig::new();
+        assert_eq!(f.max_winding(), 0);
+        for _ in 0..3 {
+            f.add_point((1.0, 1.0));
+            f.add_point((1.0, 3.0));
+            f.add_point((3.0, 3.0));
+            f.add_point((3.0, 1.0));
+            f.close();
+        }
+        assert_eq!(f.max_winding(), 3);
+        // Zig-zag with two peaks
+        f.add_point((0.0, 0.0));
+        f.add_point((1.0, 4.0));
+        f.add_point((2.0, 0.0));
+        f.add_point((3.0, 4.0));
+        f.close();
+        assert_eq!(f.max_winding(), 5);
+        f.reset();
+        assert_eq!(f.max_winding(), 0);
+    }
+
     #[test]
     fn fig_9x1() {
         let clr = Matte8::new(255);
         let mut m = Raster::<Matte8>::with_clear(9, 1);
-        let mut s = vec![0; 16];
+        let mut s = vec![0i16; 16];
         let mut f = Fig::new();
         f.add_point((0.0, 0.0));
         f.add_point((9.0, 1.0));
         f.add_point((0.0, 1.0));
         f.close();
-        f.fill(FillRule::NonZero, &mut m, clr, &mut s);
+        f.fill(FillRule::NonZero, &mut m, Paint::new(clr), &mut s);
         assert_eq!([242, 213, 185, 156, 128, 100, 71, 43, 14], m.as_u8_slice());
     }
 
+    #[test]
+    fn fig_bow_tie() {
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let mut m = Raster::<Matte8>::with_clear(8, 8);
+            let mut s = vec![0i16; 8];
+            let mut f = Fig::new();
+            f.add_point((0.0, 0.0));
+            f.add_point((8.0, 8.0));
+            f.add_point((8.0, 0.0));
+            f.add_point((0.0, 8.0));
+            f.close();
+            f.fill(rule, &mut m, Paint::new(Matte8::new(255)), &mut s);
+            // Lobes are wound in opposite directions
+            for y in 0..8 {
+                for x in 0..4 {
+                    assert_eq!(m.pixel(x, y), m.pixel(7 - x, y), "{rule:?}");
+                }
+            }
+            assert_eq!(m.pixel(1, 4), Matte8::new(255), "{rule:?}");
+        }
+    }
+
+    #[test]
+    fn fig_clockwise_non_zero() {
+        // Right lobe of a bow tie is wound clockwise.  Fill on the Matte8
+        // fast path (SIMD, with a scalar tail) and the scalar path for
+        // other pixel formats.
+        let mut f = Fig::new();
+        f.add_point((0.0, 0.0));
+        f.add_point((13.0, 6.0));
+        f.add_point((13.0, 0.0));
+        f.add_point((0.0, 6.0));
+        f.close();
+        let mut m = Raster::<Matte8>::with_clear(13, 6);
+        let mut s = vec![0i16; 13];
+        let white = Matte8::new(255);
+        f.fill(FillRule::NonZero, &mut m, Paint::new(white), &mut s);
+        let mut r = Raster::<Rgba16p>::with_clear(13, 6);
+        let clr = Rgba16p::new(65535, 65535, 65535, 65535);
+        f.fill(FillRule::NonZero, &mut r, Paint::new(clr), &mut s);
+        for x in [1, 11] {
+            assert_eq!(m.pixel(x, 3), white);
+            assert_eq!(r.pixel(x, 3), clr);
+        }
+        for (a, b) in m.pixels().iter().zip(r.pixels()) {
+            assert_eq!(Ch16::from(a.alpha()), b.alpha());
+        }
+    }
+
+    /// Fill a figure on a 4 x 2 raster
+    fn fill_4x2(pts: &[(f32, f32)]) -> Raster<Matte8> {
+        let mut m = Raster::<Matte8>::with_clear(4, 2);
+        let mut s = vec![0i16; 4];
+        let mut f = Fig::new();
+        for pt in pts {
+            f.add_point(*pt);
+        }
+        f.close();
+        let paint = Paint::new(Matte8::new(255));
+        f.fill(FillRule::NonZero, &mut m, paint, &mut s);
+        m
+    }
+
+    #[test]
+    fn fig_left_clip() {
+        let m =
+            fill_4x2(&[(-100.0, 0.0), (-100.0, 2.0), (1.5, 2.0), (1.5, 0.0)]);
+        assert_eq!(m.as_u8_slice(), [255, 128, 0, 0, 255, 128, 0, 0]);
+        // Left edge crossing x = 0 within the bottom 1/102 of row 0
+        let m = fill_4x2(&[(-100.0, 0.0), (2.0, 1.0), (4.0, 1.0), (4.0, 0.0)]);
+        let row = &m.as_u8_slice()[..4];
+        assert!(row[0].abs_diff(251) <= 1, "{row:?}");
+        assert!(row[1].abs_diff(254) <= 1, "{row:?}");
+        assert_eq!(row[2..], [255, 255]);
+    }
+
+    #[test]
+    fn fig_huge() {
+        let m = fill_4x2(&[(-1e7, -1e7), (-1e7, 1e7), (1e7, 1e7), (1e7, -1e7)]);
+        assert_eq!(m.as_u8_slice(), [255; 8]);
+        let m = fill_4x2(&[(-1e7, 1.0), (1e7, 1.0), (1e7, 2.0), (-1e7, 2.0)]);
+        assert_eq!(m.as_u8_slice(), [0, 0, 0, 0, 255, 255, 255, 255]);
+    }
+
+    #[test]
+    #[cfg(feature = "wide-coords")]
+    fn fig_wide_coords() {
+        let pts = [(1.5, 0.25), (7.0, 6.75), (0.5, 5.0), (3.0, 3.0)];
+        let fill = |off: f32| {
+            let mut m = Raster::<Matte8>::with_clear(8, 8);
+            let mut s = vec![0i16; 8];
+            let mut f = Fig::new();
+            for (x, y) in pts {
+                f.add_point((x + off, y + off));
+            }
+            f.close();
+            // Beyond the range of Fixed, without wide coordinates
+            f.translate(-off as i32, -off as i32);
+            let paint = Paint::new(Matte8::new(255));
+            f.fill(FillRule::NonZero, &mut m, paint, &mut s);
+            m
+        };
+        let near = fill(0.0);
+        assert_ne!(near.as_u8_slice(), [0; 64]);
+        assert_eq!(fill(100_000.0).as_u8_slice(), near.as_u8_slice());
+        assert_eq!(fill(-1_048_576.0).as_u8_slice(), near.as_u8_slice());
+    }
+
+    #[test]
+    fn fig_dropped_points() {
+        let mut f = Fig::new();
+        let max = usize::from(Vid::MAX);
+        for i in 0..max {
+            f.add_point(((i % 2) as f32, (i / 2) as f32 * 1e-3));
+        }
+        assert!(f.is_full());
+        assert_eq!(f.dropped_points(), 0);
+        assert_eq!(f.try_add_point((5.0, 5.0)), Err(Error::TooManyPoints));
+        for _ in 0..99 {
+            f.add_point((4.0, 4.0));
+        }
+        assert_eq!(f.dropped_points(), 100);
+        let mut g = Fig::new();
+        g.copy_from(&f);
+        assert_eq!(g.dropped_points(), 100);
+        f.reset();
+        assert_eq!(f.dropped_points(), 0);
+        assert_eq!(f.try_add_point((1.0, 1.0)), Ok(()));
+    }
+
     #[test]
     fn fig_x_bounds() {
         let clr = Matte8::new(255);
         let mut m = Raster::<Matte8>::with_clear(3, 3);
-        let mut s = vec![0; 4];
+        let mut s = vec![0i16; 4];
         let mut f = Fig::new();
         f.add_point((-1.0, 0.0));
         f.add_point((-1.0, 3.0));
         f.add_point((3.0, 1.5));
         f.close();
-        f.fill(FillRule::NonZero, &mut m, clr, &mut s);
-        assert_eq!([112, 16, 0, 255, 224, 32, 112, 16, 0], m.as_u8_slice());
+        f.fill(FillRule::NonZero, &mut m, Paint::new(clr), &mut s);
+        assert_eq!([112, 21, 0, 255, 246, 96, 112, 21, 0], m.as_u8_slice());
     }
 
     #[test]
     fn fig_partial() {
         let clr = Matte8::new(255);
         let mut m = Raster::<Matte8>::with_clear(1, 3);
-        let mut s = vec![0; 4];
+        let mut s = vec![0i16; 4];
         let mut f = Fig::new();
         f.add_point((0.5, 0.0));
         f.add_point((0.5, 1.5));
         f.add_point((1.0, 3.0));
         f.add_point((1.0, 0.0));
         f.close();
-        f.fill(FillRule::NonZero, &mut m, clr, &mut s);
+        f.fill(FillRule::NonZero, &mut m, Paint::new(clr), &mut s);
         assert_eq!([128, 117, 43], m.as_u8_slice());
     }
 
@@ -767,7 +3028,7 @@ mod test {
     fn fig_partial2() {
         let clr = Matte8::new(255);
         let mut m = Raster::<Matte8>::with_clear(3, 3);
-        let mut s = vec![0; 3];
+        let mut s = vec![0i16; 3];
         let mut f = Fig::new();
         f.add_point((1.5, 0.0));
         f.add_point((1.5, 1.5));
@@ -775,7 +3036,7 @@ mod test {
         f.add_point((3.0, 3.0));
         f.add_point((3.0, 0.0));
         f.close();
-        f.fill(FillRule::NonZero, &mut m, clr, &mut s);
+        f.fill(FillRule::NonZero, &mut m, Paint::new(clr), &mut s);
         assert_eq!([0, 128, 255, 0, 117, 255, 0, 43, 255], m.as_u8_slice());
     }
 
@@ -783,13 +3044,728 @@ mod test {
     fn fig_partial3() {
         let clr = Matte8::new(255);
         let mut m = Raster::<Matte8>::with_clear(9, 1);
-        let mut s = vec![0; 16];
+        let mut s = vec![0i16; 16];
         let mut f = Fig::new();
         f.add_point((0.0, 0.0));
         f.add_point((0.0, 0.3));
         f.add_point((9.0, 0.0));
         f.close();
-        f.fill(FillRule::NonZero, &mut m, clr, &mut s);
-        assert_eq!([73, 64, 56, 47, 39, 30, 22, 13, 4], m.as_u8_slice());
+        f.fill(FillRule::NonZero, &mut m, Paint::new(clr), &mut s);
+        assert_eq!([73, 64, 56, 47, 39, 30, 21, 13, 4], m.as_u8_slice());
+    }
+
+    /// Fill a 9 x `h` rectangle, with horizontal top and bottom edges
+    fn fill_sliver(y: f32, h: f32, bits: u8) -> Raster<Matte8> {
+        let mut m = Raster::<Matte8>::with_clear(9, 2);
+        let mut s = vec![0i32; 9];
+        let mut f = Fig::new();
+        f.add_point((0.0, y));
+        f.add_point((9.0, y));
+        f.add_point((9.0, y + h));
+        f.add_point((0.0, y + h));
+        f.close();
+        let mut paint = Paint::new(Matte8::new(255));
+        paint.coverage_bits = bits;
+        f.fill(FillRule::NonZero, &mut m, paint, &mut s);
+        m
+    }
+
+    #[test]
+    fn fig_horizontal_sliver() {
+        // Within one row: coverage is 0.4 * 255 in every column
+        for i in 0..=12 {
+            let y = i as f32 * 0.05;
+            let m = fill_sliver(y, 0.4, 16);
+            assert_eq!(m.as_u8_slice(), [[102; 9], [0; 9]].concat(), "{y}");
+            // Vertex rows are rounded to 1/256 of a pixel with 8 bits
+            let m = fill_sliver(y, 0.4, 8);
+            let (row0, row1) = m.as_u8_slice().split_at(9);
+            assert!(row0.iter().all(|v| v.abs_diff(102) <= 1), "{y}");
+            assert!(row0.iter().all(|v| *v == row0[0]), "{y}");
+            assert_eq!(row1, [0; 9]);
+        }
+        // Straddling two rows
+        for i in 13..20 {
+            let y = i as f32 * 0.05;
+            let m = fill_sliver(y, 0.4, 8);
+            let (row0, row1) = m.as_u8_slice().split_at(9);
+            let c0 = ((1.0 - y) * 255.0).round() as u8;
+            let c1 = ((y - 0.6) * 255.0).round() as u8;
+            assert!(row0.iter().all(|v| *v == row0[0]), "{y}");
+            assert!(row1.iter().all(|v| *v == row1[0]), "{y}");
+            assert!(row0[0].abs_diff(c0) <= 1, "{y}");
+            assert!(row1[0].abs_diff(c1) <= 1, "{y}");
+        }
+    }
+
+    /// Integrate coverage of a leaning sliver within a pixel.
+    ///
+    /// The left side is at `x + lean * y`, and the right side `w` further.
+    /// Column overlap is exact on each of many sub-rows.
+    fn sliver_cov(x: f32, w: f32, lean: f32, col: i32, row: i32) -> f32 {
+        let n = 1024;
+        let mut sum = 0.0;
+        for i in 0..n {
+            let y = row as f32 + (i as f32 + 0.5) / n as f32;
+            let x0 = (x + lean * y).max(col as f32);
+            let x1 = (x + lean * y + w).min(col as f32 + 1.0);
+            sum += (x1 - x0).max(0.0);
+        }
+        sum / n as f32
+    }
+
+    #[test]
+    fn fig_slivers() {
+        for w in [0.1, 0.25, 0.5] {
+            for off in [0.0, 0.1, 0.3, 0.5, 0.7, 0.85] {
+                for lean in [0.0, 0.1, 0.3] {
+                    let x = 2.0 + off;
+                    let mut m = Raster::<Matte8>::with_clear(8, 4);
+                    let mut s = vec![0i16; 8];
+                    let mut f = Fig::new();
+                    f.add_point((x, 0.0));
+                    f.add_point((x + w, 0.0));
+                    f.add_point((x + w + lean * 4.0, 4.0));
+                    f.add_point((x + lean * 4.0, 4.0));
+                    f.close();
+                    let paint = Paint::new(Matte8::new(255));
+                    f.fill(FillRule::NonZero, &mut m, paint, &mut s);
+                    for row in 0..4 {
+                        for col in 0..8 {
+                            let c = sliver_cov(x, w, lean, col, row);
+                            let c = (c * 255.0).round() as u8;
+                            let v = m.pixel(col, row).alpha().into();
+                            assert!(
+                                c.abs_diff(v) <= 1,
+                                "{w} {off} {lean} ({col}, {row}) {c} {v}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fig_sliver_one_column() {
+        // 0.25 wide vertical sliver within a single pixel column
+        let mut f = Fig::new();
+        f.add_point((2.5, 0.0));
+        f.add_point((2.75, 0.0));
+        f.add_point((2.75, 2.0));
+        f.add_point((2.5, 2.0));
+        f.close();
+        let mut s = vec![0i16; 8];
+        let mut spans = vec![];
+        f.fill_spans(
+            FillRule::NonZero,
+            8,
+            2,
+            true,
+            256,
+            &mut s,
+            &mut |y, x, len, cov| spans.push((y, x, len, cov)),
+        );
+        assert_eq!(spans, [(0, 2, 1, 64), (1, 2, 1, 64)]);
+    }
+
+    #[test]
+    fn fig_above() {
+        let clr = Matte8::new(255);
+        let mut m = Raster::<Matte8>::with_clear(4, 4);
+        let mut s = vec![0i16; 4];
+        let mut f = Fig::new();
+        f.add_point((0.0, -2.0));
+        f.add_point((0.0, 2.0));
+        f.add_point((4.0, 2.0));
+        f.close();
+        f.fill(FillRule::NonZero, &mut m, Paint::new(clr), &mut s);
+        #[rustfmt::skip]
+        assert_eq!([
+            255, 255, 128, 0,
+            255, 255, 255, 128,
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+        ], m.as_u8_slice());
+    }
+
+    #[test]
+    fn fig_horizontal() {
+        // Nearly horizontal edges, with large inverse slope
+        let clr = Matte8::new(255);
+        for dy in [1.0 / 256.0, 1.0 / 1024.0, 1.0 / 65536.0] {
+            let mut m = Raster::<Matte8>::with_clear(4, 4);
+            let mut s = vec![0i16; 4];
+            let mut f = Fig::new();
+            f.add_point((-16_000.0, 1.0));
+            f.add_point((-16_000.0, 3.0));
+            f.add_point((16_000.0, 3.0));
+            f.add_point((16_000.0, 1.0 + dy));
+            f.close();
+            f.fill(FillRule::NonZero, &mut m, Paint::new(clr), &mut s);
+            #[rustfmt::skip]
+            assert_eq!([
+                0, 0, 0, 0,
+                255, 255, 255, 255,
+                255, 255, 255, 255,
+                0, 0, 0, 0,
+            ], m.as_u8_slice(), "{dy}");
+        }
+    }
+
+    /// Simple linear congruential generator for repeatable tests
+    struct Lcg(u32);
+
+    impl Lcg {
+        /// Get next value in range (0..n)
+        fn next(&mut self, n: i32) -> i32 {
+            self.0 = self.0.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            ((self.0 >> 8) % n as u32) as i32
+        }
+    }
+
+    /// Fill a random polygon, offset by a number of pixels
+    fn fill_offset<P>(
+        pts: &[(f32, f32)],
+        rule: FillRule,
+        clr: P,
+        m: usize,
+    ) -> Raster<P>
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+        P::Chan: BlendChannel,
+    {
+        let w = 20 + m as u32 * 2;
+        let h = 12 + m as u32 * 2;
+        let mut r = Raster::with_clear(w, h);
+        let mut s = vec![0i16; w as usize];
+        let mut f = Fig::new();
+        for (x, y) in pts {
+            f.add_point((x + m as f32, y + m as f32));
+        }
+        f.close();
+        f.fill(rule, &mut r, Paint::new(clr), &mut s);
+        assert!(s.iter().all(|s| *s == 0));
+        r
+    }
+
+    /// Check that filling with a margin around all points matches filling
+    /// with points outside of the raster (spans clamped to raster edges)
+    fn check_offset<P>(pts: &[(f32, f32)], rule: FillRule, clr: P, m: usize)
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+        P::Chan: BlendChannel,
+    {
+        let a = fill_offset(pts, rule, clr, 0);
+        let b = fill_offset(pts, rule, clr, m);
+        for y in 0..12 {
+            for x in 0..20 {
+                let pb = b.pixel(x + m as i32, y + m as i32);
+                assert_eq!(a.pixel(x, y), pb, "{pts:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn fig_spans() {
+        let mut rng = Lcg(1234);
+        let m = 32;
+        for i in 0..200 {
+            let n_pts = 3 + rng.next(6);
+            let pts: Vec<_> = (0..n_pts)
+                .map(|_| {
+                    let x = (rng.next(40 * 256) - 10 * 256) as f32 / 256.0;
+                    let y = (rng.next(24 * 256) - 6 * 256) as f32 / 256.0;
+                    (x, y)
+                })
+                .collect();
+            let rule = if i % 2 == 0 {
+                FillRule::NonZero
+            } else {
+                FillRule::EvenOdd
+            };
+            check_offset(&pts, rule, Matte8::new(255), m);
+            check_offset(&pts, rule, Rgba8p::new(255, 128, 0, 255), m);
+        }
+    }
+
+    /// Get raw coverage (0 to 256) of a polygon, from spans
+    fn span_coverage(pts: &[(f32, f32)], width: u32, height: u32) -> Vec<u16> {
+        let mut f = Fig::new();
+        for pt in pts {
+            f.add_point(*pt);
+        }
+        f.close();
+        let w = width as usize;
+        let mut cov = vec![0; w * height as usize];
+        let mut s = vec![0i16; w];
+        let rule = FillRule::NonZero;
+        f.fill_spans(
+            rule,
+            width,
+            height,
+            true,
+            256,
+            &mut s,
+            &mut |y, x, n, c| {
+                let i = y as usize * w + x as usize;
+                cov[i..i + n as usize].fill(c);
+            },
+        );
+        cov
+    }
+
+    #[test]
+    fn fig_mirror_coverage() {
+        let mut rng = Lcg(2468);
+        let coord = |rng: &mut Lcg, n: i32| rng.next(n << 16) as f32 / 65536.0;
+        for i in 0..1000 {
+            // Edge with a random slope, from steep to nearly horizontal
+            let xt = 1.0 + coord(&mut rng, 18);
+            let xb = 1.0 + coord(&mut rng, 18);
+            let h = if i % 2 == 0 {
+                16.0
+            } else {
+                1.0 + coord(&mut rng, 15)
+            };
+            // Left of the edge
+            let a = span_coverage(
+                &[(0.0, 0.0), (xt, 0.0), (xb, h), (0.0, h)],
+                20,
+                16,
+            );
+            // Right of the edge, mirrored about x = 10
+            let b = span_coverage(
+                &[(20.0 - xt, 0.0), (0.0, 0.0), (0.0, h), (20.0 - xb, h)],
+                20,
+                16,
+            );
+            let full = span_coverage(
+                &[(0.0, 0.0), (20.0, 0.0), (20.0, h), (0.0, h)],
+                20,
+                16,
+            );
+            for y in 0..16 {
+                let row = y * 20;
+                for x in 0..20 {
+                    let sum = a[row + x] + b[row + 19 - x];
+                    let cov = full[row + x];
+                    if y < h as usize {
+                        assert_eq!(sum, 256, "{xt} {xb} {h} {x},{y}");
+                    } else {
+                        // Odd partial row coverage cannot be split evenly
+                        let d = sum.abs_diff(cov);
+                        assert!(d <= cov % 2, "{xt} {xb} {h} {x},{y}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn raw_coverage_ties() {
+        assert_eq!(raw_coverage(0x80, 16, true), 0);
+        assert_eq!(raw_coverage(0x81, 16, true), 1);
+        assert_eq!(raw_coverage(0x180, 16, true), 2);
+        assert_eq!(raw_coverage(0x8, 12, true), 0);
+        assert_eq!(raw_coverage(0x18, 12, true), 2);
+        assert_eq!(raw_coverage(77, 8, true), 77);
+        for cov in [0x80, 0x180, 0x7F80] {
+            let sum = raw_coverage(cov, 16, true)
+                + raw_coverage(0x1_0000 - cov, 16, true);
+            assert_eq!(sum, 256);
+        }
+    }
+
+    #[test]
+    fn fig_coverage() {
+        let mut rng = Lcg(4321);
+        for i in 0..200 {
+            let rule = if i % 2 == 0 {
+                FillRule::NonZero
+            } else {
+                FillRule::EvenOdd
+            };
+            let mut f = Fig::new();
+            for _ in 0..2 {
+                for _ in 0..3 + rng.next(6) {
+                    let x = (rng.next(30 * 256) - 5 * 256) as f32 / 256.0;
+                    let y = (rng.next(20 * 256) - 4 * 256) as f32 / 256.0;
+                    f.add_point((x, y));
+                }
+                f.close();
+            }
+            let mut m = Raster::<Matte8>::with_clear(20, 12);
+            let mut s = vec![0i16; 20];
+            f.fill(rule, &mut m, Paint::new(Matte8::new(255)), &mut s);
+            let mut c = Raster::<Matte8>::with_clear(20, 12);
+            let mut last = None;
+            f.fill_coverage(rule, 20, 12, &mut s, |y, row| {
+                assert!(last < Some(y));
+                last = Some(y);
+                for (x, cov) in row.iter().enumerate() {
+                    *c.pixel_mut(x as i32, y as i32) = Matte8::new(*cov);
+                }
+            });
+            assert_eq!(m.as_u8_slice(), c.as_u8_slice(), "{i}");
+            assert!(s.iter().all(|s| *s == 0));
+        }
+    }
+
+    #[test]
+    fn fig_sub_order() {
+        let mut rng = Lcg(8765);
+        for i in 0..200 {
+            let rule = if i % 2 == 0 {
+                FillRule::NonZero
+            } else {
+                FillRule::EvenOdd
+            };
+            // Points on a coarse grid, so vertices are often shared
+            let subs: Vec<Vec<(f32, f32)>> = (0..3)
+                .map(|_| {
+                    (0..3 + rng.next(4))
+                        .map(|_| {
+                            let x = rng.next(9) as f32 * 2.5 - 2.0;
+                            let y = rng.next(7) as f32 * 2.0 - 1.0;
+                            (x, y)
+                        })
+                        .collect()
+                })
+                .collect();
+            let fill = |order: [usize; 3]| {
+                let mut m = Raster::<Matte8>::with_clear(20, 12);
+                let mut s = vec![0i16; 20];
+                let mut f = Fig::new();
+                for sub in order.map(|j| &subs[j]) {
+                    for pt in sub {
+                        f.add_point(*pt);
+                    }
+                    f.close();
+                }
+                f.fill(rule, &mut m, Paint::new(Matte8::new(255)), &mut s);
+                m
+            };
+            let m = fill([0, 1, 2]);
+            for order in [[0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]]
+            {
+                let r = fill(order);
+                assert_eq!(m.as_u8_slice(), r.as_u8_slice(), "{i} {order:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn fig_tiny_fuzz() {
+        let mut rng = Lcg(5678);
+        for i in 0..2000 {
+            let mut f = Fig::new();
+            let mut pt = (1.0, 1.0);
+            for _ in 0..1 + rng.next(3) {
+                for _ in 0..1 + rng.next(6) {
+                    // Repeated points, and Y differing by under 1 / 65536
+                    pt = match rng.next(4) {
+                        0 => pt,
+                        1 => (pt.0 + rng.next(64) as f32 / 32.0, pt.1 + 1e-6),
+                        _ => (
+                            1.0 + rng.next(64) as f32 / 32.0,
+                            1.0 + rng.next(64) as f32 / 32.0,
+                        ),
+                    };
+                    f.add_point(pt);
+                }
+                f.close();
+            }
+            let rule = if i % 2 == 0 {
+                FillRule::NonZero
+            } else {
+                FillRule::EvenOdd
+            };
+            let mut paint = Paint::new(Matte8::new(255));
+            paint.coverage_bits = [4, 8, 16][i % 3];
+            let mut m = Raster::<Matte8>::with_clear(4, 4);
+            let mut s = vec![0i32; 4];
+            f.fill(rule, &mut m, paint, &mut s);
+            assert!(s.iter().all(|s| *s == 0), "{f:?}");
+            let mut spans = vec![];
+            f.fill_spans(rule, 4, 4, true, 256, &mut s, &mut |y, x, n, c| {
+                spans.push((y, x, n, c))
+            });
+            assert!(s.iter().all(|s| *s == 0), "{f:?}");
+            for (y, x, n, cov) in spans {
+                assert!((0..4).contains(&y) && x >= 0 && x + n as i32 <= 4);
+                assert!(cov > 0 && cov <= 256, "{f:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn fig_metrics() {
+        let clr = Matte8::new(255);
+        let mut m = Raster::<Matte8>::with_clear(9, 1);
+        let mut s = vec![0i16; 16];
+        let mut f = Fig::new();
+        f.add_point((0.0, 0.0));
+        f.add_point((9.0, 1.0));
+        f.add_point((0.0, 1.0));
+        f.close();
+        let mut mt = Metrics::default();
+        let paint = Paint::new(clr);
+        f.fill_serial(
+            FillRule::NonZero,
+            &mut m,
+            Region::new(0, 0, 9, 1),
+            paint,
+            &mut s,
+            None,
+            Some(&mut mt),
+        );
+        assert_eq!([242, 213, 185, 156, 128, 100, 71, 43, 14], m.as_u8_slice());
+        let expected = Metrics {
+            points: 3,
+            sub_figs: 1,
+            edges_added: 2,
+            max_active_edges: 2,
+            rows_scanned: 1,
+            pixels_composited: 9,
+            points_dropped: 0,
+        };
+        assert_eq!(mt, expected);
+        let mut m = Raster::<Matte8>::with_clear(3, 3);
+        let mut f = Fig::new();
+        f.add_point((-1.0, 0.0));
+        f.add_point((-1.0, 3.0));
+        f.add_point((3.0, 1.5));
+        f.close();
+        let paint = Paint::new(clr);
+        f.fill_serial(
+            FillRule::NonZero,
+            &mut m,
+            Region::new(0, 0, 3, 3),
+            paint,
+            &mut s,
+            None,
+            Some(&mut mt),
+        );
+        assert_eq!([112, 21, 0, 255, 246, 96, 112, 21, 0], m.as_u8_slice());
+        let expected = Metrics {
+            points: 3,
+            sub_figs: 1,
+            edges_added: 3,
+            max_active_edges: 2,
+            rows_scanned: 3,
+            pixels_composited: 9,
+            points_dropped: 0,
+        };
+        assert_eq!(mt, expected);
+    }
+
+    #[test]
+    fn fig_open() {
+        let fill = |close: bool| {
+            let mut m = Raster::<Matte8>::with_clear(8, 8);
+            let mut s = vec![0i16; 8];
+            let mut f = Fig::new();
+            f.add_point((1.0, 1.0));
+            f.add_point((1.5, 7.0));
+            f.add_point((7.0, 4.0));
+            f.add_point((1.0, 1.0));
+            if close {
+                f.close();
+                f.close();
+            }
+            let paint = Paint::new(Matte8::new(255));
+            f.fill(FillRule::NonZero, &mut m, paint, &mut s);
+            assert_eq!(f.max_winding(), 1);
+            m
+        };
+        let closed = fill(true);
+        assert_eq!(fill(false).as_u8_slice(), closed.as_u8_slice());
+        assert_eq!(closed.pixel(3, 4), Matte8::new(255));
+    }
+
+    #[test]
+    fn fig_refill() {
+        let fill = |f: &mut Fig| {
+            let mut m = Raster::<Matte8>::with_clear(16, 16);
+            let mut s = vec![0i16; 16];
+            let paint = Paint::new(Matte8::new(255));
+            f.fill(FillRule::NonZero, &mut m, paint, &mut s);
+            m
+        };
+        let squares = |n: usize, dx: f32| {
+            let mut f = Fig::new();
+            for i in 0..n {
+                let (x, y) = (2.0 + dx + 6.0 * i as f32, 2.0 + 7.0 * i as f32);
+                f.add_point((x, y));
+                f.add_point((x, y + 4.0));
+                f.add_point((x + 4.0, y + 4.0));
+                f.add_point((x + 4.0, y));
+                f.close();
+            }
+            f
+        };
+        let mut f = squares(1, 0.0);
+        let m = fill(&mut f);
+        assert_eq!(fill(&mut f).as_u8_slice(), m.as_u8_slice());
+        // Sorted vertices must not be reused after changes
+        f.translate(1, 0);
+        let r = fill(&mut squares(1, 1.0));
+        assert_eq!(fill(&mut f).as_u8_slice(), r.as_u8_slice());
+        let mut f = squares(1, 0.0);
+        fill(&mut f);
+        f.add_point((8.0, 9.0));
+        f.add_point((8.0, 13.0));
+        f.add_point((12.0, 13.0));
+        f.add_point((12.0, 9.0));
+        let mut g = squares(2, 0.0);
+        let mut c = Fig::new();
+        c.copy_from(&g);
+        let r = fill(&mut g);
+        assert_eq!(fill(&mut f).as_u8_slice(), r.as_u8_slice());
+        assert_eq!(fill(&mut c).as_u8_slice(), r.as_u8_slice());
+        assert_eq!(r.pixel(9, 10), Matte8::new(255));
+    }
+
+    #[test]
+    fn fig_edge_table() {
+        // Wobbly star, with a sliver, a 2-point sub-figure and a triangle
+        // above the raster
+        let mut f = Fig::new();
+        let n = TABLE_POINTS * 2;
+        for i in 0..n {
+            let a = i as f32 / n as f32 * std::f32::consts::TAU;
+            let r = 20.0 + 14.0 * (a * 7.0).sin() + (a * 301.0).cos();
+            f.add_point((32.0 + r * a.cos(), 30.0 + r * a.sin()));
+        }
+        f.close();
+        for (x, y) in [(-9.0, 40.0), (60.5, 63.9), (61.0, 40.0)] {
+            f.add_point((x, y));
+        }
+        f.close();
+        f.add_point((4.0, 4.0));
+        f.add_point((20.0, 60.0));
+        f.close();
+        for (x, y) in [(10.0, -20.0), (20.0, -8.0), (12.0, -1.0)] {
+            f.add_point((x, y));
+        }
+        f.close();
+        let fill = |f: &mut Fig, rule: FillRule| {
+            let mut m = Raster::<Rgba8p>::with_clear(64, 64);
+            let mut s = vec![0i16; 64];
+            let paint = Paint::new(Rgba8p::new(40, 80, 160, 255));
+            f.fill(rule, &mut m, paint, &mut s);
+            m
+        };
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let mut g = Fig::new();
+            g.copy_from(&f);
+            // Edges are bucketed by row, unless vertices are already sorted
+            let table = fill(&mut g, rule);
+            assert!(g.sorted.is_none() && !g.table.is_empty());
+            g.sort_vids();
+            let sorted = fill(&mut g, rule);
+            assert_eq!(table.as_u8_slice(), sorted.as_u8_slice(), "{rule:?}");
+            assert_eq!(table.pixel(32, 30), Rgba8p::new(40, 80, 160, 255));
+        }
+    }
+
+    #[test]
+    fn fig_fixed_points() {
+        // Triangle from raw bits only, without floating point
+        let pts = [
+            (0x0000_4321, 0x0000_1000),
+            (0x0005_C0DE, 0x0002_8001),
+            (0x0001_0F0F, 0x0005_FFFF),
+        ];
+        let mut f = Fig::new();
+        for (x, y) in pts {
+            f.add_point_fixed(Fixed::from_bits(x), Fixed::from_bits(y));
+        }
+        let mut m = Raster::<Matte8>::with_clear(6, 6);
+        let mut s = vec![0i16; 6];
+        let paint = Paint::new(Matte8::new(255));
+        f.fill(FillRule::NonZero, &mut m, paint, &mut s);
+        #[rustfmt::skip]
+        assert_eq!([
+            131, 99, 8, 0, 0, 0,
+            140, 255, 234, 128, 22, 0,
+            105, 255, 255, 255, 248, 86,
+            71, 255, 255, 250, 111, 1,
+            36, 255, 222, 52, 0, 0,
+            5, 168, 15, 0, 0, 0,
+        ], m.as_u8_slice());
+        // Points beyond the bounds are clipped, as with f32 points
+        let mut f = Fig::new();
+        let mut g = Fig::new();
+        for (x, y) in [(-20_000, 1), (5, 3), (2, 5)] {
+            f.add_point_fixed(Fixed::from(x), Fixed::from(y));
+            g.add_point((x as f32, y as f32));
+        }
+        let fill = |f: &mut Fig| {
+            let mut m = Raster::<Matte8>::with_clear(6, 6);
+            let mut s = vec![0i16; 6];
+            let paint = Paint::new(Matte8::new(255));
+            f.fill(FillRule::NonZero, &mut m, paint, &mut s);
+            m
+        };
+        assert_eq!(fill(&mut f).as_u8_slice(), fill(&mut g).as_u8_slice());
+        assert_eq!(f.points, g.points);
+    }
+
+    #[test]
+    fn fig_debug_svg() {
+        let mut f = Fig::new();
+        f.add_point((28.0, 4.0));
+        f.add_cubic((16.0, 12.0), (0.0, 0.0), (0.0, 32.0), (16.0, 20.0), 1.0);
+        f.add_point((36.0, 30.0));
+        f.add_point((20.0, 16.0));
+        f.close();
+        f.add_point((7.5, 11.0));
+        f.add_point((10.0, 11.0));
+        f.add_point((8.75, 13.0));
+        let svg = concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg""#,
+            r#" viewBox="-1.125 -1.125 38.25 34.25" font-size="0.5625""#,
+            r#" fill="none" stroke-width="0.0703125">"#,
+            "\n",
+            r##"<rect width="32" height="32" stroke="#888"/>"##,
+            "\n",
+            r#"<polygon points="28,4 16,12 10.75,9.390625 7,9.875 4,16"#,
+            r#" 7,22.125 10.75,22.609375 16,20 36,30 20,16""#,
+            r##" stroke="#d62728"/>"##,
+            "\n",
+            r##"<text x="28" y="4" fill="#d62728">0</text>"##,
+            "\n",
+            r##"<text x="16" y="12" fill="#d62728">1</text>"##,
+            "\n",
+            r##"<text x="10.75" y="9.390625" fill="#d62728">2</text>"##,
+            "\n",
+            r##"<text x="7" y="9.875" fill="#d62728">3</text>"##,
+            "\n",
+            r##"<text x="4" y="16" fill="#d62728">4</text>"##,
+            "\n",
+            r##"<text x="7" y="22.125" fill="#d62728">5</text>"##,
+            "\n",
+            r##"<text x="10.75" y="22.609375" fill="#d62728">6</text>"##,
+            "\n",
+            r##"<text x="16" y="20" fill="#d62728">7</text>"##,
+            "\n",
+            r##"<text x="36" y="30" fill="#d62728">8</text>"##,
+            "\n",
+            r##"<text x="20" y="16" fill="#d62728">9</text>"##,
+            "\n",
+            r##"<polyline points="7.5,11 10,11 8.75,13" stroke="#1f77b4""##,
+            r#" stroke-dasharray="0.5625 0.28125"/>"#,
+            "\n",
+            r##"<text x="7.5" y="11" fill="#1f77b4">10</text>"##,
+            "\n",
+            r##"<text x="10" y="11" fill="#1f77b4">11</text>"##,
+            "\n",
+            r##"<text x="8.75" y="13" fill="#1f77b4">12</text>"##,
+            "\n",
+            "</svg>\n",
+        );
+        assert_eq!(f.to_debug_svg(32, 32), svg);
     }
 }
@@ -1,160 +1,493 @@
-// fixed.rs     Fixed-point type.
+// fixed.rs     Fixed-point types.
 //
 // Copyright (c) 2017-2020  Douglas P Lau
 //
 use std::fmt;
+use std::iter::Sum;
 use std::ops;
 
-/// Fixed-point type
-#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
-pub struct Fixed(i32);
-
-/// Number of bits at fixed point (16.16)
+/// Number of bits at fixed point (16.16 or 48.16)
 const FRACT_BITS: i32 = 16;
 
-/// Mask of fixed fractional bits
-const FRACT_MASK: i32 = (1 << FRACT_BITS) - 1;
+/// Fixed-point type of rasterized coordinates
+#[cfg(not(feature = "wide-coords"))]
+pub(crate) type Coord = Fixed;
 
-impl fmt::Debug for Fixed {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", f32::from(*self))
-    }
-}
+/// Fixed-point type of rasterized coordinates
+#[cfg(feature = "wide-coords")]
+pub(crate) type Coord = Fixed48;
 
-impl ops::Add for Fixed {
-    type Output = Self;
+/// Implement a fixed-point type.
+///
+/// * `$fixed` Fixed-point type.
+/// * `$raw` Raw integer type.
+/// * `$wide` Integer type for intermediate products.
+/// * `$float` Float type for `Debug`.
+macro_rules! fixed_impl {
+    ($(#[$meta:meta])* $fixed:ident, $raw:ty, $wide:ty, $float:ty) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $fixed($raw);
 
-    fn add(self, rhs: Self) -> Self {
-        Fixed(self.0 + rhs.0)
-    }
-}
+        impl fmt::Debug for $fixed {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{:?}", <$float>::from(*self))
+            }
+        }
 
-impl ops::Sub for Fixed {
-    type Output = Self;
+        impl fmt::Display for $fixed {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Display::fmt(&f64::from(*self), f)
+            }
+        }
 
-    fn sub(self, rhs: Self) -> Self {
-        Fixed(self.0 - rhs.0)
-    }
-}
+        impl ops::Neg for $fixed {
+            type Output = Self;
 
-impl ops::Mul for Fixed {
-    type Output = Self;
+            fn neg(self) -> Self {
+                $fixed(-self.0)
+            }
+        }
 
-    fn mul(self, rhs: Self) -> Self {
-        let v = (self.0 as i64 * rhs.0 as i64) >> FRACT_BITS;
-        Fixed(v as i32)
-    }
-}
+        impl ops::Add for $fixed {
+            type Output = Self;
 
-impl ops::Div for Fixed {
-    type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                $fixed(self.0 + rhs.0)
+            }
+        }
 
-    fn div(self, rhs: Self) -> Self {
-        let v = ((self.0 as i64) << (FRACT_BITS as i64)) / rhs.0 as i64;
-        Fixed(v as i32)
-    }
-}
+        impl ops::Sub for $fixed {
+            type Output = Self;
 
-impl ops::Shl<u32> for Fixed {
-    type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                $fixed(self.0 - rhs.0)
+            }
+        }
 
-    fn shl(self, rhs: u32) -> Self {
-        Fixed(self.0 << rhs)
-    }
-}
+        impl ops::AddAssign for $fixed {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
+        }
 
-impl ops::Shr<u32> for Fixed {
-    type Output = Self;
+        impl ops::SubAssign for $fixed {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
+        }
 
-    fn shr(self, rhs: u32) -> Self {
-        Fixed(self.0 >> rhs)
-    }
-}
+        impl Sum for $fixed {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold($fixed::ZERO, |a, b| a + b)
+            }
+        }
 
-impl From<i32> for Fixed {
-    /// Get a fixed point value from an i32
-    fn from(i: i32) -> Self {
-        Fixed(i << FRACT_BITS)
-    }
-}
+        impl<'a> Sum<&'a $fixed> for $fixed {
+            fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                iter.copied().sum()
+            }
+        }
 
-impl From<Fixed> for i32 {
-    /// Get an i32 from a fixed point value
-    fn from(f: Fixed) -> Self {
-        f.0 >> FRACT_BITS
-    }
-}
+        impl ops::Mul for $fixed {
+            type Output = Self;
 
-impl From<f32> for Fixed {
-    /// Get a fixed point value from an f32
-    fn from(f: f32) -> Self {
-        Fixed((f * (Self::ONE.0 as f32)) as i32)
-    }
-}
+            fn mul(self, rhs: Self) -> Self {
+                $fixed::saturate(self.wide_mul(rhs))
+            }
+        }
 
-impl From<Fixed> for f32 {
-    /// Get an f32 from a fixed point value
-    fn from(f: Fixed) -> Self {
-        f.0 as f32 / Fixed::ONE.0 as f32
-    }
-}
+        impl ops::Div for $fixed {
+            type Output = Self;
 
-impl Fixed {
-    /// Fixed value of 0.
-    pub const ZERO: Self = Fixed(0);
+            fn div(self, rhs: Self) -> Self {
+                $fixed::saturate(self.wide_div(rhs))
+            }
+        }
 
-    /// Fixed value of epsilon.
-    pub const EPSILON: Self = Fixed(1);
+        impl ops::Shl<u32> for $fixed {
+            type Output = Self;
 
-    /// Fixed value of 1/2.
-    pub const HALF: Self = Fixed(1 << (FRACT_BITS - 1));
+            fn shl(self, rhs: u32) -> Self {
+                $fixed(self.0 << rhs)
+            }
+        }
 
-    /// Fixed value of 1.
-    pub const ONE: Self = Fixed(1 << FRACT_BITS);
+        impl ops::Shr<u32> for $fixed {
+            type Output = Self;
 
-    /// Get the smallest value that can be represented by this type.
-    pub const MIN: Self = Fixed(i32::MIN);
+            fn shr(self, rhs: u32) -> Self {
+                $fixed(self.0 >> rhs)
+            }
+        }
 
-    /// Get the largest value that can be represented by this type.
-    pub const MAX: Self = Fixed(i32::MAX);
+        impl From<i32> for $fixed {
+            /// Get a fixed point value from an i32
+            fn from(i: i32) -> Self {
+                $fixed(<$raw>::from(i) << FRACT_BITS)
+            }
+        }
 
-    /// Get the absolute value of a number.
-    pub fn abs(self) -> Self {
-        Fixed(self.0.abs())
-    }
+        impl From<$fixed> for $raw {
+            /// Get an integer from a fixed point value, rounding down
+            fn from(f: $fixed) -> Self {
+                f.0 >> FRACT_BITS
+            }
+        }
 
-    /// Get the largest integer less than or equal to a number.
-    pub fn floor(self) -> Self {
-        Fixed(self.0 & !FRACT_MASK)
-    }
+        impl From<f32> for $fixed {
+            /// Get a fixed point value from an f32
+            fn from(f: f32) -> Self {
+                $fixed((f * (Self::ONE.0 as f32)) as $raw)
+            }
+        }
 
-    /// Get the smallest integer greater than or equal to a number.
-    pub fn ceil(self) -> Self {
-        (self + Self::ONE - Self::EPSILON).floor()
-    }
+        impl From<f64> for $fixed {
+            /// Get a fixed point value from an f64
+            fn from(f: f64) -> Self {
+                $fixed((f * (Self::ONE.0 as f64)) as $raw)
+            }
+        }
 
-    /// Round a number to the nearest integer.
-    pub fn round(self) -> Self {
-        (self + Self::HALF).floor()
-    }
+        impl From<$fixed> for f32 {
+            /// Get an f32 from a fixed point value
+            fn from(f: $fixed) -> Self {
+                f.0 as f32 / $fixed::ONE.0 as f32
+            }
+        }
 
-    /// Get the integer part of a number.
-    pub fn trunc(self) -> Self {
-        if self.0 >= 0 {
-            self.floor()
-        } else {
-            self.ceil()
+        impl From<$fixed> for f64 {
+            /// Get an f64 from a fixed point value
+            fn from(f: $fixed) -> Self {
+                f.0 as f64 / $fixed::ONE.0 as f64
+            }
         }
+
+        impl $fixed {
+            /// Fixed value of 0.
+            pub const ZERO: Self = $fixed(0);
+
+            /// Fixed value of epsilon.
+            pub const EPSILON: Self = $fixed(1);
+
+            /// Fixed value of 1/2.
+            pub const HALF: Self = $fixed(1 << (FRACT_BITS - 1));
+
+            /// Fixed value of 1.
+            pub const ONE: Self = $fixed(1 << FRACT_BITS);
+
+            /// Get the smallest value that can be represented by this type.
+            pub const MIN: Self = $fixed(<$raw>::MIN);
+
+            /// Get the largest value that can be represented by this type.
+            pub const MAX: Self = $fixed(<$raw>::MAX);
+
+            /// Mask of fractional bits
+            const FRACT_MASK: $raw = (1 << FRACT_BITS) - 1;
+
+            /// Get a fixed value from its raw bits (value times 65,536).
+            pub const fn from_bits(bits: $raw) -> Self {
+                $fixed(bits)
+            }
+
+            /// Get the raw bits of a fixed value (value times 65,536).
+            pub const fn to_bits(self) -> $raw {
+                self.0
+            }
+
+            /// Multiply two numbers, with a wide result.
+            fn wide_mul(self, rhs: Self) -> $wide {
+                (self.0 as $wide * rhs.0 as $wide) >> FRACT_BITS
+            }
+
+            /// Divide two numbers, with a wide result.
+            fn wide_div(self, rhs: Self) -> $wide {
+                ((self.0 as $wide) << FRACT_BITS) / rhs.0 as $wide
+            }
+
+            /// Shift a wide value right, rounding to nearest with ties to even.
+            fn round_shift(v: $wide, shift: u32) -> $wide {
+                if shift == 0 {
+                    return v;
+                }
+                let q = v >> shift;
+                let r = v & ((1 << shift) - 1);
+                let half = 1 << (shift - 1);
+                if r > half || (r == half && q & 1 == 1) {
+                    q + 1
+                } else {
+                    q
+                }
+            }
+
+            /// Divide wide values, rounding to nearest with ties to even.
+            fn round_div(n: $wide, d: $wide) -> $wide {
+                let q = n.div_euclid(d);
+                let r2 = n.rem_euclid(d) * 2;
+                let d_abs = d.abs();
+                if r2 > d_abs || (r2 == d_abs && q & 1 == 1) {
+                    q + d.signum()
+                } else {
+                    q
+                }
+            }
+
+            /// Get a fixed value from a wide value, saturating to -MAX or MAX.
+            fn saturate(v: $wide) -> Self {
+                let max = <$wide>::from(<$raw>::MAX);
+                $fixed(v.clamp(-max, max) as $raw)
+            }
+
+            /// Get a fixed value from a wide value, or `None` if out of range.
+            fn checked(v: $wide) -> Option<Self> {
+                <$raw>::try_from(v).ok().map($fixed)
+            }
+
+            /// Get a fixed value from a wide value, saturating to MIN or MAX.
+            fn saturate_full(v: $wide) -> Self {
+                let (min, max) = (<$raw>::MIN, <$raw>::MAX);
+                $fixed(v.clamp(<$wide>::from(min), <$wide>::from(max)) as $raw)
+            }
+
+            /// Add two numbers, or `None` on overflow.
+            pub fn checked_add(self, rhs: Self) -> Option<Self> {
+                self.0.checked_add(rhs.0).map($fixed)
+            }
+
+            /// Subtract two numbers, or `None` on overflow.
+            pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+                self.0.checked_sub(rhs.0).map($fixed)
+            }
+
+            /// Multiply two numbers, or `None` on overflow.
+            pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+                $fixed::checked(self.wide_mul(rhs))
+            }
+
+            /// Divide two numbers, or `None` on overflow or division by zero.
+            pub fn checked_div(self, rhs: Self) -> Option<Self> {
+                if rhs.0 == 0 {
+                    return None;
+                }
+                $fixed::checked(self.wide_div(rhs))
+            }
+
+            /// Add two numbers, saturating instead of overflowing.
+            pub fn saturating_add(self, rhs: Self) -> Self {
+                $fixed(self.0.saturating_add(rhs.0))
+            }
+
+            /// Subtract two numbers, saturating instead of overflowing.
+            pub fn saturating_sub(self, rhs: Self) -> Self {
+                $fixed(self.0.saturating_sub(rhs.0))
+            }
+
+            /// Multiply two numbers, saturating to MIN or MAX.
+            ///
+            /// Unlike the `*` operator, negative results can saturate to MIN.
+            pub fn saturating_mul(self, rhs: Self) -> Self {
+                $fixed::saturate_full(self.wide_mul(rhs))
+            }
+
+            /// Divide two numbers, saturating to MIN or MAX.
+            ///
+            /// Unlike the `/` operator, negative results can saturate to MIN.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `rhs` is zero.
+            pub fn saturating_div(self, rhs: Self) -> Self {
+                $fixed::saturate_full(self.wide_div(rhs))
+            }
+
+            /// Multiply by a ratio, without rounding the intermediate product.
+            ///
+            /// The result is truncated toward zero.
+            pub fn mul_div(self, mul: Self, div: Self) -> Self {
+                let v = self.0 as $wide * mul.0 as $wide / div.0 as $wide;
+                $fixed::saturate(v)
+            }
+
+            /// Multiply two numbers, rounding to nearest.
+            ///
+            /// The `*` operator rounds toward negative infinity, which biases
+            /// results downward.  This rounds to the nearest value, with ties
+            /// to even, so negating an operand negates the result exactly.  It
+            /// saturates like `*`.
+            pub fn mul_round(self, rhs: Self) -> Self {
+                let v = self.0 as $wide * rhs.0 as $wide;
+                $fixed::saturate($fixed::round_shift(v, FRACT_BITS as u32))
+            }
+
+            /// Divide two numbers, rounding to nearest.
+            ///
+            /// The `/` operator truncates toward zero.  This rounds to the
+            /// nearest value, with ties to even.  It saturates like `/`.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `rhs` is zero.
+            pub fn div_round(self, rhs: Self) -> Self {
+                let n = (self.0 as $wide) << FRACT_BITS;
+                $fixed::saturate($fixed::round_div(n, rhs.0 as $wide))
+            }
+
+            /// Multiply by a ratio, rounding to nearest.
+            ///
+            /// This is the same as [mul_div](#method.mul_div), except the
+            /// result is rounded to the nearest value, with ties to even.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `div` is zero.
+            pub fn mul_div_round(self, mul: Self, div: Self) -> Self {
+                let v = self.0 as $wide * mul.0 as $wide;
+                $fixed::saturate($fixed::round_div(v, div.0 as $wide))
+            }
+
+            /// Get the absolute value of a number.
+            pub fn abs(self) -> Self {
+                $fixed(self.0.abs())
+            }
+
+            /// Get the largest integer less than or equal to a number.
+            pub fn floor(self) -> Self {
+                $fixed(self.0 & !Self::FRACT_MASK)
+            }
+
+            /// Get the smallest integer greater than or equal to a number.
+            pub fn ceil(self) -> Self {
+                (self + Self::ONE - Self::EPSILON).floor()
+            }
+
+            /// Round a number to the nearest integer.
+            pub fn round(self) -> Self {
+                (self + Self::HALF).floor()
+            }
+
+            /// Get the integer part of a number.
+            pub fn trunc(self) -> Self {
+                if self.0 >= 0 {
+                    self.floor()
+                } else {
+                    self.ceil()
+                }
+            }
+
+            /// Get the fractional part of a number.
+            pub fn fract(self) -> Self {
+                $fixed(self.0 & Self::FRACT_MASK)
+            }
+
+            /// Scale a number by 2^bits, rounding to the nearest integer.
+            ///
+            /// Ties are rounded to even, so values `x` and `n - x` (for an
+            /// even scaled `n`) round to integers which sum to `n`.  The number
+            /// must be non-negative, and bits no more than 16.  The result is
+            /// truncated to 32 bits.
+            pub fn round_scaled(self, bits: u32) -> i32 {
+                debug_assert!(self.0 >= 0 && bits <= FRACT_BITS as u32);
+                let shift = FRACT_BITS as u32 - bits;
+                $fixed::round_shift(self.0 as $wide, shift) as i32
+            }
+
+            /// Get the average of two numbers.
+            pub fn avg(self, rhs: Self) -> Self {
+                $fixed((self.0 + rhs.0) >> 1)
+            }
+        }
+    };
+}
+
+fixed_impl!(
+    /// Fixed-point number, with 16 integer and 16 fractional bits (16.16).
+    ///
+    /// Values range from -32,768 to just under 32,768, in steps of 1/65,536.
+    /// Arithmetic uses only integer operations, so results are the same on
+    /// every platform.  Multiplication and division saturate, but addition and
+    /// subtraction overflow like `i32`; use [saturating_add] or [checked_add]
+    /// (and the other `saturating_` and `checked_` methods) where that
+    /// matters.
+    ///
+    /// Converting from `f32` or `f64` truncates toward zero.  Use [from_bits]
+    /// to make values without floating point.
+    ///
+    /// ```rust
+    /// use footile::Fixed;
+    ///
+    /// let a = Fixed::from(1.5);
+    /// let b = Fixed::from_bits(0x0000_8000); // 0.5
+    /// assert_eq!(a + b, Fixed::from(2));
+    /// assert_eq!(-a * b, Fixed::from(-0.75));
+    /// assert_eq!(format!("{}", a / b), "3");
+    /// ```
+    ///
+    /// [checked_add]: struct.Fixed.html#method.checked_add
+    /// [from_bits]: struct.Fixed.html#method.from_bits
+    /// [saturating_add]: struct.Fixed.html#method.saturating_add
+    Fixed,
+    i32,
+    i64,
+    f32
+);
+
+fixed_impl!(
+    /// Wide fixed-point number, with 48 integer and 16 fractional bits
+    /// (48.16).
+    ///
+    /// This has the same API as [Fixed], with a range of about
+    /// ±1.4 × 10¹⁴, for very large canvases and CAD-scale coordinates.
+    /// Values beyond ±2³⁷ convert to `f64` with rounding.
+    ///
+    /// With the `wide-coords` feature, figures are rasterized with this type,
+    /// so points are clipped to 16,777,215 pixels from the origin instead of
+    /// 16,383.
+    ///
+    /// ```rust
+    /// use footile::{Fixed, Fixed48};
+    ///
+    /// let a = Fixed48::from(100_000.25);
+    /// let b = Fixed48::from(Fixed::from(-0.5));
+    /// assert_eq!(a * b, Fixed48::from(-50_000.125));
+    /// assert_eq!(f64::from(a + a), 200_000.5);
+    /// assert_eq!(i64::from(a * a), 10_000_050_000);
+    /// ```
+    ///
+    /// [Fixed]: struct.Fixed.html
+    Fixed48,
+    i64,
+    i128,
+    f64
+);
+
+impl From<Fixed> for Fixed48 {
+    /// Get a wide fixed point value from a fixed point value (always exact)
+    fn from(f: Fixed) -> Self {
+        Fixed48(f.0.into())
     }
+}
+
+impl From<i64> for Fixed48 {
+    /// Get a wide fixed point value from an i64
+    fn from(i: i64) -> Self {
+        Fixed48(i << FRACT_BITS)
+    }
+}
 
-    /// Get the fractional part of a number.
-    pub fn fract(self) -> Self {
-        Fixed(self.0 & FRACT_MASK)
+#[cfg(not(feature = "wide-coords"))]
+impl Fixed {
+    /// Get the integer part as an i32, rounding down.
+    pub(crate) fn to_i32(self) -> i32 {
+        self.into()
     }
+}
 
-    /// Get the average of two numbers.
-    pub fn avg(self, rhs: Fixed) -> Self {
-        Fixed((self.0 + rhs.0) >> 1)
+#[cfg(feature = "wide-coords")]
+impl Fixed48 {
+    /// Get the integer part as an i32, rounding down and saturating.
+    pub(crate) fn to_i32(self) -> i32 {
+        let i = i64::from(self);
+        i.clamp(i32::MIN.into(), i32::MAX.into()) as i32
     }
 }
 
@@ -183,6 +516,51 @@ mod test {
         assert_eq!(Fixed::from(3.5) - Fixed::from(1.25), Fixed::from(2.25));
     }
 
+    #[test]
+    fn fixed_neg() {
+        assert_eq!(-Fixed::from(1), Fixed::from(-1));
+        assert_eq!(-Fixed::from(-2.5), Fixed::from(2.5));
+        assert_eq!(-Fixed::ZERO, Fixed::ZERO);
+        assert_eq!(-Fixed::MAX, Fixed::ZERO - Fixed::MAX);
+    }
+
+    #[test]
+    fn fixed_assign() {
+        let mut a = Fixed::from(1.5);
+        a += Fixed::from(2);
+        assert_eq!(a, Fixed::from(3.5));
+        a -= Fixed::from(4.25);
+        assert_eq!(a, Fixed::from(-0.75));
+        a -= Fixed::EPSILON;
+        assert_eq!(a.to_bits(), -49_153);
+    }
+
+    #[test]
+    fn fixed_sum() {
+        let v = [Fixed::from(1.5), Fixed::from(-4), Fixed::from(0.25)];
+        assert_eq!(v.iter().sum::<Fixed>(), Fixed::from(-2.25));
+        assert_eq!(v.into_iter().sum::<Fixed>(), Fixed::from(-2.25));
+        assert_eq!([].iter().sum::<Fixed>(), Fixed::ZERO);
+    }
+
+    #[test]
+    fn fixed_display() {
+        assert_eq!(Fixed::from(37).to_string(), "37");
+        assert_eq!(Fixed::from(-2.5).to_string(), "-2.5");
+        assert_eq!(Fixed::EPSILON.to_string(), "0.0000152587890625");
+        assert_eq!(format!("{:.2}", Fixed::from(1.125)), "1.12");
+        assert_eq!(Fixed::MAX.to_string(), "32767.99998474121");
+    }
+
+    #[test]
+    fn fixed_hash() {
+        use std::collections::HashSet;
+        let set: HashSet<Fixed> =
+            [Fixed::from(1), Fixed::from(1.0), Fixed::HALF].into();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&Fixed::from_bits(0x8000)));
+    }
+
     #[test]
     fn fixed_mul() {
         assert_eq!(Fixed::from(2) * Fixed::from(2), Fixed::from(4));
@@ -284,6 +662,164 @@ mod test {
         assert_eq!(Fixed::from(-2.5).fract(), Fixed::from(0.5));
     }
 
+    #[test]
+    fn fixed_round_scaled() {
+        assert_eq!(Fixed::from(0.5).round_scaled(4), 8);
+        assert_eq!(Fixed::from(0.5).round_scaled(8), 128);
+        assert_eq!(Fixed::from(0.5).round_scaled(16), 32768);
+        assert_eq!(Fixed::from(1).round_scaled(16), 65536);
+        assert_eq!(Fixed::from(0.3).round_scaled(4), 5);
+        assert_eq!(Fixed::from(0.3).round_scaled(8), 77);
+        assert_eq!(Fixed::from(1).round_scaled(0), 1);
+    }
+
+    #[test]
+    fn fixed_round_scaled_ties() {
+        let bits = Fixed::from_bits;
+        assert_eq!(bits(0x80).round_scaled(8), 0);
+        assert_eq!(bits(0x81).round_scaled(8), 1);
+        assert_eq!(bits(0x180).round_scaled(8), 2);
+        assert_eq!(bits(0x280).round_scaled(8), 2);
+        assert_eq!(bits(0x800).round_scaled(4), 0);
+        assert_eq!(bits(0x1800).round_scaled(4), 2);
+        // Complementary values sum to full coverage
+        for v in [0x80, 0x180, 0x7F80, 0x8080] {
+            let (a, b) = (bits(v), Fixed::ONE - bits(v));
+            assert_eq!(a.round_scaled(8) + b.round_scaled(8), 256);
+        }
+    }
+
+    #[test]
+    fn fixed_mul_round() {
+        let bits = Fixed::from_bits;
+        assert_eq!(bits(3).mul_round(Fixed::HALF), bits(2));
+        assert_eq!(bits(1).mul_round(Fixed::HALF), bits(0));
+        assert_eq!(bits(-1).mul_round(Fixed::HALF), bits(0));
+        assert_eq!(bits(-3).mul_round(Fixed::HALF), bits(-2));
+        assert_eq!(bits(5).mul_round(Fixed::from(0.25)), bits(1));
+        assert_eq!(bits(5).mul_round(Fixed::from(0.75)), bits(4));
+        assert_eq!(bits(-5).mul_round(Fixed::from(0.75)), bits(-4));
+        // The operator rounds toward negative infinity
+        assert_eq!(bits(-1) * Fixed::HALF, bits(-1));
+        let v = Fixed::from(1.5).mul_round(Fixed::from(-2.5));
+        assert_eq!(v, Fixed::from(-3.75));
+        assert_eq!(Fixed::from(20_000).mul_round(Fixed::from(4)), Fixed::MAX);
+        let v = Fixed::from(20_000).mul_round(Fixed::from(-4));
+        assert_eq!(v, -Fixed::MAX);
+    }
+
+    #[test]
+    fn fixed_div_round() {
+        let bits = Fixed::from_bits;
+        let three = Fixed::from(3);
+        assert_eq!(Fixed::ONE.div_round(three), bits(21845));
+        assert_eq!(Fixed::from(2).div_round(three), bits(43691));
+        assert_eq!(Fixed::from(2) / three, bits(43690));
+        assert_eq!(Fixed::from(-2).div_round(three), bits(-43691));
+        assert_eq!(Fixed::from(2).div_round(-three), bits(-43691));
+        assert_eq!(Fixed::from(-2).div_round(-three), bits(43691));
+        let two = Fixed::from(2);
+        assert_eq!(bits(1).div_round(two), bits(0));
+        assert_eq!(bits(3).div_round(two), bits(2));
+        assert_eq!(bits(-3).div_round(two), bits(-2));
+        assert_eq!(bits(3).div_round(-two), bits(-2));
+        assert_eq!(bits(5).div_round(-two), bits(-2));
+        let tiny = Fixed::EPSILON;
+        assert_eq!(Fixed::from(20_000).div_round(tiny), Fixed::MAX);
+        assert_eq!(Fixed::from(-20_000).div_round(tiny), -Fixed::MAX);
+    }
+
+    #[test]
+    #[should_panic]
+    fn fixed_div_round_zero() {
+        let _ = Fixed::ONE.div_round(Fixed::ZERO);
+    }
+
+    #[test]
+    fn fixed_mul_div_round() {
+        let bits = Fixed::from_bits;
+        let (one, two, three) = (Fixed::ONE, Fixed::from(2), Fixed::from(3));
+        assert_eq!(two.mul_div_round(one, three), bits(43691));
+        assert_eq!(two.mul_div(one, three), bits(43690));
+        assert_eq!(bits(5).mul_div_round(one, two), bits(2));
+        assert_eq!(bits(7).mul_div_round(one, two), bits(4));
+        assert_eq!(bits(-7).mul_div_round(one, two), bits(-4));
+        assert_eq!(bits(7).mul_div_round(-one, two), bits(-4));
+        let v = Fixed::from(20_000).mul_div_round(Fixed::from(3), three);
+        assert_eq!(v, Fixed::from(20_000));
+    }
+
+    #[test]
+    fn fixed_saturate() {
+        let tiny = Fixed::EPSILON;
+        assert_eq!(Fixed::from(20_000) / tiny, Fixed::MAX);
+        assert_eq!(Fixed::from(-20_000) / tiny, Fixed::ZERO - Fixed::MAX);
+        assert_eq!(Fixed::from(20_000) * Fixed::from(4), Fixed::MAX);
+        assert_eq!(Fixed::MAX.saturating_add(Fixed::ONE), Fixed::MAX);
+        let v = Fixed::from(20_000).mul_div(Fixed::from(0.5), Fixed::ONE);
+        assert_eq!(v, Fixed::from(10_000));
+    }
+
+    #[test]
+    fn fixed_checked() {
+        let max = Fixed::MAX;
+        let min = Fixed::MIN;
+        let eps = Fixed::EPSILON;
+        assert_eq!(max.checked_add(eps), None);
+        assert_eq!((max - eps).checked_add(eps), Some(max));
+        assert_eq!(Fixed::from(-32768).checked_sub(eps), None);
+        assert_eq!((min + eps).checked_sub(eps), Some(min));
+        assert_eq!(Fixed::from(-32768).checked_add(Fixed::ZERO), Some(min));
+        assert_eq!(Fixed::from(256).checked_mul(Fixed::from(128)), None);
+        let v = Fixed::from(256).checked_mul(Fixed::from(-128));
+        assert_eq!(v, Some(min));
+        let v = Fixed::from(181).checked_mul(Fixed::from(181));
+        assert_eq!(v, Some(Fixed::from(32761)));
+        assert_eq!(Fixed::from(16384).checked_mul(Fixed::from(2)), None);
+        assert_eq!(max.checked_mul(Fixed::ONE), Some(max));
+        assert_eq!(Fixed::from(1).checked_div(Fixed::ZERO), None);
+        assert_eq!(Fixed::from(16384).checked_div(Fixed::HALF), None);
+        let v = Fixed::from(-16384).checked_div(Fixed::HALF);
+        assert_eq!(v, Some(min));
+        let v = Fixed::from(3).checked_div(Fixed::from(-2));
+        assert_eq!(v, Some(Fixed::from(-1.5)));
+        assert_eq!(min.checked_div(Fixed::from(-1)), None);
+    }
+
+    #[test]
+    fn fixed_saturating() {
+        let max = Fixed::MAX;
+        let min = Fixed::MIN;
+        let eps = Fixed::EPSILON;
+        assert_eq!(max.saturating_add(eps), max);
+        assert_eq!(min.saturating_add(-eps), min);
+        assert_eq!((max - eps).saturating_add(eps), max);
+        assert_eq!(min.saturating_sub(eps), min);
+        assert_eq!(max.saturating_sub(-eps), max);
+        assert_eq!((min + eps).saturating_sub(eps), min);
+        let v = Fixed::from(-32767).saturating_sub(Fixed::from(2));
+        assert_eq!(v, min);
+        assert_eq!(Fixed::from(256).saturating_mul(Fixed::from(128)), max);
+        let v = Fixed::from(256).saturating_mul(Fixed::from(-129));
+        assert_eq!(v, min);
+        let v = Fixed::from(-200).saturating_mul(Fixed::from(-200));
+        assert_eq!(v, max);
+        let v = Fixed::from(100).saturating_mul(Fixed::from(-2.5));
+        assert_eq!(v, Fixed::from(-250));
+        assert_eq!(Fixed::from(16384).saturating_div(Fixed::HALF), max);
+        assert_eq!(Fixed::from(-16385).saturating_div(Fixed::HALF), min);
+        assert_eq!(min.saturating_div(Fixed::from(-1)), max);
+        assert_eq!(Fixed::ONE.saturating_div(eps), max);
+        let v = Fixed::from(-7).saturating_div(Fixed::from(2));
+        assert_eq!(v, Fixed::from(-3.5));
+    }
+
+    #[test]
+    #[should_panic]
+    fn fixed_saturating_div_zero() {
+        let _ = Fixed::ONE.saturating_div(Fixed::ZERO);
+    }
+
     #[test]
     fn fixed_avg() {
         assert_eq!(Fixed::from(1).avg(Fixed::from(2)), Fixed::from(1.5));
@@ -303,6 +839,16 @@ mod test {
         assert_eq!(a, 2);
     }
 
+    #[test]
+    fn fixed_f64() {
+        let v = Fixed::from(1234.0625f64);
+        assert_eq!(f64::from(v), 1234.0625);
+        assert_eq!(Fixed::from(-0.75f64), Fixed::from(-0.75f32));
+        assert_eq!(Fixed::from(-1e-6f64), Fixed::ZERO);
+        assert_eq!(Fixed::from(f64::from(Fixed::MIN)), Fixed::MIN);
+        assert_eq!(Fixed::from(f64::from(Fixed::MAX)), Fixed::MAX);
+    }
+
     #[test]
     fn fixed_cmp() {
         assert!(Fixed::from(37) > Fixed::from(3));
@@ -312,3 +858,369 @@ mod test {
         assert_eq!(cmp::max(Fixed::from(37), Fixed::from(3)), Fixed::from(37));
     }
 }
+
+#[cfg(test)]
+mod test48 {
+    use super::*;
+    use std::cmp;
+
+    #[test]
+    fn fixed48_add() {
+        assert_eq!(Fixed48::from(1) + Fixed48::from(1), Fixed48::from(2));
+        assert_eq!(Fixed48::from(2) + Fixed48::from(2), Fixed48::from(4));
+        assert_eq!(Fixed48::from(2) + Fixed48::from(-2), Fixed48::from(0));
+        assert_eq!(Fixed48::from(2) + Fixed48::from(-4), Fixed48::from(-2));
+        assert_eq!(Fixed48::from(1.5) + Fixed48::from(1.5), Fixed48::from(3));
+        assert_eq!(
+            Fixed48::from(3.5) + Fixed48::from(-1.25),
+            Fixed48::from(2.25)
+        );
+    }
+
+    #[test]
+    fn fixed48_sub() {
+        assert_eq!(Fixed48::from(1) - Fixed48::from(1), Fixed48::from(0));
+        assert_eq!(Fixed48::from(3) - Fixed48::from(2), Fixed48::from(1));
+        assert_eq!(Fixed48::from(2) - Fixed48::from(-2), Fixed48::from(4));
+        assert_eq!(Fixed48::from(2) - Fixed48::from(4), Fixed48::from(-2));
+        assert_eq!(Fixed48::from(1.5) - Fixed48::from(1.5), Fixed48::from(0));
+        assert_eq!(
+            Fixed48::from(3.5) - Fixed48::from(1.25),
+            Fixed48::from(2.25)
+        );
+    }
+
+    #[test]
+    fn fixed48_neg() {
+        assert_eq!(-Fixed48::from(1), Fixed48::from(-1));
+        assert_eq!(-Fixed48::from(-2.5), Fixed48::from(2.5));
+        assert_eq!(-Fixed48::ZERO, Fixed48::ZERO);
+        assert_eq!(-Fixed48::MAX, Fixed48::ZERO - Fixed48::MAX);
+    }
+
+    #[test]
+    fn fixed48_assign() {
+        let mut a = Fixed48::from(1.5);
+        a += Fixed48::from(2);
+        assert_eq!(a, Fixed48::from(3.5));
+        a -= Fixed48::from(4.25);
+        assert_eq!(a, Fixed48::from(-0.75));
+        a -= Fixed48::EPSILON;
+        assert_eq!(a.to_bits(), -49_153);
+    }
+
+    #[test]
+    fn fixed48_sum() {
+        let v = [Fixed48::from(1.5), Fixed48::from(-4), Fixed48::from(0.25)];
+        assert_eq!(v.iter().sum::<Fixed48>(), Fixed48::from(-2.25));
+        assert_eq!(v.into_iter().sum::<Fixed48>(), Fixed48::from(-2.25));
+        assert_eq!([].iter().sum::<Fixed48>(), Fixed48::ZERO);
+    }
+
+    #[test]
+    fn fixed48_display() {
+        assert_eq!(Fixed48::from(37).to_string(), "37");
+        assert_eq!(Fixed48::from(-2.5).to_string(), "-2.5");
+        assert_eq!(Fixed48::EPSILON.to_string(), "0.0000152587890625");
+        assert_eq!(format!("{:.2}", Fixed48::from(1.125)), "1.12");
+        assert_eq!(Fixed48::from(100_000.5).to_string(), "100000.5");
+        // Rounded to the nearest f64
+        assert_eq!(Fixed48::MAX.to_string(), "140737488355328");
+    }
+
+    #[test]
+    fn fixed48_hash() {
+        use std::collections::HashSet;
+        let set: HashSet<Fixed48> =
+            [Fixed48::from(1), Fixed48::from(1.0), Fixed48::HALF].into();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&Fixed48::from_bits(0x8000)));
+    }
+
+    #[test]
+    fn fixed48_mul() {
+        assert_eq!(Fixed48::from(2) * Fixed48::from(2), Fixed48::from(4));
+        assert_eq!(Fixed48::from(3) * Fixed48::from(-2), Fixed48::from(-6));
+        assert_eq!(Fixed48::from(4) * Fixed48::from(0.5), Fixed48::from(2));
+        assert_eq!(Fixed48::from(-16) * Fixed48::from(-16), Fixed48::from(256));
+        assert_eq!(Fixed48::from(37) * Fixed48::from(3), Fixed48::from(111));
+        assert_eq!(
+            Fixed48::from(128) * Fixed48::from(128),
+            Fixed48::from(16384)
+        );
+    }
+
+    #[test]
+    fn fixed48_div() {
+        assert_eq!(Fixed48::from(4) / Fixed48::from(2), Fixed48::from(2));
+        assert_eq!(Fixed48::from(-6) / Fixed48::from(2), Fixed48::from(-3));
+        assert_eq!(Fixed48::from(2) / Fixed48::from(0.5), Fixed48::from(4));
+        assert_eq!(Fixed48::from(256) / Fixed48::from(-16), Fixed48::from(-16));
+        assert_eq!(Fixed48::from(111) / Fixed48::from(3), Fixed48::from(37));
+        assert_eq!(
+            Fixed48::from(37) / Fixed48::from(3),
+            Fixed48::from(12.33333)
+        );
+        assert_eq!(
+            Fixed48::from(16384) / Fixed48::from(128),
+            Fixed48::from(128)
+        );
+    }
+
+    #[test]
+    fn fixed48_shl() {
+        assert_eq!(Fixed48::from(0) << 2, Fixed48::from(0));
+        assert_eq!(Fixed48::from(1) << 1, Fixed48::from(2));
+        assert_eq!(Fixed48::from(0.5) << 1, Fixed48::from(1));
+        assert_eq!(Fixed48::from(0.25) << 2, Fixed48::from(1));
+        assert_eq!(Fixed48::from(0.125) << 3, Fixed48::from(1));
+    }
+
+    #[test]
+    fn fixed48_shr() {
+        assert_eq!(Fixed48::from(0) >> 2, Fixed48::from(0));
+        assert_eq!(Fixed48::from(1) >> 1, Fixed48::from(0.5));
+        assert_eq!(Fixed48::from(2) >> 1, Fixed48::from(1));
+        assert_eq!(Fixed48::from(4) >> 2, Fixed48::from(1));
+        assert_eq!(Fixed48::from(8) >> 3, Fixed48::from(1));
+    }
+
+    #[test]
+    fn fixed48_abs() {
+        assert_eq!(Fixed48::from(1).abs(), Fixed48::from(1));
+        assert_eq!(Fixed48::from(500).abs(), Fixed48::from(500));
+        assert_eq!(Fixed48::from(-500).abs(), Fixed48::from(500));
+        assert_eq!(Fixed48::from(-1.5).abs(), Fixed48::from(1.5));
+        assert_eq!(Fixed48::from(-2.5).abs(), Fixed48::from(2.5));
+    }
+
+    #[test]
+    fn fixed48_floor() {
+        assert_eq!(Fixed48::from(1).floor(), Fixed48::from(1));
+        assert_eq!(Fixed48::from(500).floor(), Fixed48::from(500));
+        assert_eq!(Fixed48::from(1.5).floor(), Fixed48::from(1));
+        assert_eq!(Fixed48::from(1.99999).floor(), Fixed48::from(1));
+        assert_eq!(Fixed48::from(-0.0001).floor(), Fixed48::from(-1));
+        assert_eq!(Fixed48::from(-2.5).floor(), Fixed48::from(-3));
+    }
+
+    #[test]
+    fn fixed48_ceil() {
+        assert_eq!(Fixed48::from(1).ceil(), Fixed48::from(1));
+        assert_eq!(Fixed48::from(500).ceil(), Fixed48::from(500));
+        assert_eq!(Fixed48::from(1.5).ceil(), Fixed48::from(2));
+        assert_eq!(Fixed48::from(1.99999).ceil(), Fixed48::from(2));
+        assert_eq!(Fixed48::from(-0.0001).ceil(), Fixed48::from(0));
+        assert_eq!(Fixed48::from(-2.5).ceil(), Fixed48::from(-2));
+    }
+
+    #[test]
+    fn fixed48_round() {
+        assert_eq!(Fixed48::from(1).round(), Fixed48::from(1));
+        assert_eq!(Fixed48::from(500).round(), Fixed48::from(500));
+        assert_eq!(Fixed48::from(1.5).round(), Fixed48::from(2));
+        assert_eq!(Fixed48::from(1.49999).round(), Fixed48::from(1));
+        assert_eq!(Fixed48::from(1.99999).round(), Fixed48::from(2));
+        assert_eq!(Fixed48::from(-0.0001).round(), Fixed48::from(0));
+        assert_eq!(Fixed48::from(-2.5).round(), Fixed48::from(-2));
+        assert_eq!(Fixed48::from(-2.9).round(), Fixed48::from(-3));
+    }
+
+    #[test]
+    fn fixed48_trunc() {
+        assert_eq!(Fixed48::from(1).trunc(), Fixed48::from(1));
+        assert_eq!(Fixed48::from(500).trunc(), Fixed48::from(500));
+        assert_eq!(Fixed48::from(1.5).trunc(), Fixed48::from(1));
+        assert_eq!(Fixed48::from(1.49999).trunc(), Fixed48::from(1));
+        assert_eq!(Fixed48::from(1.99999).trunc(), Fixed48::from(1));
+        assert_eq!(Fixed48::from(-0.0001).trunc(), Fixed48::from(0));
+        assert_eq!(Fixed48::from(-2.5).trunc(), Fixed48::from(-2));
+        assert_eq!(Fixed48::from(-2.9).trunc(), Fixed48::from(-2));
+    }
+
+    #[test]
+    fn fixed48_fract() {
+        assert_eq!(Fixed48::from(0).fract(), Fixed48::from(0));
+        assert_eq!(Fixed48::from(0.1).fract(), Fixed48::from(0.1));
+        assert_eq!(Fixed48::from(0.9).fract(), Fixed48::from(0.9));
+        assert_eq!(Fixed48::from(1.5).fract(), Fixed48::from(0.5));
+        assert_eq!(Fixed48::from(-2.5).fract(), Fixed48::from(0.5));
+    }
+
+    #[test]
+    fn fixed48_round_scaled() {
+        assert_eq!(Fixed48::from(0.5).round_scaled(4), 8);
+        assert_eq!(Fixed48::from(0.5).round_scaled(8), 128);
+        assert_eq!(Fixed48::from(0.5).round_scaled(16), 32768);
+        assert_eq!(Fixed48::from(1).round_scaled(16), 65536);
+        assert_eq!(Fixed48::from(0.3).round_scaled(4), 5);
+        assert_eq!(Fixed48::from(0.3).round_scaled(8), 77);
+        assert_eq!(Fixed48::from(1).round_scaled(0), 1);
+    }
+
+    #[test]
+    fn fixed48_rounding() {
+        let bits = Fixed48::from_bits;
+        let big = Fixed48::from(1i64 << 40);
+        assert_eq!(bits(0x180).round_scaled(8), 2);
+        assert_eq!(bits(0x280).round_scaled(8), 2);
+        assert_eq!(bits(-3).mul_round(Fixed48::HALF), bits(-2));
+        assert_eq!(bits(-1).mul_round(Fixed48::HALF), bits(0));
+        let v = (big + bits(3)).mul_round(Fixed48::HALF);
+        assert_eq!(v, Fixed48::from(1i64 << 39) + bits(2));
+        let three = Fixed48::from(3);
+        assert_eq!(Fixed48::from(-2).div_round(three), bits(-43691));
+        assert_eq!(bits(3).div_round(-Fixed48::from(2)), bits(-2));
+        let v = Fixed48::from(2).mul_div_round(Fixed48::ONE, three);
+        assert_eq!(v, bits(43691));
+        let v = big.mul_div_round(Fixed48::from(3), three);
+        assert_eq!(v, big);
+        let tiny = Fixed48::EPSILON;
+        assert_eq!(big.div_round(tiny), Fixed48::MAX);
+    }
+
+    #[test]
+    fn fixed48_saturate() {
+        let tiny = Fixed48::EPSILON;
+        let big = Fixed48::from(1i64 << 40);
+        assert_eq!(big / tiny, Fixed48::MAX);
+        assert_eq!(-big / tiny, Fixed48::ZERO - Fixed48::MAX);
+        assert_eq!(big * Fixed48::from(256), Fixed48::MAX);
+        assert_eq!(Fixed48::MAX.saturating_add(Fixed48::ONE), Fixed48::MAX);
+        let v = big.mul_div(Fixed48::from(0.5), Fixed48::ONE);
+        assert_eq!(v, Fixed48::from(1i64 << 39));
+        // Out of range for Fixed
+        let v = Fixed48::from(20_000) * Fixed48::from(4);
+        assert_eq!(v, Fixed48::from(80_000));
+    }
+
+    #[test]
+    fn fixed48_checked() {
+        let max = Fixed48::MAX;
+        let min = Fixed48::MIN;
+        let eps = Fixed48::EPSILON;
+        let lim = 1i64 << 47;
+        assert_eq!(max.checked_add(eps), None);
+        assert_eq!((max - eps).checked_add(eps), Some(max));
+        assert_eq!(Fixed48::from(-lim).checked_sub(eps), None);
+        assert_eq!((min + eps).checked_sub(eps), Some(min));
+        assert_eq!(Fixed48::from(-lim).checked_add(Fixed48::ZERO), Some(min));
+        let a = Fixed48::from(1i64 << 24);
+        let b = Fixed48::from(1i64 << 23);
+        assert_eq!(a.checked_mul(b), None);
+        assert_eq!(a.checked_mul(-b), Some(min));
+        let v =
+            Fixed48::from(11_863_283).checked_mul(Fixed48::from(11_863_283));
+        assert_eq!(v, Some(Fixed48::from(140_737_483_538_089i64)));
+        let v = Fixed48::from(32_768).checked_mul(Fixed48::from(32_768));
+        assert_eq!(v, Some(Fixed48::from(1i64 << 30)));
+        assert_eq!(max.checked_mul(Fixed48::ONE), Some(max));
+        assert_eq!(Fixed48::from(1).checked_div(Fixed48::ZERO), None);
+        let c = Fixed48::from(1i64 << 46);
+        assert_eq!(c.checked_div(Fixed48::HALF), None);
+        assert_eq!((-c).checked_div(Fixed48::HALF), Some(min));
+        let v = Fixed48::from(3).checked_div(Fixed48::from(-2));
+        assert_eq!(v, Some(Fixed48::from(-1.5)));
+        assert_eq!(min.checked_div(Fixed48::from(-1)), None);
+    }
+
+    #[test]
+    fn fixed48_saturating() {
+        let max = Fixed48::MAX;
+        let min = Fixed48::MIN;
+        let eps = Fixed48::EPSILON;
+        let lim = 1i64 << 47;
+        assert_eq!(max.saturating_add(eps), max);
+        assert_eq!(min.saturating_add(-eps), min);
+        assert_eq!((max - eps).saturating_add(eps), max);
+        assert_eq!(min.saturating_sub(eps), min);
+        assert_eq!(max.saturating_sub(-eps), max);
+        assert_eq!((min + eps).saturating_sub(eps), min);
+        let v = Fixed48::from(1 - lim).saturating_sub(Fixed48::from(2));
+        assert_eq!(v, min);
+        let a = Fixed48::from(1i64 << 24);
+        let b = Fixed48::from(1i64 << 23);
+        assert_eq!(a.saturating_mul(b), max);
+        assert_eq!(a.saturating_mul(-b - Fixed48::ONE), min);
+        assert_eq!((-a).saturating_mul(-a), max);
+        let v = Fixed48::from(100_000).saturating_mul(Fixed48::from(-2.5));
+        assert_eq!(v, Fixed48::from(-250_000));
+        let c = Fixed48::from(1i64 << 46);
+        assert_eq!(c.saturating_div(Fixed48::HALF), max);
+        assert_eq!((-c - eps).saturating_div(Fixed48::HALF), min);
+        assert_eq!(min.saturating_div(Fixed48::from(-1)), max);
+        assert_eq!(Fixed48::from(1i64 << 32).saturating_div(eps), max);
+        let v = Fixed48::from(-7).saturating_div(Fixed48::from(2));
+        assert_eq!(v, Fixed48::from(-3.5));
+    }
+
+    #[test]
+    #[should_panic]
+    fn fixed48_saturating_div_zero() {
+        let _ = Fixed48::ONE.saturating_div(Fixed48::ZERO);
+    }
+
+    #[test]
+    fn fixed48_avg() {
+        assert_eq!(Fixed48::from(1).avg(Fixed48::from(2)), Fixed48::from(1.5));
+        assert_eq!(Fixed48::from(1).avg(Fixed48::from(1)), Fixed48::from(1));
+        assert_eq!(Fixed48::from(5).avg(Fixed48::from(-5)), Fixed48::from(0));
+        assert_eq!(Fixed48::from(3).avg(Fixed48::from(37)), Fixed48::from(20));
+        assert_eq!(
+            Fixed48::from(3).avg(Fixed48::from(1.5)),
+            Fixed48::from(2.25)
+        );
+    }
+
+    #[test]
+    fn fixed48_into() {
+        let i: i64 = Fixed48::from(37).into();
+        assert_eq!(i, 37);
+        let f: f32 = Fixed48::from(2.5).into();
+        assert_eq!(f, 2.5);
+        let a: i64 = Fixed48::from(2.5).into();
+        assert_eq!(a, 2);
+        let b: i64 = Fixed48::from(100_000_000_000i64).into();
+        assert_eq!(b, 100_000_000_000);
+        let c: i64 = Fixed48::from(-2.5).into();
+        assert_eq!(c, -3);
+    }
+
+    #[test]
+    fn fixed48_f64() {
+        let v = Fixed48::from(123_456_789.5f64);
+        assert_eq!(f64::from(v), 123_456_789.5);
+        assert_eq!(v.fract(), Fixed48::HALF);
+        assert_eq!(Fixed48::from(-0.75f64), Fixed48::from(-0.75f32));
+        let v = Fixed48::from(1e12f64);
+        assert_eq!(v, Fixed48::from(1_000_000_000_000i64));
+        assert_eq!(f64::from(v), 1e12);
+        // Truncated toward zero
+        let v = Fixed48::from(-1e-6f64);
+        assert_eq!(v, Fixed48::ZERO);
+    }
+
+    #[test]
+    fn fixed48_from_fixed() {
+        for f in [Fixed::MIN, Fixed::from(-2.5), Fixed::EPSILON, Fixed::MAX] {
+            let w = Fixed48::from(f);
+            assert_eq!(w.to_bits(), i64::from(f.to_bits()));
+            assert_eq!(f64::from(w), f64::from(f));
+        }
+    }
+
+    #[test]
+    fn fixed48_cmp() {
+        assert!(Fixed48::from(37) > Fixed48::from(3));
+        assert!(Fixed48::from(3) < Fixed48::from(37));
+        assert!(Fixed48::from(-4) < Fixed48::from(4));
+        assert_eq!(
+            cmp::min(Fixed48::from(37), Fixed48::from(3)),
+            Fixed48::from(3)
+        );
+        assert_eq!(
+            cmp::max(Fixed48::from(37), Fixed48::from(3)),
+            Fixed48::from(37)
+        );
+    }
+}
@@ -2,22 +2,367 @@
 //
 // Copyright (c) 2017-2021  Douglas P Lau
 //
-use pointy::Pt;
+//! Geometry helpers for points, lines and segments.
+//!
+//! Coordinates are y-down, as in a raster: X increases to the right and Y
+//! increases downward.  For two direction vectors `a` and `b`, a positive
+//! cross product (`a.x * b.y - a.y * b.x`) means `b` turns clockwise from `a`
+//! on screen.
+use pointy::{Pt, Transform};
 
-/// 2-dimensional vector / point with associated width.
+/// Maximum subdivision depth for curve decomposition
+///
+/// Deeper subdivision would produce more points than a figure can hold.
+pub(crate) const MAX_CURVE_DEPTH: u32 = 16;
+
+/// 2-dimensional vector / point with associated width and attribute.
+///
+/// The width is used for stroking, where it can vary along a path.  The
+/// attribute is a value carried with the point, such as a color ramp offset,
+/// which is interpolated along curves in the same way as the width.
+///
+/// ```
+/// use footile::geom::WidePt;
+/// use pointy::Pt;
+///
+/// let a = WidePt(Pt::new(0.0, 0.0), 2.0, 0.0);
+/// let b = WidePt(Pt::new(10.0, 4.0), 6.0, 1.0);
+/// let m = a.midpoint(b);
+/// assert_eq!(m.0, Pt::new(5.0, 2.0));
+/// assert_eq!(m.w(), 4.0);
+/// assert_eq!(m.a(), 0.5);
+/// ```
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct WidePt(pub Pt<f32>, pub f32);
+pub struct WidePt(pub Pt<f32>, pub f32, pub f32);
 
 /// Calculate linear interpolation of two values
 ///
-/// The t value should be between 0 and 1.
+/// Returns `a` when `t` is 0 and `b` when `t` is 1.  The t value should be
+/// between 0 and 1.
 pub fn float_lerp(a: f32, b: f32, t: f32) -> f32 {
-    b + (a - b) * t
+    a + (b - a) * t
+}
+
+/// Calculate the cross product (Z component) of two vectors
+fn cross(a: Pt<f32>, b: Pt<f32>) -> f32 {
+    a.x() * b.y() - a.y() * b.x()
+}
+
+/// Find the intersection of two lines.
+///
+/// Each line passes through two points, and is not limited to the segment
+/// between them.
+///
+/// * `a0` First point on line A.
+/// * `a1` Second point on line A.
+/// * `b0` First point on line B.
+/// * `b1` Second point on line B.
+///
+/// Returns `None` if the lines are parallel or collinear, or if either pair
+/// of points is coincident.
+///
+/// ```
+/// use footile::geom::intersection;
+/// use pointy::Pt;
+///
+/// let p = intersection(
+///     Pt::new(0.0, 0.0),
+///     Pt::new(1.0, 1.0),
+///     Pt::new(4.0, 0.0),
+///     Pt::new(4.0, 1.0),
+/// );
+/// assert_eq!(p, Some(Pt::new(4.0, 4.0)));
+/// ```
+pub fn intersection(
+    a0: Pt<f32>,
+    a1: Pt<f32>,
+    b0: Pt<f32>,
+    b1: Pt<f32>,
+) -> Option<Pt<f32>> {
+    let da = a1 - a0;
+    let db = b1 - b0;
+    let cr = cross(da, db);
+    let tol = rounding_tolerance([a0, a1, b0, b1]);
+    if is_parallel(da, db, cr, tol) {
+        return None;
+    }
+    let p = a0 + da * (cross(b0 - a0, db) / cr);
+    if p.x().is_finite() && p.y().is_finite() {
+        Some(p)
+    } else {
+        None
+    }
+}
+
+/// Get a distance within rounding error of the largest coordinate
+fn rounding_tolerance(pts: [Pt<f32>; 4]) -> f32 {
+    let m = pts
+        .iter()
+        .fold(0.0, |m: f32, p| m.max(p.x().abs()).max(p.y().abs()));
+    m * 2.0 * f32::EPSILON
+}
+
+/// Check whether two direction vectors are parallel (or zero length)
+///
+/// The cross product is compared against the rounding tolerance scaled by
+/// the vector lengths, since that is how far rounding can move it.
+fn is_parallel(da: Pt<f32>, db: Pt<f32>, cr: f32, tol: f32) -> bool {
+    cr.abs() <= tol * (da.mag() + db.mag()) || !cr.is_finite()
+}
+
+/// Find the intersection of two line segments.
+///
+/// * `a0` Start of segment A.
+/// * `a1` End of segment A.
+/// * `b0` Start of segment B.
+/// * `b1` End of segment B.
+///
+/// Returns the intersection point, along with its parametric positions `t`
+/// on segment A and `u` on segment B (each from 0 at the start to 1 at the
+/// end), or `None` if the segments do not touch.
+///
+/// When the segments are collinear and overlap, the point returned is the
+/// start of the overlap nearest to `a0`.  A zero-length segment is treated as
+/// a single point, with a parametric position of 0.
+///
+/// ```
+/// use footile::geom::segment_intersection;
+/// use pointy::Pt;
+///
+/// let (p, t, u) = segment_intersection(
+///     Pt::new(0.0, 0.0),
+///     Pt::new(8.0, 0.0),
+///     Pt::new(2.0, -2.0),
+///     Pt::new(2.0, 6.0),
+/// )
+/// .unwrap();
+/// assert_eq!(p, Pt::new(2.0, 0.0));
+/// assert_eq!(t, 0.25);
+/// assert_eq!(u, 0.25);
+/// ```
+pub fn segment_intersection(
+    a0: Pt<f32>,
+    a1: Pt<f32>,
+    b0: Pt<f32>,
+    b1: Pt<f32>,
+) -> Option<(Pt<f32>, f32, f32)> {
+    let da = a1 - a0;
+    let db = b1 - b0;
+    let cr = cross(da, db);
+    let tol = rounding_tolerance([a0, a1, b0, b1]);
+    if !is_parallel(da, db, cr, tol) {
+        let ab = b0 - a0;
+        let t = cross(ab, db) / cr;
+        let u = cross(ab, da) / cr;
+        if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+            return Some((a0 + da * t, t, u));
+        }
+        return None;
+    }
+    // Parallel, collinear or degenerate segments
+    let len_sq = da.dot(da);
+    if len_sq <= tol * tol {
+        // A is a point, which may lie on B
+        if segment_dist_sq(a0, b0, b1) <= tol * tol {
+            return Some((a0, 0.0, segment_param(a0, b0, db)));
+        }
+        return None;
+    }
+    let len = len_sq.sqrt();
+    if cross(b0 - a0, da).abs() > tol * len
+        || cross(b1 - a0, da).abs() > tol * len
+    {
+        // B is not on the same line as A
+        return None;
+    }
+    let tb0 = (b0 - a0).dot(da) / len_sq;
+    let tb1 = (b1 - a0).dot(da) / len_sq;
+    let lo = tb0.min(tb1).max(0.0);
+    let hi = tb0.max(tb1).min(1.0);
+    if lo > hi {
+        return None;
+    }
+    let p = a0 + da * lo;
+    Some((p, lo, segment_param(p, b0, db)))
+}
+
+/// Get the parametric position of a point projected onto a segment
+fn segment_param(p: Pt<f32>, a: Pt<f32>, ab: Pt<f32>) -> f32 {
+    let len_sq = ab.dot(ab);
+    if len_sq > 0.0 {
+        ((p - a).dot(ab) / len_sq).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Get the X and Y scale factors of an affine transform.
+pub(crate) fn transform_scale(t: &Transform<f32>) -> (f32, f32) {
+    let o = *t * Pt::new(0.0, 0.0);
+    let sx = (*t * Pt::new(1.0, 0.0) - o).mag();
+    let sy = (*t * Pt::new(0.0, 1.0) - o).mag();
+    (sx, sy)
+}
+
+/// Map a point through the inverse of an affine transform.
+///
+/// Returns `None` if the transform is singular (not invertible).
+pub(crate) fn transform_inverse(
+    t: &Transform<f32>,
+    p: Pt<f32>,
+) -> Option<Pt<f32>> {
+    let o = *t * Pt::new(0.0, 0.0);
+    let ax = *t * Pt::new(1.0, 0.0) - o;
+    let ay = *t * Pt::new(0.0, 1.0) - o;
+    let det = ax.x() * ay.y() - ax.y() * ay.x();
+    if det.abs() <= f32::EPSILON || !det.is_finite() {
+        return None;
+    }
+    let d = p - o;
+    let x = (d.x() * ay.y() - d.y() * ay.x()) / det;
+    let y = (ax.x() * d.y() - ax.y() * d.x()) / det;
+    Some(Pt::new(x, y))
+}
+
+/// Calculate squared distance from a point to a line segment
+///
+/// * `p` Point to measure from.
+/// * `a` Start of segment.
+/// * `b` End of segment.
+///
+/// If `a` and `b` are the same, this is the squared distance to that point.
+pub fn segment_dist_sq(p: Pt<f32>, a: Pt<f32>, b: Pt<f32>) -> f32 {
+    let ab = b - a;
+    p.dist_sq(a + ab * segment_param(p, a, ab))
+}
+
+/// Calculate distance from a point to a line segment
+///
+/// * `p` Point to measure from.
+/// * `a` Start of segment.
+/// * `b` End of segment.
+///
+/// ```
+/// use footile::geom::segment_dist;
+/// use pointy::Pt;
+///
+/// let a = Pt::new(0.0, 0.0);
+/// let b = Pt::new(10.0, 0.0);
+/// assert_eq!(segment_dist(Pt::new(5.0, 3.0), a, b), 3.0);
+/// assert_eq!(segment_dist(Pt::new(13.0, 4.0), a, b), 5.0);
+/// ```
+pub fn segment_dist(p: Pt<f32>, a: Pt<f32>, b: Pt<f32>) -> f32 {
+    segment_dist_sq(p, a, b).sqrt()
+}
+
+/// Get squared tolerance for curve flattening, from a tolerance in pixels
+pub(crate) fn tol_sq(tolerance: f32) -> f32 {
+    let tol = tolerance.max(0.01);
+    tol * tol
+}
+
+/// Flatten a quadratic bézier spline into a series of lines.
+///
+/// The spline is decomposed using the De Casteljau method.  Subdivision is
+/// iterative, with an explicit stack, so deep subdivision cannot overflow the
+/// call stack.
+///
+/// * `a` Start point (not added).
+/// * `b` Control point.
+/// * `c` End point.
+/// * `tol_sq` Squared tolerance threshold.
+/// * `add` Callback to add each point.
+pub(crate) fn flatten_quad(
+    a: WidePt,
+    b: WidePt,
+    c: WidePt,
+    tol_sq: f32,
+    mut add: impl FnMut(WidePt),
+) {
+    assert!(tol_sq > 0.0);
+    let mut stack = [([a, b, c], 0); MAX_CURVE_DEPTH as usize + 1];
+    let mut n = 1;
+    while n > 0 {
+        n -= 1;
+        let ([a, b, c], depth) = stack[n];
+        let ab = a.midpoint(b);
+        let bc = b.midpoint(c);
+        let ab_bc = ab.midpoint(bc);
+        let ac = a.midpoint(c);
+        if depth >= MAX_CURVE_DEPTH || ab_bc.0.dist_sq(ac.0) <= tol_sq {
+            add(c);
+        } else {
+            // Second half is pushed first, to be popped last
+            stack[n] = ([ab_bc, bc, c], depth + 1);
+            stack[n + 1] = ([a, ab, ab_bc], depth + 1);
+            n += 2;
+        }
+    }
+}
+
+/// Flatten a cubic bézier spline into a series of lines.
+///
+/// The spline is decomposed using the De Casteljau method.  Subdivision is
+/// iterative, with an explicit stack, so deep subdivision cannot overflow the
+/// call stack.
+///
+/// A sub-curve is flat enough when its control points are close to the
+/// chord.  The curve is within 3/4 of the control point deviation from the
+/// chord, so gently curving sections need fewer segments.
+///
+/// * `pa` Start point (not added).
+/// * `pb` First control point.
+/// * `pc` Second control point.
+/// * `pd` End point.
+/// * `tol_sq` Squared tolerance threshold.
+/// * `add` Callback to add each point.
+pub(crate) fn flatten_cubic(
+    pa: WidePt,
+    pb: WidePt,
+    pc: WidePt,
+    pd: WidePt,
+    tol_sq: f32,
+    mut add: impl FnMut(WidePt),
+) {
+    assert!(tol_sq > 0.0);
+    let mut stack = [([pa, pb, pc, pd], 0); MAX_CURVE_DEPTH as usize + 1];
+    let mut n = 1;
+    while n > 0 {
+        n -= 1;
+        let ([pa, pb, pc, pd], depth) = stack[n];
+        if depth >= MAX_CURVE_DEPTH || is_cubic_flat(pa, pb, pc, pd, tol_sq) {
+            add(pd);
+        } else {
+            let ab = pa.midpoint(pb);
+            let bc = pb.midpoint(pc);
+            let cd = pc.midpoint(pd);
+            let ab_bc = ab.midpoint(bc);
+            let bc_cd = bc.midpoint(cd);
+            let pe = ab_bc.midpoint(bc_cd);
+            // Second half is pushed first, to be popped last
+            stack[n] = ([pe, bc_cd, cd, pd], depth + 1);
+            stack[n + 1] = ([pa, ab, ab_bc, pe], depth + 1);
+            n += 2;
+        }
+    }
+}
+
+/// Check if a cubic bézier spline is flat within tolerance threshold.
+fn is_cubic_flat(
+    a: WidePt,
+    b: WidePt,
+    c: WidePt,
+    d: WidePt,
+    tol_sq: f32,
+) -> bool {
+    let db = segment_dist_sq(b.0, a.0, d.0);
+    let dc = segment_dist_sq(c.0, a.0, d.0);
+    db.max(dc) * (9.0 / 16.0) <= tol_sq
 }
 
 impl Default for WidePt {
     fn default() -> Self {
-        WidePt(Pt::default(), 1.0)
+        WidePt(Pt::default(), 1.0, 0.0)
     }
 }
 
@@ -27,10 +372,174 @@ impl WidePt {
         self.1
     }
 
+    /// Get the attribute
+    pub fn a(self) -> f32 {
+        self.2
+    }
+
     /// Find the midpoint between two wide points
     pub fn midpoint(self, rhs: Self) -> Self {
         let v = self.0.midpoint(rhs.0);
         let w = (self.w() + rhs.w()) / 2.0;
-        WidePt(v, w)
+        let a = (self.a() + rhs.a()) / 2.0;
+        WidePt(v, w, a)
+    }
+
+    /// Interpolate between two wide points
+    pub fn lerp(self, rhs: Self, t: f32) -> Self {
+        let v = self.0 + (rhs.0 - self.0) * t;
+        let w = self.w() + (rhs.w() - self.w()) * t;
+        let a = self.a() + (rhs.a() - self.a()) * t;
+        WidePt(v, w, a)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lerp() {
+        assert_eq!(float_lerp(2.0, 6.0, 0.0), 2.0);
+        assert_eq!(float_lerp(2.0, 6.0, 0.25), 3.0);
+        assert_eq!(float_lerp(2.0, 6.0, 1.0), 6.0);
+        let a = WidePt(Pt::new(0.0, 0.0), 1.0, 4.0);
+        let b = WidePt(Pt::new(8.0, -4.0), 5.0, 0.0);
+        assert_eq!(a.lerp(b, 0.25), WidePt(Pt::new(2.0, -1.0), 2.0, 3.0));
+        assert_eq!(a.midpoint(b), a.lerp(b, 0.5));
+        assert_eq!(WidePt::default().w(), 1.0);
+        assert_eq!(WidePt::default().a(), 0.0);
+    }
+
+    #[test]
+    fn lines() {
+        let p = intersection(
+            Pt::new(0.0, 0.0),
+            Pt::new(2.0, 0.0),
+            Pt::new(5.0, -1.0),
+            Pt::new(5.0, 1.0),
+        );
+        assert_eq!(p, Some(Pt::new(5.0, 0.0)));
+        let p = intersection(
+            Pt::new(0.0, 0.0),
+            Pt::new(1.0, 2.0),
+            Pt::new(0.0, 4.0),
+            Pt::new(1.0, 2.0),
+        );
+        assert_eq!(p, Some(Pt::new(1.0, 2.0)));
+    }
+
+    #[test]
+    fn lines_parallel() {
+        let a0 = Pt::new(0.0, 0.0);
+        let a1 = Pt::new(3.0, 1.0);
+        // Parallel
+        let b0 = Pt::new(0.0, 2.0);
+        assert_eq!(intersection(a0, a1, b0, b0 + (a1 - a0)), None);
+        // Collinear
+        let b0 = Pt::new(6.0, 2.0);
+        assert_eq!(intersection(a0, a1, b0, Pt::new(9.0, 3.0)), None);
+        // Coincident points
+        assert_eq!(intersection(a0, a0, b0, Pt::new(0.0, 5.0)), None);
+    }
+
+    #[test]
+    fn segments() {
+        let a0 = Pt::new(0.0, 0.0);
+        let a1 = Pt::new(4.0, 4.0);
+        let (p, t, u) =
+            segment_intersection(a0, a1, Pt::new(0.0, 4.0), Pt::new(4.0, 0.0))
+                .unwrap();
+        assert_eq!(p, Pt::new(2.0, 2.0));
+        assert_eq!((t, u), (0.5, 0.5));
+        // Touching at an end point
+        let (p, t, u) =
+            segment_intersection(a0, a1, Pt::new(4.0, 4.0), Pt::new(8.0, 0.0))
+                .unwrap();
+        assert_eq!(p, Pt::new(4.0, 4.0));
+        assert_eq!((t, u), (1.0, 0.0));
+        // Lines cross, but past the end of B
+        let r =
+            segment_intersection(a0, a1, Pt::new(0.0, 4.0), Pt::new(1.0, 3.0));
+        assert_eq!(r, None);
+        // Lines cross, but before the start of A
+        let r = segment_intersection(
+            a0,
+            a1,
+            Pt::new(-4.0, 0.0),
+            Pt::new(0.0, -4.0),
+        );
+        assert_eq!(r, None);
+    }
+
+    #[test]
+    fn segments_parallel() {
+        let a0 = Pt::new(0.0, 0.0);
+        let a1 = Pt::new(4.0, 0.0);
+        let r =
+            segment_intersection(a0, a1, Pt::new(0.0, 1.0), Pt::new(4.0, 1.0));
+        assert_eq!(r, None);
+        let r =
+            segment_intersection(a0, a1, Pt::new(4.0, 1.0), Pt::new(0.0, 1.0));
+        assert_eq!(r, None);
+    }
+
+    #[test]
+    fn segments_collinear() {
+        let a0 = Pt::new(0.0, 0.0);
+        let a1 = Pt::new(4.0, 0.0);
+        // Overlapping
+        let r =
+            segment_intersection(a0, a1, Pt::new(2.0, 0.0), Pt::new(6.0, 0.0));
+        assert_eq!(r, Some((Pt::new(2.0, 0.0), 0.5, 0.0)));
+        // Overlapping, B reversed
+        let r =
+            segment_intersection(a0, a1, Pt::new(6.0, 0.0), Pt::new(2.0, 0.0));
+        assert_eq!(r, Some((Pt::new(2.0, 0.0), 0.5, 1.0)));
+        // B contains A
+        let r =
+            segment_intersection(a0, a1, Pt::new(-4.0, 0.0), Pt::new(8.0, 0.0));
+        assert_eq!(r, Some((a0, 0.0, 1.0 / 3.0)));
+        // Touching end to end
+        let r =
+            segment_intersection(a0, a1, Pt::new(4.0, 0.0), Pt::new(6.0, 0.0));
+        assert_eq!(r, Some((a1, 1.0, 0.0)));
+        // Inexact coordinates
+        let r = segment_intersection(
+            Pt::new(100.1, 0.2),
+            Pt::new(100.3, 0.6),
+            Pt::new(100.2, 0.4),
+            Pt::new(100.7, 1.4),
+        );
+        assert!(r.is_some());
+        // Disjoint
+        let r =
+            segment_intersection(a0, a1, Pt::new(5.0, 0.0), Pt::new(6.0, 0.0));
+        assert_eq!(r, None);
+    }
+
+    #[test]
+    fn segments_degenerate() {
+        let a0 = Pt::new(0.0, 0.0);
+        let a1 = Pt::new(4.0, 2.0);
+        let p = Pt::new(2.0, 1.0);
+        assert_eq!(segment_intersection(a0, a1, p, p), Some((p, 0.5, 0.0)));
+        assert_eq!(segment_intersection(p, p, a0, a1), Some((p, 0.0, 0.5)));
+        assert_eq!(segment_intersection(p, p, p, p), Some((p, 0.0, 0.0)));
+        let q = Pt::new(2.0, 2.0);
+        assert_eq!(segment_intersection(a0, a1, q, q), None);
+        assert_eq!(segment_intersection(q, q, a0, a1), None);
+        assert_eq!(segment_intersection(p, p, q, q), None);
+    }
+
+    #[test]
+    fn dist() {
+        let a = Pt::new(1.0, 1.0);
+        let b = Pt::new(1.0, 5.0);
+        assert_eq!(segment_dist(Pt::new(4.0, 3.0), a, b), 3.0);
+        assert_eq!(segment_dist(Pt::new(4.0, 9.0), a, b), 5.0);
+        assert_eq!(segment_dist(Pt::new(-2.0, -3.0), a, b), 5.0);
+        assert_eq!(segment_dist(Pt::new(1.0, 2.0), a, b), 0.0);
+        assert_eq!(segment_dist_sq(Pt::new(4.0, 5.0), a, a), 25.0);
     }
 }
@@ -2,9 +2,10 @@
 //
 // Copyright (c) 2017-2020  Douglas P Lau
 //
-use pix::chan::{Ch8, Linear, Premultiplied};
+use pix::chan::{Ch8, Channel};
 use pix::el::Pixel;
 use pix::matte::Matte8;
+use pix::rgb::Rgba8p;
 use std::any::TypeId;
 use std::slice::from_raw_parts_mut;
 
@@ -18,24 +19,29 @@ use std::arch::x86_64::*;
 ///
 /// * `dst` Destination buffer.
 /// * `sgn_area` Signed area.
+/// * `alpha` Alpha of source color, scaling coverage.
 #[inline]
-pub fn matte_src_over_non_zero<P>(dst: &mut [P], sgn_area: &mut [i16])
-where
-    P: Pixel<Chan = Ch8, Alpha = Premultiplied, Gamma = Linear>,
+pub fn matte_src_over_non_zero<P>(
+    dst: &mut [P],
+    sgn_area: &mut [i16],
+    alpha: Ch8,
+) where
+    P: Pixel,
 {
     debug_assert_eq!(TypeId::of::<P>(), TypeId::of::<Matte8>());
-    let n_bytes = dst.len() * std::mem::size_of::<Matte8>();
+    let n_bytes = std::mem::size_of_val(dst);
     let ptr = dst.as_mut_ptr() as *mut u8;
     let dst = unsafe { from_raw_parts_mut(ptr, n_bytes) };
-    accumulate_non_zero(dst, sgn_area);
+    accumulate_non_zero(dst, sgn_area, u8::from(alpha));
 }
 
 /// Accumulate signed area with non-zero fill rule.
-/// Source buffer is zeroed upon return.
+/// Source buffer is zeroed upon return.  Returns the sum of signed area.
 ///
 /// * `dst` Destination buffer.
 /// * `src` Source buffer.
-fn accumulate_non_zero(dst: &mut [u8], src: &mut [i16]) {
+/// * `alpha` Alpha to scale coverage.
+fn accumulate_non_zero(dst: &mut [u8], src: &mut [i16], alpha: u8) -> i16 {
     assert!(dst.len() <= src.len());
     #[cfg(all(
         any(target_arch = "x86", target_arch = "x86_64"),
@@ -43,33 +49,57 @@ fn accumulate_non_zero(dst: &mut [u8], src: &mut [i16]) {
     ))]
     {
         if is_x86_feature_detected!("ssse3") {
-            unsafe { accumulate_non_zero_x86(dst, src) }
-            return;
+            // Only full chunks of 8, to avoid writing past end of dst
+            let n = dst.len() & !7;
+            let (dst, dtail) = dst.split_at_mut(n);
+            let (src, stail) = src.split_at_mut(n);
+            let sum = unsafe { accumulate_non_zero_x86(dst, src, alpha) };
+            return accumulate_non_zero_fallback(dtail, stail, sum, alpha);
         }
     }
-    accumulate_non_zero_fallback(dst, src)
+    accumulate_non_zero_fallback(dst, src, 0, alpha)
 }
 
 /// Accumulate signed area with non-zero fill rule.
-fn accumulate_non_zero_fallback(dst: &mut [u8], src: &mut [i16]) {
-    let mut sum = 0;
+///
+/// * `sum` Initial sum of signed area.
+/// * `alpha` Alpha to scale coverage.
+///
+/// Returns the sum of signed area.
+fn accumulate_non_zero_fallback(
+    dst: &mut [u8],
+    src: &mut [i16],
+    sum: i16,
+    alpha: u8,
+) -> i16 {
+    let mut sum = sum;
     for (d, s) in dst.iter_mut().zip(src.iter_mut()) {
-        sum += *s;
+        // Wrap on overflow, like the SIMD fast path
+        sum = sum.wrapping_add(*s);
         *s = 0;
-        *d = saturating_cast_i16_u8(sum);
+        *d = scale_u8(saturating_cast_i16_u8(sum.wrapping_abs()), alpha);
     }
+    sum
 }
 
 /// Cast an i16 to a u8 with saturation
 fn saturating_cast_i16_u8(v: i16) -> u8 {
-    v.max(0).min(255) as u8
+    v.clamp(0, 255) as u8
 }
 
 /// Accumulate signed area with non-zero fill rule.
+///
+/// Length of dst must be a multiple of 8.  Returns the sum of signed area.
 #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "simd"))]
 #[target_feature(enable = "ssse3")]
-unsafe fn accumulate_non_zero_x86(dst: &mut [u8], src: &mut [i16]) {
+unsafe fn accumulate_non_zero_x86(
+    dst: &mut [u8],
+    src: &mut [i16],
+    alpha: u8,
+) -> i16 {
     let zero = _mm_setzero_si128();
+    let scale = alpha < u8::MAX;
+    let alpha = _mm_set1_epi16(i16::from(alpha));
     let mut sum = zero;
     let len = dst.len().min(src.len());
     let dst = dst.as_mut_ptr();
@@ -86,13 +116,19 @@ unsafe fn accumulate_non_zero_x86(dst: &mut [u8], src: &mut [i16]) {
         a = accumulate_i16x8_x86(a);
         // add in previous sum
         a = _mm_add_epi16(a, sum);
+        // winding may be negative
+        let val = _mm_abs_epi16(a);
         // pack to u8 using saturation
-        let b = _mm_packus_epi16(a, a);
+        let mut b = _mm_packus_epi16(val, val);
+        if scale {
+            b = scale_u8x8_x86(b, alpha);
+        }
         // store result to dest
         _mm_storel_epi64(d, b);
         // shuffle sum into all 16-bit lanes
         sum = _mm_shuffle_epi8(a, _mm_set1_epi16(0x0F_0E));
     }
+    _mm_extract_epi16(sum, 0) as i16
 }
 
 /// Accumulate signed area sum thru 8 pixels.
@@ -117,24 +153,29 @@ unsafe fn accumulate_i16x8_x86(mut a: __m128i) -> __m128i {
 ///
 /// * `dst` Destination buffer.
 /// * `sgn_area` Signed area.
+/// * `alpha` Alpha of source color, scaling coverage.
 #[inline]
-pub fn matte_src_over_even_odd<P>(dst: &mut [P], sgn_area: &mut [i16])
-where
-    P: Pixel<Chan = Ch8, Alpha = Premultiplied, Gamma = Linear>,
+pub fn matte_src_over_even_odd<P>(
+    dst: &mut [P],
+    sgn_area: &mut [i16],
+    alpha: Ch8,
+) where
+    P: Pixel,
 {
     debug_assert_eq!(TypeId::of::<P>(), TypeId::of::<Matte8>());
-    let n_bytes = dst.len() * std::mem::size_of::<P>();
+    let n_bytes = std::mem::size_of_val(dst);
     let ptr = dst.as_mut_ptr() as *mut u8;
     let dst = unsafe { std::slice::from_raw_parts_mut(ptr, n_bytes) };
-    accumulate_even_odd(dst, sgn_area);
+    accumulate_even_odd(dst, sgn_area, u8::from(alpha));
 }
 
 /// Accumulate signed area with even-odd fill rule.
-/// Source buffer is zeroed upon return.
+/// Source buffer is zeroed upon return.  Returns the sum of signed area.
 ///
 /// * `dst` Destination buffer.
 /// * `src` Source buffer.
-fn accumulate_even_odd(dst: &mut [u8], src: &mut [i16]) {
+/// * `alpha` Alpha to scale coverage.
+fn accumulate_even_odd(dst: &mut [u8], src: &mut [i16], alpha: u8) -> i16 {
     assert!(dst.len() <= src.len());
     #[cfg(all(
         any(target_arch = "x86", target_arch = "x86_64"),
@@ -142,31 +183,55 @@ fn accumulate_even_odd(dst: &mut [u8], src: &mut [i16]) {
     ))]
     {
         if is_x86_feature_detected!("ssse3") {
-            unsafe { accumulate_even_odd_x86(dst, src) }
-            return;
+            // Only full chunks of 8, to avoid writing past end of dst
+            let n = dst.len() & !7;
+            let (dst, dtail) = dst.split_at_mut(n);
+            let (src, stail) = src.split_at_mut(n);
+            let sum = unsafe { accumulate_even_odd_x86(dst, src, alpha) };
+            return accumulate_even_odd_fallback(dtail, stail, sum, alpha);
         }
     }
-    accumulate_even_odd_fallback(dst, src)
+    accumulate_even_odd_fallback(dst, src, 0, alpha)
 }
 
 /// Accumulate signed area with even-odd fill rule.
-fn accumulate_even_odd_fallback(dst: &mut [u8], src: &mut [i16]) {
-    let mut sum = 0;
+///
+/// * `sum` Initial sum of signed area.
+/// * `alpha` Alpha to scale coverage.
+///
+/// Returns the sum of signed area.
+fn accumulate_even_odd_fallback(
+    dst: &mut [u8],
+    src: &mut [i16],
+    sum: i16,
+    alpha: u8,
+) -> i16 {
+    let mut sum = sum;
     for (d, s) in dst.iter_mut().zip(src.iter_mut()) {
-        sum += *s;
+        // Wrap on overflow, like the SIMD fast path
+        sum = sum.wrapping_add(*s);
         *s = 0;
         let v = sum & 0xFF;
         let odd = sum & 0x100;
         let c = (v - odd).abs();
-        *d = saturating_cast_i16_u8(c);
+        *d = scale_u8(saturating_cast_i16_u8(c), alpha);
     }
+    sum
 }
 
 /// Accumulate signed area with even-odd fill rule.
+///
+/// Length of dst must be a multiple of 8.  Returns the sum of signed area.
 #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "simd"))]
 #[target_feature(enable = "ssse3")]
-unsafe fn accumulate_even_odd_x86(dst: &mut [u8], src: &mut [i16]) {
+unsafe fn accumulate_even_odd_x86(
+    dst: &mut [u8],
+    src: &mut [i16],
+    alpha: u8,
+) -> i16 {
     let zero = _mm_setzero_si128();
+    let scale = alpha < u8::MAX;
+    let alpha = _mm_set1_epi16(i16::from(alpha));
     let mut sum = zero;
     for (d, s) in dst.chunks_mut(8).zip(src.chunks_mut(8)) {
         let d = d.as_mut_ptr() as *mut __m128i;
@@ -184,12 +249,220 @@ unsafe fn accumulate_even_odd_x86(dst: &mut [u8], src: &mut [i16]) {
         val = _mm_sub_epi16(val, odd);
         val = _mm_abs_epi16(val);
         // pack to u8 using saturation
-        let b = _mm_packus_epi16(val, val);
+        let mut b = _mm_packus_epi16(val, val);
+        if scale {
+            b = scale_u8x8_x86(b, alpha);
+        }
         // store result to dest
         _mm_storel_epi64(d, b);
         // shuffle sum into all 16-bit lanes
         sum = _mm_shuffle_epi8(a, _mm_set1_epi16(0x0F_0E));
     }
+    _mm_extract_epi16(sum, 0) as i16
+}
+
+/// Composite a solid Rgba8p color using a signed area with non-zero fill rule.
+/// Source buffer is zeroed upon return.
+///
+/// * `dst` Destination buffer.
+/// * `sgn_area` Signed area.
+/// * `clr` Source color.
+#[inline]
+pub fn rgba8p_src_over_non_zero<P>(dst: &mut [P], sgn_area: &mut [i16], clr: P)
+where
+    P: Pixel,
+{
+    let (dst, clr) = rgba8p_bytes(dst, clr);
+    src_over_coverage(dst, sgn_area, clr, accumulate_non_zero);
+}
+
+/// Composite a solid Rgba8p color using a signed area with even-odd fill rule.
+/// Source buffer is zeroed upon return.
+///
+/// * `dst` Destination buffer.
+/// * `sgn_area` Signed area.
+/// * `clr` Source color.
+#[inline]
+pub fn rgba8p_src_over_even_odd<P>(dst: &mut [P], sgn_area: &mut [i16], clr: P)
+where
+    P: Pixel,
+{
+    let (dst, clr) = rgba8p_bytes(dst, clr);
+    src_over_coverage(dst, sgn_area, clr, accumulate_even_odd);
+}
+
+/// Get Rgba8p pixels and color as bytes.
+fn rgba8p_bytes<P: Pixel>(dst: &mut [P], clr: P) -> (&mut [u8], [u8; 4]) {
+    assert_eq!(TypeId::of::<P>(), TypeId::of::<Rgba8p>());
+    let n_bytes = std::mem::size_of_val(dst);
+    let ptr = dst.as_mut_ptr() as *mut u8;
+    let dst = unsafe { from_raw_parts_mut(ptr, n_bytes) };
+    let clr = clr.channels();
+    let clr = [0, 1, 2, 3].map(|i| u8::from(Ch8::from(clr[i].to_f32())));
+    (dst, clr)
+}
+
+/// Composite a solid color using coverage accumulated from signed area.
+///
+/// Coverage is accumulated in chunks into a buffer on the stack.
+///
+/// * `dst` Destination buffer, 4 bytes per pixel.
+/// * `sgn_area` Signed area.
+/// * `clr` Source color.
+/// * `accumulate` Function to accumulate coverage for a fill rule.
+fn src_over_coverage(
+    dst: &mut [u8],
+    sgn_area: &mut [i16],
+    clr: [u8; 4],
+    accumulate: fn(&mut [u8], &mut [i16], u8) -> i16,
+) {
+    let mut cov = [0; 64];
+    let mut sum = 0;
+    for (d, s) in dst.chunks_mut(64 * 4).zip(sgn_area.chunks_mut(64)) {
+        let cov = &mut cov[..d.len() / 4];
+        // Carry sum from previous chunk
+        s[0] = s[0].wrapping_add(sum);
+        sum = accumulate(cov, s, u8::MAX);
+        src_over_row(d, cov, clr);
+    }
+}
+
+/// Composite a solid color over a row of Rgba8p pixels with SrcOver.
+///
+/// * `dst` Destination buffer, 4 bytes per pixel.
+/// * `cov` Coverage of each pixel.
+/// * `clr` Source color.
+fn src_over_row(dst: &mut [u8], cov: &[u8], clr: [u8; 4]) {
+    assert!(cov.len() * 4 <= dst.len());
+    #[cfg(all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        feature = "simd"
+    ))]
+    {
+        if is_x86_feature_detected!("ssse3") {
+            // Only full chunks of 4 pixels
+            let n = cov.len() & !3;
+            let (dst, dtail) = dst.split_at_mut(n * 4);
+            let (cov, ctail) = cov.split_at(n);
+            unsafe { src_over_row_x86(dst, cov, clr) };
+            src_over_row_fallback(dtail, ctail, clr);
+            return;
+        }
+    }
+    src_over_row_fallback(dst, cov, clr)
+}
+
+/// Composite a solid color over a row of Rgba8p pixels with SrcOver.
+///
+/// Uses the same channel arithmetic as `Pixel::composite_channels_alpha`.
+fn src_over_row_fallback(dst: &mut [u8], cov: &[u8], clr: [u8; 4]) {
+    for (d, c) in dst.chunks_exact_mut(4).zip(cov) {
+        // Compositing with zero coverage would round down small values
+        if *c == 0 {
+            continue;
+        }
+        let (c, c1) = (Ch8::new(*c), Ch8::new(255 - *c));
+        for (d, s) in d.iter_mut().zip(clr) {
+            *d = u8::from(Ch8::new(s) * c + Ch8::new(*d) * c1);
+        }
+    }
+}
+
+/// Composite a solid color over a row of Rgba8p pixels with SrcOver.
+///
+/// Length of cov must be a multiple of 4.
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "simd"))]
+#[target_feature(enable = "ssse3")]
+unsafe fn src_over_row_x86(dst: &mut [u8], cov: &[u8], clr: [u8; 4]) {
+    let zero = _mm_setzero_si128();
+    let max = _mm_set1_epi16(255);
+    // color of 2 pixels, widened to u16
+    let clr = _mm_set1_epi32(i32::from_le_bytes(clr));
+    let clr = widen_ch8_x86(_mm_unpacklo_epi8(clr, zero));
+    // shuffle to spread coverage of each pixel to all 4 channels
+    let spread = _mm_set_epi8(3, 3, 3, 3, 2, 2, 2, 2, 1, 1, 1, 1, 0, 0, 0, 0);
+    for (d, c) in dst.chunks_exact_mut(16).zip(cov.chunks_exact(4)) {
+        let d = d.as_mut_ptr() as *mut __m128i;
+        let c = i32::from_le_bytes([c[0], c[1], c[2], c[3]]);
+        let c = _mm_shuffle_epi8(_mm_cvtsi32_si128(c), spread);
+        // get 4 pixels from dst
+        let a = _mm_loadu_si128(d);
+        let lo = _mm_unpacklo_epi8(a, zero);
+        let lo = src_over_x86(lo, _mm_unpacklo_epi8(c, zero), clr, max);
+        let hi = _mm_unpackhi_epi8(a, zero);
+        let hi = src_over_x86(hi, _mm_unpackhi_epi8(c, zero), clr, max);
+        // pack to u8 using saturation
+        let b = _mm_packus_epi16(lo, hi);
+        // keep pixels with zero coverage
+        let keep = _mm_cmpeq_epi8(c, zero);
+        let b = _mm_or_si128(_mm_and_si128(keep, a), _mm_andnot_si128(keep, b));
+        // store result to dest
+        _mm_storeu_si128(d, b);
+    }
+}
+
+/// Composite 2 pixels (widened to u16) with SrcOver.
+///
+/// * `d` Destination channels.
+/// * `c` Coverage of each channel.
+/// * `clr` Source color channels, widened to 12 bits.
+/// * `max` Maximum channel value in all lanes.
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "simd"))]
+#[target_feature(enable = "ssse3")]
+unsafe fn src_over_x86(
+    d: __m128i,
+    c: __m128i,
+    clr: __m128i,
+    max: __m128i,
+) -> __m128i {
+    // s * c + d * (1 - c), with Ch8 multiplication
+    let sc = _mm_mulhi_epu16(clr, widen_ch8_x86(c));
+    let c1 = widen_ch8_x86(_mm_sub_epi16(max, c));
+    _mm_add_epi16(sc, _mm_mulhi_epu16(widen_ch8_x86(d), c1))
+}
+
+/// Widen u16 lanes holding Ch8 values to 12 bits, as in Ch8 multiplication.
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "simd"))]
+#[target_feature(enable = "ssse3")]
+unsafe fn widen_ch8_x86(a: __m128i) -> __m128i {
+    _mm_or_si128(_mm_slli_epi16(a, 4), _mm_srli_epi16(a, 4))
+}
+
+/// Scale a coverage value by an alpha, rounding to nearest.
+fn scale_u8(v: u8, alpha: u8) -> u8 {
+    if alpha < u8::MAX {
+        div_255(u32::from(v) * u32::from(alpha))
+    } else {
+        v
+    }
+}
+
+/// Scale 8 coverage values (in the low 64 bits) by an alpha, rounding to
+/// nearest.
+///
+/// * `v` Packed u8 coverage values.
+/// * `alpha` Alpha, in all 16-bit lanes.
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), feature = "simd"))]
+#[target_feature(enable = "ssse3")]
+unsafe fn scale_u8x8_x86(v: __m128i, alpha: __m128i) -> __m128i {
+    // widen to u16
+    let v = _mm_unpacklo_epi8(v, _mm_setzero_si128());
+    // v = v * alpha + 128 (fits in u16)
+    let v = _mm_add_epi16(_mm_mullo_epi16(v, alpha), _mm_set1_epi16(128));
+    // (v + (v >> 8)) >> 8, same as div_255
+    let v = _mm_srli_epi16(_mm_add_epi16(v, _mm_srli_epi16(v, 8)), 8);
+    _mm_packus_epi16(v, v)
+}
+
+/// Multiply two coverage values, rounding to nearest.
+pub fn mul_coverage(a: Ch8, b: Ch8) -> Ch8 {
+    Ch8::new(div_255(u32::from(u8::from(a)) * u32::from(u8::from(b))))
+}
+
+/// Divide by 255, rounding to nearest and saturating.
+pub fn div_255(v: u32) -> u8 {
+    let v = v + 128;
+    ((v + (v >> 8)) >> 8).min(255) as u8
 }
 
 #[cfg(test)]
@@ -201,27 +474,134 @@ mod test {
         let mut a = [0; 3000];
         let mut b = [0; 3000];
         b[0] = 200;
-        accumulate_non_zero(&mut a, &mut b);
+        accumulate_non_zero(&mut a, &mut b, 255);
         for ai in a.iter() {
             assert_eq!(*ai, 200);
         }
         let mut c = [0; 5000];
         let mut d = [0; 5000];
         d[0] = 300;
-        accumulate_non_zero(&mut c, &mut d);
+        accumulate_non_zero(&mut c, &mut d, 255);
         for ci in c.iter() {
             assert_eq!(*ci, 255);
         }
     }
 
+    #[test]
+    fn non_zero_negative() {
+        // Clockwise winding gives negative signed area
+        let mut a = [0; 3000];
+        let mut b = [0; 3000];
+        b[0] = -200;
+        accumulate_non_zero(&mut a, &mut b, 255);
+        assert!(a.iter().all(|v| *v == 200));
+        let mut a = [0; 3000];
+        let mut b = [0; 3000];
+        b[0] = -200;
+        accumulate_non_zero_fallback(&mut a, &mut b, 0, 255);
+        assert!(a.iter().all(|v| *v == 200));
+    }
+
+    #[test]
+    fn scaled() {
+        // Signed area ramps up past full coverage, then down below zero
+        let mut area = vec![0i16; 1003];
+        area[0] = -20;
+        area[1..300].iter_mut().for_each(|a| *a = 1);
+        area[400..1000].iter_mut().for_each(|a| *a = -1);
+        for alpha in [1, 128, 254, 255] {
+            for (accumulate, fallback) in [
+                (
+                    accumulate_non_zero as fn(&mut [u8], &mut [i16], u8) -> i16,
+                    accumulate_non_zero_fallback
+                        as fn(&mut [u8], &mut [i16], i16, u8) -> i16,
+                ),
+                (accumulate_even_odd, accumulate_even_odd_fallback),
+            ] {
+                let mut a = vec![0; area.len()];
+                let mut b = vec![0; area.len()];
+                let (mut sa, mut sb) = (area.clone(), area.clone());
+                let sum_a = accumulate(&mut a, &mut sa, alpha);
+                let sum_b = fallback(&mut b, &mut sb, 0, alpha);
+                assert_eq!(a, b);
+                assert_eq!(sum_a, sum_b);
+                assert!(sa.iter().chain(&sb).all(|s| *s == 0));
+                let full = fallback(&mut b[..1], &mut [255], 0, alpha);
+                assert_eq!(full, 255);
+                assert_eq!(b[0], alpha);
+            }
+        }
+    }
+
+    #[test]
+    fn deep_winding() {
+        // Winding sums past i16::MAX wrap in both SIMD and scalar paths
+        let mut area = vec![256i16; 300];
+        area.extend([0; 4]);
+        for (accumulate, fallback) in [
+            (
+                accumulate_non_zero as fn(&mut [u8], &mut [i16], u8) -> i16,
+                accumulate_non_zero_fallback
+                    as fn(&mut [u8], &mut [i16], i16, u8) -> i16,
+            ),
+            (accumulate_even_odd, accumulate_even_odd_fallback),
+        ] {
+            let mut a = vec![0; area.len()];
+            let mut b = vec![0; area.len()];
+            let (mut sa, mut sb) = (area.clone(), area.clone());
+            let sum_a = accumulate(&mut a, &mut sa, 255);
+            let sum_b = fallback(&mut b, &mut sb, 0, 255);
+            assert_eq!(a, b);
+            assert_eq!(sum_a, sum_b);
+        }
+        let mut b = vec![0; area.len()];
+        accumulate_even_odd_fallback(&mut b, &mut area.clone(), 0, 255);
+        for (i, v) in b.iter().take(300).enumerate() {
+            assert_eq!(*v, if i % 2 == 0 { 255 } else { 0 });
+        }
+    }
+
+    #[test]
+    fn src_over() {
+        let mut dst = vec![];
+        let mut cov = vec![];
+        for d in (0..=255).step_by(15) {
+            for c in 0..=255 {
+                dst.extend([d, d / 2, d / 3, d]);
+                cov.push(c);
+            }
+        }
+        for clr in [[255, 255, 255, 255], [0, 64, 128, 128], [3, 2, 1, 7]] {
+            let mut a = dst.clone();
+            let mut b = dst.clone();
+            src_over_row(&mut a, &cov, clr);
+            src_over_row_fallback(&mut b, &cov, clr);
+            assert_eq!(a, b);
+        }
+    }
+
     #[test]
     fn even_odd() {
         let mut a = [0; 3000];
         let mut b = [0; 3000];
         b[0] = 300;
-        accumulate_even_odd(&mut a, &mut b);
+        accumulate_even_odd(&mut a, &mut b, 255);
         for ai in a.iter() {
             assert_eq!(*ai, 212);
         }
     }
+
+    #[test]
+    fn row_tail() {
+        // Row width is not a multiple of 8 or 16
+        for accumulate in [accumulate_non_zero, accumulate_even_odd] {
+            let mut buf = [7; 32];
+            let mut area = [0; 32];
+            area[0] = 200;
+            let (row, rest) = buf.split_at_mut(13);
+            accumulate(row, &mut area, 255);
+            assert!(row.iter().all(|v| *v == 200));
+            assert!(rest.iter().all(|v| *v == 7));
+        }
+    }
 }
@@ -0,0 +1,1818 @@
+// ink.rs       Inks for filling.
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+//! Inks for painting fills with gradients and textures.
+use crate::blend::{BlendChannel, BlendMode};
+use crate::error::Error;
+use crate::geom::WidePt;
+use pix::chan::{Ch8, Channel, Linear, Premultiplied};
+use pix::el::{PixRgba, Pixel};
+use pix::matte::Matte8;
+use pix::{ColorModel, Raster};
+use pointy::{Pt, Transform};
+
+/// Number of entries in a gradient lookup table
+const LUT_SIZE: usize = 256;
+
+/// Ordered dither (4x4 Bayer) threshold matrix
+const BAYER_4X4: [[u8; 4]; 4] =
+    [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// Ink for painting fills, with a color which can vary by pixel.
+///
+/// Inks are used by [Plotter.fill_with].  An ink provides a source color for
+/// each pixel; by default, it is composited with `SrcOver`.
+///
+/// Columns and rows passed to inks are always absolute raster coordinates,
+/// even when a path extends outside of the raster, or is limited by a
+/// scissor or region.  Only pixels within the raster are filled.  Inks
+/// defined in the same user space as the path can map them back with the
+/// [UserMap] passed to [set_user_map](#method.set_user_map).
+///
+/// [Plotter.fill_with]: ../struct.Plotter.html#method.fill_with
+pub trait Ink<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+{
+    /// Get the source color at one pixel.
+    ///
+    /// * `x` Raster column.
+    /// * `y` Raster row.
+    fn color_at(&mut self, x: i32, y: i32) -> P;
+
+    /// Composite the ink onto one destination pixel.
+    ///
+    /// * `d` Destination pixel.
+    /// * `x` Raster column.
+    /// * `y` Raster row.
+    /// * `alpha` Coverage alpha (never zero).
+    fn fill(&mut self, d: &mut P, x: i32, y: i32, alpha: Ch8) {
+        let src = self.color_at(x, y);
+        BlendMode::SrcOver.composite(d, &src, P::Chan::from(alpha));
+    }
+
+    /// Composite the ink onto one destination pixel, with raw coverage.
+    ///
+    /// By default, coverage is converted to alpha as `min(cov, 255)`, and
+    /// passed to [fill](#method.fill).  Inks which need full precision, such
+    /// as those doing their own dithering, can implement this instead.
+    ///
+    /// * `d` Destination pixel.
+    /// * `x` Raster column.
+    /// * `y` Raster row.
+    /// * `cov` Coverage, from 1 to 256 (full).
+    fn fill_cov(&mut self, d: &mut P, x: i32, y: i32, cov: u16) {
+        self.fill(d, x, y, Ch8::new(cov.min(255) as u8));
+    }
+
+    /// Composite the ink onto a run of pixels with equal coverage.
+    ///
+    /// By default, this calls [fill_cov](#method.fill_cov) for each pixel.
+    /// Inks can implement it to avoid per-pixel overhead, such as in the
+    /// interior of large fills.
+    ///
+    /// * `dst` Destination pixels.
+    /// * `x0` Raster column of first pixel.
+    /// * `y` Raster row.
+    /// * `cov` Coverage, from 1 to 256 (full).
+    fn fill_span(&mut self, dst: &mut [P], x0: i32, y: i32, cov: u16) {
+        for (x, d) in (x0..).zip(dst) {
+            self.fill_cov(d, x, y, cov);
+        }
+    }
+
+    /// Begin filling a row.
+    ///
+    /// This is called before filling any pixels in a row, so that inks can
+    /// precompute values for it.  Inks must still work if it is never
+    /// called.
+    ///
+    /// * `y` Raster row.
+    /// * `width` Number of pixels which may be filled.
+    fn begin_row(&mut self, _y: i32, _width: usize) {}
+
+    /// End filling a row.
+    fn end_row(&mut self) {}
+
+    /// Set the map from raster pixels to user space.
+    ///
+    /// This is called by the plotter before filling with the ink, so that
+    /// patterns can follow the path when its transform changes.  Inks which
+    /// are wrappers must pass it to their inner inks.
+    ///
+    /// * `map` Raster to user space map.
+    fn set_user_map(&mut self, _map: &UserMap) {}
+}
+
+impl<P, I> Ink<P> for &mut I
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+    I: Ink<P> + ?Sized,
+{
+    fn color_at(&mut self, x: i32, y: i32) -> P {
+        (**self).color_at(x, y)
+    }
+
+    fn fill(&mut self, d: &mut P, x: i32, y: i32, alpha: Ch8) {
+        (**self).fill(d, x, y, alpha)
+    }
+
+    fn fill_cov(&mut self, d: &mut P, x: i32, y: i32, cov: u16) {
+        (**self).fill_cov(d, x, y, cov)
+    }
+
+    fn fill_span(&mut self, dst: &mut [P], x0: i32, y: i32, cov: u16) {
+        (**self).fill_span(dst, x0, y, cov)
+    }
+
+    fn begin_row(&mut self, y: i32, width: usize) {
+        (**self).begin_row(y, width)
+    }
+
+    fn end_row(&mut self) {
+        (**self).end_row()
+    }
+
+    fn set_user_map(&mut self, map: &UserMap) {
+        (**self).set_user_map(map)
+    }
+}
+
+impl<P, I> Ink<P> for Box<I>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+    I: Ink<P> + ?Sized,
+{
+    fn color_at(&mut self, x: i32, y: i32) -> P {
+        (**self).color_at(x, y)
+    }
+
+    fn fill(&mut self, d: &mut P, x: i32, y: i32, alpha: Ch8) {
+        (**self).fill(d, x, y, alpha)
+    }
+
+    fn fill_cov(&mut self, d: &mut P, x: i32, y: i32, cov: u16) {
+        (**self).fill_cov(d, x, y, cov)
+    }
+
+    fn fill_span(&mut self, dst: &mut [P], x0: i32, y: i32, cov: u16) {
+        (**self).fill_span(dst, x0, y, cov)
+    }
+
+    fn begin_row(&mut self, y: i32, width: usize) {
+        (**self).begin_row(y, width)
+    }
+
+    fn end_row(&mut self) {
+        (**self).end_row()
+    }
+
+    fn set_user_map(&mut self, map: &UserMap) {
+        (**self).set_user_map(map)
+    }
+}
+
+/// Map from raster pixels to user space.
+///
+/// This undoes the plotter's pixel origin, viewport and transform.  If the
+/// transform is singular (such as a scale of zero), only the pixel origin and
+/// viewport are undone.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UserMap {
+    /// User point at raster origin
+    origin: Pt<f32>,
+    /// Change in user point per raster column
+    col: Pt<f32>,
+    /// Change in user point per raster row
+    row: Pt<f32>,
+}
+
+impl Default for UserMap {
+    /// Create an identity map, with user space the same as raster space.
+    fn default() -> Self {
+        UserMap {
+            origin: Pt::default(),
+            col: Pt::new(1.0, 0.0),
+            row: Pt::new(0.0, 1.0),
+        }
+    }
+}
+
+impl UserMap {
+    /// Create a map from raster pixels to user space.
+    ///
+    /// * `origin` User point at raster origin.
+    /// * `col` Change in user point per raster column.
+    /// * `row` Change in user point per raster row.
+    pub(crate) fn new(origin: Pt<f32>, col: Pt<f32>, row: Pt<f32>) -> Self {
+        UserMap { origin, col, row }
+    }
+
+    /// Map a raster point to user space.
+    ///
+    /// * `x` Raster X coordinate.
+    /// * `y` Raster Y coordinate.
+    pub fn point(&self, x: f32, y: f32) -> Pt<f32> {
+        self.origin + self.col * x + self.row * y
+    }
+
+    /// Map the center of a raster pixel to user space.
+    ///
+    /// * `x` Raster column.
+    /// * `y` Raster row.
+    pub fn pixel(&self, x: i32, y: i32) -> Pt<f32> {
+        self.point(x as f32 + 0.5, y as f32 + 0.5)
+    }
+
+    /// Get the change in user point per raster column.
+    pub fn col_delta(&self) -> Pt<f32> {
+        self.col
+    }
+}
+
+/// Ink which fills with a solid color.
+///
+/// This is the same as [Plotter.fill], but can be combined with other inks.
+///
+/// [Plotter.fill]: ../struct.Plotter.html#method.fill
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ColorInk<P> {
+    /// Fill color
+    clr: P,
+}
+
+impl<P> ColorInk<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+{
+    /// Create a solid color ink.
+    pub fn new(clr: P) -> Self {
+        ColorInk { clr }
+    }
+
+    /// Get the fill color.
+    pub fn color(&self) -> P {
+        self.clr
+    }
+}
+
+impl<P> Ink<P> for ColorInk<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+{
+    fn color_at(&mut self, _x: i32, _y: i32) -> P {
+        self.clr
+    }
+
+    fn fill_span(&mut self, dst: &mut [P], x0: i32, y: i32, cov: u16) {
+        if cov >= 256 && self.clr.alpha() == P::Chan::MAX {
+            // Opaque color at full coverage replaces destination
+            dst.fill(self.clr);
+        } else {
+            for (x, d) in (x0..).zip(dst) {
+                self.fill_cov(d, x, y, cov);
+            }
+        }
+    }
+}
+
+/// Ink which writes an object ID into a buffer, for picking.
+///
+/// Wherever coverage alpha exceeds the threshold, the ID is written into the
+/// buffer.  Destination pixels are never changed.
+///
+/// ```
+/// use footile::ink::Stencil;
+/// use footile::{FillRule, Path2D, Plotter};
+/// use pix::matte::Matte8;
+/// use pix::Raster;
+///
+/// let path = Path2D::default()
+///     .absolute()
+///     .move_to(2.0, 2.0)
+///     .line_to(6.0, 2.0)
+///     .line_to(4.0, 6.0)
+///     .finish();
+/// let mut ids = vec![0; 8 * 8];
+/// let mut p = Plotter::new(Raster::<Matte8>::with_clear(8, 8));
+/// p.fill_with(FillRule::NonZero, &path, &mut Stencil::new(&mut ids, 8, 7));
+/// assert_eq!(ids[3 * 8 + 4], 7);
+/// ```
+#[derive(Debug)]
+pub struct Stencil<'a> {
+    /// ID buffer, in rows
+    ids: &'a mut [u32],
+    /// Width of each row
+    width: usize,
+    /// Object ID to write
+    id: u32,
+    /// Coverage alpha threshold
+    threshold: u8,
+}
+
+impl<'a> Stencil<'a> {
+    /// Create a stencil ink, with a threshold of 127.
+    ///
+    /// * `ids` ID buffer, in rows (usually the raster size).
+    /// * `width` Width of each row.
+    /// * `id` Object ID to write.
+    pub fn new(ids: &'a mut [u32], width: usize, id: u32) -> Self {
+        Stencil {
+            ids,
+            width,
+            id,
+            threshold: 127,
+        }
+    }
+
+    /// Set the coverage alpha threshold.
+    ///
+    /// IDs are written where coverage alpha is greater than this value.
+    pub fn with_threshold(mut self, threshold: u8) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Set the object ID to write.
+    pub fn set_id(&mut self, id: u32) {
+        self.id = id;
+    }
+}
+
+impl<P> Ink<P> for Stencil<'_>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+{
+    fn color_at(&mut self, _x: i32, _y: i32) -> P {
+        P::default()
+    }
+
+    fn fill(&mut self, _d: &mut P, x: i32, y: i32, alpha: Ch8) {
+        if u8::from(alpha) > self.threshold && x >= 0 && y >= 0 {
+            let (x, y) = (x as usize, y as usize);
+            if x < self.width {
+                if let Some(id) = self.ids.get_mut(y * self.width + x) {
+                    *id = self.id;
+                }
+            }
+        }
+    }
+}
+
+/// Ink which records coverage into a matte, while filling with another ink.
+///
+/// The matte keeps the maximum coverage at each pixel, so it can be reused
+/// for effects such as drop shadows or glows.
+///
+/// ```
+/// use footile::ink::{ColorInk, Record};
+/// use footile::{FillRule, Path2D, Plotter};
+/// use pix::matte::Matte8;
+/// use pix::rgb::Rgba8p;
+/// use pix::Raster;
+///
+/// let path = Path2D::default()
+///     .absolute()
+///     .move_to(2.0, 2.0)
+///     .line_to(6.0, 2.0)
+///     .line_to(4.0, 6.0)
+///     .finish();
+/// let mut p = Plotter::new(Raster::<Rgba8p>::with_clear(8, 8));
+/// let mut matte = Raster::with_clear(8, 8);
+/// let clr = ColorInk::new(Rgba8p::new(0, 80, 160, 255));
+/// let mut ink = Record::new(clr, &mut matte, p.raster());
+/// p.fill_with(FillRule::NonZero, &path, &mut ink);
+/// assert_eq!(matte.pixel(4, 3), Matte8::new(255));
+/// ```
+pub struct Record<'a, I> {
+    /// Inner ink
+    inner: I,
+    /// Matte for recording coverage
+    matte: &'a mut Raster<Matte8>,
+}
+
+impl<'a, I> Record<'a, I> {
+    /// Create a recording ink.
+    ///
+    /// * `inner` Ink to fill with.
+    /// * `matte` Matte to record coverage.
+    /// * `raster` Raster being plotted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matte dimensions do not match the raster.
+    pub fn new<P: Pixel>(
+        inner: I,
+        matte: &'a mut Raster<Matte8>,
+        raster: &Raster<P>,
+    ) -> Self {
+        assert_eq!(
+            (matte.width(), matte.height()),
+            (raster.width(), raster.height()),
+            "record matte dimensions must match raster"
+        );
+        Record { inner, matte }
+    }
+
+    /// Get the inner ink.
+    pub fn into_inner(self) -> I {
+        self.inner
+    }
+
+    /// Record coverage alpha for a run of pixels.
+    fn record(&mut self, x0: i32, y: i32, len: usize, alpha: u8) {
+        let (w, h) = (self.matte.width() as i32, self.matte.height() as i32);
+        if y < 0 || y >= h || x0 >= w {
+            return;
+        }
+        let x1 = (x0 + len as i32).min(w);
+        let x0 = x0.max(0);
+        if x0 < x1 {
+            let row = &mut self.matte.pixels_mut()[(y * w) as usize..];
+            for m in &mut row[x0 as usize..x1 as usize] {
+                if u8::from(m.alpha()) < alpha {
+                    *m = Matte8::new(alpha);
+                }
+            }
+        }
+    }
+}
+
+impl<P, I> Ink<P> for Record<'_, I>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+    I: Ink<P>,
+{
+    fn color_at(&mut self, x: i32, y: i32) -> P {
+        self.inner.color_at(x, y)
+    }
+
+    fn fill(&mut self, d: &mut P, x: i32, y: i32, alpha: Ch8) {
+        self.inner.fill(d, x, y, alpha);
+        self.record(x, y, 1, u8::from(alpha));
+    }
+
+    fn fill_cov(&mut self, d: &mut P, x: i32, y: i32, cov: u16) {
+        self.inner.fill_cov(d, x, y, cov);
+        self.record(x, y, 1, cov.min(255) as u8);
+    }
+
+    fn fill_span(&mut self, dst: &mut [P], x0: i32, y: i32, cov: u16) {
+        self.inner.fill_span(dst, x0, y, cov);
+        self.record(x0, y, dst.len(), cov.min(255) as u8);
+    }
+
+    fn begin_row(&mut self, y: i32, width: usize) {
+        self.inner.begin_row(y, width);
+    }
+
+    fn set_user_map(&mut self, map: &UserMap) {
+        self.inner.set_user_map(map);
+    }
+
+    fn end_row(&mut self) {
+        self.inner.end_row();
+    }
+}
+
+/// Ink which fills pixels with a closure.
+///
+/// The closure is called like [Ink.fill], with the destination pixel, column,
+/// row and coverage alpha.  It may capture mutable state, which can be
+/// inspected after filling.
+///
+/// ```
+/// use footile::ink::FnInk;
+/// use footile::{FillRule, Path2D, Plotter};
+/// use pix::chan::Ch8;
+/// use pix::matte::Matte8;
+/// use pix::Raster;
+///
+/// let path = Path2D::default()
+///     .absolute()
+///     .move_to(2.0, 2.0)
+///     .line_to(6.0, 2.0)
+///     .line_to(4.0, 6.0)
+///     .finish();
+/// let mut count = 0;
+/// let mut ink = FnInk(|d: &mut Matte8, x, _y, alpha: Ch8| {
+///     if x % 2 == 0 {
+///         *d = Matte8::new(alpha);
+///     }
+///     count += 1;
+/// });
+/// let mut p = Plotter::new(Raster::with_clear(8, 8));
+/// p.fill_with(FillRule::NonZero, &path, &mut ink);
+/// assert!(count > 0);
+/// ```
+///
+/// [Ink.fill]: trait.Ink.html#method.fill
+#[derive(Clone, Copy, Debug)]
+pub struct FnInk<F>(pub F);
+
+impl<P, F> Ink<P> for FnInk<F>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+    F: FnMut(&mut P, i32, i32, Ch8),
+{
+    /// Get the color filled onto a transparent pixel at full coverage.
+    fn color_at(&mut self, x: i32, y: i32) -> P {
+        let mut clr = P::default();
+        (self.0)(&mut clr, x, y, Ch8::new(255));
+        clr
+    }
+
+    fn fill(&mut self, d: &mut P, x: i32, y: i32, alpha: Ch8) {
+        (self.0)(d, x, y, alpha)
+    }
+}
+
+/// Ink which composites an inner ink with a blend mode.
+///
+/// ```
+/// use footile::ink::{Blend, GradientStops, LinearGradient};
+/// use footile::BlendMode;
+/// use pix::rgb::Rgba8p;
+///
+/// let stops = [
+///     (0.0, Rgba8p::new(255, 255, 255, 255)),
+///     (1.0, Rgba8p::new(0, 0, 0, 255)),
+/// ];
+/// let stops = GradientStops::new(&stops).unwrap();
+/// let shade = LinearGradient::new((0.0, 0.0), (0.0, 64.0), stops);
+/// let ink = Blend::new(shade, BlendMode::Multiply);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Blend<I> {
+    /// Inner ink
+    ink: I,
+    /// Blend mode
+    mode: BlendMode,
+}
+
+impl<I> Blend<I> {
+    /// Create a blend ink.
+    ///
+    /// * `ink` Inner ink, providing source colors.
+    /// * `mode` Blend mode for compositing.
+    pub fn new(ink: I, mode: BlendMode) -> Self {
+        Blend { ink, mode }
+    }
+
+    /// Get the blend mode.
+    pub fn mode(&self) -> BlendMode {
+        self.mode
+    }
+
+    /// Unwrap the inner ink.
+    pub fn into_inner(self) -> I {
+        self.ink
+    }
+}
+
+impl<P, I> Ink<P> for Blend<I>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+    I: Ink<P>,
+{
+    fn color_at(&mut self, x: i32, y: i32) -> P {
+        self.ink.color_at(x, y)
+    }
+
+    fn fill(&mut self, d: &mut P, x: i32, y: i32, alpha: Ch8) {
+        let src = self.ink.color_at(x, y);
+        self.mode.composite(d, &src, P::Chan::from(alpha));
+    }
+
+    fn begin_row(&mut self, y: i32, width: usize) {
+        self.ink.begin_row(y, width);
+    }
+
+    fn end_row(&mut self) {
+        self.ink.end_row();
+    }
+
+    fn set_user_map(&mut self, map: &UserMap) {
+        self.ink.set_user_map(map);
+    }
+}
+
+/// Ink which fades an inner ink by a constant factor.
+///
+/// Coverage alpha passed to the inner ink is multiplied by the factor, which
+/// is the same as filling with translucent colors.
+#[derive(Clone, Debug)]
+pub struct Opacity<I> {
+    /// Inner ink
+    ink: I,
+    /// Opacity factor, from 0 to 1
+    factor: f32,
+}
+
+impl<I> Opacity<I> {
+    /// Create an opacity ink.
+    ///
+    /// * `ink` Inner ink.
+    /// * `factor` Opacity factor, clamped to 0 (transparent) to 1 (opaque).
+    pub fn new(ink: I, factor: f32) -> Self {
+        let factor = if factor.is_nan() {
+            0.0
+        } else {
+            factor.clamp(0.0, 1.0)
+        };
+        Opacity { ink, factor }
+    }
+
+    /// Get the opacity factor.
+    pub fn factor(&self) -> f32 {
+        self.factor
+    }
+
+    /// Unwrap the inner ink.
+    pub fn into_inner(self) -> I {
+        self.ink
+    }
+}
+
+impl<P, I> Ink<P> for Opacity<I>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+    I: Ink<P>,
+{
+    fn color_at(&mut self, x: i32, y: i32) -> P {
+        self.ink.color_at(x, y)
+    }
+
+    fn fill(&mut self, d: &mut P, x: i32, y: i32, alpha: Ch8) {
+        fill_scaled(&mut self.ink, d, x, y, alpha, self.factor);
+    }
+
+    fn begin_row(&mut self, y: i32, width: usize) {
+        self.ink.begin_row(y, width);
+    }
+
+    fn end_row(&mut self) {
+        self.ink.end_row();
+    }
+
+    fn set_user_map(&mut self, map: &UserMap) {
+        self.ink.set_user_map(map);
+    }
+}
+
+/// Ink which masks one ink by the alpha of another.
+///
+/// The mask ink's alpha at each pixel scales the coverage passed to the
+/// color ink.  The mask is never composited.
+#[derive(Clone, Debug)]
+pub struct Masked<A, B> {
+    /// Color ink
+    ink: A,
+    /// Mask ink
+    mask: B,
+}
+
+impl<A, B> Masked<A, B> {
+    /// Create a masked ink.
+    ///
+    /// * `ink` Color ink.
+    /// * `mask` Mask ink; only its alpha is used.
+    pub fn new(ink: A, mask: B) -> Self {
+        Masked { ink, mask }
+    }
+
+    /// Unwrap the color and mask inks.
+    pub fn into_inner(self) -> (A, B) {
+        (self.ink, self.mask)
+    }
+}
+
+impl<P, A, B> Ink<P> for Masked<A, B>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+    A: Ink<P>,
+    B: Ink<P>,
+{
+    fn color_at(&mut self, x: i32, y: i32) -> P {
+        self.ink.color_at(x, y)
+    }
+
+    fn fill(&mut self, d: &mut P, x: i32, y: i32, alpha: Ch8) {
+        let factor = self.mask.color_at(x, y).alpha().to_f32();
+        fill_scaled(&mut self.ink, d, x, y, alpha, factor);
+    }
+
+    fn begin_row(&mut self, y: i32, width: usize) {
+        self.ink.begin_row(y, width);
+        self.mask.begin_row(y, width);
+    }
+
+    fn end_row(&mut self) {
+        self.ink.end_row();
+        self.mask.end_row();
+    }
+
+    fn set_user_map(&mut self, map: &UserMap) {
+        self.ink.set_user_map(map);
+        self.mask.set_user_map(map);
+    }
+}
+
+/// Ink which modulates the coverage of an ink with a function.
+///
+/// ```
+/// use footile::ink::{GradientStops, LinearGradient, Modulate};
+/// use pix::rgb::Rgba8p;
+///
+/// let stops = [
+///     (0.0, Rgba8p::new(255, 0, 0, 255)),
+///     (1.0, Rgba8p::new(0, 0, 255, 255)),
+/// ];
+/// let stops = GradientStops::new(&stops).unwrap();
+/// let ramp = LinearGradient::new((0.0, 0.0), (64.0, 0.0), stops);
+/// // Fade out toward the bottom
+/// let ink = Modulate::new(ramp, |_x, y| 1.0 - y as f32 / 64.0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Modulate<A, F> {
+    /// Color ink
+    ink: A,
+    /// Modulation function
+    func: F,
+}
+
+impl<A, F> Modulate<A, F>
+where
+    F: FnMut(i32, i32) -> f32,
+{
+    /// Create a modulated ink.
+    ///
+    /// * `ink` Color ink.
+    /// * `func` Function of raster column and row, returning a factor which
+    ///   is clamped to 0 (transparent) to 1 (opaque).
+    pub fn new(ink: A, func: F) -> Self {
+        Modulate { ink, func }
+    }
+}
+
+impl<P, A, F> Ink<P> for Modulate<A, F>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+    A: Ink<P>,
+    F: FnMut(i32, i32) -> f32,
+{
+    fn color_at(&mut self, x: i32, y: i32) -> P {
+        self.ink.color_at(x, y)
+    }
+
+    fn fill(&mut self, d: &mut P, x: i32, y: i32, alpha: Ch8) {
+        let factor = (self.func)(x, y);
+        fill_scaled(&mut self.ink, d, x, y, alpha, factor);
+    }
+
+    fn begin_row(&mut self, y: i32, width: usize) {
+        self.ink.begin_row(y, width);
+    }
+
+    fn end_row(&mut self) {
+        self.ink.end_row();
+    }
+
+    fn set_user_map(&mut self, map: &UserMap) {
+        self.ink.set_user_map(map);
+    }
+}
+
+/// Fill with an ink, scaling coverage alpha by a factor from 0 to 1.
+///
+/// Pixels where the scaled alpha rounds to zero are skipped, since inks are
+/// never called with zero alpha.  A NaN factor is treated as zero.
+fn fill_scaled<P, I>(ink: &mut I, d: &mut P, x: i32, y: i32, alpha: Ch8, f: f32)
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+    I: Ink<P>,
+{
+    let f = if f.is_nan() { 0.0 } else { f.clamp(0.0, 1.0) };
+    let alpha = (f32::from(u8::from(alpha)) * f).round() as u8;
+    if alpha > 0 {
+        ink.fill(d, x, y, Ch8::new(alpha));
+    }
+}
+
+/// Coordinate space of an ink's points and transform.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum InkSpace {
+    /// Raster pixels, fixed to the raster
+    #[default]
+    Device,
+    /// User space of the path, following the plotter's transform
+    User,
+}
+
+/// Texture sampling filter.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Filter {
+    /// Nearest texel
+    #[default]
+    Nearest,
+    /// Bilinear interpolation of the four nearest texels
+    Bilinear,
+}
+
+/// Wrap mode, for texture coordinates outside of the texture.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Wrap {
+    /// Extend the edge texels
+    #[default]
+    Clamp,
+    /// Repeat the texture
+    Repeat,
+    /// Repeat the texture, mirroring every other time
+    Mirror,
+}
+
+/// Ink which samples a texture raster.
+///
+/// The texture is mapped onto the raster by a transform from raster pixels
+/// to texture pixels.  Each pixel is sampled at its center.
+///
+/// ```
+/// use footile::ink::{Filter, Texture, Wrap};
+/// use footile::{FillRule, Path2D, Plotter};
+/// use pix::rgb::Rgba8p;
+/// use pix::Raster;
+/// use pointy::Transform;
+///
+/// let tex = Raster::with_color(2, 2, Rgba8p::new(0, 128, 255, 255));
+/// let mut ink = Texture::new(&tex)
+///     .with_transform(Transform::with_scale(0.25, 0.25))
+///     .with_filter(Filter::Bilinear)
+///     .with_wrap(Wrap::Repeat);
+/// let path = Path2D::default()
+///     .relative()
+///     .move_to(4.0, 4.0)
+///     .line_to(24.0, 0.0)
+///     .line_to(0.0, 24.0)
+///     .line_to(-24.0, 0.0)
+///     .finish();
+/// let mut p = Plotter::new(Raster::with_clear(32, 32));
+/// p.fill_with(FillRule::NonZero, &path, &mut ink);
+/// ```
+#[derive(Clone)]
+pub struct Texture<'a, P: Pixel> {
+    /// Texture raster
+    raster: &'a Raster<P>,
+    /// Transform from raster pixels (or user space) to texture pixels
+    inverse: Transform<f32>,
+    /// Space mapped to texture pixels
+    space: InkSpace,
+    /// Map from raster pixels to user space
+    map: UserMap,
+    /// Sampling filter
+    filter: Filter,
+    /// Wrap mode
+    wrap: Wrap,
+}
+
+impl Wrap {
+    /// Wrap a texel index into the range `0..len`.
+    fn apply(self, i: i32, len: i32) -> i32 {
+        match self {
+            Wrap::Clamp => i.clamp(0, len - 1),
+            Wrap::Repeat => i.rem_euclid(len),
+            Wrap::Mirror => {
+                let i = i.rem_euclid(len * 2);
+                if i >= len {
+                    len * 2 - 1 - i
+                } else {
+                    i
+                }
+            }
+        }
+    }
+}
+
+impl<'a, P> Texture<'a, P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+{
+    /// Create a texture ink, with no transform.
+    ///
+    /// The filter is `Nearest`, and wrap mode is `Clamp`.
+    pub fn new(raster: &'a Raster<P>) -> Self {
+        Texture {
+            raster,
+            inverse: Transform::default(),
+            space: InkSpace::default(),
+            map: UserMap::default(),
+            filter: Filter::default(),
+            wrap: Wrap::default(),
+        }
+    }
+
+    /// Set the transform from raster pixels to texture pixels.
+    ///
+    /// This is the inverse of the transform used to place the texture.  With
+    /// [InkSpace::User], it is from user space instead.
+    pub fn with_transform(mut self, inverse: Transform<f32>) -> Self {
+        self.inverse = inverse;
+        self
+    }
+
+    /// Set the space mapped to texture pixels.
+    ///
+    /// The default is [InkSpace::Device].
+    pub fn with_space(mut self, space: InkSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Set the sampling filter.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Set the wrap mode.
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Sample the texture at the center of a raster pixel.
+    fn sample(&self, x: i32, y: i32) -> P {
+        let (w, h) = (self.raster.width() as i32, self.raster.height() as i32);
+        if w == 0 || h == 0 {
+            return P::default();
+        }
+        let pt = self.inverse * self.map.pixel(x, y);
+        if !(pt.x().is_finite() && pt.y().is_finite()) {
+            return P::default();
+        }
+        match self.filter {
+            Filter::Nearest => {
+                let tx = self.wrap.apply(pt.x().floor() as i32, w);
+                let ty = self.wrap.apply(pt.y().floor() as i32, h);
+                self.raster.pixel(tx, ty)
+            }
+            Filter::Bilinear => {
+                // Texel centers are at half-integer coordinates
+                let (u, v) = (pt.x() - 0.5, pt.y() - 0.5);
+                let (u0, v0) = (u.floor(), v.floor());
+                let (fu, fv) = (u - u0, v - v0);
+                let (u0, v0) = (u0 as i32, v0 as i32);
+                let x0 = self.wrap.apply(u0, w);
+                let x1 = self.wrap.apply(u0.saturating_add(1), w);
+                let y0 = self.wrap.apply(v0, h);
+                let y1 = self.wrap.apply(v0.saturating_add(1), h);
+                let texels = [
+                    (self.raster.pixel(x0, y0), (1.0 - fu) * (1.0 - fv)),
+                    (self.raster.pixel(x1, y0), fu * (1.0 - fv)),
+                    (self.raster.pixel(x0, y1), (1.0 - fu) * fv),
+                    (self.raster.pixel(x1, y1), fu * fv),
+                ];
+                let mut p = P::default();
+                for (i, c) in p.channels_mut().iter_mut().enumerate() {
+                    let v: f32 = texels
+                        .iter()
+                        .map(|(t, wt)| t.channels()[i].to_f32() * wt)
+                        .sum();
+                    // Conversion from f32 rounds to nearest
+                    *c = P::Chan::from(v.clamp(0.0, 1.0));
+                }
+                p
+            }
+        }
+    }
+}
+
+impl<P> Ink<P> for Texture<'_, P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+{
+    fn color_at(&mut self, x: i32, y: i32) -> P {
+        self.sample(x, y)
+    }
+
+    fn set_user_map(&mut self, map: &UserMap) {
+        if self.space == InkSpace::User {
+            self.map = *map;
+        }
+    }
+}
+
+/// Ink which fills with a linear gradient.
+///
+/// Colors vary along the line from the start point to the end point, and are
+/// constant perpendicular to it.  Points are in raster pixels, unless the
+/// space is [InkSpace::User].
+///
+/// ```
+/// use footile::ink::{GradientStops, LinearGradient};
+/// use pix::rgb::Rgba8p;
+///
+/// let stops = [
+///     (0.0, Rgba8p::new(0, 0, 0, 255)),
+///     (1.0, Rgba8p::new(255, 255, 255, 255)),
+/// ];
+/// let stops = GradientStops::new(&stops).unwrap();
+/// let ink = LinearGradient::new((0.0, 0.0), (256.0, 0.0), stops)
+///     .with_dither(true);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LinearGradient<P> {
+    /// Color stops
+    stops: GradientStops<P>,
+    /// Start point
+    start: Pt<f32>,
+    /// Gradient offset change per pixel, in X and Y
+    delta: Pt<f32>,
+    /// Ordered dithering enabled
+    dither: bool,
+    /// Space of start and end points
+    space: InkSpace,
+    /// Map from raster pixels to user space
+    map: UserMap,
+    /// Current row, with gradient offset at column 0 and change per column
+    row: Option<(i32, f32, f32)>,
+}
+
+impl<P> LinearGradient<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+{
+    /// Create a linear gradient ink, without dithering.
+    ///
+    /// * `start` Start point, at gradient offset 0.
+    /// * `end` End point, at gradient offset 1.
+    /// * `stops` Color stops.
+    pub fn new<S, E>(start: S, end: E, stops: GradientStops<P>) -> Self
+    where
+        S: Into<Pt<f32>>,
+        E: Into<Pt<f32>>,
+    {
+        let start = start.into();
+        let v = end.into() - start;
+        let len_sq = v.dot(v);
+        let delta = if len_sq > 0.0 {
+            v * (1.0 / len_sq)
+        } else {
+            Pt::default()
+        };
+        LinearGradient {
+            stops,
+            start,
+            delta,
+            dither: false,
+            space: InkSpace::default(),
+            map: UserMap::default(),
+            row: None,
+        }
+    }
+
+    /// Enable or disable ordered dithering.
+    ///
+    /// See [GradientStops.sample_dithered].
+    ///
+    /// [GradientStops.sample_dithered]:
+    ///     struct.GradientStops.html#method.sample_dithered
+    pub fn with_dither(mut self, dither: bool) -> Self {
+        self.dither = dither;
+        self
+    }
+
+    /// Set the space of start and end points.
+    ///
+    /// The default is [InkSpace::Device].
+    pub fn with_space(mut self, space: InkSpace) -> Self {
+        self.space = space;
+        self
+    }
+
+    /// Get the gradient offset at the center of a raster pixel.
+    fn offset(&self, x: i32, y: i32) -> f32 {
+        (self.map.pixel(x, y) - self.start).dot(self.delta)
+    }
+}
+
+impl<P> Ink<P> for LinearGradient<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+{
+    fn color_at(&mut self, x: i32, y: i32) -> P {
+        let t = match self.row {
+            Some((row, t0, dt)) if row == y => t0 + x as f32 * dt,
+            _ => self.offset(x, y),
+        };
+        if self.dither {
+            self.stops.sample_dithered(t, x, y)
+        } else {
+            self.stops.sample(t)
+        }
+    }
+
+    fn begin_row(&mut self, y: i32, _width: usize) {
+        let dt = self.map.col_delta().dot(self.delta);
+        self.row = Some((y, self.offset(0, y), dt));
+    }
+
+    fn end_row(&mut self) {
+        self.row = None;
+    }
+
+    fn set_user_map(&mut self, map: &UserMap) {
+        if self.space == InkSpace::User {
+            self.map = *map;
+        }
+    }
+}
+
+/// Ink for a gradient along a stroke.
+///
+/// Each pixel takes the parameter of the nearest point on the stroke center
+/// line, such as its arc length or pen attribute, mapped to a color by a
+/// sampling function.
+pub(crate) struct StrokeGradient<F> {
+    /// Function to sample a color at a parameter
+    sample: F,
+    /// Center line segments, with parameters
+    segs: Vec<(WidePt, WidePt, f32, f32)>,
+    /// Segments which may be nearest to the current row
+    row_segs: Vec<usize>,
+    /// Current row is set flag
+    in_row: bool,
+}
+
+impl<F> StrokeGradient<F> {
+    /// Create a new stroke gradient ink.
+    ///
+    /// * `sample` Function to sample a color at a parameter.
+    /// * `segs` Center line segments, with parameters.
+    pub(crate) fn new(
+        sample: F,
+        segs: Vec<(WidePt, WidePt, f32, f32)>,
+    ) -> Self {
+        StrokeGradient {
+            sample,
+            segs,
+            row_segs: Vec::new(),
+            in_row: false,
+        }
+    }
+
+    /// Get the parameter nearest to a point.
+    fn param_at(&self, p: Pt<f32>) -> f32 {
+        let mut best = (f32::INFINITY, 0.0);
+        let mut check = |(a, b, t0, t1): (WidePt, WidePt, f32, f32)| {
+            let ab = b.0 - a.0;
+            let len_sq = ab.dot(ab);
+            let u = if len_sq > 0.0 {
+                ((p - a.0).dot(ab) / len_sq).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let dist_sq = p.dist_sq(a.0 + ab * u);
+            if dist_sq < best.0 {
+                best = (dist_sq, t0 + (t1 - t0) * u);
+            }
+        };
+        if self.in_row && !self.row_segs.is_empty() {
+            self.row_segs.iter().for_each(|i| check(self.segs[*i]));
+        } else {
+            self.segs.iter().for_each(|seg| check(*seg));
+        }
+        best.1
+    }
+}
+
+impl<P, F> Ink<P> for StrokeGradient<F>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+    F: FnMut(f32) -> P,
+{
+    fn color_at(&mut self, x: i32, y: i32) -> P {
+        let p = Pt::new(x as f32 + 0.5, y as f32 + 0.5);
+        let t = self.param_at(p);
+        (self.sample)(t)
+    }
+
+    fn begin_row(&mut self, y: i32, _width: usize) {
+        let yc = y as f32 + 0.5;
+        self.row_segs.clear();
+        for (i, (a, b, _, _)) in self.segs.iter().enumerate() {
+            // Caps and joins stay within one width of the center line
+            let margin = a.w().max(b.w()) + 1.0;
+            let y0 = a.0.y().min(b.0.y()) - margin;
+            let y1 = a.0.y().max(b.0.y()) + margin;
+            if (y0..=y1).contains(&yc) {
+                self.row_segs.push(i);
+            }
+        }
+        self.in_row = true;
+    }
+
+    fn end_row(&mut self) {
+        self.in_row = false;
+    }
+}
+
+/// Spread mode, for gradient offsets outside of 0 to 1.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SpreadMode {
+    /// Extend the first and last stop colors
+    #[default]
+    Pad,
+    /// Repeat the gradient
+    Repeat,
+    /// Repeat the gradient, reversing every other time
+    Reflect,
+}
+
+/// Color space for interpolating between gradient stops.
+///
+/// In every space, colors are interpolated with premultiplied alpha, so
+/// translucent stops blend correctly.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum InterpSpace {
+    /// Linear light channels, as stored in pixels
+    #[default]
+    LinearPremul,
+    /// sRGB gamma-encoded channels, matching most design tools
+    Srgb,
+    /// Perceptual OKLab, for even ramps between hues
+    Oklab,
+}
+
+/// Color stops for a gradient.
+///
+/// By default, colors are interpolated between stops on premultiplied,
+/// linear channels.  Use [with_interp] to choose another color space.
+///
+/// [with_interp]: struct.GradientStops.html#method.with_interp
+///
+/// ```
+/// use footile::ink::{GradientStops, SpreadMode};
+/// use pix::matte::Matte8;
+///
+/// let stops = [(0.0, Matte8::new(0)), (1.0, Matte8::new(255))];
+/// let ramp = GradientStops::new(&stops)
+///     .unwrap()
+///     .with_spread(SpreadMode::Reflect);
+/// assert_eq!(ramp.sample(1.5), Matte8::new(128));
+/// ```
+#[derive(Clone, Debug)]
+pub struct GradientStops<P> {
+    /// Offsets and colors of all stops
+    stops: Vec<(f32, P)>,
+    /// Spread mode
+    spread: SpreadMode,
+    /// Interpolation color space
+    interp: InterpSpace,
+    /// Lookup table of colors, evenly spaced from 0 to 1
+    lut: Vec<P>,
+}
+
+impl SpreadMode {
+    /// Map a gradient offset into the range 0 to 1.
+    pub fn apply(self, t: f32) -> f32 {
+        if !t.is_finite() {
+            return 0.0;
+        }
+        match self {
+            SpreadMode::Pad => t.clamp(0.0, 1.0),
+            SpreadMode::Repeat => t - t.floor(),
+            SpreadMode::Reflect => {
+                let t = t.rem_euclid(2.0);
+                if t > 1.0 {
+                    2.0 - t
+                } else {
+                    t
+                }
+            }
+        }
+    }
+}
+
+impl InterpSpace {
+    /// Convert linear RGB components into the color space.
+    fn encode(self, rgb: [f32; 3]) -> [f32; 3] {
+        match self {
+            InterpSpace::LinearPremul => rgb,
+            InterpSpace::Srgb => rgb.map(srgb_encode),
+            InterpSpace::Oklab => {
+                let [r, g, b] = rgb;
+                let l = 0.412_221_47 * r + 0.536_332_55 * g + 0.051_445_99 * b;
+                let m = 0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b;
+                let s = 0.088_302_46 * r + 0.281_718_84 * g + 0.629_978_7 * b;
+                let (l, m, s) = (l.cbrt(), m.cbrt(), s.cbrt());
+                [
+                    0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+                    1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+                    0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+                ]
+            }
+        }
+    }
+
+    /// Convert components in the color space into linear RGB.
+    fn decode(self, c: [f32; 3]) -> [f32; 3] {
+        match self {
+            InterpSpace::LinearPremul => c,
+            InterpSpace::Srgb => c.map(srgb_decode),
+            InterpSpace::Oklab => {
+                let [ll, a, b] = c;
+                let l = ll + 0.396_337_78 * a + 0.215_803_76 * b;
+                let m = ll - 0.105_561_346 * a - 0.063_854_17 * b;
+                let s = ll - 0.089_484_18 * a - 1.291_485_5 * b;
+                let (l, m, s) = (l * l * l, m * m * m, s * s * s);
+                [
+                    4.076_741_7 * l - 3.307_711_6 * m + 0.230_969_94 * s,
+                    -1.268_438 * l + 2.609_757_4 * m - 0.341_319_38 * s,
+                    -0.004_196_086_3 * l - 0.703_418_6 * m + 1.707_614_7 * s,
+                ]
+            }
+        }
+    }
+}
+
+/// Encode a linear channel value with the sRGB transfer function
+fn srgb_encode(v: f32) -> f32 {
+    if v <= 0.003_130_8 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Decode an sRGB gamma-encoded channel value to linear
+fn srgb_decode(v: f32) -> f32 {
+    if v <= 0.040_45 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+impl<P> GradientStops<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+{
+    /// Create gradient stops, with `Pad` spread mode.
+    ///
+    /// * `stops` Offset (0 to 1) and color of each stop.  Offsets must not
+    ///   decrease; two stops at the same offset make a hard transition.
+    ///
+    /// Returns an error if there are no stops, or offsets are out of order
+    /// or outside of 0 to 1.
+    pub fn new(stops: &[(f32, P)]) -> Result<Self, Error> {
+        if stops.is_empty() {
+            return Err(Error::InvalidStops);
+        }
+        let mut prev = 0.0;
+        for (offset, _) in stops {
+            if !(prev..=1.0).contains(offset) {
+                return Err(Error::InvalidStops);
+            }
+            prev = *offset;
+        }
+        let mut ramp = GradientStops {
+            stops: stops.to_vec(),
+            spread: SpreadMode::default(),
+            interp: InterpSpace::default(),
+            lut: Vec::with_capacity(LUT_SIZE),
+        };
+        ramp.build_lut();
+        Ok(ramp)
+    }
+
+    /// Build the lookup table
+    fn build_lut(&mut self) {
+        let max = (LUT_SIZE - 1) as f32;
+        self.lut = (0..LUT_SIZE)
+            .map(|i| self.sample_clamped(i as f32 / max, 0.0))
+            .collect();
+    }
+
+    /// Set the spread mode.
+    pub fn with_spread(mut self, spread: SpreadMode) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    /// Get the spread mode.
+    pub fn spread(&self) -> SpreadMode {
+        self.spread
+    }
+
+    /// Set the interpolation color space.
+    ///
+    /// Stop colors are converted into the space, interpolated, and converted
+    /// back when building the lookup table, so sampling is no slower.
+    pub fn with_interp(mut self, interp: InterpSpace) -> Self {
+        if interp != self.interp {
+            self.interp = interp;
+            self.build_lut();
+        }
+        self
+    }
+
+    /// Get the interpolation color space.
+    pub fn interp(&self) -> InterpSpace {
+        self.interp
+    }
+
+    /// Get the stops.
+    pub fn stops(&self) -> &[(f32, P)] {
+        &self.stops
+    }
+
+    /// Sample the gradient color at an offset, using a lookup table.
+    ///
+    /// The color is from the nearest of 256 evenly spaced entries, which is
+    /// fast, but can be off slightly between entries.  Use [sample_exact]
+    /// for exact colors.
+    ///
+    /// [sample_exact]: struct.GradientStops.html#method.sample_exact
+    pub fn sample(&self, t: f32) -> P {
+        let t = self.spread.apply(t);
+        let i = (t * (LUT_SIZE - 1) as f32).round() as usize;
+        self.lut[i.min(LUT_SIZE - 1)]
+    }
+
+    /// Sample the exact gradient color at an offset.
+    pub fn sample_exact(&self, t: f32) -> P {
+        self.sample_clamped(self.spread.apply(t), 0.0)
+    }
+
+    /// Sample the gradient color at an offset from 0 to 1.
+    ///
+    /// * `t` Gradient offset.
+    /// * `bias` Amount added to channels before rounding.
+    fn sample_clamped(&self, t: f32, bias: f32) -> P {
+        // Find the first stop after the offset
+        let i = self.stops.partition_point(|(offset, _)| *offset <= t);
+        let (t0, c0) = self.stops[i.max(1) - 1];
+        let (c1, t) = match self.stops.get(i) {
+            Some(&(t1, c1)) if i > 0 => (c1, (t - t0) / (t1 - t0)),
+            _ => (c0, 0.0),
+        };
+        match self.interp {
+            InterpSpace::LinearPremul => lerp_pixel(c0, c1, t, bias),
+            space => lerp_pixel_in(space, c0, c1, t, bias),
+        }
+    }
+}
+
+impl<P> GradientStops<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
+{
+    /// Sample the gradient color at an offset, with ordered dithering.
+    ///
+    /// Before rounding, channels are offset by up to half of a step between
+    /// channel values, depending on the pixel position in a 4x4 Bayer
+    /// matrix.  This hides banding in gradients which change slowly over
+    /// many pixels.  The result is always the same for a given position.
+    ///
+    /// * `t` Gradient offset.
+    /// * `x` Raster column.
+    /// * `y` Raster row.
+    pub fn sample_dithered(&self, t: f32, x: i32, y: i32) -> P {
+        let m = BAYER_4X4[(y & 3) as usize][(x & 3) as usize];
+        let bias = ((f32::from(m) + 0.5) / 16.0 - 0.5) * P::Chan::STEP;
+        self.sample_clamped(self.spread.apply(t), bias)
+    }
+}
+
+/// Interpolate between two pixels, channel by channel.
+///
+/// * `bias` Amount added to channels before rounding.
+fn lerp_pixel<P: Pixel>(p0: P, p1: P, t: f32, bias: f32) -> P {
+    let mut p = p0;
+    let chans = p.channels_mut().iter_mut().zip(p1.channels());
+    for (c0, c1) in chans {
+        let (v0, v1) = (c0.to_f32(), c1.to_f32());
+        let v = v0 + (v1 - v0) * t + bias;
+        *c0 = P::Chan::from(v.clamp(0.0, 1.0));
+    }
+    p
+}
+
+/// Interpolate between two pixels in a color space.
+///
+/// * `bias` Amount added to channels before rounding.
+fn lerp_pixel_in<P>(space: InterpSpace, p0: P, p1: P, t: f32, bias: f32) -> P
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+{
+    let [r0, g0, b0, a0] = premul_in(space, p0);
+    let [r1, g1, b1, a1] = premul_in(space, p1);
+    let a = a0 + (a1 - a0) * t;
+    let mut rgb = [r0 + (r1 - r0) * t, g0 + (g1 - g0) * t, b0 + (b1 - b0) * t];
+    if a > 0.0 {
+        rgb = space.decode(rgb.map(|c| c / a)).map(|c| c.clamp(0.0, 1.0));
+    }
+    let chan = |v: f32| P::Chan::from((v + bias).clamp(0.0, 1.0));
+    let [r, g, b] = rgb.map(|c| chan(c * a));
+    P::Model::from_rgba::<P>(PixRgba::<P>::new::<P::Chan>(r, g, b, chan(a)))
+}
+
+/// Get premultiplied components of a pixel in a color space
+fn premul_in<P>(space: InterpSpace, p: P) -> [f32; 4]
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+{
+    let rgba = P::Model::into_rgba::<P>(p);
+    let [r, g, b, a] = [0, 1, 2, 3].map(|i| rgba.channels()[i].to_f32());
+    let rgb = if a > 0.0 {
+        [r, g, b].map(|c| (c / a).clamp(0.0, 1.0))
+    } else {
+        [0.0; 3]
+    };
+    let [c0, c1, c2] = space.encode(rgb);
+    [c0 * a, c1 * a, c2 * a, a]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pix::gray::Graya8p;
+    use pix::matte::Matte8;
+    use pix::rgb::Rgba8p;
+
+    #[test]
+    fn texture_nearest_wrap() {
+        let tex =
+            Raster::with_pixels(2, 1, &[Matte8::new(10), Matte8::new(200)][..]);
+        let sample = |wrap, x| {
+            let ink = Texture::new(&tex).with_wrap(wrap);
+            u8::from(ink.sample(x, 0).alpha())
+        };
+        let xs = [-2, -1, 0, 1, 2, 3];
+        let clamp = xs.map(|x| sample(Wrap::Clamp, x));
+        assert_eq!(clamp, [10, 10, 10, 200, 200, 200]);
+        let repeat = xs.map(|x| sample(Wrap::Repeat, x));
+        assert_eq!(repeat, [10, 200, 10, 200, 10, 200]);
+        let mirror = xs.map(|x| sample(Wrap::Mirror, x));
+        assert_eq!(mirror, [200, 10, 10, 200, 200, 10]);
+        // Rows wrap the same way
+        let ink = Texture::new(&tex).with_wrap(Wrap::Repeat);
+        assert_eq!(ink.sample(1, -1), Matte8::new(200));
+    }
+
+    #[test]
+    fn texture_bilinear_wrap() {
+        let tex =
+            Raster::with_pixels(2, 1, &[Matte8::new(0), Matte8::new(200)][..]);
+        // Two raster pixels per texel
+        let sample = |wrap, x| {
+            let ink = Texture::new(&tex)
+                .with_transform(Transform::with_scale(0.5, 0.5))
+                .with_filter(Filter::Bilinear)
+                .with_wrap(wrap);
+            u8::from(ink.sample(x, 0).alpha())
+        };
+        let xs = [-1, 0, 1, 2, 3, 4];
+        let clamp = xs.map(|x| sample(Wrap::Clamp, x));
+        assert_eq!(clamp, [0, 0, 50, 150, 200, 200]);
+        let repeat = xs.map(|x| sample(Wrap::Repeat, x));
+        assert_eq!(repeat, [150, 50, 50, 150, 150, 50]);
+        let mirror = xs.map(|x| sample(Wrap::Mirror, x));
+        assert_eq!(mirror, [0, 0, 50, 150, 200, 200]);
+    }
+
+    #[test]
+    fn texture_bilinear_premultiplied() {
+        let tex = Raster::with_pixels(
+            2,
+            1,
+            &[Rgba8p::new(100, 0, 0, 100), Rgba8p::new(0, 0, 0, 0)][..],
+        );
+        let ink = Texture::new(&tex)
+            .with_transform(Transform::with_scale(0.5, 1.0))
+            .with_filter(Filter::Bilinear);
+        assert_eq!(ink.sample(1, 0), Rgba8p::new(75, 0, 0, 75));
+        assert_eq!(ink.sample(2, 0), Rgba8p::new(25, 0, 0, 25));
+        // Empty texture is clear
+        let empty = Raster::<Rgba8p>::with_clear(0, 0);
+        assert_eq!(Texture::new(&empty).sample(0, 0), Rgba8p::default());
+    }
+
+    #[test]
+    fn invalid_stops() {
+        let c = Matte8::new(255);
+        let empty: [(f32, Matte8); 0] = [];
+        assert!(GradientStops::new(&empty).is_err());
+        assert!(GradientStops::new(&[(0.5, c), (0.25, c)]).is_err());
+        assert!(GradientStops::new(&[(-0.5, c)]).is_err());
+        assert!(GradientStops::new(&[(0.0, c), (1.5, c)]).is_err());
+        assert!(GradientStops::new(&[(f32::NAN, c)]).is_err());
+        assert!(GradientStops::new(&[(0.5, c), (0.5, c)]).is_ok());
+    }
+
+    #[test]
+    fn spread() {
+        let stops = [(0.0, Matte8::new(0)), (1.0, Matte8::new(200))];
+        let pad = GradientStops::new(&stops).unwrap();
+        let repeat = pad.clone().with_spread(SpreadMode::Repeat);
+        let reflect = pad.clone().with_spread(SpreadMode::Reflect);
+        for ramp in [&pad, &repeat, &reflect] {
+            assert_eq!(ramp.sample_exact(0.0), Matte8::new(0));
+            assert_eq!(ramp.sample_exact(0.5), Matte8::new(100));
+        }
+        assert_eq!(pad.sample_exact(1.25), Matte8::new(200));
+        assert_eq!(pad.sample_exact(-0.5), Matte8::new(0));
+        // 1.25 => 0.25
+        assert_eq!(repeat.sample_exact(1.25), Matte8::new(50));
+        assert_eq!(repeat.sample(1.25), Matte8::new(50));
+        // -0.5 => 0.5
+        assert_eq!(repeat.sample_exact(-0.5), Matte8::new(100));
+        assert_eq!(repeat.sample(-0.5), Matte8::new(100));
+        // 1.25 => 0.75
+        assert_eq!(reflect.sample_exact(1.25), Matte8::new(150));
+        assert_eq!(reflect.sample(1.25), Matte8::new(150));
+        // -0.5 => 0.5
+        assert_eq!(reflect.sample_exact(-0.5), Matte8::new(100));
+        assert_eq!(reflect.sample(-0.5), Matte8::new(100));
+        assert_eq!(reflect.sample_exact(f32::NAN), Matte8::new(0));
+    }
+
+    #[test]
+    fn interp_space() {
+        let stops = [
+            (0.0, Rgba8p::new(255, 0, 0, 255)),
+            (1.0, Rgba8p::new(0, 0, 255, 255)),
+        ];
+        let ramp = GradientStops::new(&stops).unwrap();
+        let mid = |interp| {
+            let ramp = ramp.clone().with_interp(interp);
+            assert_eq!(ramp.interp(), interp);
+            ramp.sample_exact(0.5)
+        };
+        assert_eq!(
+            mid(InterpSpace::LinearPremul),
+            Rgba8p::new(128, 0, 128, 255)
+        );
+        assert_eq!(mid(InterpSpace::Srgb), Rgba8p::new(55, 0, 55, 255));
+        assert_eq!(mid(InterpSpace::Oklab), Rgba8p::new(67, 22, 93, 255));
+        // Ends are unchanged
+        for interp in [InterpSpace::Srgb, InterpSpace::Oklab] {
+            let ramp = ramp.clone().with_interp(interp);
+            assert_eq!(ramp.sample(0.0), stops[0].1);
+            assert_eq!(ramp.sample(1.0), stops[1].1);
+        }
+    }
+
+    #[test]
+    fn interp_space_translucent() {
+        // Fading to clear keeps the hue of the opaque stop
+        let stops = [
+            (0.0, Rgba8p::new(255, 0, 0, 255)),
+            (1.0, Rgba8p::new(0, 0, 0, 0)),
+        ];
+        let ramp = GradientStops::new(&stops).unwrap();
+        for interp in [InterpSpace::Srgb, InterpSpace::Oklab] {
+            let ramp = ramp.clone().with_interp(interp);
+            assert_eq!(ramp.sample_exact(0.25), Rgba8p::new(191, 0, 0, 191));
+        }
+        // Matte has only alpha
+        let stops = [(0.0, Matte8::new(0)), (1.0, Matte8::new(200))];
+        let ramp = GradientStops::new(&stops)
+            .unwrap()
+            .with_interp(InterpSpace::Oklab);
+        assert_eq!(ramp.sample_exact(0.5), Matte8::new(100));
+    }
+
+    #[test]
+    fn hard_stops() {
+        let stops = [
+            (0.0, Graya8p::new(0, 255)),
+            (0.5, Graya8p::new(0, 255)),
+            (0.5, Graya8p::new(255, 255)),
+            (1.0, Graya8p::new(255, 255)),
+        ];
+        let ramp = GradientStops::new(&stops).unwrap();
+        assert_eq!(ramp.sample_exact(0.49), Graya8p::new(0, 255));
+        assert_eq!(ramp.sample_exact(0.5), Graya8p::new(255, 255));
+        assert_eq!(ramp.sample(0.25), Graya8p::new(0, 255));
+        assert_eq!(ramp.sample(0.75), Graya8p::new(255, 255));
+    }
+
+    #[test]
+    fn translucent() {
+        // Premultiplied interpolation does not bleed color from clear stops
+        let stops = [
+            (0.0, Rgba8p::new(0, 0, 0, 0)),
+            (1.0, Rgba8p::new(200, 0, 0, 200)),
+        ];
+        let ramp = GradientStops::new(&stops).unwrap();
+        assert_eq!(ramp.sample_exact(0.5), Rgba8p::new(100, 0, 0, 100));
+        for i in 0..=100 {
+            let t = i as f32 / 100.0;
+            let exact = ramp.sample_exact(t);
+            let lut = ramp.sample(t);
+            for (a, b) in exact.channels().iter().zip(lut.channels()) {
+                assert!(
+                    (i32::from(u8::from(*a)) - i32::from(u8::from(*b))).abs()
+                        <= 1
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn blend_modes() {
+        let tex =
+            Raster::with_pixels(1, 1, &[Rgba8p::new(128, 64, 255, 255)][..]);
+        let blend = |mode| {
+            let mut d = Rgba8p::new(200, 100, 50, 255);
+            Blend::new(Texture::new(&tex), mode).fill(
+                &mut d,
+                0,
+                0,
+                Ch8::new(128),
+            );
+            d.channels()
+                .iter()
+                .map(|c| u8::from(*c))
+                .collect::<Vec<_>>()
+        };
+        // Hand-computed at 50% coverage, within rounding
+        let expected = [
+            (BlendMode::SrcOver, [164, 82, 153, 255]),
+            (BlendMode::Multiply, [150, 62, 50, 255]),
+            (BlendMode::Screen, [214, 119, 153, 255]),
+            (BlendMode::Plus, [255, 132, 178, 255]),
+            (BlendMode::Clear, [100, 50, 25, 127]),
+        ];
+        for (mode, clr) in expected {
+            for (a, b) in blend(mode).iter().zip(clr) {
+                assert!((i32::from(*a) - b).abs() <= 1, "{mode:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn mask_extremes() {
+        let clr = Rgba8p::new(40, 80, 120, 160);
+        let tex = Raster::with_color(1, 1, clr);
+        let clear = Raster::with_color(1, 1, Rgba8p::new(0, 0, 0, 0));
+        let opaque = Raster::with_color(1, 1, Rgba8p::new(0, 0, 0, 255));
+        let dst = Rgba8p::new(10, 20, 30, 40);
+        let fill = |ink: &mut dyn Ink<Rgba8p>| {
+            let mut d = dst;
+            ink.fill(&mut d, 0, 0, Ch8::new(200));
+            d
+        };
+        let unmasked = fill(&mut Texture::new(&tex));
+        assert_ne!(unmasked, dst);
+        let mut masked = Masked::new(Texture::new(&tex), Texture::new(&clear));
+        assert_eq!(fill(&mut masked), dst);
+        let mut masked = Masked::new(Texture::new(&tex), Texture::new(&opaque));
+        assert_eq!(fill(&mut masked), unmasked);
+        let mut modulate = Modulate::new(Texture::new(&tex), |_, _| 0.0);
+        assert_eq!(fill(&mut modulate), dst);
+        let mut modulate = Modulate::new(Texture::new(&tex), |_, _| 1.0);
+        assert_eq!(fill(&mut modulate), unmasked);
+        // Out of range factors are clamped
+        let mut modulate = Modulate::new(Texture::new(&tex), |_, _| -2.0);
+        assert_eq!(fill(&mut modulate), dst);
+        let mut modulate = Modulate::new(Texture::new(&tex), |_, _| 5.0);
+        assert_eq!(fill(&mut modulate), unmasked);
+    }
+
+    #[test]
+    fn gradient_rows() {
+        let stops = [
+            (0.0, Rgba8p::new(0, 0, 0, 255)),
+            (1.0, Rgba8p::new(255, 255, 255, 255)),
+        ];
+        let stops = GradientStops::new(&stops).unwrap();
+        let mut ramp = LinearGradient::new((3.0, -5.0), (40.0, 60.0), stops);
+        for y in -2..70 {
+            let direct: Vec<_> =
+                (-2..50).map(|x| ramp.color_at(x, y)).collect();
+            ramp.begin_row(y, 52);
+            let row: Vec<_> = (-2..50).map(|x| ramp.color_at(x, y)).collect();
+            ramp.end_row();
+            assert_eq!(direct, row);
+        }
+    }
+}
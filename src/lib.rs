@@ -28,15 +28,23 @@
 #![warn(missing_docs)]
 #![warn(rustdoc::missing_doc_code_examples)]
 
+mod blend;
+mod error;
 mod fig;
 mod fixed;
-mod geom;
+pub mod geom;
 mod imgbuf;
+pub mod ink;
 mod path;
 mod plotter;
 mod stroker;
+pub mod tess;
 mod vid;
 
+pub use blend::{BlendChannel, BlendMode};
+pub use error::Error;
+pub use fig::{Fig, Metrics, Paint, SgnArea};
+pub use fixed::{Fixed, Fixed48};
 pub use path::{FillRule, Path2D, PathOp};
-pub use plotter::Plotter;
-pub use stroker::JoinStyle;
+pub use plotter::{Drawn, PixelOrigin, Plotter, PlotterBuilder, PreparedPath};
+pub use stroker::{CapStyle, JoinStyle, Marker, StrokeOptions, StrokeUnits};
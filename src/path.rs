@@ -17,6 +17,9 @@ pub enum FillRule {
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum PathOp {
     /// Close the path
+    ///
+    /// Filling treats every sub-path as closed, so this only matters for
+    /// stroking, where it joins the ends instead of capping them.
     Close(),
     /// Move to a point
     Move(Pt<f32>),
@@ -28,6 +31,10 @@ pub enum PathOp {
     Cubic(Pt<f32>, Pt<f32>, Pt<f32>),
     /// Set pen width (for stroking)
     PenWidth(f32),
+    /// Set pen attribute (for stroking with [stroke_attr])
+    ///
+    /// [stroke_attr]: struct.Plotter.html#method.stroke_attr
+    PenAttr(f32),
 }
 
 /// A `Path2D` is a builder for `Vec<PathOp>`.
@@ -86,6 +93,8 @@ impl Path2D {
     }
 
     /// Close current sub-path and move pen to origin.
+    ///
+    /// This is only needed for stroking; filled sub-paths are always closed.
     pub fn close(mut self) -> Self {
         self.ops.push(PathOp::Close());
         self.pen = Pt::default();
@@ -165,6 +174,17 @@ impl Path2D {
         self
     }
 
+    /// Set pen attribute.
+    ///
+    /// All subsequent path points will be affected, until the attribute is
+    /// changed again.  Attributes are interpolated along lines and curves.
+    ///
+    /// * `attr` Pen attribute.
+    pub fn pen_attr(mut self, attr: f32) -> Self {
+        self.ops.push(PathOp::PenAttr(attr));
+        self
+    }
+
     /// Finish path with specified operations.
     pub fn finish(self) -> Vec<PathOp> {
         self.ops
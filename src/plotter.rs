@@ -2,15 +2,57 @@
 //
 // Copyright (c) 2017-2021  Douglas P Lau
 //
-use crate::fig::Fig;
-use crate::geom::{float_lerp, WidePt};
+mod batch;
+mod ink;
+mod matte;
+mod ssaa;
+mod stream;
+mod stroke;
+
+use crate::blend::{BlendChannel, BlendMode};
+use crate::error::{check_coord, Error};
+use crate::fig::{dirty_union, region_union, Fig, Metrics, Paint, SgnArea};
+use crate::geom::{
+    flatten_cubic, flatten_quad, float_lerp, transform_inverse,
+    transform_scale, WidePt,
+};
+use crate::imgbuf::mul_coverage;
+use crate::ink::UserMap;
 use crate::path::{FillRule, PathOp};
-use crate::stroker::{JoinStyle, Stroke};
-use pix::chan::{Ch8, Linear, Premultiplied};
+use crate::stroker::{CapStyle, JoinStyle, Marker, Stroke, StrokeUnits};
+use pix::chan::{Linear, Premultiplied};
 use pix::el::Pixel;
-use pix::Raster;
+use pix::matte::Matte8;
+use pix::{Raster, Region};
 use pointy::{Pt, Transform};
 use std::borrow::Borrow;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use matte::Group;
+use ssaa::Supersample;
+
+/// Largest raster width or height to fill in one pass.
+///
+/// Figure points are clipped to 16,383 pixels, so larger rasters are filled
+/// in tiles, with the viewport offset to each one.
+const TILE_MAX: u32 = 8192;
+
+/// Largest offset of a prepared figure, in pixels.
+///
+/// Figure points are clipped to 16,383 pixels, so clipped edges stay outside
+/// of rasters up to `TILE_MAX` when offset by less than this.
+const OFFSET_MAX: f32 = 8_191.0;
+
+/// Location of integer coordinates within pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PixelOrigin {
+    /// Integer coordinates are pixel corners
+    #[default]
+    Corner,
+    /// Integer coordinates are pixel centers
+    Center,
+}
 
 /// Plotter for 2D vector [path]s.
 ///
@@ -37,22 +79,348 @@ use std::borrow::Borrow;
 /// ```
 pub struct Plotter<P>
 where
-    P: Pixel<Chan = Ch8, Alpha = Premultiplied, Gamma = Linear>,
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear>,
+    P::Chan: BlendChannel,
 {
     /// Image raster
     raster: Raster<P>,
     /// Signed area buffer
     sgn_area: Vec<i16>,
+    /// Wide signed area buffer, for coverage above 8 bits or deep windings
+    sgn_area_wide: Vec<i32>,
     /// Current pen position and width
     pen: WidePt,
     /// User to pixel affine transform
     transform: Transform<f32>,
+    /// Viewport offset, in pixels
+    viewport: Pt<f32>,
+    /// Pixel origin
+    pixel_origin: PixelOrigin,
+    /// Stroke width units
+    stroke_units: StrokeUnits,
+    /// Stroke width scale factor
+    w_scale: f32,
+    /// Curve decomposition tolerance squared
+    tol_sq: f32,
+    /// Curve decomposition tolerance in user units
+    tol_user: Option<f32>,
+    /// Stroke join / cap tolerance squared
+    stroke_tol_sq: Option<f32>,
+    /// Current stroke width
+    s_width: f32,
+    /// Current stroke attribute
+    s_attr: f32,
+    /// Current join style
+    join_style: JoinStyle,
+    /// Current cap style
+    cap_style: CapStyle,
+    /// Current start marker
+    start_marker: Option<Marker>,
+    /// Current end marker
+    end_marker: Option<Marker>,
+    /// Keep curves in stroke outlines
+    stroke_curves: bool,
+    /// Snap strokes to pixel boundaries
+    stroke_snap: bool,
+    /// Clip coverage
+    clip: Option<Arc<Raster<Matte8>>>,
+    /// Scissor rectangle
+    scissor: Option<Region>,
+    /// Blend mode
+    blend: BlendMode,
+    /// Opacity
+    opacity: f32,
+    /// Anti-aliasing enabled
+    antialias: bool,
+    /// Coverage precision in bits
+    coverage_bits: u8,
+    /// Stack of saved drawing states
+    states: Vec<State>,
+    /// Group of fills with accumulated coverage
+    group: Option<Group<P>>,
+    /// Supersampling buffers
+    ssaa: Option<Supersample>,
+    /// Coverage spans for inks, reused between fills
+    ink_spans: Vec<(i32, i32, u32, u16)>,
+    /// Metrics of last fill, if enabled
+    metrics: Option<Metrics>,
+    /// Number of points dropped from the last fill or stroke
+    dropped: usize,
+    /// Scratch figure, reused between fills
+    fig: Option<Fig>,
+    /// Scratch stroke, reused between strokes
+    stroke: Option<Stroke>,
+    /// Scratch stroke outline, reused between strokes
+    stroke_ops: Vec<PathOp>,
+    /// Region of pixels touched
+    dirty: Option<Region>,
+}
+
+/// Builder for a [Plotter] with configuration options.
+///
+/// Options which are not set use the same defaults as [Plotter::new], and
+/// values are validated the same as the corresponding setters.
+///
+/// # Example
+/// ```
+/// use footile::{JoinStyle, PlotterBuilder};
+/// use pix::matte::Matte8;
+/// use pix::Raster;
+///
+/// let raster = Raster::<Matte8>::with_clear(64, 64);
+/// let p = PlotterBuilder::new()
+///     .tolerance(0.1)
+///     .join(JoinStyle::Round)
+///     .build(raster);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PlotterBuilder {
+    /// Curve decomposition tolerance
+    tolerance: Option<f32>,
+    /// Join style
+    join_style: Option<JoinStyle>,
+    /// Cap style
+    cap_style: Option<CapStyle>,
+    /// User to pixel affine transform
+    transform: Option<Transform<f32>>,
+    /// Anti-aliasing enabled
+    antialias: Option<bool>,
+}
+
+/// A path prepared for filling many times.
+///
+/// Curves are flattened and vertices sorted once, when the path is prepared
+/// by [Plotter::prepare].  It can then be filled with [Plotter::fill_prepared]
+/// in any color, skipping straight to scanning.
+#[derive(Debug)]
+pub struct PreparedPath {
+    /// Path ops, to plot again if settings have changed
+    ops: Vec<PathOp>,
+    /// Settings used to plot the figure
+    settings: PlotSettings,
+    /// Plotted figure, with sorted vertices
+    fig: Fig,
+}
+
+/// Raster drawn by a fill or stroke, with the region it changed.
+///
+/// This dereferences to the raster, so it can be used in its place.  The
+/// region only covers the one call; use [Plotter::take_dirty] to collect
+/// changes from many calls.
+///
+/// ```rust
+/// use footile::{FillRule, Path2D, Plotter};
+/// use pix::{matte::Matte8, Raster};
+///
+/// let path = Path2D::default()
+///     .absolute()
+///     .move_to(2.0, 3.0)
+///     .line_to(6.0, 3.0)
+///     .line_to(6.0, 5.0)
+///     .line_to(2.0, 5.0)
+///     .finish();
+/// let mut p = Plotter::new(Raster::<Matte8>::with_clear(8, 8));
+/// let drawn = p.fill(FillRule::NonZero, &path, Matte8::new(255));
+/// let dirty = drawn.dirty().unwrap();
+/// assert_eq!((dirty.left(), dirty.top(), dirty.bottom()), (2, 3, 5));
+/// ```
+pub struct Drawn<'a, P: Pixel> {
+    /// Drawn raster
+    raster: &'a mut Raster<P>,
+    /// Region changed by the call
+    dirty: Option<Region>,
+}
+
+/// Settings which affect the points of a plotted figure
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PlotSettings {
+    /// User to pixel affine transform
+    transform: Transform<f32>,
+    /// Viewport offset, in pixels
+    viewport: Pt<f32>,
+    /// Pixel origin
+    pixel_origin: PixelOrigin,
+    /// Curve decomposition tolerance squared
+    tol_sq: f32,
+}
+
+/// Drawing state, saved and restored as a unit
+///
+/// The clip matte is shared with the plotter, so saving it is cheap.
+#[derive(Clone)]
+struct State {
+    /// User to pixel affine transform
+    transform: Transform<f32>,
+    /// Viewport offset, in pixels
+    viewport: Pt<f32>,
+    /// Pixel origin
+    pixel_origin: PixelOrigin,
+    /// Stroke width units
+    stroke_units: StrokeUnits,
+    /// Stroke width scale factor
+    w_scale: f32,
     /// Curve decomposition tolerance squared
     tol_sq: f32,
+    /// Curve decomposition tolerance in user units
+    tol_user: Option<f32>,
+    /// Stroke join / cap tolerance squared
+    stroke_tol_sq: Option<f32>,
     /// Current stroke width
     s_width: f32,
+    /// Current stroke attribute
+    s_attr: f32,
     /// Current join style
     join_style: JoinStyle,
+    /// Current cap style
+    cap_style: CapStyle,
+    /// Current start marker
+    start_marker: Option<Marker>,
+    /// Current end marker
+    end_marker: Option<Marker>,
+    /// Keep curves in stroke outlines
+    stroke_curves: bool,
+    /// Snap strokes to pixel boundaries
+    stroke_snap: bool,
+    /// Clip coverage
+    clip: Option<Arc<Raster<Matte8>>>,
+    /// Scissor rectangle
+    scissor: Option<Region>,
+    /// Blend mode
+    blend: BlendMode,
+    /// Opacity
+    opacity: f32,
+    /// Anti-aliasing enabled
+    antialias: bool,
+    /// Coverage precision in bits
+    coverage_bits: u8,
+}
+
+/// Signed area buffer for a fill, depending on coverage precision
+enum AreaBuf<'a> {
+    /// Up to 8 bits of coverage, and 127 windings
+    Narrow(&'a mut [i16]),
+    /// Up to 16 bits of coverage
+    Wide(&'a mut [i32]),
+}
+
+/// Figure which can be filled to a raster region
+trait FillSource {
+    /// Get an upper bound of winding number magnitude.
+    fn max_winding(&self) -> u32;
+
+    /// Fill to a raster region with a signed area buffer.
+    fn fill_area<P, A>(
+        &mut self,
+        rule: FillRule,
+        raster: &mut Raster<P>,
+        reg: Region,
+        paint: Paint<P>,
+        sgn_area: &mut [A],
+        metrics: Option<&mut Metrics>,
+    ) -> Option<Region>
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+        P::Chan: BlendChannel,
+        A: SgnArea;
+}
+
+impl FillSource for Fig {
+    fn max_winding(&self) -> u32 {
+        Fig::max_winding(self)
+    }
+    fn fill_area<P, A>(
+        &mut self,
+        rule: FillRule,
+        raster: &mut Raster<P>,
+        reg: Region,
+        paint: Paint<P>,
+        sgn_area: &mut [A],
+        metrics: Option<&mut Metrics>,
+    ) -> Option<Region>
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+        P::Chan: BlendChannel,
+        A: SgnArea,
+    {
+        if metrics.is_none() {
+            self.fill_region(rule, raster, reg, paint, sgn_area)
+        } else {
+            self.fill_serial(rule, raster, reg, paint, sgn_area, None, metrics)
+        }
+    }
+}
+
+/// Check if a figure may wind too deeply for a narrow signed area buffer.
+///
+/// Even-odd coverage only depends on the low bits of the signed area, so it
+/// is correct even when the sum wraps.
+fn is_deep<F: FillSource>(fig: &F, rule: FillRule) -> bool {
+    rule == FillRule::NonZero
+        && fig.max_winding() > <i16 as SgnArea>::MAX_WINDING
+}
+
+/// Fill a figure to a raster region, with optional metrics.
+fn fill_monitored<F, P>(
+    fig: &mut F,
+    rule: FillRule,
+    raster: &mut Raster<P>,
+    reg: Region,
+    paint: Paint<P>,
+    sgn_area: AreaBuf,
+    metrics: Option<&mut Metrics>,
+) -> Option<Region>
+where
+    F: FillSource,
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+    P::Chan: BlendChannel,
+{
+    match sgn_area {
+        AreaBuf::Narrow(area) => {
+            fig.fill_area(rule, raster, reg, paint, area, metrics)
+        }
+        AreaBuf::Wide(area) => {
+            fig.fill_area(rule, raster, reg, paint, area, metrics)
+        }
+    }
+}
+
+/// Create a signed area buffer for a raster width
+fn new_sgn_area(width: u32) -> Vec<i16> {
+    let len = width as usize;
+    // Capacity must be 8-element multiple (for SIMD)
+    let cap = ((len + 7) >> 3) << 3;
+    let mut sgn_area = Vec::with_capacity(cap);
+    sgn_area.resize(len, 0);
+    sgn_area
+}
+
+/// Get tiles of a region to fill, if it is too large for one pass.
+///
+/// Regions over `TILE_MAX` pixels wide or high, after scaling by `fx` and
+/// `fy` for sampling, are split into tiles, left to right and top to bottom.
+fn tiles(
+    reg: Region,
+    fx: u32,
+    fy: u32,
+) -> Option<impl Iterator<Item = Region>> {
+    let (tw, th) = (TILE_MAX / fx, TILE_MAX / fy);
+    let (width, height) = (reg.width(), reg.height());
+    if width <= tw && height <= th {
+        return None;
+    }
+    Some((0..height).step_by(th as usize).flat_map(move |y| {
+        (0..width).step_by(tw as usize).map(move |x| {
+            let (w, h) = (tw.min(width - x), th.min(height - y));
+            Region::new(reg.left() + x as i32, reg.top() + y as i32, w, h)
+        })
+    }))
+}
+
+/// Get the offset of a tile from the origin of its region
+fn tile_offset(reg: Region, tile: Region) -> Pt<f32> {
+    let x = tile.left() - reg.left();
+    let y = tile.top() - reg.top();
+    Pt::new(x as f32, y as f32)
 }
 
 /// Plot destination
@@ -66,6 +434,18 @@ trait PlotDest {
     ///
     /// * `joined` If true, join ends of sub-plot.
     fn close(&mut self, joined: bool);
+
+    /// Add a cubic bézier spline, if supported.
+    ///
+    /// Returns false if the spline must be decomposed into points.
+    fn add_cubic(&mut self, _b: WidePt, _c: WidePt, _d: WidePt) -> bool {
+        false
+    }
+
+    /// Get the number of points dropped because the destination was full.
+    fn dropped_points(&self) -> usize {
+        0
+    }
 }
 
 impl PlotDest for Fig {
@@ -75,6 +455,9 @@ impl PlotDest for Fig {
     fn close(&mut self, _joined: bool) {
         Fig::close(self);
     }
+    fn dropped_points(&self) -> usize {
+        Fig::dropped_points(self)
+    }
 }
 
 impl PlotDest for Stroke {
@@ -84,33 +467,201 @@ impl PlotDest for Stroke {
     fn close(&mut self, joined: bool) {
         Stroke::close(self, joined);
     }
+    fn add_cubic(&mut self, b: WidePt, c: WidePt, d: WidePt) -> bool {
+        Stroke::add_cubic(self, b, c, d)
+    }
+    fn dropped_points(&self) -> usize {
+        Stroke::dropped_points(self)
+    }
+}
+
+/// Plot destination which scales points (X and Y factors)
+struct Scaled<'a, D: PlotDest>(&'a mut D, f32, f32);
+
+impl<'a, D: PlotDest> PlotDest for Scaled<'a, D> {
+    fn add_point(&mut self, pt: WidePt) {
+        let p = Pt::new(pt.0.x() * self.1, pt.0.y() * self.2);
+        let w = pt.w() * (self.1 + self.2) / 2.0;
+        self.0.add_point(WidePt(p, w, pt.a()));
+    }
+    fn close(&mut self, joined: bool) {
+        self.0.close(joined);
+    }
+    fn dropped_points(&self) -> usize {
+        self.0.dropped_points()
+    }
+}
+
+/// Plot destination which sends points to two destinations
+struct Tee<'a, A: PlotDest, B: PlotDest>(&'a mut A, &'a mut B);
+
+impl<'a, A: PlotDest, B: PlotDest> PlotDest for Tee<'a, A, B> {
+    fn add_point(&mut self, pt: WidePt) {
+        self.0.add_point(pt);
+        self.1.add_point(pt);
+    }
+    fn close(&mut self, joined: bool) {
+        self.0.close(joined);
+        self.1.close(joined);
+    }
+    fn dropped_points(&self) -> usize {
+        self.0.dropped_points() + self.1.dropped_points()
+    }
+}
+
+impl<'a, P: Pixel> Drawn<'a, P> {
+    /// Get the region of pixels which may have been changed.
+    ///
+    /// Only pixels within the raster are included.  Returns `None` if no
+    /// pixels were touched.
+    pub fn dirty(&self) -> Option<Region> {
+        self.dirty
+    }
+
+    /// Get the drawn raster, for as long as the plotter is borrowed.
+    pub fn into_raster(self) -> &'a mut Raster<P> {
+        self.raster
+    }
+}
+
+impl<P: Pixel> Deref for Drawn<'_, P> {
+    type Target = Raster<P>;
+
+    fn deref(&self) -> &Raster<P> {
+        self.raster
+    }
+}
+
+impl<P: Pixel> DerefMut for Drawn<'_, P> {
+    fn deref_mut(&mut self) -> &mut Raster<P> {
+        self.raster
+    }
+}
+
+impl PlotterBuilder {
+    /// Create a new plotter builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set tolerance threshold for curve decomposition.
+    ///
+    /// See [Plotter::set_tolerance].
+    pub fn tolerance(mut self, t: f32) -> Self {
+        self.tolerance = Some(t);
+        self
+    }
+
+    /// Set stroke join style.
+    ///
+    /// See [Plotter::set_join].
+    pub fn join(mut self, js: JoinStyle) -> Self {
+        self.join_style = Some(js);
+        self
+    }
+
+    /// Set stroke cap style.
+    ///
+    /// See [Plotter::set_cap].
+    pub fn cap(mut self, cs: CapStyle) -> Self {
+        self.cap_style = Some(cs);
+        self
+    }
+
+    /// Set the transform.
+    ///
+    /// See [Plotter::set_transform].
+    pub fn transform(mut self, t: Transform<f32>) -> Self {
+        self.transform = Some(t);
+        self
+    }
+
+    /// Set anti-aliasing for fills and strokes.
+    ///
+    /// See [Plotter::set_antialias].
+    pub fn antialias(mut self, antialias: bool) -> Self {
+        self.antialias = Some(antialias);
+        self
+    }
+
+    /// Build a plotter.
+    ///
+    /// * `raster` Raster to draw.
+    pub fn build<P>(self, raster: Raster<P>) -> Plotter<P>
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+        P::Chan: BlendChannel,
+    {
+        let mut plotter = Plotter::new(raster);
+        if let Some(t) = self.tolerance {
+            plotter.set_tolerance(t);
+        }
+        if let Some(js) = self.join_style {
+            plotter.set_join(js);
+        }
+        if let Some(cs) = self.cap_style {
+            plotter.set_cap(cs);
+        }
+        if let Some(t) = self.transform {
+            plotter.set_transform(t);
+        }
+        if let Some(antialias) = self.antialias {
+            plotter.set_antialias(antialias);
+        }
+        plotter
+    }
 }
 
 impl<P> Plotter<P>
 where
-    P: Pixel<Chan = Ch8, Alpha = Premultiplied, Gamma = Linear>,
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+    P::Chan: BlendChannel,
 {
     /// Create a new plotter.
     ///
+    /// Use [PlotterBuilder] to configure options at construction.
+    ///
     /// * `raster` Raster to draw.
     pub fn new(raster: Raster<P>) -> Self {
         let tol = 0.3;
-        let len = raster.width() as usize;
-        // Capacity must be 8-element multiple (for SIMD)
-        let cap = ((len + 7) >> 3) << 3;
-        let mut sgn_area = vec![0; cap];
-        // Remove excess elements
-        for _ in 0..cap - len {
-            sgn_area.pop();
-        }
+        let sgn_area = new_sgn_area(raster.width());
         Plotter {
             raster,
             sgn_area,
+            sgn_area_wide: vec![],
             pen: WidePt::default(),
             transform: Transform::default(),
+            viewport: Pt::default(),
+            pixel_origin: PixelOrigin::Corner,
+            stroke_units: StrokeUnits::PixelSpace,
+            w_scale: 1.0,
             tol_sq: tol * tol,
+            tol_user: None,
+            stroke_tol_sq: None,
             s_width: 1.0,
+            s_attr: 0.0,
             join_style: JoinStyle::Miter(4.0),
+            cap_style: CapStyle::Butt,
+            start_marker: None,
+            end_marker: None,
+            stroke_curves: false,
+            stroke_snap: false,
+            clip: None,
+            scissor: None,
+            blend: BlendMode::default(),
+            opacity: 1.0,
+            antialias: true,
+            coverage_bits: 8,
+            states: vec![],
+            group: None,
+            ssaa: None,
+            ink_spans: vec![],
+            metrics: None,
+            dropped: 0,
+            fig: None,
+            stroke: None,
+            stroke_ops: vec![],
+            dirty: None,
         }
     }
 
@@ -124,24 +675,264 @@ where
         self.raster.height()
     }
 
+    /// Set the raster, returning the previous one.
+    ///
+    /// Any active group is ended, and any clip (including in saved states) is
+    /// cleared if the dimensions change.
+    ///
+    /// * `raster` Raster to draw.
+    pub fn set_raster(&mut self, raster: Raster<P>) -> Raster<P> {
+        self.end_group();
+        if raster.region() != self.raster.region() {
+            self.clip = None;
+            self.states.iter_mut().for_each(|s| s.clip = None);
+        }
+        if raster.width() != self.raster.width() {
+            self.sgn_area = new_sgn_area(raster.width());
+            self.sgn_area_wide.clear();
+        }
+        self.dirty = None;
+        std::mem::replace(&mut self.raster, raster)
+    }
+
+    /// Save the drawing state.
+    ///
+    /// The state includes transform, viewport, pixel origin, tolerances, pen
+    /// width, stroke styles, clip, scissor, blend mode, opacity,
+    /// anti-aliasing and coverage precision.  It is pushed onto a stack, to
+    /// be restored later with [restore_state].
+    ///
+    /// [restore_state]: struct.Plotter.html#method.restore_state
+    pub fn save_state(&mut self) -> &mut Self {
+        let state = State {
+            transform: self.transform,
+            viewport: self.viewport,
+            pixel_origin: self.pixel_origin,
+            stroke_units: self.stroke_units,
+            w_scale: self.w_scale,
+            tol_sq: self.tol_sq,
+            tol_user: self.tol_user,
+            stroke_tol_sq: self.stroke_tol_sq,
+            s_width: self.s_width,
+            s_attr: self.s_attr,
+            join_style: self.join_style,
+            cap_style: self.cap_style,
+            start_marker: self.start_marker.clone(),
+            end_marker: self.end_marker.clone(),
+            stroke_curves: self.stroke_curves,
+            stroke_snap: self.stroke_snap,
+            clip: self.clip.clone(),
+            scissor: self.scissor,
+            blend: self.blend,
+            opacity: self.opacity,
+            antialias: self.antialias,
+            coverage_bits: self.coverage_bits,
+        };
+        self.states.push(state);
+        self
+    }
+
+    /// Restore the most recently saved drawing state.
+    ///
+    /// If no state is saved, this has no effect.
+    pub fn restore_state(&mut self) -> &mut Self {
+        if let Some(state) = self.states.pop() {
+            self.transform = state.transform;
+            self.viewport = state.viewport;
+            self.pixel_origin = state.pixel_origin;
+            self.stroke_units = state.stroke_units;
+            self.w_scale = state.w_scale;
+            self.tol_sq = state.tol_sq;
+            self.tol_user = state.tol_user;
+            self.stroke_tol_sq = state.stroke_tol_sq;
+            self.s_width = state.s_width;
+            self.s_attr = state.s_attr;
+            self.join_style = state.join_style;
+            self.cap_style = state.cap_style;
+            self.start_marker = state.start_marker;
+            self.end_marker = state.end_marker;
+            self.stroke_curves = state.stroke_curves;
+            self.stroke_snap = state.stroke_snap;
+            self.clip = state.clip;
+            self.scissor = state.scissor;
+            self.blend = state.blend;
+            self.opacity = state.opacity;
+            self.antialias = state.antialias;
+            self.coverage_bits = state.coverage_bits;
+        }
+        self
+    }
+
     /// Reset pen.
     fn reset(&mut self) {
-        self.pen = WidePt(Pt::default(), self.s_width);
+        self.pen = WidePt(Pt::default(), self.s_width, self.s_attr);
+    }
+
+    /// Clear the raster to default pixels.
+    pub fn clear(&mut self) -> &mut Self {
+        self.raster.clear();
+        self.add_dirty(self.raster.region());
+        self.sgn_area.iter_mut().for_each(|s| *s = 0);
+        self.sgn_area_wide.iter_mut().for_each(|s| *s = 0);
+        self
+    }
+
+    /// Clear the raster with a solid color.
+    ///
+    /// * `clr` Color to fill the raster.
+    pub fn clear_with(&mut self, clr: P) -> &mut Self {
+        let region = self.raster.region();
+        self.raster.copy_color(region, clr);
+        self.add_dirty(region);
+        self.sgn_area.iter_mut().for_each(|s| *s = 0);
+        self.sgn_area_wide.iter_mut().for_each(|s| *s = 0);
+        self
     }
 
     /// Set tolerance threshold for curve decomposition.
+    ///
+    /// The tolerance is in raster pixels, and applies after the transform.
+    /// It is used for flattening curves, and also for round stroke joins and
+    /// caps unless a stroke tolerance is set.
     pub fn set_tolerance(&mut self, t: f32) -> &mut Self {
         let tol = t.max(0.01);
         self.tol_sq = tol * tol;
+        self.tol_user = None;
+        self
+    }
+
+    /// Set tolerance threshold for curve decomposition, in user units.
+    ///
+    /// The tolerance in raster pixels is derived by multiplying by the
+    /// maximum scale factor of the transform, and is updated whenever the
+    /// transform changes.  This keeps the number of segments for a curve the
+    /// same at any scale.  Calling [set_tolerance] switches back to a fixed
+    /// tolerance in raster pixels.
+    ///
+    /// [set_tolerance]: struct.Plotter.html#method.set_tolerance
+    pub fn set_tolerance_auto(&mut self, user_tol: f32) -> &mut Self {
+        self.tol_user = Some(user_tol);
+        self.update_tolerance();
+        self
+    }
+
+    /// Update tolerance from the transform scale, if in user units.
+    fn update_tolerance(&mut self) {
+        if let Some(t) = self.tol_user {
+            let (sx, sy) = transform_scale(&self.transform);
+            let tol = (t * sx.max(sy)).max(0.01);
+            self.tol_sq = tol * tol;
+        }
+    }
+
+    /// Set tolerance threshold for round stroke joins and caps.
+    ///
+    /// The tolerance is in raster pixels.  It does not affect curve
+    /// decomposition.  If not set, the curve decomposition tolerance is used.
+    pub fn set_stroke_tolerance(&mut self, t: f32) -> &mut Self {
+        let tol = t.max(0.01);
+        self.stroke_tol_sq = Some(tol * tol);
         self
     }
 
     /// Set the transform.
     pub fn set_transform(&mut self, t: Transform<f32>) -> &mut Self {
         self.transform = t;
+        self.update_w_scale();
+        self.update_tolerance();
+        self
+    }
+
+    /// Set the viewport offset.
+    ///
+    /// The offset is subtracted from points after the transform, so the
+    /// raster acts as a window into a larger image.  This allows rendering
+    /// the same paths into a series of tiles.  Geometry outside of the raster
+    /// is skipped cheaply.
+    ///
+    /// * `x` Left edge of the raster, in pixels.
+    /// * `y` Top edge of the raster, in pixels.
+    pub fn set_viewport(&mut self, x: f32, y: f32) -> &mut Self {
+        self.viewport = Pt::new(x, y);
+        self
+    }
+
+    /// Set the pixel origin.
+    ///
+    /// With `Center`, integer coordinates refer to pixel centers, as in some
+    /// other rasterizers, so points are offset by half a pixel after the
+    /// transform.  The default is `Corner`.
+    ///
+    /// * `origin` Pixel origin.
+    pub fn set_pixel_origin(&mut self, origin: PixelOrigin) -> &mut Self {
+        self.pixel_origin = origin;
+        self
+    }
+
+    /// Map a raster point back to user space.
+    ///
+    /// This undoes the pixel origin, viewport and transform, so that
+    /// patterns painted from [fill_spans] (which reports raster columns and
+    /// rows) can follow the path when the transform changes.  For the center
+    /// of pixel `(x, y)`, pass `(x + 0.5, y + 0.5)`.  Inks are given the same
+    /// mapping, with [Ink.set_user_map].
+    ///
+    /// Returns `None` if the transform is singular, such as a scale of zero.
+    ///
+    /// * `x` Raster X coordinate.
+    /// * `y` Raster Y coordinate.
+    ///
+    /// [fill_spans]: struct.Plotter.html#method.fill_spans
+    /// [Ink.set_user_map]: ink/trait.Ink.html#method.set_user_map
+    pub fn user_point(&self, x: f32, y: f32) -> Option<Pt<f32>> {
+        transform_inverse(&self.transform, self.device_point(x, y))
+    }
+
+    /// Map a raster point back to device space, before the viewport and
+    /// pixel origin.
+    fn device_point(&self, x: f32, y: f32) -> Pt<f32> {
+        let pt = Pt::new(x, y);
+        let pt = match self.pixel_origin {
+            PixelOrigin::Corner => pt,
+            PixelOrigin::Center => pt - Pt::new(0.5, 0.5),
+        };
+        pt + self.viewport
+    }
+
+    /// Get the map from raster pixels to user space, for inks.
+    fn user_map(&self) -> UserMap {
+        let pts = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)];
+        let [o, c, r] = match pts.map(|(x, y)| self.user_point(x, y)) {
+            [Some(o), Some(c), Some(r)] => [o, c, r],
+            // Singular transform; only undo pixel origin and viewport
+            _ => pts.map(|(x, y)| self.device_point(x, y)),
+        };
+        UserMap::new(o, c - o, r - o)
+    }
+
+    /// Set units for stroke widths.
+    ///
+    /// With `UserSpace` units, pen widths are scaled by the average of the
+    /// transform's X and Y scale factors.  The default is `PixelSpace`.
+    ///
+    /// * `units` Stroke width units.
+    pub fn set_stroke_units(&mut self, units: StrokeUnits) -> &mut Self {
+        self.stroke_units = units;
+        self.update_w_scale();
         self
     }
 
+    /// Update the stroke width scale factor.
+    fn update_w_scale(&mut self) {
+        self.w_scale = match self.stroke_units {
+            StrokeUnits::UserSpace => {
+                let (sx, sy) = transform_scale(&self.transform);
+                (sx + sy) / 2.0
+            }
+            StrokeUnits::PixelSpace => 1.0,
+        };
+    }
+
     /// Set pen stroke width.
     ///
     /// All subsequent path points will be affected, until the stroke width
@@ -152,36 +943,326 @@ where
         self.s_width = width;
     }
 
+    /// Set pen stroke attribute.
+    ///
+    /// All subsequent path points will be affected, until the attribute is
+    /// changed again.
+    ///
+    /// * `attr` Pen stroke attribute.
+    fn pen_attr(&mut self, attr: f32) {
+        self.s_attr = attr;
+    }
+
     /// Set stroke join style.
     ///
+    /// A negative miter limit is treated as zero.
+    ///
     /// * `js` Join style.
     pub fn set_join(&mut self, js: JoinStyle) -> &mut Self {
-        self.join_style = js;
+        self.join_style = match js {
+            JoinStyle::Miter(ml) => JoinStyle::Miter(ml.max(0.0)),
+            _ => js,
+        };
         self
     }
 
-    /// Move the pen.
-    fn move_pen(&mut self, p: WidePt) {
-        self.pen = p;
+    /// Set stroke cap style.
+    ///
+    /// * `cs` Cap style.
+    pub fn set_cap(&mut self, cs: CapStyle) -> &mut Self {
+        self.cap_style = cs;
+        self
     }
 
-    /// Transform a point.
-    fn transform_point(&self, p: WidePt) -> WidePt {
-        let pt = self.transform * p.0;
-        WidePt(pt, p.w())
+    /// Set stroke end markers.
+    ///
+    /// Markers are added to the ends of open sub-paths, rotated to match the
+    /// end tangents and scaled by the stroke width.  The stroke is shortened
+    /// by the inset of each marker.
+    ///
+    /// * `start` Marker for the start of each sub-path.
+    /// * `end` Marker for the end of each sub-path.
+    pub fn set_markers(
+        &mut self,
+        start: Option<Marker>,
+        end: Option<Marker>,
+    ) -> &mut Self {
+        self.start_marker = start;
+        self.end_marker = end;
+        self
     }
 
-    /// Add a series of ops.
-    fn add_ops<T, D>(&mut self, ops: T, dst: &mut D)
-    where
-        T: IntoIterator,
-        T::Item: Borrow<PathOp>,
-        D: PlotDest,
-    {
-        self.reset();
-        for op in ops {
-            self.add_op(dst, op.borrow());
-        }
+    /// Set whether stroke outlines keep curves.
+    ///
+    /// When set, curves in stroked paths are offset as bézier splines
+    /// instead of being decomposed into lines first.  This produces much
+    /// smaller outlines from [stroke_path].  Curves are always decomposed
+    /// when markers are set, or with [stroke_profile].
+    ///
+    /// * `curves` Keep curves in stroke outlines.
+    ///
+    /// [stroke_path]: struct.Plotter.html#method.stroke_path
+    /// [stroke_profile]: struct.Plotter.html#method.stroke_profile
+    pub fn set_stroke_curves(&mut self, curves: bool) -> &mut Self {
+        self.stroke_curves = curves;
+        self
+    }
+
+    /// Set whether strokes are snapped to pixel boundaries.
+    ///
+    /// When set, horizontal and vertical straight segments are moved so
+    /// that stroke edges land on pixel boundaries, making thin strokes crisp.
+    /// Diagonal and curved segments are not affected.
+    ///
+    /// * `snap` Snap strokes to pixel boundaries.
+    pub fn set_stroke_snap(&mut self, snap: bool) -> &mut Self {
+        self.stroke_snap = snap;
+        self
+    }
+
+    /// Set the blend mode for fills and strokes.
+    ///
+    /// * `blend` Blend mode.
+    pub fn set_blend(&mut self, blend: BlendMode) -> &mut Self {
+        self.blend = blend;
+        self
+    }
+
+    /// Set the opacity for fills and strokes.
+    ///
+    /// Coverage is multiplied by the opacity before compositing, without
+    /// changing the color.
+    ///
+    /// * `opacity` Opacity, clamped to 0 (transparent) to 1 (opaque).  NaN
+    ///   is treated as 0.
+    pub fn set_opacity(&mut self, opacity: f32) -> &mut Self {
+        self.opacity = if opacity.is_nan() {
+            0.0
+        } else {
+            opacity.clamp(0.0, 1.0)
+        };
+        self
+    }
+
+    /// Set anti-aliasing for fills and strokes.
+    ///
+    /// When disabled, each pixel is either fully covered or not, depending on
+    /// whether at least half of its area is covered.  This is useful for
+    /// masks and pixel art.  Anti-aliasing is enabled by default.
+    ///
+    /// * `antialias` Enable anti-aliasing.
+    pub fn set_antialias(&mut self, antialias: bool) -> &mut Self {
+        self.antialias = antialias;
+        self
+    }
+
+    /// Set coverage precision for fills and strokes.
+    ///
+    /// Pixel coverage along edges is quantized to this many bits.  The
+    /// default of 8 bits matches 8-bit channels, and uses SIMD for solid
+    /// `Matte8` and `Rgba8p` fills.  With 16 bits, 16-bit rasters get
+    /// smoother edges, but the signed area buffer takes twice the memory and
+    /// SIMD is not used.  With 4 bits, edges are coarser, but filling is no
+    /// faster.
+    ///
+    /// This applies to ink fills, [fill_matte] and [fill_spans] as well, but
+    /// [fill_subpixel] and [fill_ssaa] always use 8 bits.  Ink and span
+    /// coverage is reported with 8-bit precision.
+    ///
+    /// * `bits` Coverage precision: 4, 8 or 16.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is not 4, 8 or 16.
+    ///
+    /// [fill_matte]: struct.Plotter.html#method.fill_matte
+    /// [fill_spans]: struct.Plotter.html#method.fill_spans
+    /// [fill_subpixel]: struct.Plotter.html#method.fill_subpixel
+    /// [fill_ssaa]: struct.Plotter.html#method.fill_ssaa
+    pub fn set_coverage_bits(&mut self, bits: u8) -> &mut Self {
+        assert!(
+            matches!(bits, 4 | 8 | 16),
+            "coverage bits must be 4, 8 or 16"
+        );
+        self.coverage_bits = bits;
+        self
+    }
+
+    /// Set a clip path.
+    ///
+    /// All subsequent drawing is restricted to the area inside the clip path.
+    /// Coverage along the clip edges is anti-aliased.  If a clip is already
+    /// set, the new clip is intersected with it.
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    pub fn set_clip<T>(&mut self, rule: FillRule, ops: T) -> &mut Self
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        let matte = self.fill_matte(rule, ops);
+        self.intersect_clip(matte);
+        self
+    }
+
+    /// Set a clip matte.
+    ///
+    /// All subsequent drawing is multiplied by the coverage of the matte.  If
+    /// a clip is already set, the new clip is intersected with it.
+    ///
+    /// * `matte` Clip coverage, with the same dimensions as the raster.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matte dimensions do not match the plotter raster.
+    pub fn set_clip_matte(&mut self, matte: Raster<Matte8>) -> &mut Self {
+        assert_eq!(
+            (matte.width(), matte.height()),
+            (self.width(), self.height()),
+            "clip matte dimensions must match raster"
+        );
+        self.intersect_clip(matte);
+        self
+    }
+
+    /// Intersect clip with a matte.
+    fn intersect_clip(&mut self, mut matte: Raster<Matte8>) {
+        if let Some(clip) = &self.clip {
+            for (m, c) in matte.pixels_mut().iter_mut().zip(clip.pixels()) {
+                *m = Matte8::new(mul_coverage(m.alpha(), c.alpha()));
+            }
+        }
+        self.clip = Some(Arc::new(matte));
+    }
+
+    /// Clear the clip, allowing drawing to the entire raster.
+    pub fn clear_clip(&mut self) -> &mut Self {
+        self.clip = None;
+        self
+    }
+
+    /// Set a scissor rectangle.
+    ///
+    /// All subsequent drawing onto the raster is restricted to the
+    /// rectangle, in raster pixels.  Unlike a clip, no matte is needed, and
+    /// rows and columns outside the rectangle are not scanned at all.  Like
+    /// the clip, it does not affect [fill_matte], [fill_spans] or
+    /// [fill_subpixel].
+    ///
+    /// * `scissor` Rectangle (x, y, width, height), or `None` to draw to the
+    ///   entire raster.
+    ///
+    /// [fill_matte]: struct.Plotter.html#method.fill_matte
+    /// [fill_spans]: struct.Plotter.html#method.fill_spans
+    /// [fill_subpixel]: struct.Plotter.html#method.fill_subpixel
+    pub fn set_scissor(
+        &mut self,
+        scissor: Option<(i32, i32, u32, u32)>,
+    ) -> &mut Self {
+        self.scissor = scissor.map(Region::from);
+        self
+    }
+
+    /// Restrict a region of the raster to the scissor rectangle.
+    ///
+    /// Returns `None` if nothing is left.
+    fn scissor_region(&self, reg: Region) -> Option<Region> {
+        let reg = match self.scissor {
+            Some(scissor) => reg.intersection(scissor),
+            None => reg,
+        };
+        if reg.width() > 0 && reg.height() > 0 {
+            Some(reg)
+        } else {
+            None
+        }
+    }
+
+    /// Move the pen.
+    fn move_pen(&mut self, p: WidePt) {
+        self.pen = p;
+    }
+
+    /// Transform a point.
+    fn transform_point(&self, p: WidePt) -> WidePt {
+        let pt = self.transform * p.0 - self.viewport;
+        let pt = match self.pixel_origin {
+            PixelOrigin::Corner => pt,
+            PixelOrigin::Center => pt + Pt::new(0.5, 0.5),
+        };
+        WidePt(pt, p.w() * self.w_scale, p.a())
+    }
+
+    /// Add a series of ops.
+    ///
+    /// Points dropped because the destination is full are counted.
+    fn add_ops<T, D>(&mut self, ops: T, dst: &mut D)
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        D: PlotDest,
+    {
+        self.reset();
+        for op in ops {
+            self.add_op(dst, op.borrow());
+        }
+        self.dropped = dst.dropped_points();
+    }
+
+    /// Add a series of ops, checking that coordinates can be plotted.
+    ///
+    /// Ops are added until one is found to be invalid.
+    fn try_add_ops<T, D>(&mut self, ops: T, dst: &mut D) -> Result<(), Error>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        D: PlotDest,
+    {
+        self.reset();
+        let res = ops.into_iter().try_for_each(|op| {
+            let op = op.borrow();
+            self.check_op(op)?;
+            self.add_op(dst, op);
+            Ok(())
+        });
+        self.dropped = dst.dropped_points();
+        res
+    }
+
+    /// Check that a path operation can be plotted.
+    fn check_op(&self, op: &PathOp) -> Result<(), Error> {
+        match *op {
+            PathOp::Close() => Ok(()),
+            PathOp::Move(pb) | PathOp::Line(pb) => self.check_point(pb),
+            PathOp::Quad(pb, pc) => {
+                self.check_point(pb)?;
+                self.check_point(pc)
+            }
+            PathOp::Cubic(pb, pc, pd) => {
+                self.check_point(pb)?;
+                self.check_point(pc)?;
+                self.check_point(pd)
+            }
+            PathOp::PenWidth(w) => check_coord(w * self.w_scale),
+            PathOp::PenAttr(a) => check_coord(a),
+        }
+    }
+
+    /// Check that a point can be plotted, after transform.
+    ///
+    /// Large rasters are filled in tiles, so the range is relative to the
+    /// nearest tile origin.
+    fn check_point(&self, p: Pt<f32>) -> Result<(), Error> {
+        if !p.x().is_finite() || !p.y().is_finite() {
+            return Err(Error::NonFinite);
+        }
+        let pt = self.transform_point(WidePt(p, self.s_width, self.s_attr)).0;
+        let tile_origin =
+            |len: u32| (len.saturating_sub(1) / TILE_MAX * TILE_MAX) as f32;
+        let (x, y) = (pt.x(), pt.y());
+        check_coord(x - x.clamp(0.0, tile_origin(self.width())))?;
+        check_coord(y - y.clamp(0.0, tile_origin(self.height())))
     }
 
     /// Add a path operation.
@@ -193,6 +1274,7 @@ where
             PathOp::Quad(pb, pc) => self.quad_to(dst, pb, pc),
             PathOp::Cubic(pb, pc, pd) => self.cubic_to(dst, pb, pc, pd),
             PathOp::PenWidth(w) => self.pen_width(w),
+            PathOp::PenAttr(a) => self.pen_attr(a),
         };
     }
 
@@ -206,7 +1288,7 @@ where
     ///
     /// * `pb` New point.
     fn move_to<D: PlotDest>(&mut self, dst: &mut D, pb: Pt<f32>) {
-        let p = WidePt(pb, self.s_width);
+        let p = WidePt(pb, self.s_width, self.s_attr);
         dst.close(false);
         let b = self.transform_point(p);
         dst.add_point(b);
@@ -217,7 +1299,7 @@ where
     ///
     /// * `pb` End point.
     fn line_to<D: PlotDest>(&mut self, dst: &mut D, pb: Pt<f32>) {
-        let p = WidePt(pb, self.s_width);
+        let p = WidePt(pb, self.s_width, self.s_attr);
         let b = self.transform_point(p);
         dst.add_point(b);
         self.move_pen(p);
@@ -232,19 +1314,23 @@ where
     /// * `end` End point.
     fn quad_to<D: PlotDest>(&mut self, dst: &mut D, cp: Pt<f32>, end: Pt<f32>) {
         let pen = self.pen;
-        let bb = WidePt(cp, (pen.w() + self.s_width) / 2.0);
-        let cc = WidePt(end, self.s_width);
+        let w = (pen.w() + self.s_width) / 2.0;
+        let a = (pen.a() + self.s_attr) / 2.0;
+        let bb = WidePt(cp, w, a);
+        let cc = WidePt(end, self.s_width, self.s_attr);
         let a = self.transform_point(pen);
         let b = self.transform_point(bb);
         let c = self.transform_point(cc);
-        self.quad_to_tran(dst, a, b, c);
+        // Elevate to cubic, if destination supports curves
+        let b0 = a.lerp(b, 2.0 / 3.0);
+        let b1 = c.lerp(b, 2.0 / 3.0);
+        if !dst.add_cubic(b0, b1, c) {
+            self.quad_to_tran(dst, a, b, c);
+        }
         self.move_pen(cc);
     }
 
-    /// Add a quadratic bézier spline.
-    ///
-    /// The spline is decomposed into a series of lines using the DeCastlejau
-    /// method.
+    /// Add a quadratic bézier spline, decomposed into a series of lines.
     fn quad_to_tran<D: PlotDest>(
         &self,
         dst: &mut D,
@@ -252,27 +1338,7 @@ where
         b: WidePt,
         c: WidePt,
     ) {
-        let ab = a.midpoint(b);
-        let bc = b.midpoint(c);
-        let ab_bc = ab.midpoint(bc);
-        let ac = a.midpoint(c);
-        if self.is_within_tolerance(ab_bc, ac) {
-            dst.add_point(c);
-        } else {
-            self.quad_to_tran(dst, a, ab, ab_bc);
-            self.quad_to_tran(dst, ab_bc, bc, c);
-        }
-    }
-
-    /// Check if two points are within tolerance threshold.
-    fn is_within_tolerance(&self, a: WidePt, b: WidePt) -> bool {
-        self.is_within_tolerance2(a.0, b.0)
-    }
-
-    /// Check if two points are within tolerance threshold.
-    fn is_within_tolerance2(&self, a: Pt<f32>, b: Pt<f32>) -> bool {
-        assert!(self.tol_sq > 0.0);
-        a.dist_sq(b) <= self.tol_sq
+        flatten_quad(a, b, c, self.tol_sq, |p| dst.add_point(p));
     }
 
     /// Add a cubic bézier spline.
@@ -291,23 +1357,25 @@ where
         end: Pt<f32>,
     ) {
         let pen = self.pen;
-        let w0 = float_lerp(pen.w(), self.s_width, 1.0 / 3.0);
-        let w1 = float_lerp(pen.w(), self.s_width, 2.0 / 3.0);
-        let bb = WidePt(cp0, w0);
-        let cc = WidePt(cp1, w1);
-        let dd = WidePt(end, self.s_width);
+        let w0 = float_lerp(self.s_width, pen.w(), 1.0 / 3.0);
+        let w1 = float_lerp(self.s_width, pen.w(), 2.0 / 3.0);
+        // Attributes vary evenly from start to end of the spline
+        let a0 = float_lerp(pen.a(), self.s_attr, 1.0 / 3.0);
+        let a1 = float_lerp(pen.a(), self.s_attr, 2.0 / 3.0);
+        let bb = WidePt(cp0, w0, a0);
+        let cc = WidePt(cp1, w1, a1);
+        let dd = WidePt(end, self.s_width, self.s_attr);
         let a = self.transform_point(pen);
         let b = self.transform_point(bb);
         let c = self.transform_point(cc);
         let d = self.transform_point(dd);
-        self.cubic_to_tran(dst, a, b, c, d);
+        if !dst.add_cubic(b, c, d) {
+            self.cubic_to_tran(dst, a, b, c, d);
+        }
         self.move_pen(dd);
     }
 
-    /// Add a cubic bézier spline.
-    ///
-    /// The spline is decomposed into a series of lines using the DeCastlejau
-    /// method.
+    /// Add a cubic bézier spline, decomposed into a series of lines.
     fn cubic_to_tran<D: PlotDest>(
         &self,
         dst: &mut D,
@@ -316,89 +1384,2470 @@ where
         pc: WidePt,
         pd: WidePt,
     ) {
-        let ab = pa.midpoint(pb);
-        let bc = pb.midpoint(pc);
-        let cd = pc.midpoint(pd);
-        let ab_bc = ab.midpoint(bc);
-        let bc_cd = bc.midpoint(cd);
-        let pe = ab_bc.midpoint(bc_cd);
-        let ad = pa.midpoint(pd);
-        if self.is_within_tolerance(pe, ad) {
-            dst.add_point(pd);
-        } else {
-            self.cubic_to_tran(dst, pa, ab, ab_bc, pe);
-            self.cubic_to_tran(dst, pe, bc_cd, cd, pd);
-        }
+        flatten_cubic(pa, pb, pc, pd, self.tol_sq, |p| dst.add_point(p));
     }
 
     /// Fill path onto the raster.
     ///
+    /// Rasters over 8,192 pixels wide or high are filled in tiles, plotting
+    /// the path once for each.
+    ///
     /// * `rule` Fill rule.
     /// * `ops` PathOp iterator.
     /// * `clr` Color to fill.
-    pub fn fill<T>(&mut self, rule: FillRule, ops: T, clr: P) -> &mut Raster<P>
+    pub fn fill<T>(&mut self, rule: FillRule, ops: T, clr: P) -> Drawn<'_, P>
     where
         T: IntoIterator,
         T::Item: Borrow<PathOp>,
     {
-        let mut fig = Fig::new();
-        self.add_ops(ops, &mut fig);
+        self.drawn(|p| {
+            let reg = p.raster.region();
+            p.plot_tiles(reg, ops, 1, 1, |p, fig, tile| {
+                p.fill_fig_scissor(fig, rule, clr, tile);
+            });
+        })
+    }
+
+    /// Draw with a closure, recording the region it changed.
+    ///
+    /// The region is also added to the dirty region.
+    fn drawn<F>(&mut self, f: F) -> Drawn<'_, P>
+    where
+        F: FnOnce(&mut Self),
+    {
+        let dirty = self.dirty.take();
+        f(self);
+        let drawn = self.dirty.take();
+        self.dirty = dirty_union(dirty, drawn);
+        Drawn {
+            raster: &mut self.raster,
+            dirty: drawn,
+        }
+    }
+
+    /// Plot a path into the scratch figure, and fill it with a function.
+    ///
+    /// Points are scaled by `fx` and `fy`, for sampling above the raster
+    /// resolution.  Figure points are clipped to 16,383 pixels, so if the
+    /// region is too large, the path is plotted again for each tile, with
+    /// the viewport offset to it.  The function is called with the figure
+    /// and the region (or tile) to fill, whose origin figure points are
+    /// relative to.
+    fn plot_tiles<T, F>(
+        &mut self,
+        reg: Region,
+        ops: T,
+        fx: u32,
+        fy: u32,
+        mut fill: F,
+    ) where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        F: FnMut(&mut Self, &mut Fig, Region),
+    {
+        let mut fig = self.take_fig();
+        match tiles(reg, fx, fy) {
+            None => {
+                self.plot_scaled(ops, &mut fig, fx, fy);
+                fill(self, &mut fig, reg);
+            }
+            Some(tiles) => {
+                let ops: Vec<PathOp> =
+                    ops.into_iter().map(|op| *op.borrow()).collect();
+                let viewport = self.viewport;
+                for tile in tiles {
+                    self.viewport = viewport + tile_offset(reg, tile);
+                    fig.reset();
+                    self.plot_scaled(&ops, &mut fig, fx, fy);
+                    fill(self, &mut fig, tile);
+                }
+                self.viewport = viewport;
+            }
+        }
+        self.fig = Some(fig);
+    }
+
+    /// Plot a path into a figure, scaling points by `fx` and `fy`.
+    fn plot_scaled<T>(&mut self, ops: T, fig: &mut Fig, fx: u32, fy: u32)
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        if fx == 1 && fy == 1 {
+            self.add_ops(ops, fig);
+        } else {
+            self.add_ops(ops, &mut Scaled(fig, fx as f32, fy as f32));
+        }
         // Closing figure required to handle coincident start/end points
         fig.close();
-        fig.fill(rule, &mut self.raster, clr, &mut self.sgn_area[..]);
-        &mut self.raster
     }
 
-    /// Stroke path onto the raster.
+    /// Get settings which affect the points of a plotted figure.
+    fn plot_settings(&self) -> PlotSettings {
+        PlotSettings {
+            transform: self.transform,
+            viewport: self.viewport,
+            pixel_origin: self.pixel_origin,
+            tol_sq: self.tol_sq,
+        }
+    }
+
+    /// Prepare a path for filling many times.
+    ///
+    /// The path is plotted with the current transform, viewport, pixel origin
+    /// and tolerance, and its vertices are sorted.
     ///
     /// * `ops` PathOp iterator.
-    /// * `clr` Color to stroke.
-    pub fn stroke<T>(&mut self, ops: T, clr: P) -> &mut Raster<P>
+    ///
+    /// ```rust
+    /// use footile::{FillRule, Path2D, Plotter};
+    /// use pix::{matte::Matte8, Raster};
+    ///
+    /// let path = Path2D::default()
+    ///     .absolute()
+    ///     .move_to(2.0, 2.0)
+    ///     .quad_to(14.0, 2.0, 14.0, 14.0)
+    ///     .line_to(2.0, 14.0)
+    ///     .finish();
+    /// let mut p = Plotter::new(Raster::with_clear(16, 16));
+    /// let prepared = p.prepare(&path);
+    /// for value in [64, 128, 255] {
+    ///     p.fill_prepared(FillRule::NonZero, &prepared, Matte8::new(value));
+    /// }
+    /// ```
+    pub fn prepare<T>(&mut self, ops: T) -> PreparedPath
     where
         T: IntoIterator,
         T::Item: Borrow<PathOp>,
     {
-        let mut stroke = Stroke::new(self.join_style, self.tol_sq);
-        self.add_ops(ops, &mut stroke);
-        let ops = stroke.path_ops();
-        self.fill(FillRule::NonZero, ops.iter(), clr)
+        let ops: Vec<PathOp> = ops.into_iter().map(|op| *op.borrow()).collect();
+        let mut fig = Fig::new();
+        self.add_ops(&ops, &mut fig);
+        fig.sort_vids();
+        PreparedPath {
+            ops,
+            settings: self.plot_settings(),
+            fig,
+        }
     }
 
-    /// Get a reference to the raster.
-    pub fn raster(&self) -> &Raster<P> {
-        &self.raster
+    /// Fill a prepared path onto the raster.
+    ///
+    /// If the transform, viewport, pixel origin or tolerance has changed since
+    /// the path was prepared, it is plotted again, the same as [fill].
+    ///
+    /// * `rule` Fill rule.
+    /// * `path` Prepared path.
+    /// * `clr` Color to fill.
+    ///
+    /// [fill]: struct.Plotter.html#method.fill
+    pub fn fill_prepared(
+        &mut self,
+        rule: FillRule,
+        path: &PreparedPath,
+        clr: P,
+    ) -> Drawn<'_, P> {
+        self.drawn(|p| {
+            let tiled = p.width() > TILE_MAX || p.height() > TILE_MAX;
+            if tiled || path.settings != p.plot_settings() {
+                p.fill(rule, &path.ops, clr);
+                return;
+            }
+            let mut fig = p.take_fig();
+            fig.copy_from(&path.fig);
+            p.dropped = fig.dropped_points();
+            p.fill_fig(&mut fig, rule, clr);
+            p.fig = Some(fig);
+        })
     }
 
-    /// Get a mutable reference to the raster.
-    pub fn raster_mut(&mut self) -> &mut Raster<P> {
-        &mut self.raster
+    /// Fill a prepared path onto the raster, translated by an offset.
+    ///
+    /// The offset is in pixels, after the transform, and can include
+    /// fractions.  Translating does not change the order of vertices, so
+    /// they are not sorted again.  As with [fill_prepared], the path is
+    /// plotted again if settings have changed, and also if the offset is
+    /// over 8,191 pixels.
+    ///
+    /// * `rule` Fill rule.
+    /// * `path` Prepared path.
+    /// * `dx` Offset X, in pixels.
+    /// * `dy` Offset Y, in pixels.
+    /// * `clr` Color to fill.
+    ///
+    /// [fill_prepared]: struct.Plotter.html#method.fill_prepared
+    pub fn fill_prepared_offset(
+        &mut self,
+        rule: FillRule,
+        path: &PreparedPath,
+        dx: f32,
+        dy: f32,
+        clr: P,
+    ) -> Drawn<'_, P> {
+        self.drawn(|p| {
+            let tiled = p.width() > TILE_MAX || p.height() > TILE_MAX;
+            let near = dx.abs() < OFFSET_MAX && dy.abs() < OFFSET_MAX;
+            if tiled || !near || path.settings != p.plot_settings() {
+                let viewport = p.viewport;
+                p.viewport = viewport - Pt::new(dx, dy);
+                p.fill(rule, &path.ops, clr);
+                p.viewport = viewport;
+                return;
+            }
+            let mut fig = p.take_fig();
+            fig.copy_from(&path.fig);
+            fig.offset(dx, dy);
+            p.dropped = fig.dropped_points();
+            p.fill_fig(&mut fig, rule, clr);
+            p.fig = Some(fig);
+        })
     }
 
-    /// Consume the plotter and get the raster.
-    pub fn into_raster(self) -> Raster<P> {
-        self.raster
+    /// Fill path onto the raster, with an extra transform for this call only.
+    ///
+    /// The transform `t` is applied to the path before the plotter's
+    /// transform, which is left unchanged.  This is useful for drawing one
+    /// path at many placements.
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    /// * `t` Transform applied before the plotter's transform.
+    /// * `clr` Color to fill.
+    pub fn fill_transformed<T>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        t: &Transform<f32>,
+        clr: P,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        self.drawn(|p| {
+            p.with_transform(t, |p| {
+                p.fill(rule, ops, clr);
+            });
+        })
     }
-}
 
-#[cfg(test)]
-mod test {
-    use crate::*;
-    use pix::matte::Matte8;
-    use pix::Raster;
+    /// Fill path onto the raster, checking for invalid input.
+    ///
+    /// Unlike [fill], this returns an error instead of panicking or plotting
+    /// garbage when coordinates are NaN, infinite or too large after
+    /// transform, when the path has too many points, or when the raster is
+    /// empty.  Nothing is drawn if an error is returned.
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    /// * `clr` Color to fill.
+    ///
+    /// [fill]: struct.Plotter.html#method.fill
+    pub fn try_fill<T>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        clr: P,
+    ) -> Result<&mut Raster<P>, Error>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        self.check_raster()?;
+        self.try_fill_ops(rule, ops, clr)?;
+        Ok(&mut self.raster)
+    }
 
-    #[test]
-    fn overlapping() {
-        let path = Path2D::default()
-            .absolute()
-            .move_to(8.0, 4.0)
-            .line_to(8.0, 3.0)
-            .cubic_to(8.0, 3.0, 8.0, 3.0, 9.0, 3.75)
-            .line_to(8.0, 3.75)
-            .line_to(8.5, 3.75)
-            .line_to(8.5, 3.5)
-            .finish();
-        let r = Raster::with_clear(16, 16);
-        let mut p = Plotter::new(r);
-        p.fill(FillRule::NonZero, &path, Matte8::new(255));
+    /// Check that the raster is not empty.
+    fn check_raster(&self) -> Result<(), Error> {
+        if self.raster.width() > 0 && self.raster.height() > 0 {
+            Ok(())
+        } else {
+            Err(Error::EmptyRaster)
+        }
+    }
+
+    /// Fill path onto the raster, if it is valid.
+    fn try_fill_ops<T>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        clr: P,
+    ) -> Result<(), Error>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        let mut fig = self.take_fig();
+        if tiles(self.raster.region(), 1, 1).is_some() {
+            // Check the path once, then fill it in tiles
+            let ops: Vec<PathOp> =
+                ops.into_iter().map(|op| *op.borrow()).collect();
+            let res = self.try_plot(&ops, &mut fig);
+            self.fig = Some(fig);
+            if res.is_ok() {
+                self.fill(rule, &ops, clr);
+            }
+            return res;
+        }
+        let res = self.try_plot(ops, &mut fig);
+        if res.is_ok() {
+            fig.close();
+            self.fill_fig(&mut fig, rule, clr);
+        }
+        self.fig = Some(fig);
+        res
+    }
+
+    /// Plot a path into a figure, if it is valid and no points are dropped.
+    fn try_plot<T>(&mut self, ops: T, fig: &mut Fig) -> Result<(), Error>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        self.try_add_ops(ops, fig)?;
+        if fig.dropped_points() > 0 {
+            return Err(Error::TooManyPoints);
+        }
+        Ok(())
+    }
+
+    /// Fill path onto a region of the raster.
+    ///
+    /// Path coordinates (after the transform) are relative to the region
+    /// origin, and anything outside the region is clipped.  Only rows and
+    /// columns within the region are scanned, so this is useful for drawing
+    /// glyphs into cells of a large atlas.
+    ///
+    /// * `reg` Region of raster to fill.
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    /// * `clr` Color to fill.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the region is not within the raster.
+    pub fn fill_region<R, T>(
+        &mut self,
+        reg: R,
+        rule: FillRule,
+        ops: T,
+        clr: P,
+    ) -> Drawn<'_, P>
+    where
+        R: Into<Region>,
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        self.drawn(|p| {
+            let reg = reg.into();
+            assert_eq!(
+                p.raster.intersection(reg),
+                reg,
+                "region outside raster"
+            );
+            p.plot_tiles(reg, ops, 1, 1, |p, fig, tile| {
+                p.fill_fig_scissor(fig, rule, clr, tile);
+            });
+        })
+    }
+
+    /// Take the scratch figure (or a new one, if in use), reset to empty.
+    fn take_fig(&mut self) -> Fig {
+        let mut fig = self.fig.take().unwrap_or_default();
+        fig.reset();
+        fig
+    }
+
+    /// Get the wide signed area buffer, sized to the raster width.
+    fn wide_area(&mut self) -> &mut [i32] {
+        self.sgn_area_wide.resize(self.width() as usize, 0);
+        &mut self.sgn_area_wide[..]
+    }
+
+    /// Fill a figure onto the raster, which must not need tiles.
+    fn fill_fig(&mut self, fig: &mut Fig, rule: FillRule, clr: P) {
+        let reg = self.raster.region();
+        self.fill_fig_scissor(fig, rule, clr, reg);
+    }
+
+    /// Fill a figure onto a region of the raster (or group), within the
+    /// scissor rectangle.
+    fn fill_fig_scissor(
+        &mut self,
+        fig: &mut Fig,
+        rule: FillRule,
+        clr: P,
+        reg: Region,
+    ) {
+        // Figure points are relative to the region origin
+        let (x, y) = (reg.left(), reg.top());
+        let reg = match self.scissor_region(reg) {
+            Some(reg) => reg,
+            None => return,
+        };
+        let (dx, dy) = (reg.left() - x, reg.top() - y);
+        if dx == 0 && dy == 0 {
+            self.fill_fig_region(fig, rule, clr, reg);
+        } else {
+            fig.translate(-dx, -dy);
+            self.fill_fig_region(fig, rule, clr, reg);
+            fig.translate(dx, dy);
+        }
+    }
+
+    /// Fill a figure onto a region of the raster (or group).
+    fn fill_fig_region<F: FillSource>(
+        &mut self,
+        fig: &mut F,
+        rule: FillRule,
+        clr: P,
+        reg: Region,
+    ) {
+        let width = self.raster.width() as usize;
+        let coverage_bits = self.coverage_bits;
+        let sgn_area = if coverage_bits > 8 || is_deep(fig, rule) {
+            self.sgn_area_wide.resize(width, 0);
+            AreaBuf::Wide(&mut self.sgn_area_wide[..])
+        } else {
+            AreaBuf::Narrow(&mut self.sgn_area[..])
+        };
+        match &mut self.group {
+            Some(group) => {
+                let (width, height) =
+                    (self.raster.width(), self.raster.height());
+                let matte = group.matte(clr, width, height);
+                // Coverage is summed, so shared edges add up to full
+                let paint = Paint {
+                    clr: Matte8::new(255),
+                    blend: BlendMode::Plus,
+                    opacity: 1.0,
+                    antialias: self.antialias,
+                    clip: None,
+                    coverage_bits,
+                };
+                let metrics = self.metrics.as_mut();
+                if let Some(dirty) = fill_monitored(
+                    fig, rule, matte, reg, paint, sgn_area, metrics,
+                ) {
+                    group.add_dirty(dirty);
+                }
+            }
+            None => {
+                let paint = Paint {
+                    clr,
+                    blend: self.blend,
+                    opacity: self.opacity,
+                    antialias: self.antialias,
+                    clip: self.clip.as_deref(),
+                    coverage_bits,
+                };
+                let raster = &mut self.raster;
+                let metrics = self.metrics.as_mut();
+                if let Some(dirty) = fill_monitored(
+                    fig, rule, raster, reg, paint, sgn_area, metrics,
+                ) {
+                    self.add_dirty(dirty);
+                }
+            }
+        }
+    }
+
+    /// Add a region to the dirty region.
+    fn add_dirty(&mut self, dirty: Region) {
+        self.dirty = Some(match self.dirty {
+            Some(d) => region_union(d, dirty),
+            None => dirty,
+        });
+    }
+
+    /// Enable or disable collecting metrics.
+    ///
+    /// Metrics are collected for each fill or stroke, and can be checked
+    /// with [last_metrics].  While enabled, rows are always filled on the
+    /// calling thread.  Metrics are disabled by default.
+    ///
+    /// [last_metrics]: struct.Plotter.html#method.last_metrics
+    pub fn set_metrics(&mut self, enable: bool) -> &mut Self {
+        self.metrics = enable.then(Metrics::default);
+        self
+    }
+
+    /// Get metrics from the last fill or stroke.
+    ///
+    /// If metrics are disabled, all counts are zero.
+    pub fn last_metrics(&self) -> Metrics {
+        self.metrics.unwrap_or_default()
+    }
+
+    /// Check if the last fill or stroke was truncated.
+    ///
+    /// Paths (and stroke outlines) are limited to about 16 million points.
+    /// Points past that are dropped, drawing only part of the path; this
+    /// returns true if any were.  Use [last_dropped_points] to get the count,
+    /// or [try_fill] to get an error instead.
+    ///
+    /// ```rust
+    /// use footile::{FillRule, Path2D, Plotter};
+    /// use pix::{matte::Matte8, Raster};
+    ///
+    /// let path = Path2D::default()
+    ///     .move_to(1.0, 1.0)
+    ///     .line_to(7.0, 1.0)
+    ///     .line_to(4.0, 7.0)
+    ///     .finish();
+    /// let mut p = Plotter::new(Raster::with_clear(8, 8));
+    /// p.fill(FillRule::NonZero, &path, Matte8::new(255));
+    /// assert!(!p.last_fill_truncated());
+    /// ```
+    ///
+    /// [last_dropped_points]: struct.Plotter.html#method.last_dropped_points
+    /// [try_fill]: struct.Plotter.html#method.try_fill
+    pub fn last_fill_truncated(&self) -> bool {
+        self.dropped > 0
+    }
+
+    /// Get the number of points dropped from the last fill or stroke.
+    ///
+    /// For strokes, this includes points dropped from the path and from its
+    /// outline.
+    pub fn last_dropped_points(&self) -> usize {
+        self.dropped
+    }
+
+    /// Take the dirty region.
+    ///
+    /// This is the region of pixels which may have been changed by drawing
+    /// (or clearing) since the last call.  Only pixels within the raster are
+    /// included.  Returns `None` if no pixels were touched.
+    pub fn take_dirty(&mut self) -> Option<Region> {
+        self.dirty.take()
+    }
+
+    /// Fill a path which is already in raster coordinates.
+    fn fill_raster_coords(&mut self, rule: FillRule, ops: &[PathOp], clr: P) {
+        self.with_raster_coords(|p| {
+            p.fill(rule, ops, clr);
+        });
+    }
+
+    /// Call a function with a transform composed before the plotter's
+    /// transform.
+    fn with_transform<R>(
+        &mut self,
+        t: &Transform<f32>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let transform = self.transform;
+        let w_scale = self.w_scale;
+        let tol_sq = self.tol_sq;
+        self.transform = *t * transform;
+        self.update_w_scale();
+        self.update_tolerance();
+        let res = f(self);
+        self.transform = transform;
+        self.w_scale = w_scale;
+        self.tol_sq = tol_sq;
+        res
+    }
+
+    /// Call a function with no transform, so paths are in raster coordinates.
+    fn with_raster_coords<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        // Keep points dropped from the stroke, as well as its outline
+        let dropped = self.dropped;
+        let transform = std::mem::take(&mut self.transform);
+        let viewport = std::mem::take(&mut self.viewport);
+        let origin = std::mem::take(&mut self.pixel_origin);
+        let w_scale = std::mem::replace(&mut self.w_scale, 1.0);
+        let res = f(self);
+        self.dropped += dropped;
+        self.transform = transform;
+        self.viewport = viewport;
+        self.pixel_origin = origin;
+        self.w_scale = w_scale;
+        res
+    }
+
+    /// Get a reference to the raster.
+    pub fn raster(&self) -> &Raster<P> {
+        &self.raster
+    }
+
+    /// Get a mutable reference to the raster.
+    pub fn raster_mut(&mut self) -> &mut Raster<P> {
+        &mut self.raster
+    }
+
+    /// Consume the plotter and get the raster.
+    pub fn into_raster(self) -> Raster<P> {
+        self.raster
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PlotDest;
+    use crate::geom::{segment_dist_sq, WidePt};
+    use crate::*;
+    use pix::el::Pixel;
+    use pix::matte::{Matte16, Matte8};
+    use pix::rgb::{Rgb8, Rgba8p};
+    use pix::{Raster, Region};
+    use pointy::{Pt, Transform};
+
+    #[test]
+    fn overlapping() {
+        // Cubic is a straight line to (9, 3.75), and the path doubles back
+        // along y = 3.75, with three vertices sharing it
+        let path = Path2D::default()
+            .absolute()
+            .move_to(8.0, 4.0)
+            .line_to(8.0, 3.0)
+            .cubic_to(8.0, 3.0, 8.0, 3.0, 9.0, 3.75)
+            .line_to(8.0, 3.75)
+            .line_to(8.5, 3.75)
+            .line_to(8.5, 3.5)
+            .finish();
+        let pts = [
+            (8.0, 4.0),
+            (8.0, 3.0),
+            (9.0, 3.75),
+            (8.0, 3.75),
+            (8.5, 3.75),
+            (8.5, 3.5),
+        ];
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let mut p = Plotter::new(Raster::with_clear(16, 16));
+            p.fill(rule, &path, Matte8::new(255));
+            // Winding is 1 over an area of 0.375, all within one pixel
+            let mut expected = Raster::<Matte8>::with_clear(16, 16);
+            *expected.pixel_mut(8, 3) = Matte8::new(96);
+            assert_eq!(p.raster().as_u8_slice(), expected.as_u8_slice());
+            // Coverage does not depend on the order of tied vertices
+            for start in 0..pts.len() {
+                for rev in [false, true] {
+                    let mut ring = pts.to_vec();
+                    ring.rotate_left(start);
+                    if rev {
+                        ring.reverse();
+                    }
+                    let mut path = Path2D::default().absolute();
+                    path = path.move_to(ring[0].0, ring[0].1);
+                    for (x, y) in &ring[1..] {
+                        path = path.line_to(*x, *y);
+                    }
+                    let path = path.close().finish();
+                    p.clear().fill(rule, &path, Matte8::new(255));
+                    assert_eq!(
+                        p.raster().as_u8_slice(),
+                        expected.as_u8_slice(),
+                        "{rule:?} {start} {rev}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clip() {
+        let clr = Rgba8p::new(255, 128, 0, 255);
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.0, 32.0)
+            .line_to(32.0, 32.0)
+            .line_to(32.0, 0.0)
+            .close()
+            .finish();
+        let tri = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.0, 32.0)
+            .line_to(32.0, 0.0)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::with_clear(32, 32));
+        p.fill(FillRule::NonZero, &tri, clr);
+        let mut c = Plotter::new(Raster::with_clear(32, 32));
+        c.set_clip(FillRule::NonZero, &tri);
+        c.fill(FillRule::NonZero, &rect, clr);
+        assert_eq!(p.raster().pixels(), c.raster().pixels());
+        // Diagonal edge pixels are anti-aliased
+        let px = c.raster().pixel(10, 21);
+        assert!(px.alpha() > 0.into() && px.alpha() < 255.into());
+        // Nested clips intersect
+        let half = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.0, 16.0)
+            .line_to(32.0, 16.0)
+            .line_to(32.0, 0.0)
+            .close()
+            .finish();
+        c.raster_mut().clear();
+        c.set_clip(FillRule::NonZero, &half);
+        c.fill(FillRule::NonZero, &rect, clr);
+        assert_eq!(c.raster().pixel(4, 4), clr);
+        assert_eq!(c.raster().pixel(4, 24), Rgba8p::default());
+        assert_eq!(c.raster().pixel(28, 8), Rgba8p::default());
+        c.raster_mut().clear();
+        c.clear_clip();
+        c.fill(FillRule::NonZero, &rect, clr);
+        assert_eq!(c.raster().pixel(24, 24), clr);
+    }
+
+    #[test]
+    fn clip_matte() {
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.0, 8.0)
+            .line_to(16.0, 8.0)
+            .line_to(16.0, 0.0)
+            .close()
+            .finish();
+        let mut matte = Raster::<Matte8>::with_clear(16, 8);
+        for y in 0..8 {
+            for x in 0..16 {
+                // Compositing is not exact below 16
+                *matte.pixel_mut(x, y) = Matte8::new((16 + x * 15 + y) as u8);
+            }
+        }
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(16, 8));
+        p.set_clip_matte(matte.clone());
+        p.fill(FillRule::NonZero, &rect, Matte8::new(255));
+        assert_eq!(p.raster().pixels(), matte.pixels());
+    }
+
+    #[test]
+    #[should_panic]
+    fn clip_matte_size() {
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(16, 8));
+        p.set_clip_matte(Raster::with_clear(8, 8));
+    }
+
+    #[test]
+    fn blend_modes() {
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.0, 4.0)
+            .line_to(4.0, 4.0)
+            .line_to(4.0, 0.0)
+            .close()
+            .finish();
+        let dst = Rgba8p::new(100, 150, 200, 255);
+        let src = Rgba8p::new(50, 100, 0, 255);
+        let half = Rgba8p::new(40, 0, 20, 128);
+        for (blend, clr, expected) in [
+            (BlendMode::SrcOver, src, src),
+            (BlendMode::Multiply, src, Rgba8p::new(20, 59, 0, 255)),
+            (BlendMode::Screen, src, Rgba8p::new(130, 191, 200, 255)),
+            (BlendMode::Plus, src, Rgba8p::new(150, 250, 200, 255)),
+            (BlendMode::Clear, src, Rgba8p::default()),
+            // (s·d + d·(255 - sa)) / 255, with opaque destination
+            (BlendMode::Multiply, half, Rgba8p::new(65, 75, 115, 255)),
+            (BlendMode::Screen, half, Rgba8p::new(124, 150, 204, 255)),
+        ] {
+            let mut p = Plotter::new(Raster::with_color(4, 4, dst));
+            p.set_blend(blend);
+            p.fill(FillRule::NonZero, &rect, clr);
+            assert_eq!(p.raster().pixel(2, 2), expected, "{:?}", blend);
+        }
+    }
+
+    #[test]
+    fn channel_16() {
+        use pix::chan::{Ch16, Channel};
+        use pix::matte::Matte16;
+        use pix::rgb::Rgba16p;
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.0, 4.0)
+            .line_to(4.0, 4.0)
+            .line_to(4.0, 0.0)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::with_clear(4, 4));
+        p.fill(FillRule::NonZero, &rect, Matte16::new(65535));
+        for m in p.raster().pixels() {
+            assert_eq!(m.alpha(), Ch16::MAX);
+        }
+        let clr = Rgba16p::new(1000, 20000, 65535, 65535);
+        let mut p = Plotter::new(Raster::with_clear(4, 4));
+        p.fill(FillRule::NonZero, &rect, clr);
+        assert_eq!(p.raster().pixel(2, 2), clr);
+        let dst = Rgba16p::new(32768, 65535, 0, 65535);
+        let mut p = Plotter::new(Raster::with_color(4, 4, dst));
+        p.set_blend(BlendMode::Multiply);
+        p.fill(FillRule::NonZero, &rect, clr);
+        let expected = Rgba16p::new(500, 20000, 0, 65535);
+        assert_eq!(p.raster().pixel(2, 2), expected);
+        // Edge coverage matches 8-bit coverage, scaled
+        let tri = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(9.0, 1.0)
+            .line_to(0.0, 1.0)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::with_clear(9, 1));
+        p.fill(FillRule::NonZero, &tri, Matte16::new(65535));
+        let mut m = Plotter::new(Raster::with_clear(9, 1));
+        m.fill(FillRule::NonZero, &tri, Matte8::new(255));
+        for (a, b) in p.raster().pixels().iter().zip(m.raster().pixels()) {
+            assert_eq!(a.alpha(), Ch16::from(b.alpha()));
+        }
+    }
+
+    #[test]
+    fn channel_32() {
+        use pix::chan::{Ch32, Channel};
+        use pix::rgb::Rgba32p;
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.0, 4.0)
+            .line_to(4.0, 4.0)
+            .line_to(4.0, 0.0)
+            .close()
+            .finish();
+        let clr = Rgba32p::new(0.25, 0.5, 1.0, 1.0);
+        let mut p = Plotter::new(Raster::with_clear(4, 4));
+        p.fill(FillRule::NonZero, &rect, clr);
+        assert_eq!(p.raster().pixel(2, 2), clr);
+        assert_eq!(p.raster().pixel(2, 2).alpha(), Ch32::MAX);
+        // Accumulate many faint fills without quantization
+        let a = 1.0 / 256.0;
+        let mut p = Plotter::new(Raster::with_clear(4, 4));
+        p.set_blend(BlendMode::Plus);
+        for _ in 0..256 {
+            p.fill(FillRule::NonZero, &rect, Rgba32p::new(a, a, a, a));
+        }
+        let alpha = f32::from(p.raster().pixel(2, 2).alpha());
+        assert!((1.0 - alpha).abs() < 1e-4, "{alpha}");
+        let mut p = Plotter::new(Raster::with_clear(4, 4));
+        p.set_opacity(1.0 / 3.0);
+        p.fill(FillRule::NonZero, &rect, clr);
+        let alpha = f32::from(p.raster().pixel(2, 2).alpha());
+        assert!((1.0 / 3.0 - alpha).abs() < 1e-6, "{alpha}");
+    }
+
+    #[test]
+    fn antialias() {
+        let pts = [(4.0, 2.0), (14.0, 30.0), (28.0, 3.0)];
+        let tri = Path2D::default()
+            .absolute()
+            .move_to(pts[0].0, pts[0].1)
+            .line_to(pts[1].0, pts[1].1)
+            .line_to(pts[2].0, pts[2].1)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::with_clear(32, 32));
+        p.set_antialias(false);
+        p.fill(FillRule::NonZero, &tri, Matte8::new(255));
+        // Reference: pixel centers inside all edges (except near vertices,
+        // where half coverage does not imply the center is inside)
+        let near_vertex = |x: f32, y: f32| {
+            pts.iter().any(|(px, py)| (px - x).hypot(py - y) < 1.5)
+        };
+        let inside = |x: f32, y: f32| {
+            (0..3).all(|i| {
+                let (x0, y0) = pts[i];
+                let (x1, y1) = pts[(i + 1) % 3];
+                (x1 - x0) * (y - y0) < (y1 - y0) * (x - x0)
+            })
+        };
+        for y in 0..32 {
+            for x in 0..32 {
+                let pix = p.raster().pixel(x, y);
+                let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+                if near_vertex(cx, cy) {
+                    assert!(pix == Matte8::new(0) || pix == Matte8::new(255));
+                } else {
+                    let v = if inside(cx, cy) { 255 } else { 0 };
+                    assert_eq!(pix, Matte8::new(v), "{x},{y}");
+                }
+            }
+        }
+        let line = Path2D::default()
+            .absolute()
+            .pen_width(2.5)
+            .move_to(pts[0].0, pts[0].1)
+            .line_to(pts[1].0, pts[1].1)
+            .line_to(pts[2].0, pts[2].1)
+            .finish();
+        let mut p = Plotter::new(Raster::with_clear(32, 32));
+        p.set_antialias(false);
+        p.stroke(&line, Rgba8p::new(255, 0, 0, 255));
+        for clr in p.raster().pixels() {
+            let a = u8::from(clr.alpha());
+            assert!(a == 0 || a == 255);
+        }
+    }
+
+    #[test]
+    fn dim_pixels() {
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.0, 4.0)
+            .line_to(2.0, 4.0)
+            .line_to(2.0, 0.0)
+            .close()
+            .finish();
+        let dim = Rgba8p::new(3, 2, 1, 4);
+        let mut p = Plotter::new(Raster::with_color(8, 4, dim));
+        for _ in 0..4 {
+            p.fill(FillRule::NonZero, &rect, Rgba8p::new(200, 100, 50, 255));
+        }
+        // Pixels with zero coverage in the same rows are unchanged
+        assert_eq!(p.raster().pixel(6, 2), dim);
+    }
+
+    #[test]
+    fn blend_plus() {
+        let rect = |x0: f32, x1: f32| {
+            Path2D::default()
+                .absolute()
+                .move_to(x0, 0.0)
+                .line_to(x0, 4.0)
+                .line_to(x1, 4.0)
+                .line_to(x1, 0.0)
+                .close()
+                .finish()
+        };
+        let white = Rgba8p::new(255, 255, 255, 255);
+        let clr = Rgba8p::new(255, 128, 32, 255);
+        for i in 1..16 {
+            // Faint coverage of pixel 1 is rounded the same as Screen
+            let x = 1.0 + i as f32 / 256.0;
+            let mut p = Plotter::new(Raster::with_clear(4, 4));
+            p.set_blend(BlendMode::Plus);
+            p.fill(FillRule::NonZero, rect(0.0, x), clr);
+            let mut s = Plotter::new(Raster::with_clear(4, 4));
+            s.set_blend(BlendMode::Screen);
+            s.fill(FillRule::NonZero, rect(0.0, x), clr);
+            assert_eq!(p.raster().pixel(1, 2), s.raster().pixel(1, 2), "{x}");
+            // Complementary coverage sums to full coverage
+            let mut p = Plotter::new(Raster::with_clear(4, 4));
+            p.set_blend(BlendMode::Plus);
+            p.fill(FillRule::NonZero, rect(0.0, x), white);
+            p.fill(FillRule::NonZero, rect(x, 4.0), white);
+            assert_eq!(p.raster().pixel(1, 2), white, "{x}");
+        }
+    }
+
+    #[test]
+    fn opacity() {
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.0, 4.0)
+            .line_to(4.0, 4.0)
+            .line_to(4.0, 0.0)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::with_clear(4, 4));
+        p.set_opacity(0.5);
+        p.fill(FillRule::NonZero, &rect, Rgba8p::new(200, 100, 0, 255));
+        assert_eq!(p.raster().pixel(2, 2), Rgba8p::new(100, 50, 0, 128));
+        let mut m = Plotter::new(Raster::with_clear(4, 4));
+        m.set_opacity(0.5);
+        m.fill(FillRule::NonZero, &rect, Matte8::new(255));
+        assert_eq!(m.raster().pixel(2, 2), Matte8::new(128));
+        m.set_opacity(f32::NAN);
+        assert_eq!(m.opacity, 0.0);
+        m.fill(FillRule::NonZero, &rect, Matte8::new(255));
+        assert_eq!(m.raster().pixel(2, 2), Matte8::new(128));
+        m.set_opacity(f32::INFINITY);
+        assert_eq!(m.opacity, 1.0);
+    }
+
+    #[test]
+    fn clear() {
+        let clr = Rgba8p::new(10, 20, 30, 255);
+        let mut p = Plotter::new(Raster::with_clear(8, 4));
+        p.clear_with(clr);
+        assert!(p.raster().pixels().iter().all(|px| *px == clr));
+        p.clear();
+        assert!(p
+            .raster()
+            .pixels()
+            .iter()
+            .all(|px| *px == Rgba8p::default()));
+    }
+
+    #[test]
+    fn set_raster() {
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.0, 4.0)
+            .line_to(40.0, 4.0)
+            .line_to(40.0, 0.0)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::with_clear(4, 4));
+        p.fill(FillRule::NonZero, &rect, Matte8::new(255));
+        let old = p.set_raster(Raster::with_clear(37, 4));
+        assert_eq!(old.width(), 4);
+        assert!(old.pixels().iter().all(|px| *px == Matte8::new(255)));
+        p.fill(FillRule::NonZero, &rect, Matte8::new(255));
+        assert_eq!(p.width(), 37);
+        assert!(p.raster().pixels().iter().all(|px| *px == Matte8::new(255)));
+    }
+
+    #[test]
+    fn viewport() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(3.0)
+            .move_to(4.0, 4.0)
+            .cubic_to(60.0, 0.0, 0.0, 60.0, 60.0, 60.0)
+            .line_to(4.0, 60.0)
+            .close()
+            .finish();
+        let clr = Rgba8p::new(255, 128, 0, 255);
+        let mut p = Plotter::new(Raster::with_clear(64, 64));
+        p.fill(FillRule::NonZero, &path, clr);
+        p.stroke(&path, Rgba8p::new(0, 0, 255, 128));
+        let mut tile = Plotter::new(Raster::with_clear(16, 16));
+        for ty in 0..4 {
+            for tx in 0..4 {
+                let (x, y) = (tx * 16, ty * 16);
+                tile.clear().set_viewport(x as f32, y as f32);
+                tile.fill(FillRule::NonZero, &path, clr);
+                tile.stroke(&path, Rgba8p::new(0, 0, 255, 128));
+                for j in 0..16 {
+                    for i in 0..16 {
+                        assert_eq!(
+                            tile.raster().pixel(i, j),
+                            p.raster().pixel(x + i, y + j)
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dirty() {
+        let shapes = [
+            Path2D::default()
+                .absolute()
+                .move_to(10.5, 4.25)
+                .line_to(3.0, 20.0)
+                .line_to(22.75, 17.5)
+                .close()
+                .finish(),
+            Path2D::default()
+                .absolute()
+                .move_to(-8.0, -8.0)
+                .line_to(12.0, 30.0)
+                .line_to(40.0, 9.5)
+                .close()
+                .finish(),
+            Path2D::default()
+                .absolute()
+                .move_to(40.0, 40.0)
+                .line_to(50.0, 40.0)
+                .line_to(50.0, 50.0)
+                .close()
+                .finish(),
+        ];
+        for shape in shapes.iter() {
+            let mut p = Plotter::new(Raster::with_clear(32, 32));
+            p.fill(FillRule::NonZero, shape, Matte8::new(255));
+            let dirty = p.take_dirty();
+            let r = p.raster();
+            let changed: Vec<(i32, i32)> = (0..32)
+                .flat_map(|y| (0..32).map(move |x| (x, y)))
+                .filter(|(x, y)| r.pixel(*x, *y) != Matte8::new(0))
+                .collect();
+            match dirty {
+                Some(d) => {
+                    // Edge pixels with no net coverage may be included
+                    let left = changed.iter().map(|p| p.0).min().unwrap();
+                    let right = changed.iter().map(|p| p.0).max().unwrap();
+                    let top = changed.iter().map(|p| p.1).min().unwrap();
+                    let bottom = changed.iter().map(|p| p.1).max().unwrap();
+                    assert!(d.left() <= left && d.left() >= left - 1);
+                    assert!(d.right() > right && d.right() <= right + 2);
+                    assert!(d.top() <= top && d.top() >= top - 1);
+                    assert!(d.bottom() > bottom && d.bottom() <= bottom + 2);
+                }
+                None => assert!(changed.is_empty()),
+            }
+            assert_eq!(p.take_dirty(), None);
+        }
+    }
+
+    #[test]
+    fn drawn_dirty() {
+        let tri = Path2D::default()
+            .absolute()
+            .move_to(10.5, 4.25)
+            .line_to(3.0, 20.0)
+            .line_to(22.75, 17.5)
+            .close()
+            .finish();
+        let line = Path2D::default()
+            .absolute()
+            .move_to(20.0, 24.0)
+            .line_to(29.5, 30.0)
+            .finish();
+        let off = Path2D::default()
+            .absolute()
+            .move_to(40.0, 40.0)
+            .line_to(50.0, 40.0)
+            .line_to(50.0, 50.0)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::with_clear(32, 32));
+        p.pen_width(2.0);
+        let clr = Matte8::new(255);
+        let mut ink = ink::ColorInk::new(Matte8::new(64));
+        let t = Transform::with_translate(-36.0, -38.0);
+        let opts = StrokeOptions::default();
+        let mut total = None;
+        for i in 0..10 {
+            let before = p.raster().clone();
+            let rule = FillRule::NonZero;
+            let drawn = match i {
+                0 => p.fill(rule, &tri, Matte8::new(128)),
+                1 => p.stroke(&line, clr),
+                2 => p.fill_with(rule, &off, &mut ink),
+                3 => p.stroke_with(&tri, &mut ink),
+                4 => p.fill_with(rule, &tri, &mut ink),
+                5 => p.fill_transformed(rule, &off, &t, Matte8::new(96)),
+                6 => p.draw(rule, &off, None, None),
+                7 => p.stroke_opts(&tri, &opts, Matte8::new(192)),
+                8 => p.fill_streamed(rule, &line, clr),
+                _ => p.fill_stroke(rule, &off, clr, clr),
+            };
+            let dirty = drawn.dirty();
+            let changed: Vec<(i32, i32)> = (0..32)
+                .flat_map(|y| (0..32).map(move |x| (x, y)))
+                .filter(|(x, y)| drawn.pixel(*x, *y) != before.pixel(*x, *y))
+                .collect();
+            match dirty {
+                Some(d) => {
+                    // Edge pixels with no net change may be included
+                    let left = changed.iter().map(|p| p.0).min().unwrap();
+                    let right = changed.iter().map(|p| p.0).max().unwrap();
+                    let top = changed.iter().map(|p| p.1).min().unwrap();
+                    let bottom = changed.iter().map(|p| p.1).max().unwrap();
+                    assert!(d.left() <= left && d.left() >= left - 1);
+                    assert!(d.right() > right && d.right() <= right + 2);
+                    assert!(d.top() <= top && d.top() >= top - 1);
+                    assert!(d.bottom() > bottom && d.bottom() <= bottom + 2);
+                }
+                None => assert!(changed.is_empty()),
+            }
+            total = crate::fig::dirty_union(total, dirty);
+        }
+        assert_eq!(p.take_dirty(), total);
+    }
+
+    #[test]
+    fn fill_bands() {
+        use crate::fig::{Fig, Paint};
+        let fish = Path2D::default()
+            .relative()
+            .move_to(112.0, 24.0)
+            .line_to(-32.0, 24.0)
+            .cubic_to(-96.0, -48.0, -96.0, 80.0, 0.0, 32.0)
+            .line_to(32.0, 24.0)
+            .line_to(-16.0, -40.0)
+            .close()
+            .finish();
+        let mut pb = Path2D::default().absolute().move_to(112.0, 64.0);
+        for n in 1..7 {
+            let th = std::f32::consts::PI * 4.0 * (n as f32) / 7.0;
+            pb = pb.line_to(64.0 + th.cos() * 48.0, 64.0 + th.sin() * 48.0);
+        }
+        let heptagram = pb.close().finish();
+        let clr = Rgba8p::new(127, 96, 96, 255);
+        for path in [&fish, &heptagram] {
+            for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+                let mut p = Plotter::<Rgba8p>::new(Raster::with_clear(1, 1));
+                let mut fig = Fig::new();
+                let mut sgn_area = vec![0i16; 128];
+                p.add_ops(path, &mut fig);
+                fig.close();
+                let mut a = Raster::with_clear(128, 128);
+                let da = fig.fill(rule, &mut a, Paint::new(clr), &mut sgn_area);
+                for band_rows in [1, 7, 32] {
+                    let mut b = Raster::with_clear(128, 128);
+                    let paint = Paint::new(clr);
+                    let db = fig.fill_bands(
+                        rule,
+                        &mut b,
+                        Region::new(0, 0, 128, 128),
+                        paint,
+                        &mut sgn_area,
+                        band_rows,
+                    );
+                    assert_eq!(a.pixels(), b.pixels());
+                    assert_eq!(da, db);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn user_point() {
+        let square = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.0, 8.0)
+            .line_to(8.0, 8.0)
+            .line_to(8.0, 0.0)
+            .close()
+            .finish();
+        // Horizontal gradient across the square, in user space
+        let gradient = |p: &mut Plotter<Rgba8p>| {
+            let mut texels = vec![];
+            let mut spans = vec![];
+            p.fill_spans(FillRule::NonZero, &square, |y, x, len, _cov| {
+                spans.push((y, x, len))
+            });
+            for (y, x, len) in spans {
+                for x in x..x + len as i32 {
+                    let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                    let pt = p.user_point(px, py).unwrap();
+                    texels.push((pt.x() * 32.0).round() as u8);
+                }
+            }
+            texels
+        };
+        let mut p = Plotter::new(Raster::with_clear(64, 64));
+        p.set_transform(Transform::with_scale(2.0, 2.0));
+        let a = gradient(&mut p);
+        p.set_transform(Transform::with_scale(2.0, 2.0).translate(29.5, 19.5))
+            .set_viewport(4.0, 2.0)
+            .set_pixel_origin(PixelOrigin::Center);
+        let b = gradient(&mut p);
+        assert_eq!(a.len(), 256);
+        assert_eq!(a, b);
+        // 16 pixels across 8 units: centers at 0.25, 0.75, ...
+        assert_eq!(
+            &a[..16],
+            &[
+                8, 24, 40, 56, 72, 88, 104, 120, 136, 152, 168, 184, 200, 216,
+                232, 248
+            ]
+        );
+        let pt = p.user_point(26.5, 18.5).unwrap();
+        assert_eq!(pt, Pt::new(0.25, 0.25));
+        p.set_transform(Transform::with_scale(0.0, 1.0));
+        assert_eq!(p.user_point(1.0, 1.0), None);
+    }
+
+    #[test]
+    fn coverage_bits() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.5, 0.0)
+            .line_to(0.5, 2.0)
+            .line_to(0.0, 2.0)
+            .close()
+            .finish();
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let mut p = Plotter::new(Raster::<Matte8>::with_clear(2, 2));
+            p.set_coverage_bits(4).fill(rule, &path, Matte8::new(255));
+            assert_eq!(p.raster().pixel(0, 1), Matte8::new(8 << 4));
+            let mut p = Plotter::new(Raster::<Matte8>::with_clear(2, 2));
+            p.set_coverage_bits(8).fill(rule, &path, Matte8::new(255));
+            assert_eq!(p.raster().pixel(0, 1), Matte8::new(128));
+            let mut p = Plotter::new(Raster::<Matte16>::with_clear(2, 2));
+            p.fill(rule, &path, Matte16::new(65535));
+            assert_eq!(p.raster().pixel(0, 1), Matte16::new(32896));
+            p.clear().set_coverage_bits(16).fill(
+                rule,
+                &path,
+                Matte16::new(65535),
+            );
+            assert_eq!(p.raster().pixel(0, 1), Matte16::new(32768));
+            assert_eq!(p.raster().pixel(1, 1), Matte16::new(0));
+        }
+    }
+
+    #[test]
+    fn coverage_bits_ink() {
+        let tri = Path2D::default()
+            .absolute()
+            .move_to(0.3, 0.0)
+            .line_to(7.0, 2.7)
+            .line_to(1.1, 8.0)
+            .close()
+            .finish();
+        let clr = Rgba8p::new(40, 80, 20, 255);
+        let dst = Rgba8p::new(100, 150, 200, 255);
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            for bits in [4, 8, 16] {
+                let mut p = Plotter::new(Raster::with_color(8, 8, dst));
+                p.set_coverage_bits(bits).fill(rule, &tri, clr);
+                let mut q = Plotter::new(Raster::with_color(8, 8, dst));
+                q.set_coverage_bits(bits);
+                q.fill_with(rule, &tri, &mut ink::ColorInk::new(clr));
+                assert_eq!(p.raster().pixels(), q.raster().pixels(), "{bits}");
+                let matte = q.fill_matte(rule, &tri);
+                let mut m = Plotter::new(Raster::<Matte8>::with_clear(8, 8));
+                m.set_coverage_bits(bits).fill(rule, &tri, Matte8::new(255));
+                assert_eq!(matte.pixels(), m.raster().pixels(), "{bits}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn coverage_bits_invalid() {
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(2, 2));
+        p.set_coverage_bits(12);
+    }
+
+    #[test]
+    fn rgba_fast() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(1.5, 0.0)
+            .line_to(148.0, 8.25)
+            .line_to(2.0, 19.5)
+            .close()
+            .move_to(8.0, 2.0)
+            .line_to(8.0, 16.0)
+            .line_to(130.0, 9.0)
+            .close()
+            .finish();
+        let bg = Rgba8p::new(20, 40, 60, 200);
+        let full = Raster::with_color(150, 20, Matte8::new(255));
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            for clr in [Rgba8p::new(0, 64, 128, 128), Rgba8p::new(9, 9, 9, 9)] {
+                let mut fast = Plotter::new(Raster::with_color(150, 20, bg));
+                fast.fill(rule, &path, clr);
+                // Full clip forces the per-pixel path
+                let mut slow = Plotter::new(Raster::with_color(150, 20, bg));
+                slow.set_clip_matte(full.clone()).fill(rule, &path, clr);
+                assert_eq!(fast.raster().pixels(), slow.raster().pixels());
+            }
+        }
+    }
+
+    #[test]
+    fn fill_region() {
+        let glyph = Path2D::default()
+            .absolute()
+            .move_to(1.0, -2.0)
+            .cubic_to(9.0, 0.0, 8.0, 10.0, 1.0, 10.0)
+            .line_to(4.0, 5.0)
+            .line_to(-3.0, 4.5)
+            .close()
+            .finish();
+        let clr = Rgba8p::new(0, 100, 200, 255);
+        let bg = Rgba8p::new(10, 20, 30, 40);
+        let mut g = Plotter::new(Raster::with_color(8, 9, bg));
+        g.fill(FillRule::NonZero, &glyph, clr);
+        let mut atlas = Plotter::new(Raster::with_color(96, 32, bg));
+        atlas.take_dirty();
+        for (x, y) in [(0, 0), (40, 10), (88, 23)] {
+            atlas.fill_region((x, y, 8, 9), FillRule::NonZero, &glyph, clr);
+            let mut cell = Raster::with_clear(8, 9);
+            cell.copy_raster((), atlas.raster(), (x, y, 8, 9));
+            assert_eq!(cell.pixels(), g.raster().pixels());
+        }
+        // Nothing drawn outside of the cells
+        let mut cells = Raster::with_color(96, 32, bg);
+        for (x, y) in [(0, 0), (40, 10), (88, 23)] {
+            cells.copy_raster((x, y, 8, 9), g.raster(), ());
+        }
+        assert_eq!(atlas.raster().pixels(), cells.pixels());
+        let dirty = atlas.take_dirty().unwrap();
+        assert!(dirty.left() >= 0 && dirty.right() <= 96);
+        assert!(dirty.top() >= 0 && dirty.bottom() <= 32);
+    }
+
+    #[test]
+    fn pixel_origin() {
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(2.0, 0.0)
+            .line_to(2.0, 2.0)
+            .line_to(0.0, 2.0)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(4, 4));
+        p.fill(FillRule::NonZero, &rect, Matte8::new(255));
+        #[rustfmt::skip]
+        let corner = [
+            255, 255, 0, 0,
+            255, 255, 0, 0,
+            0, 0, 0, 0,
+            0, 0, 0, 0,
+        ];
+        assert_eq!(p.raster().as_u8_slice(), corner);
+        p.clear().set_pixel_origin(PixelOrigin::Center);
+        p.fill(FillRule::NonZero, &rect, Matte8::new(255));
+        // Rectangle covers (0.5, 0.5) to (2.5, 2.5)
+        #[rustfmt::skip]
+        let center = [
+            64, 128, 64, 0,
+            128, 255, 128, 0,
+            64, 128, 64, 0,
+            0, 0, 0, 0,
+        ];
+        assert_eq!(p.raster().as_u8_slice(), center);
+        // Offset is applied after the transform
+        p.clear().set_transform(Transform::with_scale(2.0, 2.0));
+        p.fill(FillRule::NonZero, &rect, Matte8::new(255));
+        #[rustfmt::skip]
+        let scaled = [
+            64, 128, 128, 128,
+            128, 255, 255, 255,
+            128, 255, 255, 255,
+            128, 255, 255, 255,
+        ];
+        assert_eq!(p.raster().as_u8_slice(), scaled);
+        // Strokes are offset the same as fills
+        let line = Path2D::default()
+            .absolute()
+            .pen_width(1.0)
+            .move_to(1.0, 0.0)
+            .line_to(1.0, 1.0)
+            .finish();
+        p.clear().set_transform(Transform::with_scale(1.0, 2.0));
+        p.stroke(&line, Matte8::new(255));
+        #[rustfmt::skip]
+        let stroked = [
+            0, 128, 0, 0,
+            0, 255, 0, 0,
+            0, 128, 0, 0,
+            0, 0, 0, 0,
+        ];
+        assert_eq!(p.raster().as_u8_slice(), stroked);
+    }
+
+    #[test]
+    fn save_state() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(4.0, 4.0)
+            .line_to(20.0, 8.0)
+            .line_to(8.0, 20.0)
+            .finish();
+        // Configure plotter for a nesting level
+        let configure = |p: &mut Plotter<Matte8>, level: usize| {
+            let t = [
+                Transform::with_translate(2.0, 3.0),
+                Transform::with_scale(1.5, 1.25),
+                Transform::with_rotate(0.3).translate(6.0, 0.0),
+            ];
+            let w = [2.0, 5.0, 3.5];
+            let j = [JoinStyle::Bevel, JoinStyle::Round, JoinStyle::Miter(2.0)];
+            p.set_transform(t[level])
+                .set_viewport([-1.0, 2.5, 0.0][level], [0.5, -1.0, 1.5][level])
+                .set_join(j[level])
+                .set_tolerance(0.1 * (level + 1) as f32)
+                .set_opacity(1.0 - 0.2 * level as f32);
+            p.fill(
+                FillRule::NonZero,
+                [PathOp::PenWidth(w[level])],
+                Matte8::new(0),
+            );
+            if level == 2 {
+                p.set_clip(FillRule::NonZero, &path);
+            }
+        };
+        let draw = |p: &mut Plotter<Matte8>| {
+            p.clear()
+                .stroke(&path, Matte8::new(255))
+                .as_u8_slice()
+                .to_vec()
+        };
+        let expected: Vec<_> = (0..4)
+            .map(|n| {
+                let mut p = Plotter::new(Raster::with_clear(32, 32));
+                (0..n).for_each(|level| configure(&mut p, level));
+                draw(&mut p)
+            })
+            .collect();
+        let mut p = Plotter::new(Raster::with_clear(32, 32));
+        for level in 0..3 {
+            p.save_state();
+            configure(&mut p, level);
+        }
+        assert_eq!(draw(&mut p), expected[3]);
+        for n in (0..3).rev() {
+            p.restore_state();
+            assert_eq!(draw(&mut p), expected[n]);
+        }
+        // Unbalanced restore has no effect
+        p.restore_state();
+        assert_eq!(draw(&mut p), expected[0]);
+        // Drawing differs at each level
+        for n in 1..4 {
+            assert_ne!(expected[n - 1], expected[n]);
+        }
+        p.set_viewport(3.0, 4.0)
+            .save_state()
+            .set_viewport(-7.0, 8.0);
+        p.restore_state();
+        assert_eq!(p.viewport, Pt::new(3.0, 4.0));
+    }
+
+    #[test]
+    fn builder() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(3.0)
+            .move_to(2.0, 2.0)
+            .quad_to(30.0, 4.0, 10.0, 12.0)
+            .line_to(4.0, 9.0)
+            .finish();
+        let t = Transform::with_scale(1.5, 2.0);
+        let mut a = PlotterBuilder::new()
+            .tolerance(0.0)
+            .join(JoinStyle::Miter(-1.0))
+            .cap(CapStyle::Square)
+            .transform(t)
+            .antialias(false)
+            .build(Raster::<Matte8>::with_clear(32, 32));
+        let mut b = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        b.set_tolerance(0.0)
+            .set_join(JoinStyle::Miter(-1.0))
+            .set_cap(CapStyle::Square)
+            .set_transform(t)
+            .set_antialias(false);
+        assert_eq!(a.tol_sq, 0.01 * 0.01);
+        assert_eq!(a.join_style, JoinStyle::Miter(0.0));
+        a.stroke(&path, Matte8::new(255));
+        b.stroke(&path, Matte8::new(255));
+        assert_eq!(a.raster().pixels(), b.raster().pixels());
+        // Unset options are the same as Plotter::new
+        let mut a = PlotterBuilder::new().build(Raster::with_clear(32, 32));
+        let mut b = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        a.stroke(&path, Matte8::new(255));
+        b.stroke(&path, Matte8::new(255));
+        assert_eq!(a.raster().pixels(), b.raster().pixels());
+    }
+
+    #[test]
+    fn tolerance_auto() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(20.0, 20.0)
+            .cubic_to(300.0, 0.0, 300.0, 300.0, 20.0, 280.0)
+            .quad_to(-100.0, 150.0, 20.0, 20.0)
+            .finish();
+        let clr = Matte8::new(255);
+        let mut a = Plotter::new(Raster::with_clear(32, 32));
+        a.set_tolerance_auto(3.0);
+        assert_eq!(a.tol_sq, 3.0 * 3.0);
+        a.set_transform(Transform::with_scale(0.05, 0.1));
+        assert!((a.tol_sq - 0.3 * 0.3).abs() < 1e-6);
+        a.fill(FillRule::NonZero, &path, clr);
+        let mut b = Plotter::new(Raster::with_clear(32, 32));
+        b.set_transform(Transform::with_scale(0.05, 0.1));
+        b.set_tolerance(0.3);
+        b.fill(FillRule::NonZero, &path, clr);
+        assert_eq!(a.raster().pixels(), b.raster().pixels());
+        // Clamped like set_tolerance
+        a.set_transform(Transform::with_scale(0.001, 0.001));
+        assert_eq!(a.tol_sq, 0.01 * 0.01);
+        // Fixed tolerance is not changed by transform
+        a.set_tolerance(0.5);
+        a.set_transform(Transform::with_scale(4.0, 4.0));
+        assert_eq!(a.tol_sq, 0.5 * 0.5);
+    }
+
+    #[test]
+    fn scissor() {
+        let circle = Path2D::default()
+            .absolute()
+            .pen_width(3.0)
+            .move_to(32.0, 2.0)
+            .cubic_to(50.0, 2.0, 62.0, 14.0, 62.0, 32.0)
+            .cubic_to(62.0, 50.0, 50.0, 62.0, 32.0, 62.0)
+            .cubic_to(14.0, 62.0, 2.0, 50.0, 2.0, 32.0)
+            .cubic_to(2.0, 14.0, 14.0, 2.0, 32.0, 2.0)
+            .close()
+            .finish();
+        let clr = Rgba8p::new(64, 128, 0, 255);
+        let edge = Rgba8p::new(0, 0, 255, 255);
+        let mut p = Plotter::new(Raster::with_clear(64, 64));
+        p.fill(FillRule::NonZero, &circle, clr);
+        p.stroke(&circle, edge);
+        let full = p.raster().clone();
+        let rects = [(5, 7, 11, 9), (40, 50, 30, 30), (-4, 20, 64, 3)];
+        for (x, y, w, h) in rects {
+            p.clear().take_dirty();
+            p.set_scissor(Some((x, y, w, h)));
+            p.fill(FillRule::NonZero, &circle, clr);
+            p.stroke(&circle, edge);
+            let reg = p.raster().intersection((x, y, w, h));
+            let dirty = p.take_dirty().unwrap();
+            assert_eq!(reg.intersection(dirty), dirty);
+            for row in 0..64 {
+                for col in 0..64 {
+                    let inside = col >= reg.left()
+                        && col < reg.right()
+                        && row >= reg.top()
+                        && row < reg.bottom();
+                    let expected = if inside {
+                        full.pixel(col, row)
+                    } else {
+                        Rgba8p::default()
+                    };
+                    assert_eq!(p.raster().pixel(col, row), expected);
+                }
+            }
+        }
+        // Nothing is drawn outside of the raster
+        p.clear().set_scissor(Some((64, 0, 8, 8)));
+        p.fill(FillRule::NonZero, &circle, clr);
+        assert_eq!(p.raster().pixels(), Raster::with_clear(64, 64).pixels());
+        p.set_scissor(None);
+        p.fill(FillRule::NonZero, &circle, clr);
+        p.stroke(&circle, edge);
+        assert_eq!(p.raster().pixels(), full.pixels());
+    }
+
+    #[test]
+    fn metrics() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(2.0, 2.0)
+            .line_to(14.0, 4.0)
+            .line_to(6.0, 12.0)
+            .move_to(20.0, 20.0)
+            .line_to(28.0, 20.0)
+            .line_to(24.0, 30.0)
+            .finish();
+        let clr = Matte8::new(255);
+        let mut a = Plotter::new(Raster::with_clear(32, 32));
+        a.fill(FillRule::NonZero, &path, clr);
+        assert_eq!(a.last_metrics(), Metrics::default());
+        let mut b = Plotter::new(Raster::with_clear(32, 32));
+        b.set_metrics(true).fill(FillRule::NonZero, &path, clr);
+        assert_eq!(a.raster().pixels(), b.raster().pixels());
+        let m = b.last_metrics();
+        assert_eq!((m.points, m.sub_figs), (6, 2));
+        assert_eq!((m.edges_added, m.max_active_edges), (5, 2));
+        // Rows 2 through 12 and 20 through 30, skipping empty rows between
+        assert_eq!(m.rows_scanned, 22);
+        assert!(m.pixels_composited > 0);
+        b.set_metrics(false);
+        assert_eq!(b.last_metrics(), Metrics::default());
+    }
+
+    #[test]
+    #[ignore = "slow: fills over 16 million points"]
+    fn fill_truncated() {
+        let clr = Matte8::new(255);
+        let tri = Path2D::default()
+            .move_to(1.0, 1.0)
+            .line_to(7.0, 1.0)
+            .line_to(4.0, 7.0)
+            .finish();
+        // Zig-zag with 100 points past the limit
+        let zig =
+            |i: i32| PathOp::Line(Pt::new((i % 2) as f32, i as f32 * 1e-7));
+        let many = || (0..(1 << 24) + 100).map(zig);
+        let mut p = Plotter::new(Raster::with_clear(8, 8));
+        p.set_metrics(true).fill(FillRule::NonZero, many(), clr);
+        assert!(p.last_fill_truncated());
+        assert_eq!(p.last_dropped_points(), 100);
+        assert_eq!(p.last_metrics().points_dropped, 100);
+        p.fill(FillRule::NonZero, &tri, clr);
+        assert!(!p.last_fill_truncated());
+        assert_eq!(p.last_metrics().points_dropped, 0);
+        assert_eq!(
+            p.try_fill(FillRule::NonZero, many(), clr).err(),
+            Some(Error::TooManyPoints)
+        );
+        assert_eq!(p.last_dropped_points(), 100);
+        p.stroke(&tri, clr);
+        assert!(!p.last_fill_truncated());
+    }
+
+    #[test]
+    fn scratch_reuse() {
+        let tri = Path2D::default()
+            .absolute()
+            .move_to(4.0, 2.0)
+            .line_to(14.0, 30.0)
+            .quad_to(20.0, 4.0, 28.0, 3.0)
+            .close()
+            .finish();
+        let line = Path2D::default()
+            .absolute()
+            .pen_width(2.5)
+            .move_to(2.0, 20.0)
+            .line_to(16.0, 8.0)
+            .line_to(30.0, 28.0)
+            .finish();
+        let clr = Rgba8p::new(40, 20, 0, 64);
+        let mut a = Plotter::new(Raster::with_clear(32, 32));
+        a.fill(FillRule::NonZero, &line, clr).clear();
+        a.stroke(&tri, clr).clear();
+        a.fill(FillRule::NonZero, &tri, clr);
+        a.stroke(&line, clr);
+        let mut b = Plotter::new(Raster::with_clear(32, 32));
+        b.fill(FillRule::NonZero, &tri, clr);
+        b.stroke(&line, clr);
+        assert_eq!(a.raster().pixels(), b.raster().pixels());
+    }
+
+    #[test]
+    fn fill_many_points() {
+        // Circle polygon with n points
+        let circle = |n: usize| {
+            (0..n).map(move |i| {
+                let a = i as f32 / n as f32 * std::f32::consts::TAU;
+                PathOp::Line(Pt::new(
+                    32.0 + 28.0 * a.cos(),
+                    32.0 + 28.0 * a.sin(),
+                ))
+            })
+        };
+        let area = |r: &Raster<Matte8>| {
+            r.pixels()
+                .iter()
+                .map(|p| u32::from(u8::from(p.alpha())))
+                .sum()
+        };
+        let mut a = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+        a.fill(FillRule::NonZero, circle(1000), Matte8::new(255));
+        let mut b = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+        b.fill(FillRule::NonZero, circle(100_000), Matte8::new(255));
+        let (area_a, area_b): (u32, u32) = (area(a.raster()), area(b.raster()));
+        assert!(area_a.abs_diff(area_b) < area_a / 500);
+        // Complete all the way around
+        for i in 0..32 {
+            let a = i as f32 / 32.0 * std::f32::consts::TAU;
+            let x = (32.0 + 24.0 * a.cos()) as i32;
+            let y = (32.0 + 24.0 * a.sin()) as i32;
+            assert_eq!(b.raster().pixel(x, y), Matte8::new(255));
+        }
+    }
+
+    #[test]
+    fn fill_many_edges() {
+        // Wavy strips, far enough apart to never share a pixel
+        let strip = |i: usize| {
+            let x = i as f32 * 4.0;
+            let wave = |y: f32| (y / 5.0).sin() * 2.0;
+            let down = (0..=16).map(move |j| {
+                let y = j as f32 * 3.7;
+                PathOp::Line(Pt::new(x + wave(y), y))
+            });
+            let up = (0..=16).rev().map(move |j| {
+                let y = j as f32 * 3.7 + 2.0;
+                PathOp::Line(Pt::new(x + 1.3 + wave(y), y))
+            });
+            down.chain(up).chain([PathOp::Close()])
+        };
+        let clr = Matte8::new(255);
+        let mut a = Plotter::new(Raster::<Matte8>::with_clear(160, 64));
+        a.fill(FillRule::NonZero, (0..40).flat_map(strip), clr);
+        let mut b = Plotter::new(Raster::<Matte8>::with_clear(160, 64));
+        for i in 0..40 {
+            b.fill(FillRule::NonZero, strip(i), clr);
+        }
+        assert_eq!(a.raster().pixels(), b.raster().pixels());
+    }
+
+    #[test]
+    fn fill_sparse_rows() {
+        // Tiny squares, spread far apart vertically
+        let square = |i: usize| {
+            let (x, y) = ((i % 5) as f32 * 6.5 + 0.5, i as f32 * 37.3 + 1.25);
+            [
+                PathOp::Move(Pt::new(x, y)),
+                PathOp::Line(Pt::new(x + 3.5, y)),
+                PathOp::Line(Pt::new(x + 3.5, y + 2.75)),
+                PathOp::Line(Pt::new(x, y + 2.75)),
+                PathOp::Close(),
+            ]
+        };
+        let clr = Matte8::new(255);
+        let mut a = Plotter::new(Raster::<Matte8>::with_clear(40, 1024));
+        for i in 0..27 {
+            a.fill(FillRule::NonZero, square(i), clr);
+        }
+        let squares = || (0..27).flat_map(square);
+        let mut b = Plotter::new(Raster::<Matte8>::with_clear(40, 1024));
+        b.fill(FillRule::NonZero, squares(), clr);
+        assert_eq!(a.raster().pixels(), b.raster().pixels());
+        // Clip rows stay aligned
+        let clip = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(40.0, 1024.0)
+            .line_to(0.0, 1024.0)
+            .close()
+            .finish();
+        let mut c = Plotter::new(Raster::<Matte8>::with_clear(40, 1024));
+        c.set_clip(FillRule::NonZero, &clip);
+        for i in 0..27 {
+            c.fill(FillRule::NonZero, square(i), clr);
+        }
+        let mut e = Plotter::new(Raster::<Matte8>::with_clear(40, 1024));
+        e.set_clip(FillRule::NonZero, &clip);
+        e.fill(FillRule::NonZero, squares(), clr);
+        assert_eq!(c.raster().pixels(), e.raster().pixels());
+        // Progress is still reported every 16 rows
+        let mut rows = vec![];
+        let mut ink = ink::ColorInk::new(clr);
+        let mut d = Plotter::new(Raster::<Matte8>::with_clear(40, 1024));
+        d.fill_with_progress(FillRule::NonZero, squares(), &mut ink, |row| {
+            rows.push(row);
+            true
+        });
+        assert_eq!(rows, (1..61).map(|i| i * 16).collect::<Vec<_>>());
+        let mut f = Plotter::new(Raster::<Matte8>::with_clear(40, 1024));
+        f.fill_with(FillRule::NonZero, squares(), &mut ink);
+        assert_eq!(f.raster().pixels(), d.raster().pixels());
+    }
+
+    #[test]
+    fn fig_curves() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(2.0, 16.0)
+            .quad_to(16.0, -8.0, 30.0, 16.0)
+            .cubic_to(30.0, 36.0, 2.0, 36.0, 2.0, 16.0)
+            .close()
+            .finish();
+        let clr = Matte8::new(255);
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        p.set_tolerance(0.25).fill(FillRule::NonZero, &path, clr);
+        let mut fig = Fig::default();
+        fig.add_quad((2.0, 16.0), (16.0, -8.0), (30.0, 16.0), 0.25);
+        fig.add_cubic(
+            (30.0, 16.0),
+            (30.0, 36.0),
+            (2.0, 36.0),
+            (2.0, 16.0),
+            0.25,
+        );
+        fig.close();
+        let mut r = Raster::<Matte8>::with_clear(32, 32);
+        let mut sgn_area = vec![0i16; 32];
+        fig.fill(FillRule::NonZero, &mut r, Paint::new(clr), &mut sgn_area);
+        assert_eq!(p.raster().pixels(), r.pixels());
+        assert!(sgn_area.iter().all(|s| *s == 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "signed area too short")]
+    fn fig_sgn_area_short() {
+        let mut fig = Fig::new();
+        fig.add_point((1.0, 1.0));
+        fig.add_point((3.0, 1.0));
+        fig.add_point((2.0, 3.0));
+        fig.close();
+        let mut r = Raster::<Matte8>::with_clear(8, 8);
+        let paint = Paint::new(Matte8::new(255));
+        fig.fill(FillRule::NonZero, &mut r, paint, &mut [0i16; 4]);
+    }
+
+    #[test]
+    fn fill_wide_raster() {
+        let (width, height) = (40_000, 64);
+        let triangle = Path2D::default()
+            .absolute()
+            .move_to(0.0, 64.0)
+            .line_to(40_000.0, 0.0)
+            .line_to(40_000.0, 64.0)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::with_clear(width, height));
+        let r = p.fill(FillRule::NonZero, &triangle, Matte8::new(255));
+        // Hypotenuse is at y = 32 in the middle, and y = 0.016 at x = 39990
+        assert_eq!(r.pixel(20_000, 31), Matte8::new(0));
+        assert_eq!(r.pixel(20_000, 32), Matte8::new(255));
+        assert_eq!(r.pixel(39_990, 1), Matte8::new(255));
+        assert_eq!(r.pixel(39_999, 63), Matte8::new(255));
+        assert!(u8::from(r.pixel(39_990, 0).one()) > 250);
+        // No seams at tile edges
+        assert_eq!(r.pixel(8191, 60), Matte8::new(255));
+        assert_eq!(r.pixel(8192, 60), Matte8::new(255));
+        let row: Vec<u8> = (8180..8200)
+            .map(|x| u8::from(r.pixel(x, 50).one()))
+            .collect();
+        assert!(row.windows(2).all(|w| w[0] <= w[1]), "{row:?}");
+    }
+
+    #[test]
+    fn fill_wide_raster_entry_points() {
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(10.0, 1.0)
+            .line_to(19_990.0, 1.0)
+            .line_to(19_990.0, 3.0)
+            .line_to(10.0, 3.0)
+            .close()
+            .finish();
+        let plotter = || Plotter::new(Raster::with_clear(20_000, 4));
+        let clr = Matte8::new(255);
+        let rule = FillRule::NonZero;
+        let mut p = plotter();
+        let expected = p.fill(rule, &rect, clr).clone();
+        assert_eq!(expected.pixel(15_000, 2), clr);
+        // Fill and stroke; the stroke replaces matte coverage
+        let drawn = |r: &Raster<Matte8>, x| {
+            (0..4).all(|y| u8::from(r.pixel(x, y).one()) > 0)
+        };
+        let mut p = plotter();
+        let r = p.fill_stroke(rule, &rect, clr, clr);
+        assert!(drawn(&r, 15_000) && !drawn(&r, 19_995));
+        let mut p = plotter();
+        let r = p.draw(rule, &rect, Some(clr), Some(clr));
+        assert!(drawn(&r, 15_000) && !drawn(&r, 19_995));
+        let mut p = plotter();
+        let r = p.draw(rule, &rect, Some(clr), None);
+        assert_eq!(r.pixels(), expected.pixels());
+        // Other fills with the same coverage
+        let mut p = plotter();
+        assert_eq!(
+            p.try_fill(rule, &rect, clr).unwrap().pixels(),
+            expected.pixels()
+        );
+        let mut p = plotter();
+        let mut ink = ink::ColorInk::new(clr);
+        let r = p.fill_with_progress(rule, &rect, &mut ink, |_y| true);
+        assert_eq!(r.pixels(), expected.pixels());
+        let mut p = plotter();
+        let r = p.fill_with(rule, &rect, &mut ink::ColorInk::new(clr));
+        assert_eq!(r.pixels(), expected.pixels());
+        let mut p = plotter();
+        let batch = [(&rect[..], Transform::default())];
+        assert_eq!(p.fill_batch(rule, batch, clr).pixels(), expected.pixels());
+        let mut p = plotter();
+        assert_eq!(
+            p.fill_ssaa(rule, &rect, clr, 2).pixels(),
+            expected.pixels()
+        );
+        let mut p = plotter();
+        let reg = Region::new(0, 0, 20_000, 4);
+        let r = p.fill_region(reg, rule, &rect, clr);
+        assert_eq!(r.pixels(), expected.pixels());
+        let mut p = plotter();
+        assert_eq!(p.fill_matte(rule, &rect).pixels(), expected.pixels());
+        let all = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(20_000.0, 0.0)
+            .line_to(20_000.0, 4.0)
+            .line_to(0.0, 4.0)
+            .close()
+            .finish();
+        let mut p = plotter();
+        p.set_clip(rule, &rect).fill(rule, &all, clr);
+        assert_eq!(p.raster().pixels(), expected.pixels());
+        let mut p = plotter();
+        let mut spans = vec![];
+        p.fill_spans(rule, &rect, |y, x, len, cov| {
+            spans.push((y, x, len, cov))
+        });
+        assert_eq!(spans, [(1, 10, 19_980, 255), (2, 10, 19_980, 255)]);
+        let mut p = plotter();
+        let rgb = p.fill_subpixel(rule, &rect, [0, 0, 1, 0, 0]);
+        assert_eq!(rgb.pixel(15_000, 2), Rgb8::new(255, 255, 255));
+        assert_eq!(rgb.pixel(15_000, 0), Rgb8::new(0, 0, 0));
+    }
+
+    #[test]
+    fn fill_huge_coords() {
+        let fill = |pts: &[(f32, f32)]| {
+            let mut path = Path2D::default().absolute();
+            for (i, (x, y)) in pts.iter().enumerate() {
+                path = match i {
+                    0 => path.move_to(*x, *y),
+                    _ => path.line_to(*x, *y),
+                };
+            }
+            let path = path.close().finish();
+            let mut p = Plotter::new(Raster::with_clear(32, 16));
+            p.fill(FillRule::NonZero, &path, Matte8::new(255));
+            p.into_raster()
+        };
+        // Vertex far off the raster, and the same figure clipped to it
+        let cases =
+            [((14.0, 2.0), 1e6, 2.0, 14.0), ((2.0, 14.0), 5e5, 2.0, 2.0)];
+        for (p1, y2, x0, x1) in cases {
+            let huge = fill(&[(2.0, 2.0), p1, (1e6, y2)]);
+            // X where edges from (x0, 2) and p1 cross y = 16
+            let x_at = |x: f64, y: f64| {
+                let slope = (y2 as f64 - y) / (1e6 - x);
+                (x + (16.0 - y) / slope) as f32
+            };
+            let clipped = fill(&[
+                (2.0, 2.0),
+                p1,
+                (x_at(x1, p1.1 as f64), 16.0),
+                (x_at(x0, 2.0), 16.0),
+            ]);
+            let (huge, clipped) = (huge.as_u8_slice(), clipped.as_u8_slice());
+            for (i, (h, c)) in huge.iter().zip(clipped).enumerate() {
+                assert!(h.abs_diff(*c) <= 1, "{i}: {h} != {c}");
+            }
+            assert_eq!(clipped[10 * 32 + 14], 255);
+        }
+    }
+
+    #[test]
+    fn fill_open_subpaths() {
+        let triangles = |close: bool| {
+            let mut path = Path2D::default().absolute();
+            for x in [2.0, 18.0] {
+                path = path
+                    .move_to(x, 2.0)
+                    .line_to(x + 3.5, 14.0)
+                    .line_to(x + 12.0, 7.5)
+                    .line_to(x, 2.0);
+                if close {
+                    path = path.close();
+                }
+            }
+            path.finish()
+        };
+        let fill = |path: &[PathOp]| {
+            let mut p = Plotter::new(Raster::with_clear(32, 16));
+            p.fill(FillRule::NonZero, path, Matte8::new(255));
+            p.into_raster()
+        };
+        let closed = fill(&triangles(true));
+        let open = fill(&triangles(false));
+        assert_eq!(open.as_u8_slice(), closed.as_u8_slice());
+        assert_eq!(open.pixel(6, 7), Matte8::new(255));
+        assert_eq!(open.pixel(22, 7), Matte8::new(255));
+    }
+
+    #[test]
+    fn fill_prepared() {
+        let fish = Path2D::default()
+            .relative()
+            .move_to(56.0, 8.0)
+            .line_to(-24.0, 16.0)
+            .cubic_to(-32.0, -24.0, -32.0, 40.0, 0.0, 16.0)
+            .line_to(24.0, 16.0)
+            .line_to(-16.0, -24.0)
+            .close()
+            .finish();
+        let fill = |t: Transform<f32>, clr: Matte8| {
+            let mut p = Plotter::new(Raster::with_clear(64, 64));
+            p.set_transform(t);
+            p.fill(FillRule::EvenOdd, &fish, clr);
+            p.into_raster()
+        };
+        let mut p = Plotter::new(Raster::with_clear(64, 64));
+        let prepared = p.prepare(&fish);
+        for clr in [Matte8::new(255), Matte8::new(100)] {
+            p.clear();
+            p.fill_prepared(FillRule::EvenOdd, &prepared, clr);
+            let r = fill(Transform::default(), clr);
+            assert_eq!(p.raster().as_u8_slice(), r.as_u8_slice());
+        }
+        // Plotted again after the transform changes
+        let t = Transform::with_scale(0.5, 0.75);
+        p.clear().set_transform(t);
+        p.fill_prepared(FillRule::EvenOdd, &prepared, Matte8::new(255));
+        let r = fill(t, Matte8::new(255));
+        assert_eq!(p.raster().as_u8_slice(), r.as_u8_slice());
+    }
+
+    #[test]
+    fn fill_prepared_offset() {
+        // Fish, with a bubble off the left of the raster
+        let fish = Path2D::default()
+            .relative()
+            .move_to(40.0, 8.0)
+            .line_to(-24.0, 16.0)
+            .cubic_to(-32.0, -24.0, -32.0, 40.0, 0.0, 16.0)
+            .line_to(24.0, 16.0)
+            .line_to(-16.0, -24.0)
+            .close()
+            .absolute()
+            .move_to(-14.0, 56.0)
+            .quad_to(-6.0, 52.0, -2.0, 60.0)
+            .quad_to(-8.0, 66.0, -14.0, 56.0)
+            .finish();
+        let mut p = Plotter::new(Raster::with_clear(64, 64));
+        let prepared = p.prepare(&fish);
+        // Offsets are exact in fixed point, so both fills have equal points
+        let offsets = [
+            (0.0, 0.0),
+            (0.25, 0.5),
+            (3.75, -1.3125),
+            (-0.125, 10.4375),
+            (17.625, 2.1875),
+            (-40.5, -0.75),
+            (9000.0, 0.0),
+        ];
+        for (dx, dy) in offsets {
+            p.clear();
+            p.fill_prepared_offset(
+                FillRule::NonZero,
+                &prepared,
+                dx,
+                dy,
+                Matte8::new(255),
+            );
+            let mut q = Plotter::new(Raster::with_clear(64, 64));
+            q.set_viewport(-dx, -dy);
+            let again = q.prepare(&fish);
+            q.fill_prepared(FillRule::NonZero, &again, Matte8::new(255));
+            let (a, b) = (p.raster().as_u8_slice(), q.raster().as_u8_slice());
+            assert_eq!(a, b, "{dx},{dy}");
+        }
+        // Bubble is moved onto the raster
+        p.clear();
+        p.fill_prepared_offset(
+            FillRule::NonZero,
+            &prepared,
+            20.0,
+            0.0,
+            Matte8::new(255),
+        );
+        assert_eq!(p.raster().pixel(12, 58), Matte8::new(255));
+    }
+
+    #[test]
+    fn fill_deep_winding() {
+        // 200 concentric squares, all wound the same direction
+        let squares = || {
+            (0..200).flat_map(|i| {
+                let (a, b) = (2.0 + i as f32 * 0.05, 30.0 - i as f32 * 0.05);
+                [
+                    PathOp::Move(Pt::new(a, a)),
+                    PathOp::Line(Pt::new(b, a)),
+                    PathOp::Line(Pt::new(b, b)),
+                    PathOp::Line(Pt::new(a, b)),
+                    PathOp::Close(),
+                ]
+            })
+        };
+        let clr = Matte8::new(255);
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        p.fill(FillRule::NonZero, squares(), clr);
+        let m = p.fill_matte(FillRule::NonZero, squares());
+        let mut q = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        q.fill_with(FillRule::NonZero, squares(), &mut ink::ColorInk::new(clr));
+        for y in 12..20 {
+            for x in 12..20 {
+                assert_eq!(p.raster().pixel(x, y), clr);
+                assert_eq!(m.pixel(x, y), clr);
+                assert_eq!(q.raster().pixel(x, y), clr);
+            }
+        }
+        // Even-odd: 200 windings is even, so the center is empty
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        p.fill(FillRule::EvenOdd, squares(), clr);
+        assert_eq!(p.raster().pixel(16, 16), Matte8::new(0));
+    }
+
+    #[test]
+    fn try_fill_invalid() {
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(16, 16));
+        let square = Path2D::default()
+            .absolute()
+            .move_to(2.0, 2.0)
+            .line_to(12.0, 2.0)
+            .line_to(12.0, 12.0)
+            .line_to(2.0, 12.0)
+            .close()
+            .finish();
+        let clr = Matte8::new(255);
+        assert!(p.try_fill(FillRule::NonZero, &square, clr).is_ok());
+        assert!(p.try_stroke(&square, clr).is_ok());
+        let nan = [
+            PathOp::Move(Pt::new(0.0, 0.0)),
+            PathOp::Line(Pt::new(f32::NAN, 4.0)),
+        ];
+        assert_eq!(
+            p.clear().try_fill(FillRule::NonZero, nan, clr).err(),
+            Some(Error::NonFinite)
+        );
+        assert_eq!(p.try_stroke(nan, clr).err(), Some(Error::NonFinite));
+        let far = [
+            PathOp::Move(Pt::new(0.0, 0.0)),
+            PathOp::Line(Pt::new(4.0, 1e8)),
+        ];
+        assert_eq!(
+            p.try_fill(FillRule::NonZero, far, clr).err(),
+            Some(Error::OutOfRange)
+        );
+        let wide = [
+            PathOp::PenWidth(f32::INFINITY),
+            PathOp::Line(Pt::new(4.0, 4.0)),
+        ];
+        assert_eq!(p.try_stroke(wide, clr).err(), Some(Error::NonFinite));
+        assert!(p.raster().pixels().iter().all(|v| *v == Matte8::new(0)));
+        p.set_transform(Transform::with_scale(1e8, 1e8));
+        assert_eq!(
+            p.try_fill(FillRule::NonZero, &square, clr).err(),
+            Some(Error::OutOfRange)
+        );
+        p.set_transform(Transform::with_scale(1e-7, 1e-7));
+        assert!(p.try_fill(FillRule::NonZero, far, clr).is_ok());
+        let many = (0..(1 << 24) + 16)
+            .map(|i| PathOp::Line(Pt::new((i % 2) as f32, i as f32 * 1e-7)));
+        p.set_transform(Transform::default());
+        assert_eq!(
+            p.try_fill(FillRule::NonZero, many, clr).err(),
+            Some(Error::TooManyPoints)
+        );
+        let mut e = Plotter::new(Raster::<Matte8>::with_clear(0, 4));
+        assert_eq!(
+            e.try_fill(FillRule::NonZero, &square, clr).err(),
+            Some(Error::EmptyRaster)
+        );
+    }
+
+    #[cfg(feature = "wide-coords")]
+    #[test]
+    fn try_fill_wide_coords() {
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(16, 16));
+        let far = Path2D::default()
+            .absolute()
+            .move_to(2.0, 2.0)
+            .line_to(100_000.0, 2.0)
+            .line_to(100_000.0, 12.0)
+            .line_to(2.0, 12.0)
+            .close()
+            .finish();
+        let clr = Matte8::new(255);
+        assert!(p.try_fill(FillRule::NonZero, &far, clr).is_ok());
+        assert!(p.try_stroke(&far, clr).is_ok());
+        assert_eq!(p.raster().pixel(8, 8), clr);
+        let far = [
+            PathOp::Move(Pt::new(0.0, 0.0)),
+            PathOp::Line(Pt::new(4.0, 1e8)),
+        ];
+        assert_eq!(
+            p.try_fill(FillRule::NonZero, far, clr).err(),
+            Some(Error::OutOfRange)
+        );
+    }
+
+    #[test]
+    fn try_fuzz() {
+        // Simple xorshift generator, so results are repeatable
+        let mut seed = 0x2545_f491_u32;
+        let mut rand = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            seed
+        };
+        let vals = [
+            0.0,
+            0.5,
+            -3.25,
+            7.0,
+            31.9,
+            1e-7,
+            -1e-7,
+            100.0,
+            16_000.0,
+            -16_384.0,
+            1e9,
+            f32::MAX,
+            f32::MIN_POSITIVE,
+            f32::NAN,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+        ];
+        let val = move |r: u32| {
+            let v = vals[(r % vals.len() as u32) as usize];
+            if r & 0x100 != 0 {
+                v + (r >> 16) as f32 / 2048.0
+            } else {
+                v
+            }
+        };
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        let clr = Matte8::new(255);
+        for i in 0..2000 {
+            let mut ops = vec![];
+            for _ in 0..rand() % 24 {
+                let kind = rand() % 6;
+                let mut pt = || Pt::new(val(rand()), val(rand()));
+                let op = match kind {
+                    0 => PathOp::Close(),
+                    1 => PathOp::Move(pt()),
+                    2 => PathOp::Line(pt()),
+                    3 => PathOp::Quad(pt(), pt()),
+                    4 => PathOp::Cubic(pt(), pt(), pt()),
+                    _ => PathOp::PenWidth(pt().x()),
+                };
+                ops.push(op);
+            }
+            let scale = [1.0, 0.5, 1e-6, 300.0, f32::NAN][i % 5];
+            p.set_transform(Transform::with_scale(scale, scale));
+            p.set_join([JoinStyle::Round, JoinStyle::Miter(4.0)][i % 2]);
+            p.set_cap([CapStyle::Round, CapStyle::Square][i % 3 % 2]);
+            let rule = [FillRule::NonZero, FillRule::EvenOdd][i % 2];
+            let _ = p.try_fill(rule, &ops, clr);
+            let _ = p.try_stroke(&ops, clr);
+        }
+    }
+
+    #[test]
+    fn curve_small_stack() {
+        let thread = std::thread::Builder::new()
+            .stack_size(128 * 1024)
+            .spawn(|| {
+                let path = Path2D::default()
+                    .absolute()
+                    .pen_width(2.0)
+                    .move_to(0.0, 0.0)
+                    .cubic_to(10_000.0, 0.0, -10_000.0, 10_000.0, 32.0, 32.0)
+                    .quad_to(10_000.0, -5_000.0, 0.0, 64.0)
+                    .close()
+                    .finish();
+                let mut p = Plotter::new(Raster::with_clear(64, 64));
+                p.set_tolerance(0.01).set_join(JoinStyle::Round);
+                p.fill(FillRule::NonZero, &path, Matte8::new(255));
+                p.stroke(&path, Matte8::new(255));
+                p.raster().pixels().contains(&Matte8::new(255))
+            })
+            .unwrap();
+        assert!(thread.join().unwrap());
+    }
+
+    impl PlotDest for Vec<Pt<f32>> {
+        fn add_point(&mut self, pt: WidePt) {
+            self.push(pt.0);
+        }
+        fn close(&mut self, _joined: bool) {}
+    }
+
+    impl PlotDest for Vec<WidePt> {
+        fn add_point(&mut self, pt: WidePt) {
+            self.push(pt);
+        }
+        fn close(&mut self, _joined: bool) {}
+    }
+
+    #[test]
+    fn curve_attrs() {
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+        p.set_tolerance(0.01);
+        let curves = [
+            PathOp::Quad(Pt::new(32.0, 16.0), Pt::new(64.0, 0.0)),
+            PathOp::Cubic(
+                Pt::new(64.0 / 3.0, 24.0),
+                Pt::new(128.0 / 3.0, -24.0),
+                Pt::new(64.0, 0.0),
+            ),
+        ];
+        for curve in curves {
+            let ops = [
+                PathOp::PenAttr(0.0),
+                PathOp::Move(Pt::new(0.0, 0.0)),
+                PathOp::PenAttr(1.0),
+                curve,
+            ];
+            let mut pts: Vec<WidePt> = vec![];
+            p.add_ops(ops, &mut pts);
+            assert!(pts.len() > 4);
+            assert_eq!(pts[0].a(), 0.0);
+            assert_eq!(pts[pts.len() - 1].a(), 1.0);
+            // X varies evenly along these curves, and so do attributes
+            for pt in pts {
+                assert!((pt.a() - pt.0.x() / 64.0).abs() < 0.001);
+            }
+        }
+    }
+
+    #[test]
+    fn cubic_flatness() {
+        let p = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+        let wp = |x, y| WidePt(Pt::new(x, y), 1.0, 0.0);
+        // Straight, with control points bunched near one end
+        let mut pts: Vec<Pt<f32>> = vec![];
+        let (a, b, c, d) =
+            (wp(0.0, 0.0), wp(60.0, 0.0), wp(62.0, 0.0), wp(64.0, 0.0));
+        p.cubic_to_tran(&mut pts, a, b, c, d);
+        assert_eq!(pts, [Pt::new(64.0, 0.0)]);
+        // S-curve, with curve midpoint on the chord
+        let (a, b, c, d) = (
+            wp(0.0, 0.0),
+            wp(32.0, -16.0),
+            wp(32.0, 80.0),
+            wp(64.0, 64.0),
+        );
+        let mut pts = vec![a.0];
+        p.cubic_to_tran(&mut pts, a, b, c, d);
+        assert!(pts.len() > 4);
+        // Every point on the curve must be near the flattened polyline
+        let tol = p.tol_sq.sqrt() + 0.001;
+        for i in 0..=100 {
+            let t = i as f32 / 100.0;
+            let u = 1.0 - t;
+            let pt = a.0 * (u * u * u)
+                + b.0 * (3.0 * u * u * t)
+                + c.0 * (3.0 * u * t * t)
+                + d.0 * (t * t * t);
+            let dist = pts
+                .windows(2)
+                .map(|w| segment_dist_sq(pt, w[0], w[1]))
+                .fold(f32::MAX, f32::min);
+            assert!(dist.sqrt() <= tol, "t: {t} dist: {}", dist.sqrt());
+        }
+    }
+
+    #[test]
+    fn fill_above() {
+        let triangle = |dy: f32| {
+            Path2D::default()
+                .absolute()
+                .move_to(16.3, -dy)
+                .line_to(2.0, 28.0)
+                .line_to(29.5, 20.0)
+                .close()
+                .finish()
+        };
+        // Enough points to be filled from an edge table
+        let polygon = |dy: f32| {
+            let n = 600;
+            let mut path = Path2D::default().absolute();
+            for i in 0..n {
+                let a = i as f32 / n as f32 * std::f32::consts::TAU;
+                let r = 12.0 + (a * 9.0).sin();
+                // On a 1/256 pixel grid, so translating points is exact
+                let x = (r * a.cos() * 256.0).round() / 256.0;
+                let y = (r * a.sin() * 256.0).round() / 256.0;
+                path = path.line_to(16.0 + x, 4.0 - dy + y);
+            }
+            path.close().finish()
+        };
+        for dy in [10.0, 10.25, 0.5, 3.75, 1.0, 16.0] {
+            for path in [triangle(dy), polygon(dy)] {
+                let mut p = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+                p.fill(FillRule::NonZero, &path, Matte8::new(255));
+                // Same shape, translated into view on a taller raster
+                let mut q = Plotter::new(Raster::with_clear(32, 96));
+                q.set_transform(Transform::with_translate(0.0, 64.0));
+                q.fill(FillRule::NonZero, &path, Matte8::new(255));
+                let (above, visible) =
+                    q.raster().as_u8_slice().split_at(64 * 32);
+                assert_eq!(p.raster().as_u8_slice(), visible, "{dy}");
+                assert!(above.iter().any(|v| *v > 0), "{dy}");
+                p.clear().fill_streamed(
+                    FillRule::NonZero,
+                    &path,
+                    Matte8::new(255),
+                );
+                assert_eq!(p.raster().as_u8_slice(), visible, "{dy}");
+            }
+        }
     }
 }
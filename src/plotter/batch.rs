@@ -0,0 +1,153 @@
+// batch.rs       Plotter batch fills.
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+use super::{tile_offset, tiles, Drawn, Plotter};
+use crate::blend::BlendChannel;
+use crate::path::{FillRule, PathOp};
+use pix::chan::{Linear, Premultiplied};
+use pix::el::Pixel;
+use pix::Region;
+use pointy::Transform;
+
+impl<P> Plotter<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+    P::Chan: BlendChannel,
+{
+    /// Fill a batch of paths onto the raster.
+    ///
+    /// Each path has its own transform, which is applied before the plotter
+    /// transform.  The paths are combined into one figure and rasterized in a
+    /// single pass, so overlapping paths are filled as one shape with the
+    /// fill rule.  This is much faster than filling many small paths (such as
+    /// glyphs) one at a time.  Very large batches may take multiple passes.
+    ///
+    /// * `rule` Fill rule.
+    /// * `paths` Iterator of paths with transforms.
+    /// * `clr` Color to fill.
+    pub fn fill_batch<'a, T>(
+        &mut self,
+        rule: FillRule,
+        paths: T,
+        clr: P,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator<Item = (&'a [PathOp], Transform<f32>)>,
+    {
+        self.drawn(|p| {
+            let reg = p.raster.region();
+            match tiles(reg, 1, 1) {
+                None => p.fill_batch_region(rule, paths, clr, reg),
+                Some(tiles) => {
+                    let paths: Vec<_> = paths.into_iter().collect();
+                    let viewport = p.viewport;
+                    for tile in tiles {
+                        p.viewport = viewport + tile_offset(reg, tile);
+                        p.fill_batch_region(
+                            rule,
+                            paths.iter().copied(),
+                            clr,
+                            tile,
+                        );
+                    }
+                    p.viewport = viewport;
+                }
+            }
+        })
+    }
+
+    /// Fill a batch of paths onto a region of the raster.
+    fn fill_batch_region<'a, T>(
+        &mut self,
+        rule: FillRule,
+        paths: T,
+        clr: P,
+        reg: Region,
+    ) where
+        T: IntoIterator<Item = (&'a [PathOp], Transform<f32>)>,
+    {
+        let mut fig = self.take_fig();
+        let mut dropped = 0;
+        for (ops, t) in paths {
+            self.with_transform(&t, |p| p.add_ops(ops, &mut fig));
+            fig.close();
+            if fig.is_half_full() {
+                self.fill_fig_scissor(&mut fig, rule, clr, reg);
+                dropped += fig.dropped_points();
+                fig.reset();
+            }
+        }
+        self.dropped = dropped + fig.dropped_points();
+        self.fill_fig_scissor(&mut fig, rule, clr, reg);
+        self.fig = Some(fig);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    use pix::matte::Matte8;
+    use pix::rgb::Rgba8p;
+    use pix::Raster;
+    use pointy::Transform;
+
+    #[test]
+    fn fill_batch() {
+        let glyph = Path2D::default()
+            .absolute()
+            .move_to(1.0, 0.0)
+            .cubic_to(6.0, 0.0, 6.0, 6.0, 1.0, 6.0)
+            .line_to(3.0, 3.0)
+            .close()
+            .finish();
+        let clr = Rgba8p::new(20, 40, 80, 160);
+        let scale = Transform::with_scale(1.5, 1.5);
+        let mut p = Plotter::new(Raster::with_clear(64, 64));
+        let mut b = Plotter::new(Raster::with_clear(64, 64));
+        p.set_transform(scale);
+        b.set_transform(scale);
+        let mut paths = vec![];
+        for y in 0..5 {
+            for x in 0..5 {
+                let t =
+                    Transform::with_translate(x as f32 * 8.0, y as f32 * 8.0);
+                p.set_transform(t * scale);
+                p.fill(FillRule::NonZero, &glyph, clr);
+                paths.push((&glyph[..], t));
+            }
+        }
+        b.fill_batch(FillRule::NonZero, paths, clr);
+        assert_eq!(p.raster().pixels(), b.raster().pixels());
+    }
+
+    #[test]
+    fn fill_batch_tolerance() {
+        let glyph = Path2D::default()
+            .absolute()
+            .move_to(1.0, 0.0)
+            .cubic_to(6.0, 0.0, 6.0, 6.0, 1.0, 6.0)
+            .line_to(3.0, 3.0)
+            .close()
+            .finish();
+        let clr = Matte8::new(255);
+        let mut p = Plotter::new(Raster::with_clear(128, 64));
+        let mut b = Plotter::new(Raster::with_clear(128, 64));
+        p.set_tolerance_auto(0.25);
+        b.set_tolerance_auto(0.25);
+        // Curve tolerance is in user units, so it depends on each transform
+        let mut paths = vec![];
+        for (i, s) in [0.5, 2.0, 8.0].into_iter().enumerate() {
+            let t = Transform::with_scale(s, s)
+                .translate(i as f32 * 24.0 + 4.0, 4.0);
+            p.fill_transformed(FillRule::NonZero, &glyph, &t, clr);
+            paths.push((&glyph[..], t));
+        }
+        let tol_sq = b.tol_sq;
+        b.fill_batch(FillRule::NonZero, paths, clr);
+        assert_eq!(p.raster().pixels(), b.raster().pixels());
+        assert_eq!(b.tol_sq, tol_sq);
+        assert_eq!(b.transform, Transform::default());
+    }
+}
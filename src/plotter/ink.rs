@@ -0,0 +1,993 @@
+// ink.rs         Plotter fills and strokes with inks.
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+use super::{Drawn, Plotter};
+use crate::blend::{BlendChannel, BlendMode};
+use crate::ink::{FnInk, GradientStops, Ink, StrokeGradient, UserMap};
+use crate::path::{FillRule, PathOp};
+use pix::chan::{Ch8, Linear, Premultiplied};
+use pix::el::Pixel;
+use pix::Region;
+use std::borrow::Borrow;
+
+impl<P> Plotter<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+    P::Chan: BlendChannel,
+{
+    /// Fill path onto the raster with an ink.
+    ///
+    /// The blend mode, opacity, clip and scissor are applied.  With a blend
+    /// mode other than `SrcOver`, source colors come from the ink's
+    /// `color_at`, replacing its own compositing (such as a [Blend] ink's
+    /// mode).  Inks are always composited directly onto the raster, even
+    /// within a group.  The ink is borrowed, so any state it records can be
+    /// inspected afterwards; use [fill_ink] to pass it by value.
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    /// * `ink` Ink to fill.
+    ///
+    /// [Blend]: ink/struct.Blend.html
+    /// [fill_ink]: struct.Plotter.html#method.fill_ink
+    pub fn fill_with<T, I>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        ink: &mut I,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        I: Ink<P> + ?Sized,
+    {
+        let map = self.user_map();
+        self.drawn(|p| p.fill_ink_map(rule, ops, ink, &map, None))
+    }
+
+    /// Fill path onto the raster with an ink, mapping its pixels to user
+    /// space, with an optional progress callback.
+    fn fill_ink_map<T, I>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        ink: &mut I,
+        map: &UserMap,
+        progress: Option<&mut dyn FnMut(u32) -> bool>,
+    ) where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        I: Ink<P> + ?Sized,
+    {
+        let mut spans = std::mem::take(&mut self.ink_spans);
+        let antialias = self.antialias;
+        let mut push = |y, x, len, cov| spans.push((y, x, len, cov));
+        let dirty =
+            self.plot_spans(rule, ops, antialias, 256, &mut push, progress);
+        if let Some(reg) = dirty.and_then(|d| self.scissor_region(d)) {
+            ink.set_user_map(map);
+            self.composite_ink(&spans, ink, reg);
+            self.add_dirty(reg);
+        }
+        spans.clear();
+        self.ink_spans = spans;
+    }
+
+    /// Fill path onto the raster with an ink, by value.
+    ///
+    /// This is the same as [fill_with], for inks with no state to inspect
+    /// afterwards.
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    /// * `ink` Ink to fill.
+    ///
+    /// [fill_with]: struct.Plotter.html#method.fill_with
+    pub fn fill_ink<T, I>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        mut ink: I,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        I: Ink<P>,
+    {
+        self.fill_with(rule, ops, &mut ink)
+    }
+
+    /// Fill path onto the raster with a closure.
+    ///
+    /// This is the same as [fill_with], using [FnInk].  The closure is called
+    /// with the destination pixel, column, row and coverage alpha of each
+    /// covered pixel.
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    /// * `f` Closure to fill each pixel.
+    ///
+    /// [fill_with]: struct.Plotter.html#method.fill_with
+    /// [FnInk]: ink/struct.FnInk.html
+    pub fn fill_fn<T, F>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        f: F,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        F: FnMut(&mut P, i32, i32, Ch8),
+    {
+        self.fill_with(rule, ops, &mut FnInk(f))
+    }
+
+    /// Composite an ink onto a region of the raster, using coverage spans.
+    ///
+    /// Coverage is passed to the ink unquantized (0 to 256), unless opacity
+    /// or clip applies.  In that case, it is converted to alpha first, then
+    /// multiplied by opacity and clip coverage.
+    ///
+    /// * `spans` Row, column, length and coverage of runs, top to bottom.
+    /// * `ink` Ink to fill.
+    /// * `reg` Region to composite.
+    fn composite_ink<I: Ink<P> + ?Sized>(
+        &mut self,
+        spans: &[(i32, i32, u32, u16)],
+        ink: &mut I,
+        reg: Region,
+    ) {
+        let opacity = Ch8::from(self.opacity);
+        let blend = self.blend;
+        let width = self.raster.width() as usize;
+        let (left, right) = (reg.left(), reg.right());
+        let (top, bottom) = (reg.top(), reg.bottom());
+        let clip = self.clip.as_ref().map(|c| c.pixels());
+        let pixels = self.raster.pixels_mut();
+        let mut row = None;
+        for &(y, x, len, cov) in spans {
+            if y < top || y >= bottom {
+                continue;
+            }
+            let x0 = x.max(left);
+            let x1 = (x + len as i32).min(right);
+            if x0 >= x1 {
+                continue;
+            }
+            if row != Some(y) {
+                if row.is_some() {
+                    ink.end_row();
+                }
+                ink.begin_row(y, reg.width() as usize);
+                row = Some(y);
+            }
+            let start = y as usize * width;
+            let dst = &mut pixels[start + x0 as usize..start + x1 as usize];
+            let src_over = blend == BlendMode::SrcOver;
+            if src_over && clip.is_none() && opacity == Ch8::new(255) {
+                ink.fill_span(dst, x0, y, cov);
+                continue;
+            }
+            let alpha = Ch8::new(cov.min(255) as u8).mul_coverage(opacity);
+            for (x, d) in (x0..).zip(dst) {
+                let alpha = match clip {
+                    Some(clip) => {
+                        alpha.mul_coverage(clip[start + x as usize].alpha())
+                    }
+                    None => alpha,
+                };
+                if u8::from(alpha) == 0 {
+                    continue;
+                }
+                if src_over {
+                    ink.fill_cov(d, x, y, u8::from(alpha).into());
+                } else {
+                    let src = ink.color_at(x, y);
+                    blend.composite(d, &src, P::Chan::from(alpha));
+                }
+            }
+        }
+        if row.is_some() {
+            ink.end_row();
+        }
+    }
+
+    /// Fill path onto the raster with an ink, and a progress callback.
+    ///
+    /// The callback is invoked every 16 rows with the index of the row about
+    /// to be scanned.  If it returns `false`, scanning stops immediately, and
+    /// only the rows already scanned are filled, leaving the raster partially
+    /// drawn.  The plotter can still be used for subsequent drawing.  Rows are
+    /// always scanned on the calling thread.  Rasters over 8,192 pixels wide
+    /// are filled in tiles, so rows may be reported more than once.
+    ///
+    /// The ink is used the same as with [fill_with].
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    /// * `ink` Ink to fill.
+    /// * `progress` Callback with current row, returning `false` to stop.
+    ///
+    /// [fill_with]: struct.Plotter.html#method.fill_with
+    pub fn fill_with_progress<T, I, F>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        ink: &mut I,
+        mut progress: F,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        I: Ink<P> + ?Sized,
+        F: FnMut(u32) -> bool,
+    {
+        self.drawn(|p| {
+            let map = p.user_map();
+            p.fill_ink_map(rule, ops, ink, &map, Some(&mut progress));
+        })
+    }
+
+    /// Stroke path onto the raster with an ink.
+    ///
+    /// The stroke outline is filled as with [fill_with].  The ink is
+    /// borrowed, so any state it records can be inspected afterwards.
+    ///
+    /// * `ops` PathOp iterator.
+    /// * `ink` Ink to stroke.
+    ///
+    /// [fill_with]: struct.Plotter.html#method.fill_with
+    pub fn stroke_with<T, I>(&mut self, ops: T, ink: &mut I) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        I: Ink<P> + ?Sized,
+    {
+        let map = self.user_map();
+        self.drawn(|p| {
+            let mut stroke = p.take_stroke();
+            p.add_ops(ops, &mut stroke);
+            let mut ops = std::mem::take(&mut p.stroke_ops);
+            ops.clear();
+            p.stroke_outline(&mut stroke, &mut ops);
+            p.with_raster_coords(|p| {
+                p.fill_ink_map(FillRule::NonZero, &ops, ink, &map, None);
+            });
+            p.stroke_ops = ops;
+            p.stroke = Some(stroke);
+        })
+    }
+
+    /// Stroke path onto the raster with an ink, by value.
+    ///
+    /// This is the same as [stroke_with], for inks with no state to inspect
+    /// afterwards.
+    ///
+    /// * `ops` PathOp iterator.
+    /// * `ink` Ink to stroke.
+    ///
+    /// [stroke_with]: struct.Plotter.html#method.stroke_with
+    pub fn stroke_ink<T, I>(&mut self, ops: T, mut ink: I) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        I: Ink<P>,
+    {
+        self.stroke_with(ops, &mut ink)
+    }
+
+    /// Stroke path onto the raster with a gradient along its length.
+    ///
+    /// The gradient runs from offset 0 at the start of the path to 1 at the
+    /// end, by arc length of the center line.  Each pixel takes the offset of
+    /// the nearest center line point, so joins take the offset of the
+    /// corner.  Pen widths are from the path, as with [stroke].
+    ///
+    /// * `ops` PathOp iterator.
+    /// * `stops` Gradient color stops.
+    ///
+    /// [stroke]: struct.Plotter.html#method.stroke
+    pub fn stroke_gradient<T>(
+        &mut self,
+        ops: T,
+        stops: &GradientStops<P>,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        self.drawn(|p| {
+            let mut stroke = p.take_stroke();
+            stroke.set_curves(false);
+            p.add_ops(ops, &mut stroke);
+            let mut ops = std::mem::take(&mut p.stroke_ops);
+            ops.clear();
+            p.stroke_outline(&mut stroke, &mut ops);
+            let sample = |t| stops.sample(t);
+            let mut ink = StrokeGradient::new(sample, stroke.arc_segments());
+            p.with_raster_coords(|p| {
+                p.fill_with(FillRule::NonZero, &ops, &mut ink);
+            });
+            p.stroke_ops = ops;
+            p.stroke = Some(stroke);
+        })
+    }
+
+    /// Stroke path onto the raster, colored by pen attribute.
+    ///
+    /// Pen attributes are set with [PathOp::PenAttr], and interpolated along
+    /// lines and curves.  Each pixel takes the attribute of the nearest
+    /// center line point, mapped to a color by the `sample` function.  Pen
+    /// widths are from the path, as with [stroke].
+    ///
+    /// * `ops` PathOp iterator.
+    /// * `sample` Function to get the color for an attribute.
+    ///
+    /// ```
+    /// use footile::ink::GradientStops;
+    /// use footile::{Path2D, Plotter};
+    /// use pix::{rgb::Rgba8p, Raster};
+    ///
+    /// let path = Path2D::default()
+    ///     .absolute()
+    ///     .pen_width(4.0)
+    ///     .pen_attr(0.0)
+    ///     .move_to(2.0, 8.0)
+    ///     .pen_attr(1.0)
+    ///     .line_to(30.0, 8.0)
+    ///     .finish();
+    /// let stops = [
+    ///     (0.0, Rgba8p::new(255, 0, 0, 255)),
+    ///     (1.0, Rgba8p::new(0, 0, 255, 255)),
+    /// ];
+    /// let stops = GradientStops::new(&stops).unwrap();
+    /// let mut p = Plotter::new(Raster::with_clear(32, 16));
+    /// p.stroke_attr(&path, |a| stops.sample(a));
+    /// ```
+    ///
+    /// [PathOp::PenAttr]: enum.PathOp.html#variant.PenAttr
+    /// [stroke]: struct.Plotter.html#method.stroke
+    pub fn stroke_attr<T, F>(&mut self, ops: T, sample: F) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        F: FnMut(f32) -> P,
+    {
+        self.drawn(|p| {
+            let mut stroke = p.take_stroke();
+            stroke.set_curves(false);
+            p.add_ops(ops, &mut stroke);
+            let mut ops = std::mem::take(&mut p.stroke_ops);
+            ops.clear();
+            p.stroke_outline(&mut stroke, &mut ops);
+            let mut ink = StrokeGradient::new(sample, stroke.attr_segments());
+            p.with_raster_coords(|p| {
+                p.fill_with(FillRule::NonZero, &ops, &mut ink);
+            });
+            p.stroke_ops = ops;
+            p.stroke = Some(stroke);
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use pix::el::Pixel;
+    use pix::matte::Matte8;
+    use pix::rgb::Rgba8p;
+    use pix::Raster;
+    use pointy::{Pt, Transform};
+
+    #[test]
+    fn stroke_gradient() {
+        // L-shape: 16 across, then 16 down
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(4.0)
+            .move_to(8.0, 8.0)
+            .line_to(24.0, 8.0)
+            .line_to(24.0, 24.0)
+            .finish();
+        let stops = [
+            (0.0, Rgba8p::new(255, 0, 0, 255)),
+            (1.0, Rgba8p::new(0, 0, 255, 255)),
+        ];
+        let stops = ink::GradientStops::new(&stops).unwrap();
+        let mut a = Plotter::new(Raster::with_clear(32, 32));
+        a.set_join(JoinStyle::Miter(4.0));
+        a.stroke(&path, Rgba8p::new(255, 255, 255, 255));
+        let mut b = Plotter::new(Raster::with_clear(32, 32));
+        b.set_join(JoinStyle::Miter(4.0));
+        let r = b.stroke_gradient(&path, &stops);
+        // Same coverage as a solid stroke
+        for (pa, pb) in a.raster().pixels().iter().zip(r.pixels()) {
+            assert_eq!(pa.alpha(), pb.alpha());
+        }
+        let red = |x, y| u8::from(r.pixel(x, y).one());
+        let blue = |x, y| u8::from(r.pixel(x, y).three());
+        // Start and end
+        assert!(red(8, 8) > 250 && blue(8, 8) < 10);
+        assert!(blue(24, 23) > 240 && red(24, 23) < 15);
+        // Corner, including outside of the miter join, is half way
+        for (x, y) in [(24, 8), (25, 6)] {
+            assert!((red(x, y) as i32 - 128).abs() < 12);
+            assert!((blue(x, y) as i32 - 128).abs() < 12);
+        }
+        // Ramp increases along the path
+        let reds: Vec<_> = (8..24).map(|x| red(x, 8)).collect();
+        assert!(reds.windows(2).all(|w| w[0] >= w[1]));
+        let blues: Vec<_> = (8..24).map(|y| blue(24, y)).collect();
+        assert!(blues.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn stroke_attr() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(4.0)
+            .pen_attr(0.0)
+            .move_to(4.0, 8.0)
+            .pen_attr(1.0)
+            .line_to(28.0, 8.0)
+            .finish();
+        let mut a = Plotter::new(Raster::with_clear(32, 16));
+        a.stroke(&path, Rgba8p::new(255, 255, 255, 255));
+        let mut b = Plotter::new(Raster::with_clear(32, 16));
+        let mut attrs = vec![];
+        let r = b.stroke_attr(&path, |v| {
+            attrs.push(v);
+            let c = (v * 255.0).round() as u8;
+            Rgba8p::new(c, c, c, 255)
+        });
+        // Same coverage as a solid stroke
+        for (pa, pb) in a.raster().pixels().iter().zip(r.pixels()) {
+            assert_eq!(pa.alpha(), pb.alpha());
+        }
+        assert!(attrs.iter().all(|v| (0.0..=1.0).contains(v)));
+        let vals: Vec<_> =
+            (4..28).map(|x| u8::from(r.pixel(x, 8).one())).collect();
+        assert!(vals[0] < 16 && vals[23] > 240);
+        assert!(vals.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn blend_modes_ink() {
+        let tri = Path2D::default()
+            .absolute()
+            .move_to(0.5, 0.0)
+            .line_to(7.0, 2.5)
+            .line_to(1.0, 8.0)
+            .close()
+            .finish();
+        let clr = Rgba8p::new(40, 80, 20, 160);
+        for dst in [Rgba8p::new(100, 150, 200, 255), Rgba8p::new(30, 60, 0, 90)]
+        {
+            for blend in [
+                BlendMode::SrcOver,
+                BlendMode::Multiply,
+                BlendMode::Screen,
+                BlendMode::Plus,
+                BlendMode::Clear,
+            ] {
+                let mut p = Plotter::new(Raster::with_color(8, 8, dst));
+                p.set_blend(blend);
+                p.fill(FillRule::NonZero, &tri, clr);
+                let mut q = Plotter::new(Raster::with_color(8, 8, dst));
+                q.set_blend(blend);
+                q.fill_with(
+                    FillRule::NonZero,
+                    &tri,
+                    &mut ink::ColorInk::new(clr),
+                );
+                assert_eq!(
+                    p.raster().pixels(),
+                    q.raster().pixels(),
+                    "{blend:?} {dst:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fill_with_progress() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(4.5, 2.25)
+            .line_to(60.0, 10.5)
+            .line_to(20.25, 61.75)
+            .close()
+            .finish();
+        let clr = Matte8::new(255);
+        let mut ink = ink::ColorInk::new(clr);
+        let mut a = Plotter::new(Raster::with_clear(64, 64));
+        a.fill_with(FillRule::NonZero, &path, &mut ink);
+        let mut rows = vec![];
+        let mut p = Plotter::new(Raster::with_clear(64, 64));
+        p.fill_with_progress(FillRule::NonZero, &path, &mut ink, |row| {
+            rows.push(row);
+            true
+        });
+        assert_eq!(rows, [16, 32, 48]);
+        assert_eq!(a.raster().pixels(), p.raster().pixels());
+        // Stop part way
+        let mut p = Plotter::new(Raster::with_clear(64, 64));
+        p.fill_with_progress(FillRule::NonZero, &path, &mut ink, |row| {
+            row < 32
+        });
+        assert!(p.sgn_area.iter().all(|s| *s == 0));
+        let n = 32 * 64;
+        assert_eq!(a.raster().pixels()[..n], p.raster().pixels()[..n]);
+        assert!(p.raster().pixels()[n..]
+            .iter()
+            .all(|c| *c == Matte8::new(0)));
+        // Next fill is not corrupted
+        p.clear();
+        p.fill_with(FillRule::NonZero, &path, &mut ink);
+        assert_eq!(a.raster().pixels(), p.raster().pixels());
+        // Winding too deep for the narrow signed area buffer
+        let deep: Vec<PathOp> = (0..200).flat_map(|_| path.clone()).collect();
+        let mut p = Plotter::new(Raster::with_clear(64, 64));
+        p.fill_with_progress(FillRule::NonZero, &deep, &mut ink, |row| {
+            row < 32
+        });
+        assert!(p.sgn_area_wide.iter().all(|s| *s == 0));
+        assert!(p.raster().pixels()[n..]
+            .iter()
+            .all(|c| *c == Matte8::new(0)));
+        p.clear();
+        p.fill_with(FillRule::NonZero, &path, &mut ink);
+        assert_eq!(a.raster().pixels(), p.raster().pixels());
+    }
+
+    #[test]
+    fn ink_user_space() {
+        let square = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(0.0, 8.0)
+            .line_to(8.0, 8.0)
+            .line_to(8.0, 0.0)
+            .close()
+            .finish();
+        let stops = [
+            (0.0, Rgba8p::new(0, 0, 0, 255)),
+            (1.0, Rgba8p::new(255, 255, 255, 255)),
+        ];
+        let stops = ink::GradientStops::new(&stops).unwrap();
+        let ramp = ink::LinearGradient::new((0.0, 0.0), (8.0, 0.0), stops)
+            .with_space(ink::InkSpace::User);
+        let mut a = Plotter::new(Raster::with_clear(64, 64));
+        a.set_transform(Transform::with_scale(2.0, 2.0));
+        a.fill_with(FillRule::NonZero, &square, &mut ramp.clone());
+        let mut b = Plotter::new(Raster::with_clear(64, 64));
+        b.set_transform(Transform::with_scale(2.0, 2.0).translate(29.5, 19.5))
+            .set_viewport(4.0, 2.0)
+            .set_pixel_origin(PixelOrigin::Center);
+        b.fill_with(FillRule::NonZero, &square, &mut ramp.clone());
+        // Square is at (0, 0) in a, and (26, 18) in b
+        for y in 0..16 {
+            for x in 0..16 {
+                let pa = a.raster().pixel(x, y);
+                assert_eq!(pa, b.raster().pixel(x + 26, y + 18));
+            }
+        }
+        // 16 pixels across 8 units: centers at 1/32, 3/32, ...
+        let row: Vec<u8> = (0..16)
+            .map(|x| a.raster().pixel(x, 4).one().into())
+            .collect();
+        assert!(row[0] < 10 && row[15] > 245);
+        assert!(row.windows(2).all(|w| w[0] < w[1]));
+        // Strokes follow the transform too
+        let mut c = Plotter::new(Raster::with_clear(64, 64));
+        c.set_transform(Transform::with_scale(2.0, 2.0).translate(4.0, 4.0))
+            .pen_width(2.0);
+        c.stroke_with(&square, &mut ramp.clone());
+        let red = |x, y| u8::from(c.raster().pixel(x, y).one());
+        assert!(red(4, 12) < 16 && red(19, 12) > 240);
+        // Singular transform only undoes the viewport
+        let mut d = Plotter::new(Raster::<Rgba8p>::with_clear(8, 8));
+        d.set_transform(Transform::with_scale(0.0, 1.0))
+            .set_viewport(2.0, 0.0);
+        let map = d.user_map();
+        assert_eq!(map.pixel(1, 1), Pt::new(3.5, 1.5));
+    }
+
+    #[test]
+    fn fill_with_texture() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(2.5, 3.0)
+            .line_to(28.0, 6.25)
+            .line_to(12.0, 30.0)
+            .close()
+            .finish();
+        let clr = Rgba8p::new(32, 64, 128, 192);
+        let tex = Raster::with_color(3, 5, clr);
+        let mut a = Plotter::new(Raster::with_clear(32, 32));
+        let mut b = Plotter::new(Raster::with_clear(32, 32));
+        for opacity in [1.0, 0.5] {
+            a.clear().set_opacity(opacity).take_dirty();
+            b.clear().set_opacity(opacity).take_dirty();
+            a.fill(FillRule::NonZero, &path, clr);
+            let mut ink = ink::Texture::new(&tex).with_wrap(ink::Wrap::Repeat);
+            b.fill_with(FillRule::NonZero, &path, &mut ink);
+            assert_eq!(a.raster().pixels(), b.raster().pixels());
+            assert_eq!(a.take_dirty(), b.take_dirty());
+        }
+        // Scissor applies to inks
+        b.clear().set_scissor(Some((0, 0, 8, 32)));
+        b.fill_with(FillRule::NonZero, &path, &mut ink::Texture::new(&tex));
+        assert!(b
+            .raster()
+            .rows((8, 0, 24, 32))
+            .flatten()
+            .all(|p| *p == Rgba8p::default()));
+    }
+
+    #[test]
+    fn fill_with_dyn_inks() {
+        let square = Path2D::default()
+            .absolute()
+            .move_to(2.0, 2.0)
+            .line_to(14.0, 2.0)
+            .line_to(14.0, 14.0)
+            .line_to(2.0, 14.0)
+            .finish();
+        let red = Raster::with_color(1, 1, Rgba8p::new(255, 0, 0, 255));
+        let blue = Raster::with_color(1, 1, Rgba8p::new(0, 0, 255, 255));
+        let mut inks: Vec<Box<dyn ink::Ink<Rgba8p>>> = vec![
+            Box::new(ink::Texture::new(&red)),
+            Box::new(ink::Opacity::new(ink::Texture::new(&blue), 0.5)),
+        ];
+        let mut p = Plotter::new(Raster::with_clear(16, 16));
+        let mut q = Plotter::new(Raster::with_clear(16, 16));
+        // Boxed inks can be reused, by box or by trait object
+        for _ in 0..2 {
+            p.clear();
+            q.clear();
+            for ink in inks.iter_mut() {
+                p.fill_with(FillRule::NonZero, &square, ink);
+                q.fill_with(FillRule::NonZero, &square, ink.as_mut());
+            }
+            assert_eq!(p.raster().pixel(8, 8), Rgba8p::new(127, 0, 128, 255));
+            assert_eq!(p.raster().pixels(), q.raster().pixels());
+        }
+    }
+
+    #[test]
+    fn fill_with_row_hooks() {
+        /// Ink which records rows
+        #[derive(Default)]
+        struct Rows {
+            rows: Vec<(i32, usize)>,
+            row: Option<i32>,
+        }
+        impl ink::Ink<Matte8> for Rows {
+            fn color_at(&mut self, _x: i32, y: i32) -> Matte8 {
+                assert_eq!(self.row, Some(y));
+                Matte8::new(255)
+            }
+            fn begin_row(&mut self, y: i32, width: usize) {
+                assert_eq!(self.row, None);
+                self.rows.push((y, width));
+                self.row = Some(y);
+            }
+            fn end_row(&mut self) {
+                assert!(self.row.take().is_some());
+            }
+        }
+        let path = Path2D::default()
+            .absolute()
+            .move_to(2.0, 3.0)
+            .line_to(9.0, 3.0)
+            .line_to(9.0, 7.0)
+            .line_to(2.0, 7.0)
+            .finish();
+        let mut ink = Rows::default();
+        let mut p = Plotter::new(Raster::with_clear(16, 16));
+        p.fill_with(FillRule::NonZero, &path, &mut ink);
+        assert_eq!(ink.row, None);
+        let ys: Vec<i32> = ink.rows.iter().map(|(y, _)| *y).collect();
+        assert_eq!(ys, [3, 4, 5, 6]);
+        assert!(ink.rows.iter().all(|(_, width)| *width >= 7));
+    }
+
+    #[test]
+    fn fill_with_raw_coverage() {
+        /// Ink which records raw coverage
+        #[derive(Default)]
+        struct Coverage(Vec<(i32, i32, u16)>);
+        impl ink::Ink<Matte8> for Coverage {
+            fn color_at(&mut self, _x: i32, _y: i32) -> Matte8 {
+                Matte8::new(255)
+            }
+            fn fill_cov(&mut self, d: &mut Matte8, x: i32, y: i32, cov: u16) {
+                self.0.push((x, y, cov));
+                self.fill(d, x, y, pix::chan::Ch8::new(cov.min(255) as u8));
+            }
+        }
+        // Right edge covers 37/256 of its pixels
+        let right = 5.0 + 37.0 / 256.0;
+        let path = Path2D::default()
+            .absolute()
+            .move_to(2.0, 1.0)
+            .line_to(right, 1.0)
+            .line_to(right, 3.0)
+            .line_to(2.0, 3.0)
+            .finish();
+        let mut ink = Coverage::default();
+        let mut p = Plotter::new(Raster::with_clear(8, 4));
+        let matte = p.fill_matte(FillRule::NonZero, &path);
+        p.fill_with(FillRule::NonZero, &path, &mut ink);
+        for y in 1..3 {
+            for x in 2..5 {
+                assert!(ink.0.contains(&(x, y, 256)));
+            }
+            assert!(ink.0.contains(&(5, y, 37)));
+        }
+        assert_eq!(ink.0.len(), 8);
+        // Default conversion to alpha matches coverage
+        assert_eq!(p.raster().pixels(), matte.pixels());
+    }
+
+    #[test]
+    fn fill_with_color_spans() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(28.0, -6.0)
+            .line_to(4.0, 12.0)
+            .cubic_to(-4.0, 0.0, -4.0, 32.0, 40.0, 20.0)
+            .line_to(8.0, 30.0)
+            .line_to(24.0, 3.5)
+            .close()
+            .finish();
+        let bg = Rgba8p::new(20, 40, 60, 80);
+        let mut a = Plotter::new(Raster::with_color(32, 32, bg));
+        let mut b = Plotter::new(Raster::with_color(32, 32, bg));
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            for clr in
+                [Rgba8p::new(200, 100, 0, 255), Rgba8p::new(0, 50, 0, 99)]
+            {
+                a.clear_with(bg).fill(rule, &path, clr);
+                b.clear_with(bg);
+                b.fill_with(rule, &path, &mut ink::ColorInk::new(clr));
+                assert_eq!(a.raster().pixels(), b.raster().pixels());
+                // Per-pixel path matches span path
+                let mut per_pixel =
+                    ink::Opacity::new(ink::ColorInk::new(clr), 1.0);
+                b.clear_with(bg).fill_with(rule, &path, &mut per_pixel);
+                assert_eq!(a.raster().pixels(), b.raster().pixels());
+            }
+        }
+    }
+
+    #[test]
+    fn fill_with_stencil() {
+        let square = |x: f32, y: f32| {
+            Path2D::default()
+                .absolute()
+                .move_to(x, y)
+                .line_to(x + 6.0, y)
+                .line_to(x + 6.0, y + 6.0)
+                .line_to(x, y + 6.0)
+                .finish()
+        };
+        let bg = Rgba8p::new(1, 2, 3, 4);
+        let mut ids = vec![0; 16 * 16];
+        let mut p = Plotter::new(Raster::with_color(16, 16, bg));
+        let mut stencil = ink::Stencil::new(&mut ids, 16, 1);
+        p.fill_with(FillRule::NonZero, square(2.0, 2.0), &mut stencil);
+        stencil.set_id(2);
+        // Right and bottom edges cover 1/4 of their pixels
+        p.fill_with(FillRule::NonZero, square(5.0, 5.0), &mut stencil);
+        p.fill_with(FillRule::NonZero, square(8.25, 8.25), &mut stencil);
+        assert!(p.raster().pixels().iter().all(|p| *p == bg));
+        let id = |x: usize, y: usize| ids[y * 16 + x];
+        assert_eq!(id(2, 2), 1);
+        assert_eq!(id(7, 4), 1);
+        // Overlap holds the later ID
+        assert_eq!(id(5, 5), 2);
+        assert_eq!(id(7, 7), 2);
+        assert_eq!(id(13, 13), 2);
+        // Fringe below threshold holds none
+        assert_eq!(id(14, 10), 0);
+        assert_eq!(id(10, 14), 0);
+        assert_eq!(id(8, 4), 0);
+    }
+
+    #[test]
+    fn fill_fn_state() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(2.5, 1.0)
+            .line_to(13.0, 3.0)
+            .line_to(6.0, 14.0)
+            .close()
+            .finish();
+        let clr = Rgba8p::new(10, 20, 30, 255);
+        let mut a = Plotter::new(Raster::with_clear(16, 16));
+        a.fill(FillRule::NonZero, &path, clr);
+        // Histogram of coverage alpha, with a checksum of pixel positions
+        let mut histogram = [0; 256];
+        let mut checksum = 0;
+        let mut b = Plotter::new(Raster::with_clear(16, 16));
+        b.fill_fn(FillRule::NonZero, &path, |d, x, y, alpha| {
+            histogram[usize::from(u8::from(alpha))] += 1;
+            checksum += x * 16 + y;
+            BlendMode::SrcOver.composite(d, &clr, alpha);
+        });
+        assert_eq!(a.raster().pixels(), b.raster().pixels());
+        let covered: Vec<_> = (0..16)
+            .flat_map(|y| (0..16).map(move |x| (x, y)))
+            .filter(|(x, y)| u8::from(a.raster().pixel(*x, *y).alpha()) > 0)
+            .collect();
+        assert_eq!(histogram.iter().sum::<i32>(), covered.len() as i32);
+        assert_eq!(checksum, covered.iter().map(|(x, y)| x * 16 + y).sum());
+        assert!(histogram[255] > 0 && histogram[1..255].iter().any(|n| *n > 0));
+    }
+
+    #[test]
+    fn fill_with_raster_coords() {
+        // Triangle extending above and left of the raster
+        let path = Path2D::default()
+            .absolute()
+            .move_to(-6.5, -9.0)
+            .line_to(14.0, 3.5)
+            .line_to(2.0, 13.0)
+            .close()
+            .finish();
+        for scissor in [None, Some((1, 2, 9, 7))] {
+            let mut p = Plotter::new(Raster::<Rgba8p>::with_clear(16, 16));
+            let mut matte = p.fill_matte(FillRule::NonZero, &path);
+            if let Some((sx, sy, sw, sh)) = scissor {
+                p.set_scissor(scissor);
+                for y in 0..16 {
+                    for x in 0..16 {
+                        let inside = (sx..sx + sw as i32).contains(&x)
+                            && (sy..sy + sh as i32).contains(&y);
+                        if !inside {
+                            *matte.pixel_mut(x, y) = Matte8::new(0);
+                        }
+                    }
+                }
+            }
+            let mut triples = vec![];
+            p.fill_fn(FillRule::NonZero, &path, |_d, x, y, alpha| {
+                triples.push((x, y, u8::from(alpha)));
+            });
+            let mut expected = vec![];
+            for y in 0..16 {
+                for x in 0..16 {
+                    let a = u8::from(matte.pixel(x, y).alpha());
+                    if a > 0 {
+                        expected.push((x, y, a));
+                    }
+                }
+            }
+            triples.sort_by_key(|(x, y, _)| (*y, *x));
+            assert_eq!(triples, expected);
+            // Top row is covered
+            let top = scissor.map_or(0, |(_x, y, _w, _h)| y);
+            assert!(triples.iter().any(|(_x, y, _a)| *y == top));
+        }
+    }
+
+    #[test]
+    fn fill_with_record() {
+        // Circle from four cubic arcs
+        let (cx, cy, r) = (16.0, 15.5, 11.3);
+        let k = r * 0.552_284_8;
+        let circle = Path2D::default()
+            .absolute()
+            .move_to(cx + r, cy)
+            .cubic_to(cx + r, cy + k, cx + k, cy + r, cx, cy + r)
+            .cubic_to(cx - k, cy + r, cx - r, cy + k, cx - r, cy)
+            .cubic_to(cx - r, cy - k, cx - k, cy - r, cx, cy - r)
+            .cubic_to(cx + k, cy - r, cx + r, cy - k, cx + r, cy)
+            .finish();
+        let stops = [
+            (0.0, Rgba8p::new(255, 0, 0, 255)),
+            (1.0, Rgba8p::new(0, 0, 128, 128)),
+        ];
+        let stops = ink::GradientStops::new(&stops).unwrap();
+        let ramp = ink::LinearGradient::new((4.0, 4.0), (28.0, 28.0), stops);
+        let mut p = Plotter::new(Raster::with_clear(32, 32));
+        let expected = p.fill_matte(FillRule::NonZero, &circle);
+        let mut a = Plotter::new(Raster::with_clear(32, 32));
+        a.fill_with(FillRule::NonZero, &circle, &mut ramp.clone());
+        let mut matte = Raster::with_clear(32, 32);
+        let mut ink = ink::Record::new(ramp, &mut matte, p.raster());
+        p.fill_with(FillRule::NonZero, &circle, &mut ink);
+        assert_eq!(matte.pixels(), expected.pixels());
+        assert_eq!(a.raster().pixels(), p.raster().pixels());
+        // Recording keeps the maximum coverage
+        let mut ink = ink::Record::new(
+            ink::ColorInk::new(Rgba8p::new(0, 0, 0, 255)),
+            &mut matte,
+            p.raster(),
+        );
+        p.set_opacity(0.5)
+            .fill_with(FillRule::NonZero, &circle, &mut ink);
+        assert_eq!(matte.pixels(), expected.pixels());
+    }
+
+    #[test]
+    #[should_panic(expected = "record matte dimensions must match raster")]
+    fn fill_with_record_size() {
+        let p = Plotter::new(Raster::<Rgba8p>::with_clear(32, 32));
+        let mut matte = Raster::with_clear(32, 16);
+        let _ink = ink::Record::new(
+            ink::ColorInk::new(Rgba8p::new(0, 0, 0, 255)),
+            &mut matte,
+            p.raster(),
+        );
+    }
+
+    #[test]
+    fn fill_with_opacity() {
+        let square = Path2D::default()
+            .absolute()
+            .move_to(4.0, 4.0)
+            .line_to(12.0, 4.0)
+            .line_to(12.0, 12.0)
+            .line_to(4.0, 12.0)
+            .finish();
+        let tex = Raster::with_color(1, 1, Matte8::new(255));
+        let mut p = Plotter::new(Raster::with_clear(16, 16));
+        p.fill_with(FillRule::NonZero, &square, &mut ink::Texture::new(&tex));
+        let full = p.raster().clone();
+        let mut quarter = ink::Opacity::new(ink::Texture::new(&tex), 0.25);
+        p.clear()
+            .fill_with(FillRule::NonZero, &square, &mut quarter);
+        for (a, b) in full.pixels().iter().zip(p.raster().pixels()) {
+            let a = f32::from(u8::from(a.alpha()));
+            assert_eq!(u8::from(b.alpha()), (a * 0.25).round() as u8);
+        }
+        assert!(full.pixels().contains(&Matte8::new(255)));
+    }
+
+    #[test]
+    fn fill_with_dither() {
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(0.0, 0.0)
+            .line_to(256.0, 0.0)
+            .line_to(256.0, 8.0)
+            .line_to(0.0, 8.0)
+            .finish();
+        let stops = [
+            (0.0, Rgba8p::new(64, 64, 64, 255)),
+            (1.0, Rgba8p::new(96, 96, 96, 255)),
+        ];
+        let stops = ink::GradientStops::new(&stops).unwrap();
+        let ramp = ink::LinearGradient::new((0.0, 0.0), (256.0, 0.0), stops);
+        // Longest run of identical columns
+        let longest_run = |r: &Raster<Rgba8p>| {
+            let column = |x| (0..8).map(|y| r.pixel(x, y)).collect::<Vec<_>>();
+            let (mut run, mut longest) = (1, 1);
+            for x in 1..256 {
+                run = if column(x) == column(x - 1) {
+                    run + 1
+                } else {
+                    1
+                };
+                longest = longest.max(run);
+            }
+            longest
+        };
+        let mut p = Plotter::new(Raster::with_clear(256, 8));
+        p.fill_with(FillRule::NonZero, &rect, &mut ramp.clone());
+        assert!(longest_run(p.raster()) >= 8);
+        let mut dither = ramp.with_dither(true);
+        p.clear()
+            .fill_with(FillRule::NonZero, &rect, &mut dither.clone());
+        assert!(longest_run(p.raster()) <= 4);
+        // Dithering is deterministic
+        let dithered = p.raster().clone();
+        p.clear().fill_with(FillRule::NonZero, &rect, &mut dither);
+        assert_eq!(p.raster().pixels(), dithered.pixels());
+    }
+}
@@ -0,0 +1,659 @@
+// matte.rs       Plotter coverage mattes and groups.
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+use super::{is_deep, new_sgn_area, tiles, Drawn, Plotter};
+use crate::blend::BlendChannel;
+use crate::fig::{dirty_union, region_union, Paint};
+use crate::path::{FillRule, PathOp};
+use pix::chan::{Linear, Premultiplied};
+use pix::el::Pixel;
+use pix::matte::Matte8;
+use pix::rgb::Rgb8;
+use pix::{Raster, Region};
+use std::borrow::Borrow;
+
+/// Group of fills with accumulated coverage
+pub(super) struct Group<P> {
+    /// Coverage mattes for each color
+    mattes: Vec<(P, Raster<Matte8>)>,
+    /// Region of pixels touched
+    dirty: Option<Region>,
+}
+
+impl<P> Default for Group<P> {
+    fn default() -> Self {
+        Group {
+            mattes: vec![],
+            dirty: None,
+        }
+    }
+}
+
+impl<P: PartialEq> Group<P> {
+    /// Get the coverage matte for a color.
+    pub(super) fn matte(
+        &mut self,
+        clr: P,
+        width: u32,
+        height: u32,
+    ) -> &mut Raster<Matte8> {
+        let i = match self.mattes.iter().position(|(c, _)| *c == clr) {
+            Some(i) => i,
+            None => {
+                self.mattes.push((clr, Raster::with_clear(width, height)));
+                self.mattes.len() - 1
+            }
+        };
+        &mut self.mattes[i].1
+    }
+
+    /// Add a region to the dirty region.
+    pub(super) fn add_dirty(&mut self, dirty: Region) {
+        self.dirty = Some(match self.dirty {
+            Some(d) => region_union(d, dirty),
+            None => dirty,
+        });
+    }
+}
+
+/// Filter horizontal subpixel samples into RGB pixel coverage.
+///
+/// Each pixel has three samples, for the red, green and blue channels.  The
+/// coverage of each channel is a weighted sum of its sample and two samples
+/// on either side, which reduces color fringes.
+///
+/// * `samples` Sample coverage, three times as wide as `rgb`.
+/// * `rgb` Pixel coverage.
+/// * `filter` Filter weights.
+/// * `reg` Region of pixels to filter.
+fn lcd_filter(
+    samples: &Raster<Matte8>,
+    rgb: &mut Raster<Rgb8>,
+    filter: [u8; 5],
+    reg: Region,
+) {
+    let total: u32 = filter.iter().map(|w| u32::from(*w)).sum();
+    let s_width = samples.width() as usize;
+    let (left, top) = (reg.left() as usize, reg.top() as usize);
+    for (y, row) in rgb.rows_mut(reg).enumerate() {
+        let s_row = &samples.pixels()[(top + y) * s_width..][..s_width];
+        let sample = |i: usize| {
+            // Samples beyond the edges have no coverage
+            match (i >= 2).then(|| s_row.get(i - 2)).flatten() {
+                Some(s) => u32::from(u8::from(s.alpha())),
+                None => 0,
+            }
+        };
+        for (x, p) in row.iter_mut().enumerate() {
+            let mut chan = [0; 3];
+            for (c, ch) in chan.iter_mut().enumerate() {
+                // Index of sample, offset by 2 for leftmost weight
+                let i = (left + x) * 3 + c;
+                let sum: u32 = filter
+                    .iter()
+                    .enumerate()
+                    .map(|(k, w)| u32::from(*w) * sample(i + k))
+                    .sum();
+                *ch = ((sum + total / 2) / total.max(1)) as u8;
+            }
+            *p = Rgb8::new(chan[0], chan[1], chan[2]);
+        }
+    }
+}
+
+impl<P> Plotter<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+    P::Chan: BlendChannel,
+{
+    /// Fill path coverage into a new matte.
+    ///
+    /// The matte has the same dimensions as the raster, which is not changed.
+    /// Coverage is quantized to the [coverage bits], and thresholded if
+    /// [anti-aliasing] is disabled, but blend mode, opacity and clip are not
+    /// applied.  The matte can be
+    /// composited later (possibly many times) with [composite_matte].
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    ///
+    /// [composite_matte]: struct.Plotter.html#method.composite_matte
+    /// [coverage bits]: struct.Plotter.html#method.set_coverage_bits
+    /// [anti-aliasing]: struct.Plotter.html#method.set_antialias
+    pub fn fill_matte<T>(&mut self, rule: FillRule, ops: T) -> Raster<Matte8>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        let reg = self.raster.region();
+        let bits = self.coverage_bits;
+        let antialias = self.antialias;
+        let mut matte = Raster::with_clear(self.width(), self.height());
+        self.plot_tiles(reg, ops, 1, 1, |p, fig, tile| {
+            let mut paint = Paint::new(Matte8::new(255));
+            paint.coverage_bits = bits;
+            paint.antialias = antialias;
+            if bits > 8 || is_deep(fig, rule) {
+                let sgn_area = p.wide_area();
+                fig.fill_region(rule, &mut matte, tile, paint, sgn_area);
+            } else {
+                let sgn_area = &mut p.sgn_area[..];
+                fig.fill_region(rule, &mut matte, tile, paint, sgn_area);
+            }
+        });
+        matte
+    }
+
+    /// Fill path coverage for horizontal RGB subpixel anti-aliasing.
+    ///
+    /// Coverage is evaluated at three times the horizontal resolution, for
+    /// LCD panels with red, green and blue stripes, left to right.  Each
+    /// channel of the returned raster is the filtered coverage of one
+    /// subpixel.  The filter weights (such as `[1, 2, 3, 2, 1]`) are divided
+    /// by their sum; `[0, 0, 1, 0, 0]` disables filtering, at the cost of
+    /// color fringes.  Like [fill_matte], the raster is not changed and
+    /// blend mode, opacity and clip are not applied.
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    /// * `filter` FIR filter weights, centered on each subpixel.
+    ///
+    /// [fill_matte]: struct.Plotter.html#method.fill_matte
+    pub fn fill_subpixel<T>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        filter: [u8; 5],
+    ) -> Raster<Rgb8>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        let reg = self.raster.region();
+        let (width, height) = (self.width(), self.height());
+        let mut samples = Raster::with_clear(width * 3, height);
+        let mut dirty = None;
+        self.plot_tiles(reg, ops, 3, 1, |_, fig, tile| {
+            let (x, y) = (tile.left() * 3, tile.top());
+            let tile = Region::new(x, y, tile.width() * 3, tile.height());
+            let paint = Paint::new(Matte8::new(255));
+            let d = if is_deep(fig, rule) {
+                let mut sgn_area = vec![0; tile.width() as usize];
+                let sgn_area = &mut sgn_area[..];
+                fig.fill_region::<_, i32>(
+                    rule,
+                    &mut samples,
+                    tile,
+                    paint,
+                    sgn_area,
+                )
+            } else {
+                let mut sgn_area = new_sgn_area(tile.width());
+                let sgn_area = &mut sgn_area[..];
+                fig.fill_region(rule, &mut samples, tile, paint, sgn_area)
+            };
+            dirty = dirty_union(dirty, d);
+        });
+        let mut rgb = Raster::with_clear(width, height);
+        if let Some(dirty) = dirty {
+            // Filter spreads coverage up to 2 samples on either side
+            let left = (dirty.left() - 2).max(0) / 3;
+            let right = ((dirty.right() + 4) / 3).min(width as i32);
+            let w = (right - left) as u32;
+            let reg = Region::new(left, dirty.top(), w, dirty.height());
+            lcd_filter(&samples, &mut rgb, filter, reg);
+        }
+        rgb
+    }
+
+    /// Fill path coverage as runs of pixels, without touching the raster.
+    ///
+    /// The callback is invoked for each maximal run of equal, non-zero
+    /// coverage, from top to bottom.  Coverage is the same as [fill_matte];
+    /// blend mode, opacity and clip are not applied.  Only the raster
+    /// dimensions are used.
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    /// * `spans` Callback with row, column, length and coverage of each run.
+    ///
+    /// [fill_matte]: struct.Plotter.html#method.fill_matte
+    pub fn fill_spans<T, F>(&mut self, rule: FillRule, ops: T, mut spans: F)
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        F: FnMut(i32, i32, u32, u8),
+    {
+        let mut spans = |y, x, len, cov: u16| spans(y, x, len, cov as u8);
+        let antialias = self.antialias;
+        self.plot_spans(rule, ops, antialias, 255, &mut spans, None);
+    }
+
+    /// Plot a path and get runs of coverage, from top to bottom.
+    ///
+    /// Tiles are scanned separately, so their runs are collected, sorted and
+    /// joined.  Progress rows are relative to the raster, and scanning stops
+    /// if the callback returns `false`.
+    ///
+    /// Returns the region of pixels scanned.
+    pub(super) fn plot_spans<T>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        antialias: bool,
+        max_cov: u16,
+        spans: &mut dyn FnMut(i32, i32, u32, u16),
+        mut progress: Option<&mut dyn FnMut(u32) -> bool>,
+    ) -> Option<Region>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        let reg = self.raster.region();
+        let bits = self.coverage_bits;
+        let tiled = tiles(reg, 1, 1).is_some();
+        let mut runs = vec![];
+        let mut dirty = None;
+        let mut stopped = false;
+        self.plot_tiles(reg, ops, 1, 1, |p, fig, tile| {
+            if stopped {
+                return;
+            }
+            let (x, y) = (tile.left(), tile.top());
+            let mut push = |ty: i32, tx: i32, len, cov| {
+                if tiled {
+                    runs.push((ty + y, tx + x, len, cov));
+                } else {
+                    spans(ty, tx, len, cov);
+                }
+            };
+            // Rows are relative to the tile top
+            let stopped = &mut stopped;
+            let mut check = progress.as_mut().map(|progress| {
+                move |ty| {
+                    *stopped = !progress(y as u32 + ty);
+                    !*stopped
+                }
+            });
+            let check =
+                check.as_mut().map(|c| c as &mut dyn FnMut(u32) -> bool);
+            let (w, h) = (tile.width(), tile.height());
+            let d = if bits > 8 || is_deep(fig, rule) {
+                let sgn_area = p.wide_area();
+                fig.fill_spans_progress(
+                    rule, w, h, antialias, bits, max_cov, sgn_area, check,
+                    &mut push,
+                )
+            } else {
+                let sgn_area = &mut p.sgn_area[..];
+                fig.fill_spans_progress(
+                    rule, w, h, antialias, bits, max_cov, sgn_area, check,
+                    &mut push,
+                )
+            };
+            let d = d.map(|d| {
+                Region::new(d.left() + x, d.top() + y, d.width(), d.height())
+            });
+            dirty = dirty_union(dirty, d);
+        });
+        runs.sort_unstable_by_key(|&(y, x, _, _)| (y, x));
+        // Join runs split at tile edges
+        runs.dedup_by(|r, prev| {
+            let joined =
+                r.0 == prev.0 && r.1 == prev.1 + prev.2 as i32 && r.3 == prev.3;
+            if joined {
+                prev.2 += r.2;
+            }
+            joined
+        });
+        for (y, x, len, cov) in runs {
+            spans(y, x, len, cov);
+        }
+        dirty
+    }
+
+    /// Composite a color onto the raster, using coverage from a matte.
+    ///
+    /// The current blend mode, opacity and clip are applied.
+    ///
+    /// * `matte` Coverage, with the same dimensions as the raster.
+    /// * `clr` Color to composite.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matte dimensions do not match the raster.
+    pub fn composite_matte(
+        &mut self,
+        matte: &Raster<Matte8>,
+        clr: P,
+    ) -> Drawn<'_, P> {
+        self.drawn(|p| {
+            assert_eq!(matte.region(), p.raster.region());
+            if let Some(reg) = p.scissor_region(p.raster.region()) {
+                p.composite_matte_region(matte, clr, reg);
+                p.add_dirty(reg);
+            }
+        })
+    }
+
+    /// Composite coverage onto the raster (or group).
+    ///
+    /// * `matte` Pixel coverage.
+    /// * `clr` Color to composite.
+    /// * `reg` Region of coverage.
+    pub(super) fn composite_coverage(
+        &mut self,
+        matte: &Raster<Matte8>,
+        clr: P,
+        reg: Region,
+    ) {
+        let reg = match self.scissor_region(reg) {
+            Some(reg) => reg,
+            None => return,
+        };
+        match &mut self.group {
+            Some(group) => {
+                let (width, height) =
+                    (self.raster.width(), self.raster.height());
+                let dst = group.matte(clr, width, height);
+                // Coverage is summed, so shared edges add up to full
+                for (d, s) in dst.rows_mut(reg).zip(matte.rows(reg)) {
+                    for (d, s) in d.iter_mut().zip(s) {
+                        let alpha = u8::from(d.alpha());
+                        let cov = u8::from(s.alpha());
+                        *d = Matte8::new(alpha.saturating_add(cov));
+                    }
+                }
+                group.add_dirty(reg);
+            }
+            None => {
+                self.composite_matte_region(matte, clr, reg);
+                self.add_dirty(reg);
+            }
+        }
+    }
+
+    /// Begin a group of fills and strokes.
+    ///
+    /// Until the group is ended, coverage is accumulated separately for each
+    /// color, instead of being composited onto the raster.  This prevents
+    /// seams where paths of the same color share an edge, such as tessellated
+    /// meshes or map polygons.  If a group is already active, this has no
+    /// effect.
+    pub fn begin_group(&mut self) -> &mut Self {
+        if self.group.is_none() {
+            self.group = Some(Group::default());
+        }
+        self
+    }
+
+    /// End a group of fills and strokes.
+    ///
+    /// Accumulated coverage for each color is composited onto the raster, in
+    /// the order the colors were first used, with the current blend mode,
+    /// opacity and clip.
+    pub fn end_group(&mut self) -> Drawn<'_, P> {
+        self.drawn(|p| {
+            if let Some(group) = p.group.take() {
+                if let Some(dirty) = group.dirty {
+                    for (clr, matte) in group.mattes {
+                        p.composite_matte_region(&matte, clr, dirty);
+                    }
+                    p.add_dirty(dirty);
+                }
+            }
+        })
+    }
+
+    /// Composite a color onto a region of the raster, using matte coverage.
+    fn composite_matte_region(
+        &mut self,
+        matte: &Raster<Matte8>,
+        clr: P,
+        reg: Region,
+    ) {
+        let opacity = P::Chan::from(self.opacity);
+        let width = reg.width() as usize;
+        let mut clip = self.clip.as_ref().map(|c| c.rows(reg));
+        let rows = self.raster.rows_mut(reg).zip(matte.rows(reg));
+        for (dst, cov) in rows {
+            let clip = clip.as_mut().and_then(|c| c.next());
+            for i in 0..width {
+                let alpha = P::Chan::from(cov[i].alpha()).mul_coverage(opacity);
+                let alpha = match clip {
+                    Some(clip) => {
+                        alpha.mul_coverage(P::Chan::from(clip[i].alpha()))
+                    }
+                    None => alpha,
+                };
+                self.blend.composite(&mut dst[i], &clr, alpha);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use pix::el::Pixel;
+    use pix::matte::Matte8;
+    use pix::rgb::{Rgb8, Rgba8p};
+    use pix::Raster;
+
+    #[test]
+    fn antialias_matte() {
+        let tri = Path2D::default()
+            .absolute()
+            .move_to(4.0, 2.0)
+            .line_to(14.0, 30.0)
+            .line_to(28.0, 3.0)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        p.set_antialias(false);
+        let matte = p.fill_matte(FillRule::NonZero, &tri);
+        for m in matte.pixels() {
+            assert!(*m == Matte8::new(0) || *m == Matte8::new(255));
+        }
+        p.fill(FillRule::NonZero, &tri, Matte8::new(255));
+        assert_eq!(matte.pixels(), p.raster().pixels());
+        let mut spans = 0;
+        p.fill_spans(FillRule::NonZero, &tri, |_y, _x, _len, cov| {
+            assert_eq!(cov, 255);
+            spans += 1;
+        });
+        assert!(spans > 0);
+    }
+
+    #[test]
+    fn group() {
+        let tri0 = Path2D::default()
+            .absolute()
+            .move_to(4.0, 4.0)
+            .line_to(28.0, 4.0)
+            .line_to(28.0, 28.0)
+            .close()
+            .finish();
+        let tri1 = Path2D::default()
+            .absolute()
+            .move_to(4.0, 4.0)
+            .line_to(28.0, 28.0)
+            .line_to(4.0, 28.0)
+            .close()
+            .finish();
+        let red = Rgba8p::new(255, 0, 0, 255);
+        let blue = Rgba8p::new(0, 0, 255, 255);
+        let mut p = Plotter::new(Raster::with_color(32, 32, red));
+        p.fill(FillRule::NonZero, &tri0, blue);
+        p.fill(FillRule::NonZero, &tri1, blue);
+        // Seam along the diagonal
+        assert_ne!(p.raster().pixel(10, 10), blue);
+        let mut p = Plotter::new(Raster::with_color(32, 32, red));
+        p.begin_group();
+        p.fill(FillRule::NonZero, &tri0, blue);
+        p.fill(FillRule::NonZero, &tri1, blue);
+        assert_eq!(p.raster().pixel(10, 10), red);
+        p.end_group();
+        for y in 4..28 {
+            for x in 4..28 {
+                assert_eq!(p.raster().pixel(x, y), blue, "{x},{y}");
+            }
+        }
+        assert_eq!(p.raster().pixel(2, 2), red);
+        // Dirty region may be one pixel wider than covered
+        let dirty = p.take_dirty().unwrap();
+        assert_eq!((dirty.left(), dirty.top(), dirty.bottom()), (4, 4, 28));
+        assert!(dirty.right() >= 28 && dirty.right() <= 29);
+    }
+
+    #[test]
+    fn fill_matte() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(28.0, 6.0)
+            .line_to(20.0, 12.0)
+            .cubic_to(-4.0, 0.0, -4.0, 32.0, 20.0, 20.0)
+            .line_to(28.0, 26.0)
+            .line_to(24.0, 16.0)
+            .close()
+            .finish();
+        let bg = Rgba8p::new(20, 80, 40, 255);
+        let mut a = Plotter::new(Raster::with_color(32, 32, bg));
+        let mut b = Plotter::new(Raster::with_color(32, 32, bg));
+        let matte = b.fill_matte(FillRule::NonZero, &path);
+        assert!(b.raster().pixels().iter().all(|p| *p == bg));
+        for clr in [Rgba8p::new(200, 100, 0, 255), Rgba8p::new(0, 60, 90, 128)]
+        {
+            a.fill(FillRule::NonZero, &path, clr);
+            b.composite_matte(&matte, clr);
+            assert_eq!(a.raster().pixels(), b.raster().pixels());
+        }
+    }
+
+    #[test]
+    fn fill_matte_scaled() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(28.0, -6.0)
+            .line_to(4.0, 12.0)
+            .cubic_to(-4.0, 0.0, -4.0, 32.0, 40.0, 20.0)
+            .line_to(8.0, 30.0)
+            .line_to(24.0, 3.5)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let full = p.clear().fill(rule, &path, Matte8::new(255)).clone();
+            for v in [1, 128, 254] {
+                p.clear().fill(rule, &path, Matte8::new(v));
+                for (a, b) in full.pixels().iter().zip(p.raster().pixels()) {
+                    let a = crate::imgbuf::div_255(
+                        u32::from(u8::from(a.alpha())) * u32::from(v),
+                    );
+                    assert_eq!(a, u8::from(b.alpha()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fill_spans() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(28.0, -6.0)
+            .line_to(4.0, 12.0)
+            .cubic_to(-4.0, 0.0, -4.0, 32.0, 40.0, 20.0)
+            .line_to(8.0, 30.0)
+            .line_to(24.0, 3.5)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::<Rgba8p>::with_clear(32, 32));
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let matte = p.fill_matte(rule, &path);
+            assert!(matte.pixels().iter().any(|p| *p == Matte8::new(255)));
+            let mut spans = Raster::<Matte8>::with_clear(32, 32);
+            let mut last = None;
+            p.fill_spans(rule, &path, |y, x, len, cov| {
+                assert!(cov > 0 && len > 0);
+                let row = &mut spans.pixels_mut()[y as usize * 32..][..32];
+                let run = &mut row[x as usize..][..len as usize];
+                assert!(run.iter().all(|p| *p == Matte8::new(0)));
+                run.iter_mut().for_each(|p| *p = Matte8::new(cov));
+                // Runs are in order, and adjacent runs differ in coverage
+                if let Some((ly, lx, lcov)) = last {
+                    assert!(y > ly || (y == ly && x >= lx));
+                    assert!(y != ly || x != lx || cov != lcov);
+                }
+                last = Some((y, x + len as i32, cov));
+            });
+            assert_eq!(matte.pixels(), spans.pixels());
+        }
+        assert!(p.raster().pixels().iter().all(|p| *p == Rgba8p::default()));
+        assert!(p.sgn_area.iter().all(|s| *s == 0));
+    }
+
+    #[test]
+    fn fill_subpixel() {
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(10.33, 0.0)
+            .line_to(10.33, 4.0)
+            .line_to(16.0, 4.0)
+            .line_to(16.0, 0.0)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::<Rgba8p>::with_clear(20, 4));
+        let rgb = p.fill_subpixel(FillRule::NonZero, &rect, [0, 0, 1, 0, 0]);
+        // Edge at sample 30.99: red barely covered
+        assert_eq!(rgb.pixel(10, 1), Rgb8::new(3, 255, 255));
+        assert_eq!(rgb.pixel(9, 1), Rgb8::new(0, 0, 0));
+        assert_eq!(rgb.pixel(15, 1), Rgb8::new(255, 255, 255));
+        let rgb = p.fill_subpixel(FillRule::NonZero, &rect, [1, 2, 3, 2, 1]);
+        // (3*3 + 255*3) / 9, (3*2 + 255*6) / 9, (3 + 255*8) / 9
+        assert_eq!(rgb.pixel(10, 1), Rgb8::new(86, 171, 227));
+        // (3*2 + 255) / 9
+        assert_eq!(rgb.pixel(9, 1), Rgb8::new(0, 0, 29));
+        // Right edge at sample 48: (255*8) / 9, (255*6) / 9, (255*3) / 9, ...
+        assert_eq!(rgb.pixel(15, 1), Rgb8::new(255, 227, 170));
+        assert_eq!(rgb.pixel(16, 1), Rgb8::new(85, 28, 0));
+        assert_eq!(rgb.pixel(17, 1), Rgb8::new(0, 0, 0));
+        assert!(p.raster().pixels().iter().all(|p| *p == Rgba8p::default()));
+    }
+
+    #[test]
+    fn matte_partial() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(1.5, 0.0)
+            .line_to(38.0, 4.25)
+            .line_to(2.0, 9.5)
+            .close()
+            .move_to(8.0, 2.0)
+            .line_to(8.0, 6.0)
+            .line_to(30.0, 5.0)
+            .close()
+            .finish();
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let mut full = Plotter::new(Raster::with_clear(40, 10));
+            full.fill(rule, &path, Matte8::new(255));
+            let mut half = Plotter::new(Raster::with_clear(40, 10));
+            half.fill(rule, &path, Matte8::new(128));
+            let mut zero = Plotter::new(Raster::with_clear(40, 10));
+            zero.fill(rule, &path, Matte8::new(0));
+            assert!(full.raster().pixels().contains(&Matte8::new(255)));
+            for (f, h) in
+                full.raster().pixels().iter().zip(half.raster().pixels())
+            {
+                let v = u32::from(u8::from(f.alpha())) * 128;
+                assert_eq!(u8::from(h.alpha()), ((v + 127) / 255) as u8);
+            }
+            assert!(half.raster().pixels().contains(&Matte8::new(128)));
+            assert!(zero
+                .raster()
+                .pixels()
+                .iter()
+                .all(|p| *p == Matte8::new(0)));
+        }
+    }
+}
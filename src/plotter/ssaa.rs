@@ -0,0 +1,248 @@
+// ssaa.rs        Plotter supersampled fills.
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+use super::{is_deep, new_sgn_area, Drawn, Plotter};
+use crate::blend::BlendChannel;
+use crate::fig::Paint;
+use crate::path::{FillRule, PathOp};
+use pix::chan::{Linear, Premultiplied};
+use pix::el::Pixel;
+use pix::matte::Matte8;
+use pix::{Raster, Region};
+use std::borrow::Borrow;
+
+/// Supersampling buffers, reused between fills
+pub(super) struct Supersample {
+    /// Sample coverage, scaled up from pixels
+    samples: Raster<Matte8>,
+    /// Signed area buffer for samples
+    sgn_area: Vec<i16>,
+    /// Pixel coverage, filtered down from samples
+    matte: Raster<Matte8>,
+}
+
+impl Supersample {
+    /// Create supersampling buffers.
+    ///
+    /// * `width` Width in pixels.
+    /// * `height` Height in pixels.
+    /// * `factor` Number of samples per pixel in each dimension.
+    fn new(width: u32, height: u32, factor: u32) -> Self {
+        let (s_width, s_height) = (width * factor, height * factor);
+        Supersample {
+            samples: Raster::with_clear(s_width, s_height),
+            sgn_area: new_sgn_area(s_width),
+            matte: Raster::with_clear(width, height),
+        }
+    }
+
+    /// Check if buffers have the given dimensions.
+    fn fits(&self, width: u32, height: u32, factor: u32) -> bool {
+        self.matte.width() == width
+            && self.matte.height() == height
+            && self.samples.width() == width * factor
+    }
+}
+
+/// Box filter sample coverage down to pixel coverage.
+///
+/// Every sample has the same color, so averaging coverage is equivalent to
+/// averaging premultiplied color.
+///
+/// * `samples` Sample coverage.
+/// * `matte` Pixel coverage.
+/// * `factor` Number of samples per pixel in each dimension.
+/// * `reg` Region of pixels to filter.
+fn downsample(
+    samples: &Raster<Matte8>,
+    matte: &mut Raster<Matte8>,
+    factor: u32,
+    reg: Region,
+) {
+    let n = factor * factor;
+    let f = factor as usize;
+    let s_width = samples.width() as usize;
+    let (left, top) = (reg.left() as usize, reg.top() as usize);
+    for (y, row) in matte.rows_mut(reg).enumerate() {
+        let s_row = &samples.pixels()[(top + y) * f * s_width..];
+        for (x, p) in row.iter_mut().enumerate() {
+            let sx = (left + x) * f;
+            let sum: u32 = s_row
+                .chunks(s_width)
+                .take(f)
+                .flat_map(|r| &r[sx..sx + f])
+                .map(|s| u32::from(u8::from(s.alpha())))
+                .sum();
+            *p = Matte8::new(((sum + n / 2) / n) as u8);
+        }
+    }
+}
+
+impl<P> Plotter<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+    P::Chan: BlendChannel,
+{
+    /// Fill path onto the raster, with supersampling.
+    ///
+    /// Coverage is rendered at `factor` times the raster resolution, then box
+    /// filtered down.  This can improve quality for dense, thin geometry
+    /// (such as hatching or tiny text), where overlapping edges within a
+    /// pixel make analytic coverage inaccurate.  The cost is roughly `factor`
+    /// squared times that of [fill], plus a temporary matte of that many
+    /// bytes per pixel, which is reused by later calls.  The current blend
+    /// mode, opacity and clip are applied; anti-aliasing is always enabled.
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    /// * `clr` Color to fill.
+    /// * `factor` Samples per pixel in each dimension (2 or 4).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `factor` is not 2 or 4.
+    ///
+    /// [fill]: struct.Plotter.html#method.fill
+    pub fn fill_ssaa<T>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        clr: P,
+        factor: u8,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        self.drawn(|p| {
+            assert!(factor == 2 || factor == 4, "invalid factor: {}", factor);
+            let factor = u32::from(factor);
+            let (width, height) = (p.width(), p.height());
+            let mut ss = match p.ssaa.take() {
+                Some(ss) if ss.fits(width, height, factor) => ss,
+                _ => Supersample::new(width, height, factor),
+            };
+            let reg = p.raster.region();
+            p.plot_tiles(reg, ops, factor, factor, |p, fig, tile| {
+                let f = factor as i32;
+                let (x, y) = (tile.left() * f, tile.top() * f);
+                let (w, h) = (tile.width() * factor, tile.height() * factor);
+                let tile = Region::new(x, y, w, h);
+                let paint = Paint::new(Matte8::new(255));
+                let samples = &mut ss.samples;
+                let dirty = if is_deep(fig, rule) {
+                    let mut sgn_area = vec![0; w as usize];
+                    let sgn_area = &mut sgn_area[..];
+                    fig.fill_region::<_, i32>(
+                        rule, samples, tile, paint, sgn_area,
+                    )
+                } else {
+                    let sgn_area = &mut ss.sgn_area[..];
+                    fig.fill_region(rule, samples, tile, paint, sgn_area)
+                };
+                if let Some(dirty) = dirty {
+                    let left = dirty.left() / f;
+                    let top = dirty.top() / f;
+                    let right = (dirty.right() + f - 1) / f;
+                    let bottom = (dirty.bottom() + f - 1) / f;
+                    let (w, h) = ((right - left) as u32, (bottom - top) as u32);
+                    let reg = Region::new(left, top, w, h);
+                    downsample(&ss.samples, &mut ss.matte, factor, reg);
+                    p.composite_coverage(&ss.matte, clr, reg);
+                    // Clear samples for the next call
+                    let reg =
+                        Region::new(left * f, top * f, w * factor, h * factor);
+                    for row in ss.samples.rows_mut(reg) {
+                        row.iter_mut().for_each(|s| *s = Matte8::new(0));
+                    }
+                }
+            });
+            p.ssaa = Some(ss);
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use pix::el::Pixel;
+    use pix::matte::Matte8;
+    use pix::rgb::Rgba8p;
+    use pix::{Raster, Region};
+
+    #[test]
+    fn downsample() {
+        // 2x2 pixels from 4x4 samples
+        let samples = Raster::<Matte8>::with_u8_buffer(
+            4,
+            4,
+            vec![
+                255, 0, 255, 255, //
+                0, 255, 255, 255, //
+                10, 20, 0, 0, //
+                30, 41, 0, 1,
+            ],
+        );
+        let mut matte = Raster::with_clear(2, 2);
+        super::downsample(&samples, &mut matte, 2, Region::new(0, 0, 2, 2));
+        assert_eq!(matte.as_u8_slice(), [128, 255, 25, 0]);
+        // Only region is filtered
+        let mut matte = Raster::with_clear(2, 2);
+        super::downsample(&samples, &mut matte, 2, Region::new(1, 0, 1, 2));
+        assert_eq!(matte.as_u8_slice(), [0, 255, 0, 0]);
+        // 1x1 pixel from 4x4 samples: (1632 + 8) / 16
+        let mut matte = Raster::with_clear(1, 1);
+        super::downsample(&samples, &mut matte, 4, Region::new(0, 0, 1, 1));
+        assert_eq!(matte.as_u8_slice(), [102]);
+    }
+
+    #[test]
+    fn fill_ssaa() {
+        // Dense diagonal hatch, with lines thinner than a pixel
+        let mut path = Path2D::default().absolute();
+        for i in 0..24 {
+            let x = i as f32 * 1.5 - 12.0;
+            path = path
+                .move_to(x, 0.0)
+                .line_to(x + 0.5, 0.0)
+                .line_to(x + 16.5, 16.0)
+                .line_to(x + 16.0, 16.0)
+                .close();
+        }
+        let path = path.finish();
+        let clr = Rgba8p::new(0, 100, 200, 255);
+        let mut a = Plotter::new(Raster::with_clear(16, 16));
+        a.fill(FillRule::NonZero, &path, clr);
+        let mut b = Plotter::new(Raster::with_clear(16, 16));
+        for factor in [4, 2] {
+            b.clear().take_dirty();
+            b.fill_ssaa(FillRule::NonZero, &path, clr, factor);
+            assert_eq!(b.take_dirty(), Some(Region::new(0, 0, 16, 16)));
+            let mut total = (0, 0);
+            for (pa, pb) in a.raster().pixels().iter().zip(b.raster().pixels())
+            {
+                let (aa, ab) = (u8::from(pa.alpha()), u8::from(pb.alpha()));
+                assert!((i32::from(aa) - i32::from(ab)).abs() <= 32);
+                total = (total.0 + u32::from(aa), total.1 + u32::from(ab));
+            }
+            let diff = (total.0 as f32 - total.1 as f32).abs();
+            assert!(diff < total.0 as f32 * 0.02, "{:?}", total);
+        }
+        // Samples are cleared between calls
+        let rect = Path2D::default()
+            .absolute()
+            .move_to(2.0, 2.0)
+            .line_to(4.0, 2.0)
+            .line_to(4.0, 4.0)
+            .line_to(2.0, 4.0)
+            .close()
+            .finish();
+        b.clear().fill_ssaa(FillRule::NonZero, &rect, clr, 2);
+        for (i, p) in b.raster().pixels().iter().enumerate() {
+            let (x, y) = (i % 16, i / 16);
+            let inside = (2..4).contains(&x) && (2..4).contains(&y);
+            assert_eq!(*p, if inside { clr } else { Rgba8p::default() });
+        }
+    }
+}
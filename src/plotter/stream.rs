@@ -0,0 +1,197 @@
+// stream.rs      Plotter streamed fills.
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+use super::{Drawn, FillSource, PlotDest, Plotter, TILE_MAX};
+use crate::blend::BlendChannel;
+use crate::fig::{EdgeStream, Metrics, Paint, SgnArea};
+use crate::geom::WidePt;
+use crate::path::{FillRule, PathOp};
+use pix::chan::{Linear, Premultiplied};
+use pix::el::Pixel;
+use pix::{Raster, Region};
+use std::borrow::Borrow;
+
+impl FillSource for EdgeStream {
+    fn max_winding(&self) -> u32 {
+        EdgeStream::max_winding(self)
+    }
+    fn fill_area<P, A>(
+        &mut self,
+        rule: FillRule,
+        raster: &mut Raster<P>,
+        reg: Region,
+        paint: Paint<P>,
+        sgn_area: &mut [A],
+        metrics: Option<&mut Metrics>,
+    ) -> Option<Region>
+    where
+        P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+        P::Chan: BlendChannel,
+        A: SgnArea,
+    {
+        self.fill_band(rule, raster, reg, paint, sgn_area, metrics)
+    }
+}
+
+impl PlotDest for EdgeStream {
+    fn add_point(&mut self, pt: WidePt) {
+        EdgeStream::add_point(self, pt.0);
+    }
+    fn close(&mut self, _joined: bool) {
+        EdgeStream::close(self);
+    }
+}
+
+impl<P> Plotter<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+    P::Chan: BlendChannel,
+{
+    /// Fill path onto the raster, streaming its points without storing them.
+    ///
+    /// The path is plotted once to count the edges starting on each row, then
+    /// again for each band of rows, so memory depends on the raster height and
+    /// the number of edges crossing a row, rather than the number of points.
+    /// Most paths are plotted twice.  This is useful for generated paths with
+    /// millions of points, which would otherwise be collected.
+    ///
+    /// Rasters over 8,192 pixels wide or high are filled the same as [fill].
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator, cloned for each pass.
+    /// * `clr` Color to fill.
+    ///
+    /// ```rust
+    /// use footile::{FillRule, PathOp, Plotter};
+    /// use pix::{matte::Matte8, Raster};
+    ///
+    /// let zigzag = (0..=1000).map(|i| {
+    ///     let x = if i % 2 == 0 { 4.0 } else { 12.0 };
+    ///     PathOp::Line(pointy::Pt::new(x, i as f32 / 100.0))
+    /// });
+    /// let ops = std::iter::once(PathOp::Move(pointy::Pt::new(4.0, 0.0)))
+    ///     .chain(zigzag)
+    ///     .chain([PathOp::Line(pointy::Pt::new(4.0, 10.0))]);
+    /// let mut p = Plotter::new(Raster::<Matte8>::with_clear(16, 16));
+    /// p.fill_streamed(FillRule::NonZero, ops, Matte8::new(255));
+    /// ```
+    ///
+    /// [fill]: struct.Plotter.html#method.fill
+    pub fn fill_streamed<T>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        clr: P,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator + Clone,
+        T::Item: Borrow<PathOp>,
+    {
+        self.drawn(|p| {
+            if p.width() > TILE_MAX || p.height() > TILE_MAX {
+                p.fill(rule, ops, clr);
+                return;
+            }
+            let reg = match p.scissor_region(p.raster.region()) {
+                Some(reg) => reg,
+                None => return,
+            };
+            // Points are relative to the region origin
+            let mut stream =
+                EdgeStream::new(reg.height(), -reg.left(), -reg.top());
+            p.add_ops(ops.clone(), &mut stream);
+            stream.close();
+            if let Some(m) = &mut p.metrics {
+                *m = stream.metrics();
+            }
+            while stream.next_band() {
+                p.add_ops(ops.clone(), &mut stream);
+                stream.close();
+                p.fill_fig_region(&mut stream, rule, clr, reg);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    use pix::matte::Matte8;
+    use pix::rgb::Rgba8p;
+    use pix::Raster;
+    use pointy::Pt;
+
+    #[test]
+    fn fill_streamed() {
+        let fish = Path2D::default()
+            .relative()
+            .move_to(40.0, 8.0)
+            .line_to(-24.0, 16.0)
+            .cubic_to(-32.0, -24.0, -32.0, 40.0, 0.0, 16.0)
+            .line_to(24.0, 16.0)
+            .line_to(-16.0, -24.0)
+            .close()
+            .absolute()
+            .move_to(-20_000.0, 30.0)
+            .line_to(20.0, 62.5)
+            .line_to(30.0, -90_000.0)
+            .close()
+            .move_to(50.0, -30.0)
+            .line_to(60.0, -10.0)
+            .line_to(55.0, 0.0)
+            .finish();
+        let settings = [
+            (FillRule::NonZero, (0.0, 0.0), None),
+            (FillRule::EvenOdd, (0.0, 0.0), None),
+            (FillRule::NonZero, (3.3, -20.7), None),
+            (FillRule::EvenOdd, (-10.2, 40.1), Some((9, 17, 40, 30))),
+        ];
+        for (rule, (x, y), scissor) in settings {
+            let mut p = Plotter::new(Raster::<Rgba8p>::with_clear(64, 64));
+            p.set_viewport(x, y).set_scissor(scissor).set_metrics(true);
+            let clr = Rgba8p::new(40, 80, 160, 255);
+            p.fill(rule, &fish, clr);
+            let metrics = p.last_metrics();
+            let filled = p.raster().as_u8_slice().to_vec();
+            p.clear().fill_streamed(rule, &fish, clr);
+            assert_eq!(p.raster().as_u8_slice(), filled, "{rule:?} {x},{y}");
+            let streamed = p.last_metrics();
+            assert_eq!(streamed.points, metrics.points);
+            assert_eq!(streamed.rows_scanned, metrics.rows_scanned);
+        }
+    }
+
+    #[test]
+    fn fill_streamed_zigzag() {
+        // Passes are counted by Move ops
+        let passes = std::cell::Cell::new(0);
+        let n = 1_000_000;
+        let zigzag = (0..=n).map(|i| {
+            let x = if i % 2 == 0 { 8.0 } else { 12.0 };
+            let y = 4.0 + 56.0 * i as f32 / n as f32;
+            PathOp::Line(Pt::new(x, y))
+        });
+        // Zigzag along the left side of a square
+        let ops = std::iter::once(PathOp::Move(Pt::new(8.0, 4.0)))
+            .inspect(|_| passes.set(passes.get() + 1))
+            .chain(zigzag)
+            .chain([
+                PathOp::Line(Pt::new(56.0, 60.0)),
+                PathOp::Line(Pt::new(56.0, 4.0)),
+                PathOp::Close(),
+            ]);
+        let mut p = Plotter::new(Raster::with_clear(64, 64));
+        p.set_metrics(true);
+        p.fill_streamed(FillRule::NonZero, ops.clone(), Matte8::new(255));
+        let streamed = p.raster().as_u8_slice().to_vec();
+        // Only edges crossing a row are kept at once
+        assert!(p.last_metrics().max_active_edges < 64);
+        // One pass to count edges, and one for each band of rows
+        assert!(passes.get() > 2);
+        p.clear().fill(FillRule::NonZero, ops, Matte8::new(255));
+        assert_eq!(p.raster().as_u8_slice(), streamed);
+        assert_eq!(p.raster().pixel(32, 32), Matte8::new(255));
+    }
+}
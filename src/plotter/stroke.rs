@@ -0,0 +1,1202 @@
+// stroke.rs      Plotter strokes.
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+use super::{tiles, Drawn, Plotter, Tee};
+use crate::blend::BlendChannel;
+use crate::error::Error;
+use crate::path::{FillRule, PathOp};
+use crate::stroker::{Stroke, StrokeOptions, StrokeUnits};
+use pix::chan::{Linear, Premultiplied};
+use pix::el::Pixel;
+use pix::Raster;
+use pointy::{Pt, Transform};
+use std::borrow::Borrow;
+
+impl<P> Plotter<P>
+where
+    P: Pixel<Alpha = Premultiplied, Gamma = Linear> + Send + Sync,
+    P::Chan: BlendChannel,
+{
+    /// Stroke path onto the raster.
+    ///
+    /// The stroke outline is filled in a single pass, so regions where the
+    /// stroke overlaps itself are only covered once, even with translucent
+    /// colors.
+    ///
+    /// * `ops` PathOp iterator.
+    /// * `clr` Color to stroke.
+    pub fn stroke<T>(&mut self, ops: T, clr: P) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        self.drawn(|p| {
+            let mut stroke = p.take_stroke();
+            p.add_ops(ops, &mut stroke);
+            p.fill_stroke_outline(&mut stroke, clr);
+            p.stroke = Some(stroke);
+        })
+    }
+
+    /// Stroke path onto the raster, with an extra transform for this call
+    /// only.
+    ///
+    /// The transform `t` is applied to the path before the plotter's
+    /// transform, which is left unchanged.  With [StrokeUnits::UserSpace],
+    /// pen widths are scaled by the combined transform.
+    ///
+    /// * `ops` PathOp iterator.
+    /// * `t` Transform applied before the plotter's transform.
+    /// * `clr` Color to stroke.
+    ///
+    /// [StrokeUnits::UserSpace]: enum.StrokeUnits.html#variant.UserSpace
+    pub fn stroke_transformed<T>(
+        &mut self,
+        ops: T,
+        t: &Transform<f32>,
+        clr: P,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        self.drawn(|p| {
+            p.with_transform(t, |p| {
+                p.stroke(ops, clr);
+            });
+        })
+    }
+
+    /// Stroke path onto the raster, checking for invalid input.
+    ///
+    /// Unlike [stroke], this returns an error instead of panicking or
+    /// plotting garbage when coordinates or pen widths are NaN, infinite or
+    /// too large after transform, when the path or stroke outline has too
+    /// many points, or when the raster is empty.  Nothing is drawn if an error
+    /// is returned.
+    ///
+    /// * `ops` PathOp iterator.
+    /// * `clr` Color to stroke.
+    ///
+    /// [stroke]: struct.Plotter.html#method.stroke
+    pub fn try_stroke<T>(
+        &mut self,
+        ops: T,
+        clr: P,
+    ) -> Result<&mut Raster<P>, Error>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        self.check_raster()?;
+        let mut stroke = self.take_stroke();
+        let mut res = self.try_add_ops(ops, &mut stroke);
+        if res.is_ok() && stroke.dropped_points() > 0 {
+            res = Err(Error::TooManyPoints);
+        }
+        if res.is_ok() {
+            let mut ops = std::mem::take(&mut self.stroke_ops);
+            ops.clear();
+            self.stroke_outline(&mut stroke, &mut ops);
+            res = self.with_raster_coords(|p| {
+                p.try_fill_ops(FillRule::NonZero, &ops, clr)
+            });
+            self.stroke_ops = ops;
+        }
+        self.stroke = Some(stroke);
+        res.map(move |_| &mut self.raster)
+    }
+
+    /// Fill the outline of a stroke onto the raster.
+    fn fill_stroke_outline(&mut self, stroke: &mut Stroke, clr: P) {
+        let mut ops = std::mem::take(&mut self.stroke_ops);
+        ops.clear();
+        self.stroke_outline(stroke, &mut ops);
+        self.fill_raster_coords(FillRule::NonZero, &ops, clr);
+        self.stroke_ops = ops;
+    }
+
+    /// Stroke path onto the raster, with options for this call only.
+    ///
+    /// The options replace the plotter's join style, cap style and
+    /// (optionally) pen width and tolerance, which are restored afterwards.
+    /// Other stroke settings, such as markers, still apply.
+    ///
+    /// * `ops` PathOp iterator.
+    /// * `opts` Stroke options.
+    /// * `clr` Color to stroke.
+    pub fn stroke_opts<T>(
+        &mut self,
+        ops: T,
+        opts: &StrokeOptions,
+        clr: P,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        self.drawn(|p| {
+            p.save_state();
+            p.set_join(opts.join).set_cap(opts.cap);
+            if let Some(t) = opts.tolerance {
+                p.set_tolerance(t);
+                p.stroke_tol_sq = None;
+            }
+            match opts.width_override {
+                Some(w) => {
+                    p.pen_width(w);
+                    let ops: Vec<PathOp> = ops
+                        .into_iter()
+                        .map(|op| match *op.borrow() {
+                            PathOp::PenWidth(_) => PathOp::PenWidth(w),
+                            op => op,
+                        })
+                        .collect();
+                    p.stroke(&ops, clr);
+                }
+                None => {
+                    p.stroke(ops, clr);
+                }
+            }
+            p.restore_state();
+        })
+    }
+
+    /// Take the scratch stroke (or a new one, if in use), reset with current
+    /// styles.
+    pub(super) fn take_stroke(&mut self) -> Stroke {
+        let tol_sq = self.stroke_tol_sq.unwrap_or(self.tol_sq);
+        let (js, cs) = (self.join_style, self.cap_style);
+        let mut stroke = match self.stroke.take() {
+            Some(mut stroke) => {
+                stroke.reset(js, cs, tol_sq);
+                stroke
+            }
+            None => Stroke::new(js, cs, tol_sq),
+        };
+        stroke.set_markers(self.start_marker.clone(), self.end_marker.clone());
+        stroke.set_curves(
+            self.stroke_curves
+                && self.start_marker.is_none()
+                && self.end_marker.is_none(),
+        );
+        stroke
+    }
+
+    /// Stroke path onto the raster with a width profile.
+    ///
+    /// Pen widths from the path are replaced by the result of the profile
+    /// function, which is called with the fraction of sub-path length at each
+    /// point (from 0 at the start to 1 at the end).  Returning 0 at the ends
+    /// produces pointed tips.
+    ///
+    /// * `ops` PathOp iterator.
+    /// * `profile` Width profile function.
+    /// * `clr` Color to stroke.
+    pub fn stroke_profile<T, F>(
+        &mut self,
+        ops: T,
+        profile: F,
+        clr: P,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+        F: Fn(f32) -> f32,
+    {
+        self.drawn(|p| {
+            let mut stroke = p.take_stroke();
+            stroke.set_curves(false);
+            p.add_ops(ops, &mut stroke);
+            stroke.apply_profile(|t| profile(t) * p.w_scale);
+            p.fill_stroke_outline(&mut stroke, clr);
+            p.stroke = Some(stroke);
+        })
+    }
+
+    /// Stroke path onto the raster with a flat (calligraphic) nib.
+    ///
+    /// The stroke is the sweep of a flat pen held at a fixed angle, so its
+    /// width depends on the direction of the path relative to the nib.  Pen
+    /// widths from the path are ignored.
+    ///
+    /// * `ops` PathOp iterator.
+    /// * `nib_width` Width of nib (in stroke units).
+    /// * `nib_angle` Angle of nib, in radians.
+    /// * `clr` Color to stroke.
+    pub fn stroke_nib<T>(
+        &mut self,
+        ops: T,
+        nib_width: f32,
+        nib_angle: f32,
+        clr: P,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        self.drawn(|p| {
+            let mut nib = Pt::from_angle(nib_angle) * (nib_width / 2.0);
+            if p.stroke_units == StrokeUnits::UserSpace {
+                nib = p.transform * nib - p.transform * Pt::default();
+            }
+            let mut stroke = p.take_stroke();
+            stroke.set_curves(false);
+            p.add_ops(ops, &mut stroke);
+            let ops = stroke.nib_ops(nib);
+            p.stroke = Some(stroke);
+            p.fill_raster_coords(FillRule::NonZero, &ops, clr);
+        })
+    }
+
+    /// Create the outline of a stroked path.
+    ///
+    /// The outline is made of closed sub-paths in raster coordinates (with
+    /// the transform and viewport already applied).  Filling it with the
+    /// `NonZero` rule and an identity transform produces the same result as
+    /// [stroke].
+    ///
+    /// * `ops` PathOp iterator.
+    ///
+    /// [stroke]: struct.Plotter.html#method.stroke
+    pub fn stroke_path<T>(&mut self, ops: T) -> Vec<PathOp>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        let mut stroke = self.take_stroke();
+        self.add_ops(ops, &mut stroke);
+        let mut outline = vec![];
+        self.stroke_outline(&mut stroke, &mut outline);
+        self.stroke = Some(stroke);
+        outline
+    }
+
+    /// Append the outline of a stroke to path ops.
+    pub(super) fn stroke_outline(
+        &self,
+        stroke: &mut Stroke,
+        ops: &mut Vec<PathOp>,
+    ) {
+        if self.stroke_snap {
+            stroke.snap_to_pixels();
+        }
+        stroke.path_ops(ops);
+    }
+
+    /// Fill and then stroke a path onto the raster.
+    ///
+    /// The path is decomposed only once (unless the raster is filled in
+    /// tiles), but the result is the same as calling [fill] and then
+    /// [stroke].
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    /// * `fill_clr` Color to fill.
+    /// * `stroke_clr` Color to stroke.
+    ///
+    /// [fill]: struct.Plotter.html#method.fill
+    /// [stroke]: struct.Plotter.html#method.stroke
+    pub fn fill_stroke<T>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        fill_clr: P,
+        stroke_clr: P,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        self.drawn(|p| {
+            let mut stroke = p.take_stroke();
+            let tiled = tiles(p.raster.region(), 1, 1).is_some();
+            if stroke.curves() || tiled {
+                p.stroke = Some(stroke);
+                // Stroke curves cannot be shared with fill, nor can figures
+                // be shared between tiles
+                let ops: Vec<PathOp> =
+                    ops.into_iter().map(|op| *op.borrow()).collect();
+                p.fill(rule, &ops, fill_clr);
+                p.stroke(&ops, stroke_clr);
+                return;
+            }
+            let mut fig = p.take_fig();
+            p.add_ops(ops, &mut Tee(&mut fig, &mut stroke));
+            // Closing figure required to handle coincident start/end points
+            fig.close();
+            p.fill_fig(&mut fig, rule, fill_clr);
+            p.fig = Some(fig);
+            p.fill_stroke_outline(&mut stroke, stroke_clr);
+            p.stroke = Some(stroke);
+        })
+    }
+
+    /// Draw a path onto the raster, with optional fill and stroke colors.
+    ///
+    /// The fill is drawn first, so the stroke is rendered over it where
+    /// they overlap.  When both colors are given, this is the same as
+    /// [fill_stroke]; a `None` color skips that stage.
+    ///
+    /// * `rule` Fill rule.
+    /// * `ops` PathOp iterator.
+    /// * `fill` Color to fill, or `None`.
+    /// * `stroke` Color to stroke, or `None`.
+    ///
+    /// [fill_stroke]: struct.Plotter.html#method.fill_stroke
+    pub fn draw<T>(
+        &mut self,
+        rule: FillRule,
+        ops: T,
+        fill: Option<P>,
+        stroke: Option<P>,
+    ) -> Drawn<'_, P>
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        match (fill, stroke) {
+            (Some(fill), Some(stroke)) => {
+                self.fill_stroke(rule, ops, fill, stroke)
+            }
+            (Some(fill), None) => self.fill(rule, ops, fill),
+            (None, Some(stroke)) => self.stroke(ops, stroke),
+            (None, None) => self.drawn(|_| ()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use pix::el::Pixel;
+    use pix::matte::Matte8;
+    use pix::rgb::Rgba8p;
+    use pix::Raster;
+    use pointy::{Pt, Transform};
+
+    #[test]
+    fn caps() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(6.0)
+            .move_to(10.0, 8.0)
+            .line_to(30.0, 8.0)
+            .finish();
+        let row = |cap| {
+            let r = Raster::with_clear(40, 16);
+            let mut p = Plotter::new(r);
+            p.set_cap(cap);
+            let r = p.stroke(&path, Matte8::new(255));
+            r.as_u8_slice()[8 * 40..9 * 40].to_vec()
+        };
+        let butt = row(CapStyle::Butt);
+        assert_eq!(&butt[8..12], [0, 0, 255, 255]);
+        assert_eq!(&butt[28..32], [255, 255, 0, 0]);
+        let round = row(CapStyle::Round);
+        assert_eq!(&round[8..12], [255, 255, 255, 255]);
+        assert_eq!(&round[28..32], [255, 255, 255, 255]);
+        assert_eq!(round[6], 0);
+        let square = row(CapStyle::Square);
+        assert_eq!(&square[6..12], [0, 255, 255, 255, 255, 255]);
+        assert_eq!(&square[28..34], [255, 255, 255, 255, 255, 0]);
+    }
+
+    #[test]
+    fn dots() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(6.0)
+            .move_to(8.0, 8.0)
+            .move_to(24.0, 8.0)
+            .move_to(40.0, 8.0)
+            .close()
+            .move_to(56.0, 8.0)
+            .line_to(56.0, 8.0)
+            .move_to(72.0, 8.0)
+            .finish();
+        for cap in [CapStyle::Round, CapStyle::Square] {
+            let r = Raster::with_clear(80, 16);
+            let mut p = Plotter::new(r);
+            p.set_cap(cap);
+            let r = p.stroke(&path, Matte8::new(255));
+            let row = &r.as_u8_slice()[8 * 80..9 * 80];
+            let mut blobs = 0;
+            for (i, v) in row.iter().enumerate() {
+                if *v > 0 && (i == 0 || row[i - 1] == 0) {
+                    blobs += 1;
+                }
+            }
+            assert_eq!(blobs, 5);
+            assert_eq!(row[8], 255);
+            assert_eq!(row[16], 0);
+        }
+        let r = Raster::with_clear(80, 16);
+        let mut p = Plotter::new(r);
+        let r = p.stroke(&path, Matte8::new(255));
+        assert!(r.as_u8_slice().iter().all(|v| *v == 0));
+    }
+
+    /// Paths from the stroke examples
+    fn stroke_examples() -> Vec<Vec<PathOp>> {
+        vec![
+            Path2D::default()
+                .relative()
+                .pen_width(5.0)
+                .move_to(16.0, 48.0)
+                .line_to(32.0, 0.0)
+                .line_to(-16.0, -32.0)
+                .close()
+                .finish(),
+            Path2D::default()
+                .relative()
+                .pen_width(6.0)
+                .move_to(16.0, 15.0)
+                .line_to(32.0, 1.0)
+                .line_to(-32.0, 1.0)
+                .line_to(32.0, 15.0)
+                .line_to(-32.0, 15.0)
+                .line_to(32.0, 1.0)
+                .line_to(-32.0, 1.0)
+                .finish(),
+            Path2D::default()
+                .relative()
+                .pen_width(8.0)
+                .move_to(32.0, 16.0)
+                .line_to(16.0, 16.0)
+                .line_to(-16.0, 16.0)
+                .line_to(-16.0, -16.0)
+                .line_to(16.0, -16.0)
+                .line_to(0.0, 32.0)
+                .finish(),
+            Path2D::default()
+                .relative()
+                .pen_width(2.0)
+                .move_to(8.0, 16.0)
+                .cubic_to(64.0, -16.0, 64.0, 48.0, 0.0, 32.0)
+                .finish(),
+            Path2D::default()
+                .relative()
+                .pen_width(2.0)
+                .move_to(0.0, 16.0)
+                .quad_to(100.0, 16.0, 0.0, 32.0)
+                .finish(),
+            Path2D::default()
+                .relative()
+                .pen_width(0.0)
+                .move_to(32.0, 24.0)
+                .pen_width(9.0)
+                .cubic_to(-32.0, -24.0, -32.0, 40.0, 0.0, 16.0)
+                .finish(),
+            Path2D::default()
+                .relative()
+                .pen_width(2.0)
+                .move_to(24.0, 48.0)
+                .line_to(8.0, 8.0)
+                .move_to(0.0, -8.0)
+                .line_to(-8.0, 8.0)
+                .finish(),
+        ]
+    }
+
+    #[test]
+    fn stroke_path() {
+        let joins = [JoinStyle::Miter(4.0), JoinStyle::Bevel, JoinStyle::Round];
+        for path in stroke_examples() {
+            for join in joins {
+                let mut p = Plotter::new(Raster::with_clear(64, 64));
+                p.set_join(join);
+                let outline = p.stroke_path(&path);
+                p.stroke(&path, Matte8::new(255));
+                let mut p2 = Plotter::new(Raster::with_clear(64, 64));
+                p2.fill(FillRule::NonZero, &outline, Matte8::new(255));
+                assert_eq!(p.raster().pixels(), p2.raster().pixels());
+            }
+        }
+    }
+
+    #[test]
+    fn stroke_transformed() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(2.0)
+            .move_to(4.0, 4.0)
+            .line_to(12.0, 4.0)
+            .finish();
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(32, 16));
+        p.set_transform(Transform::with_scale(2.0, 2.0));
+        let r = p.stroke(&path, Matte8::new(255));
+        assert_eq!(r.pixel(8, 8), Matte8::new(255));
+        assert_eq!(r.pixel(23, 8), Matte8::new(255));
+        assert_eq!(r.pixel(24, 8), Matte8::new(0));
+    }
+
+    #[test]
+    fn stroke_units() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(2.0)
+            .move_to(1.0, 4.0)
+            .line_to(6.0, 4.0)
+            .finish();
+        let thickness = |units, scale| {
+            let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+            p.set_stroke_units(units);
+            p.set_transform(Transform::with_scale(scale, scale));
+            let r = p.stroke(&path, Matte8::new(255));
+            let x = (3.0 * scale) as i32;
+            (0..64)
+                .filter(|y| r.pixel(x, *y) == Matte8::new(255))
+                .count()
+        };
+        assert_eq!(thickness(StrokeUnits::PixelSpace, 3.0), 2);
+        assert_eq!(thickness(StrokeUnits::PixelSpace, 6.0), 2);
+        assert_eq!(thickness(StrokeUnits::UserSpace, 3.0), 6);
+        assert_eq!(thickness(StrokeUnits::UserSpace, 6.0), 12);
+    }
+
+    #[test]
+    fn stroke_profile() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(4.0, 40.0)
+            .cubic_to(30.0, 0.0, 30.0, 64.0, 60.0, 24.0)
+            .finish();
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+        p.set_cap(CapStyle::Round);
+        p.set_join(JoinStyle::Round);
+        let profile = |t: f32| 12.0 * (t * std::f32::consts::PI).sin();
+        let r = p.stroke_profile(&path, profile, Matte8::new(255));
+        let px = r.as_u8_slice();
+        let column =
+            |x: usize| (0..64).map(|y| u32::from(px[y * 64 + x])).sum::<u32>();
+        // Tips are pointed
+        assert_eq!(column(3), 0);
+        assert!(column(5) < 255 * 3);
+        assert!(column(58) < 255 * 3);
+        assert_eq!(column(60), 0);
+        // Width increases toward the middle
+        assert!(column(8) < column(24));
+        assert!(column(32) > 255 * 13);
+        assert!(column(48) > column(56));
+    }
+
+    #[test]
+    fn stroke_overlap() {
+        let path = Path2D::default()
+            .relative()
+            .pen_width(8.0)
+            .move_to(32.0, 16.0)
+            .line_to(16.0, 16.0)
+            .line_to(-16.0, 16.0)
+            .line_to(-16.0, -16.0)
+            .line_to(16.0, -16.0)
+            .line_to(0.0, 32.0)
+            .finish();
+        let clr = Rgba8p::new(64, 32, 0, 128);
+        let mut p = Plotter::new(Raster::with_clear(64, 64));
+        let r = p.stroke(&path, clr);
+        // Overlap at bottom of diamond
+        assert_eq!(r.pixel(32, 46), clr);
+        // Diamond only
+        assert_eq!(r.pixel(16, 32), clr);
+        // Vertical line only
+        assert_eq!(r.pixel(32, 32), clr);
+        assert!(r.pixels().iter().all(|p| p.alpha() <= clr.alpha()));
+    }
+
+    #[test]
+    fn markers() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(4.0)
+            .move_to(8.0, 16.0)
+            .line_to(56.0, 16.0)
+            .finish();
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 32));
+        p.set_markers(Some(Marker::arrow()), Some(Marker::arrow()));
+        let ops = p.stroke_path(&path);
+        assert!(ops.contains(&PathOp::Move(Pt::new(56.0, 16.0))));
+        assert!(ops.contains(&PathOp::Move(Pt::new(8.0, 16.0))));
+        let r = p.stroke(&path, Matte8::new(255));
+        let px = |x: usize, y: usize| r.as_u8_slice()[y * 64 + x];
+        // Arrow tips
+        assert!(px(55, 15) > 0 && px(55, 16) > 0);
+        assert_eq!(px(56, 15), 0);
+        assert!(px(8, 15) > 0 && px(8, 16) > 0);
+        assert_eq!(px(7, 15), 0);
+        // Arrow bases are wider than the stroke
+        assert_eq!(px(45, 11), 255);
+        assert_eq!(px(45, 20), 255);
+        assert_eq!(px(18, 11), 255);
+        assert_eq!(px(32, 13), 0);
+        assert_eq!(px(32, 14), 255);
+        // Arrow on the end of a curve
+        let path = Path2D::default()
+            .relative()
+            .pen_width(2.0)
+            .move_to(8.0, 16.0)
+            .cubic_to(64.0, -16.0, 64.0, 48.0, 0.0, 32.0)
+            .finish();
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+        p.set_markers(None, Some(Marker::arrow()));
+        let ops = p.stroke_path(&path);
+        assert!(ops.contains(&PathOp::Move(Pt::new(8.0, 48.0))));
+        let r = p.stroke(&path, Matte8::new(255));
+        let px = |x: usize, y: usize| r.as_u8_slice()[y * 64 + x];
+        assert!(px(8, 47) > 0);
+        assert_eq!(px(6, 47), 0);
+        assert_eq!(px(6, 48), 0);
+        // No markers on closed sub-paths
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(4.0)
+            .move_to(8.0, 8.0)
+            .line_to(56.0, 8.0)
+            .line_to(56.0, 24.0)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 32));
+        let plain = p.stroke_path(&path);
+        p.set_markers(Some(Marker::arrow()), Some(Marker::arrow()));
+        assert_eq!(p.stroke_path(&path), plain);
+    }
+
+    #[test]
+    fn round_join_scaled() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(4.0)
+            .move_to(1.0, 1.0)
+            .line_to(5.0, 1.0)
+            .line_to(5.0, 5.0)
+            .finish();
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+        p.set_transform(Transform::with_scale(10.0, 10.0))
+            .set_stroke_units(StrokeUnits::UserSpace)
+            .set_join(JoinStyle::Round);
+        let center = Pt::new(50.0, 10.0);
+        let arc: Vec<Pt<f32>> = p
+            .stroke_path(&path)
+            .iter()
+            .filter_map(|op| match op {
+                PathOp::Line(pt) => Some(*pt),
+                _ => None,
+            })
+            // Outer corner of join only
+            .filter(|pt| pt.x() >= 50.0 && pt.y() <= 10.0)
+            .filter(|pt| (pt.dist(center) - 20.0).abs() < 0.01)
+            .collect();
+        assert!(arc.len() > 4);
+        for pts in arc.windows(2) {
+            let mid = pts[0].midpoint(pts[1]);
+            assert!(20.0 - mid.dist(center) <= 0.3);
+        }
+    }
+
+    #[test]
+    fn round_join_wide() {
+        // Subdividing the arcs of very wide strokes must terminate
+        for width in [1e20, 3e38, f32::INFINITY] {
+            let path = Path2D::default()
+                .absolute()
+                .pen_width(width)
+                .move_to(1.0, 1.0)
+                .line_to(5.0, 1.0)
+                .line_to(5.0, 5.0)
+                .finish();
+            let mut p = Plotter::new(Raster::<Matte8>::with_clear(8, 8));
+            p.set_join(JoinStyle::Round).set_cap(CapStyle::Round);
+            let ops = p.stroke_path(&path);
+            assert!(ops.len() < 1 << 20);
+        }
+    }
+
+    #[test]
+    fn stroke_tolerance() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(16.0)
+            .move_to(8.0, 8.0)
+            .quad_to(56.0, 8.0, 56.0, 56.0)
+            .line_to(8.0, 56.0)
+            .finish();
+        let count = |tol, stroke_tol: Option<f32>| {
+            let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+            p.set_join(JoinStyle::Round).set_tolerance(tol);
+            if let Some(t) = stroke_tol {
+                p.set_stroke_tolerance(t);
+            }
+            p.stroke_path(&path).len()
+        };
+        let fine = count(0.1, None);
+        let coarse = count(2.0, None);
+        assert_eq!(count(0.1, Some(0.1)), fine);
+        assert!(fine > coarse);
+        // Stroke tolerance only affects the join
+        let fine_join = count(2.0, Some(0.1));
+        let coarse_join = count(0.1, Some(2.0));
+        assert!(fine_join > coarse);
+        assert!(coarse_join > coarse);
+        assert!(fine_join < fine);
+        assert!(coarse_join < fine);
+    }
+
+    #[test]
+    fn miter_jitter() {
+        let mut seed = 12345u32;
+        let mut jitter = |scale: f32| {
+            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+            ((seed >> 16) as f32 / 65536.0 - 0.5) * scale
+        };
+        for scale in [1.0, 0.01, 0.0001] {
+            let mut path = Path2D::default().absolute().pen_width(4.0);
+            path = path.move_to(0.0, 32.0);
+            for i in 1..64 {
+                path = path.line_to(i as f32, 32.0 + jitter(scale));
+            }
+            let path = path.finish();
+            let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+            p.set_join(JoinStyle::Miter(4.0));
+            for op in p.stroke_path(&path) {
+                if let PathOp::Move(pt) | PathOp::Line(pt) = op {
+                    assert!((pt.y() - 32.0).abs() <= 4.0 * 4.0 + scale);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn miter_limit() {
+        // Maximum distance of outline from the join point
+        let extent = |angle: f32| {
+            let path = Path2D::default()
+                .absolute()
+                .pen_width(4.0)
+                .move_to(0.0, 32.0)
+                .line_to(32.0, 32.0)
+                .line_to(32.0 - angle.cos() * 16.0, 32.0 - angle.sin() * 16.0)
+                .finish();
+            let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+            p.set_join(JoinStyle::Miter(4.0));
+            let mut ext: f32 = 0.0;
+            for op in p.stroke_path(&path) {
+                if let PathOp::Move(pt) | PathOp::Line(pt) = op {
+                    let d = pt.dist(Pt::new(32.0, 32.0));
+                    if d < 12.0 {
+                        ext = ext.max(d);
+                    }
+                }
+            }
+            ext
+        };
+        let mut prev = extent(0.7);
+        for i in 1..1000 {
+            let ext = extent(0.7 - i as f32 * 0.0005);
+            assert!(ext <= 4.0 * 4.0);
+            assert!((ext - prev).abs() < 0.1);
+            prev = ext;
+        }
+    }
+
+    #[test]
+    fn miter_limit_negative() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(4.0)
+            .move_to(4.0, 28.0)
+            .line_to(16.0, 4.0)
+            .line_to(28.0, 28.0)
+            .finish();
+        let mut a = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        let mut b = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        a.set_join(JoinStyle::Miter(-1.0));
+        b.set_join(JoinStyle::Miter(0.0));
+        assert_eq!(a.join_style, JoinStyle::Miter(0.0));
+        a.stroke(&path, Matte8::new(255));
+        b.stroke(&path, Matte8::new(255));
+        assert_eq!(a.raster().pixels(), b.raster().pixels());
+    }
+
+    #[test]
+    fn stroke_curves() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(6.0)
+            .move_to(8.0, 32.0)
+            .cubic_to(8.0, 0.0, 56.0, 0.0, 56.0, 32.0)
+            .quad_to(56.0, 56.0, 32.0, 56.0)
+            .cubic_to(16.0, 56.0, 24.0, 40.0, 8.0, 32.0)
+            .close()
+            .move_to(16.0, 16.0)
+            .quad_to(32.0, 8.0, 48.0, 16.0)
+            .finish();
+        for (js, cs) in [
+            (JoinStyle::Miter(4.0), CapStyle::Butt),
+            (JoinStyle::Round, CapStyle::Round),
+            (JoinStyle::Bevel, CapStyle::Square),
+        ] {
+            let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 64));
+            p.set_join(js).set_cap(cs).set_tolerance(0.1);
+            let lines = p.stroke_path(&path);
+            let expected = p.stroke(&path, Matte8::new(255)).clone();
+            p.set_stroke_curves(true);
+            p.raster_mut().clear();
+            let curves = p.stroke_path(&path);
+            assert!(curves.len() * 4 < lines.len());
+            assert!(curves.iter().any(|op| matches!(op, PathOp::Cubic(..))));
+            let r = p.stroke(&path, Matte8::new(255));
+            let mut total = 0;
+            for (a, b) in r.as_u8_slice().iter().zip(expected.as_u8_slice()) {
+                let diff = (i32::from(*a) - i32::from(*b)).abs();
+                assert!(diff < 128);
+                total += diff;
+            }
+            assert!(total < 255 * 12);
+        }
+    }
+
+    #[test]
+    fn width_change_join() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(4.0)
+            .move_to(4.0, 8.0)
+            .line_to(20.0, 8.0)
+            .pen_width(8.0)
+            .line_to(20.0, 28.0)
+            .finish();
+        let near = |ops: &[PathOp], x: f32, y: f32| {
+            ops.iter().any(|op| match op {
+                PathOp::Line(pt) => pt.dist(Pt::new(x, y)) < 0.001,
+                _ => false,
+            })
+        };
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        // Miter points are on both (non-parallel) offset edges
+        let ops = p.stroke_path(&path);
+        assert!(near(&ops, 21.8, 6.0));
+        assert!(near(&ops, 17.8, 10.0));
+        let r = p.stroke(&path, Matte8::new(255));
+        let px = |x: usize, y: usize| r.as_u8_slice()[y * 32 + x];
+        assert!(px(17, 10) > 50 && px(17, 10) < 80);
+        assert!(px(21, 6) > 200 && px(21, 6) < 240);
+        assert_eq!(px(22, 6), 0);
+    }
+
+    #[test]
+    fn stroke_snap() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(1.0)
+            .move_to(2.0, 10.3)
+            .line_to(30.0, 10.3)
+            .pen_width(2.0)
+            .move_to(5.7, 14.0)
+            .line_to(5.7, 30.0)
+            .finish();
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        p.set_stroke_snap(true);
+        let r = p.stroke(&path, Matte8::new(255));
+        let px = |x: usize, y: usize| r.as_u8_slice()[y * 32 + x];
+        for x in 2..30 {
+            assert_eq!(px(x, 9), 0);
+            assert_eq!(px(x, 10), 255);
+            assert_eq!(px(x, 11), 0);
+        }
+        for y in 14..30 {
+            assert_eq!(px(4, y), 0);
+            assert_eq!(px(5, y), 255);
+            assert_eq!(px(6, y), 255);
+            assert_eq!(px(7, y), 0);
+        }
+        // Diagonal lines are not snapped
+        let path = Path2D::default()
+            .absolute()
+            .move_to(2.3, 2.3)
+            .line_to(20.0, 20.0)
+            .finish();
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(32, 32));
+        let ops = p.stroke_path(&path);
+        p.set_stroke_snap(true);
+        assert_eq!(p.stroke_path(&path), ops);
+    }
+
+    #[test]
+    fn stroke_nib() {
+        let path = Path2D::default()
+            .absolute()
+            .move_to(8.0, 8.0)
+            .line_to(24.0, 24.0)
+            .move_to(40.0, 24.0)
+            .line_to(56.0, 8.0)
+            .finish();
+        let mut p = Plotter::new(Raster::<Matte8>::with_clear(64, 32));
+        let angle = std::f32::consts::FRAC_PI_4;
+        let r = p.stroke_nib(&path, 8.0, angle, Matte8::new(255));
+        let row = |y: usize| {
+            let px = &r.as_u8_slice()[y * 64..(y + 1) * 64];
+            let left = px[..32].iter().map(|v| u32::from(*v)).sum::<u32>();
+            let right = px[32..].iter().map(|v| u32::from(*v)).sum::<u32>();
+            (left, right)
+        };
+        // Parallel to nib: hairline
+        let (left, right) = row(16);
+        assert!(left > 0 && left < 255);
+        // Perpendicular to nib: full width, horizontally 8 * sqrt(2)
+        assert!(right > 255 * 11 && right < 255 * 12);
+    }
+
+    #[test]
+    fn stroke_with_state() {
+        let clr = Rgba8p::new(40, 80, 120, 160);
+        for path in stroke_examples() {
+            let mut a = Plotter::new(Raster::with_clear(64, 64));
+            a.stroke(&path, clr);
+            let mut b = Plotter::new(Raster::with_clear(64, 64));
+            b.stroke_ink(&path, ink::ColorInk::new(clr));
+            assert_eq!(a.raster().pixels(), b.raster().pixels());
+            // Count covered pixels, reading back after stroking
+            let mut count = 0;
+            let mut ink = ink::FnInk(|d: &mut Rgba8p, _x, _y, alpha| {
+                BlendMode::SrcOver.composite(d, &clr, alpha);
+                count += 1;
+            });
+            b.clear().stroke_with(&path, &mut ink);
+            assert_eq!(a.raster().pixels(), b.raster().pixels());
+            let outline = a.stroke_path(&path);
+            let matte = a.fill_matte(FillRule::NonZero, &outline);
+            let covered = matte
+                .pixels()
+                .iter()
+                .filter(|p| u8::from(p.alpha()) > 0)
+                .count();
+            assert_eq!(count, covered);
+        }
+    }
+
+    #[test]
+    fn fill_stroke() {
+        let fill_clr = Rgba8p::new(0, 64, 128, 128);
+        let stroke_clr = Rgba8p::new(128, 64, 0, 192);
+        for path in stroke_examples() {
+            for curves in [false, true] {
+                let mut p = Plotter::new(Raster::with_clear(64, 64));
+                p.set_transform(Transform::with_scale(0.9, 1.1))
+                    .set_stroke_curves(curves);
+                p.fill(FillRule::NonZero, &path, fill_clr);
+                p.stroke(&path, stroke_clr);
+                let mut p2 = Plotter::new(Raster::with_clear(64, 64));
+                p2.set_transform(Transform::with_scale(0.9, 1.1))
+                    .set_stroke_curves(curves);
+                p2.fill_stroke(FillRule::NonZero, &path, fill_clr, stroke_clr);
+                assert_eq!(p.raster().pixels(), p2.raster().pixels());
+            }
+        }
+    }
+
+    #[test]
+    fn fill_stroke_wide_raster() {
+        let fill_clr = Rgba8p::new(0, 64, 128, 128);
+        let stroke_clr = Rgba8p::new(128, 64, 0, 192);
+        let plotter = || {
+            let mut p = Plotter::new(Raster::with_clear(20_000, 64));
+            // Straddle the edge of the first tile
+            p.set_transform(Transform::with_translate(8_160.0, 0.0));
+            p
+        };
+        for path in stroke_examples() {
+            let mut p = plotter();
+            p.fill(FillRule::NonZero, &path, fill_clr);
+            p.stroke(&path, stroke_clr);
+            let mut p2 = plotter();
+            p2.fill_stroke(FillRule::NonZero, &path, fill_clr, stroke_clr);
+            assert_eq!(p.raster().pixels(), p2.raster().pixels());
+            assert!(p.take_dirty().is_some_and(|d| d.right() > 8_192));
+        }
+    }
+
+    #[test]
+    fn draw() {
+        let fill_clr = Rgba8p::new(0, 0, 128, 128);
+        let stroke_clr = Rgba8p::new(255, 0, 0, 255);
+        let rect = Path2D::default()
+            .absolute()
+            .pen_width(4.0)
+            .move_to(8.0, 8.0)
+            .line_to(8.0, 24.0)
+            .line_to(24.0, 24.0)
+            .line_to(24.0, 8.0)
+            .close()
+            .finish();
+        let mut p = Plotter::new(Raster::with_clear(32, 32));
+        p.draw(FillRule::NonZero, &rect, Some(fill_clr), Some(stroke_clr));
+        // Stroke overlapping the fill edge is drawn over the fill
+        assert_eq!(p.raster().pixel(9, 16), stroke_clr);
+        // Stroke outside the fill edge
+        assert_eq!(p.raster().pixel(6, 16), stroke_clr);
+        // Fill only inside the stroke
+        assert_eq!(p.raster().pixel(16, 16), fill_clr);
+        let mut f = Plotter::new(Raster::with_clear(32, 32));
+        f.draw(FillRule::NonZero, &rect, Some(fill_clr), None);
+        let mut f2 = Plotter::new(Raster::with_clear(32, 32));
+        f2.fill(FillRule::NonZero, &rect, fill_clr);
+        assert_eq!(f.raster().pixels(), f2.raster().pixels());
+        let mut s = Plotter::new(Raster::with_clear(32, 32));
+        s.draw(FillRule::NonZero, &rect, None, Some(stroke_clr));
+        let mut s2 = Plotter::new(Raster::with_clear(32, 32));
+        s2.stroke(&rect, stroke_clr);
+        assert_eq!(s.raster().pixels(), s2.raster().pixels());
+        let mut n = Plotter::new(Raster::<Rgba8p>::with_clear(32, 32));
+        n.draw(FillRule::NonZero, &rect, None, None);
+        assert!(n
+            .raster()
+            .pixels()
+            .iter()
+            .all(|px| *px == Rgba8p::default()));
+    }
+
+    #[test]
+    fn draw_wide_raster() {
+        let fill_clr = Rgba8p::new(0, 0, 128, 128);
+        let stroke_clr = Rgba8p::new(255, 0, 0, 255);
+        let rect = Path2D::default()
+            .absolute()
+            .pen_width(4.0)
+            .move_to(8_180.0, 8.0)
+            .line_to(8_180.0, 24.0)
+            .line_to(8_204.0, 24.0)
+            .line_to(8_204.0, 8.0)
+            .close()
+            .finish();
+        let plotter = || Plotter::new(Raster::with_clear(20_000, 32));
+        let colors = [
+            (Some(fill_clr), Some(stroke_clr)),
+            (Some(fill_clr), None),
+            (None, Some(stroke_clr)),
+        ];
+        for (fill, stroke) in colors {
+            let mut p = plotter();
+            p.draw(FillRule::NonZero, &rect, fill, stroke);
+            let mut p2 = plotter();
+            if let Some(clr) = fill {
+                p2.fill(FillRule::NonZero, &rect, clr);
+            }
+            if let Some(clr) = stroke {
+                p2.stroke(&rect, clr);
+            }
+            assert_eq!(p.raster().pixels(), p2.raster().pixels());
+            // Fill on both sides of the tile edge, and stroke on both ends
+            let r = p.raster();
+            let inside = fill.unwrap_or_default();
+            assert_eq!(
+                (r.pixel(8_191, 16), r.pixel(8_192, 16)),
+                (inside, inside)
+            );
+            if let Some(clr) = stroke {
+                assert_eq!(
+                    (r.pixel(8_180, 16), r.pixel(8_204, 16)),
+                    (clr, clr)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fill_stroke_transformed() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(1.5)
+            .move_to(1.0, 1.0)
+            .quad_to(8.0, 0.0, 6.0, 6.0)
+            .line_to(2.0, 7.0)
+            .close()
+            .finish();
+        let clr = Matte8::new(255);
+        let scale = Transform::with_scale(2.0, 2.0);
+        let t = Transform::with_translate(3.0, 2.0).rotate(0.5);
+        let mut p = Plotter::new(Raster::with_clear(32, 32));
+        p.set_transform(scale).set_tolerance_auto(0.25);
+        let (w_scale, tol_sq) = (p.w_scale, p.tol_sq);
+        let mut q = Plotter::new(Raster::with_clear(32, 32));
+        q.set_transform(t * scale).set_tolerance_auto(0.25);
+        let fill = q.fill(FillRule::NonZero, &path, clr).clone();
+        let stroke = q.clear().stroke(&path, clr).clone();
+        assert!(fill.pixels().contains(&clr));
+        assert_eq!(
+            p.fill_transformed(FillRule::NonZero, &path, &t, clr)
+                .as_u8_slice(),
+            fill.as_u8_slice()
+        );
+        assert_eq!(
+            p.clear().stroke_transformed(&path, &t, clr).as_u8_slice(),
+            stroke.as_u8_slice()
+        );
+        // Persistent transform is untouched
+        assert_eq!(p.transform, scale);
+        assert_eq!((p.w_scale, p.tol_sq), (w_scale, tol_sq));
+        let mut q = Plotter::new(Raster::with_clear(32, 32));
+        q.set_transform(scale).set_tolerance_auto(0.25);
+        assert_eq!(
+            p.clear().fill(FillRule::NonZero, &path, clr).as_u8_slice(),
+            q.fill(FillRule::NonZero, &path, clr).as_u8_slice()
+        );
+    }
+
+    #[test]
+    fn stroke_opts() {
+        let path = Path2D::default()
+            .absolute()
+            .pen_width(2.0)
+            .move_to(4.0, 4.0)
+            .line_to(28.0, 8.0)
+            .line_to(6.0, 26.0)
+            .finish();
+        let clr = Matte8::new(255);
+        let round = StrokeOptions {
+            width_override: Some(5.0),
+            join: JoinStyle::Round,
+            cap: CapStyle::Round,
+            tolerance: Some(0.1),
+        };
+        let bevel = StrokeOptions {
+            join: JoinStyle::Bevel,
+            cap: CapStyle::Square,
+            ..StrokeOptions::default()
+        };
+        // Configure each style with setters for comparison
+        let stroke_with = |opts: &StrokeOptions| {
+            let mut p = Plotter::new(Raster::with_clear(32, 32));
+            p.set_join(opts.join).set_cap(opts.cap);
+            if let Some(t) = opts.tolerance {
+                p.set_tolerance(t);
+            }
+            let ops: Vec<_> = match opts.width_override {
+                Some(w) => path
+                    .iter()
+                    .map(|op| match op {
+                        PathOp::PenWidth(_) => PathOp::PenWidth(w),
+                        op => *op,
+                    })
+                    .collect(),
+                None => path.clone(),
+            };
+            p.stroke(&ops, clr).as_u8_slice().to_vec()
+        };
+        let mut p = Plotter::new(Raster::with_clear(32, 32));
+        p.set_join(JoinStyle::Miter(2.0)).set_tolerance(0.5);
+        p.fill(FillRule::NonZero, [PathOp::PenWidth(1.5)], clr);
+        for opts in [&round, &bevel, &round] {
+            p.clear().stroke_opts(&path, opts, clr);
+            assert_eq!(p.raster().as_u8_slice(), stroke_with(opts));
+        }
+        assert_ne!(stroke_with(&round), stroke_with(&bevel));
+        // Plotter defaults are untouched
+        assert_eq!(p.join_style, JoinStyle::Miter(2.0));
+        assert_eq!(p.cap_style, CapStyle::Butt);
+        assert_eq!(p.tol_sq, 0.5 * 0.5);
+        assert_eq!(p.s_width, 1.5);
+        assert!(p.states.is_empty());
+    }
+}
@@ -5,13 +5,31 @@
 use crate::geom::WidePt;
 use crate::path::PathOp;
 use crate::vid::Vid;
-use pointy::{Line, Pt};
+use pointy::Pt;
+use std::borrow::Borrow;
 use std::fmt;
 
+/// Maximum subdivision depth for offsetting curves
+const MAX_OFFSET_DEPTH: u32 = 10;
+
+/// Maximum subdivision depth for round joins and caps
+const MAX_ARC_DEPTH: u32 = 16;
+
+/// Minimum half width of a nib sweep
+const NIB_HAIRLINE: f32 = 0.25;
+
+/// Threshold for treating a segment as horizontal or vertical
+const SNAP_EPSILON: f32 = 1e-3;
+
+/// Threshold for treating a join between two segments as straight
+const STRAIGHT_EPSILON: f32 = 1e-6;
+
 /// Style for stroke joins.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum JoinStyle {
     /// Mitered join with limit (miter length to stroke width ratio)
+    ///
+    /// Miters exceeding the limit are clipped.
     Miter(f32),
     /// Beveled join
     Bevel,
@@ -19,6 +37,82 @@ pub enum JoinStyle {
     Round,
 }
 
+/// Style for stroke caps.
+///
+/// Caps are added to the ends of open sub-paths.  With `Round` or `Square`
+/// caps, a sub-path containing a single point is stroked as a dot.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CapStyle {
+    /// Butt cap (ends exactly at end point)
+    Butt,
+    /// Rounded cap
+    Round,
+    /// Square cap (extends past end point by half stroke width)
+    Square,
+}
+
+/// Options for a single stroke call.
+///
+/// These are applied only for the duration of the call, without changing
+/// plotter settings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeOptions {
+    /// Width for the whole stroke, replacing pen widths from the path
+    pub width_override: Option<f32>,
+    /// Join style
+    pub join: JoinStyle,
+    /// Cap style
+    pub cap: CapStyle,
+    /// Tolerance for curve decomposition, joins and caps (in raster pixels)
+    pub tolerance: Option<f32>,
+}
+
+impl Default for StrokeOptions {
+    fn default() -> Self {
+        StrokeOptions {
+            width_override: None,
+            join: JoinStyle::Miter(4.0),
+            cap: CapStyle::Butt,
+            tolerance: None,
+        }
+    }
+}
+
+/// Units for stroke widths.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StrokeUnits {
+    /// Widths are in user units, scaled by the plotter transform
+    UserSpace,
+    /// Widths are in raster pixels, unaffected by the transform
+    PixelSpace,
+}
+
+/// Marker for the ends of open stroked sub-paths.
+///
+/// A marker path is defined in a frame with its origin at the end point, the
+/// X axis pointing away from the stroke along the end tangent, and units of
+/// stroke width.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Marker {
+    /// Marker path ops
+    ops: Vec<PathOp>,
+    /// Distance to shorten stroke (in stroke widths)
+    inset: f32,
+}
+
+/// Offset of one side of a segment
+#[derive(Clone, Copy, Debug)]
+struct Offset {
+    /// Start point
+    p0: Pt<f32>,
+    /// Direction at start point
+    d0: Pt<f32>,
+    /// End point
+    p1: Pt<f32>,
+    /// Direction at end point
+    d1: Pt<f32>,
+}
+
 /// Stroke direction enum
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Dir {
@@ -44,12 +138,266 @@ struct SubStroke {
 pub struct Stroke {
     /// Join style
     join_style: JoinStyle,
+    /// Cap style
+    cap_style: CapStyle,
     /// Tolerance squared
     tol_sq: f32,
+    /// Start marker
+    start_marker: Option<Marker>,
+    /// End marker
+    end_marker: Option<Marker>,
+    /// Keep curves in offsets
+    curves: bool,
     /// All points
     points: Vec<WidePt>,
+    /// Curve control points for segments ending at each point
+    ctrl: Vec<Option<(WidePt, WidePt)>>,
     /// All sub-strokes
     subs: Vec<SubStroke>,
+    /// Number of points dropped after the stroke was full
+    dropped: usize,
+}
+
+impl Marker {
+    /// Create a new marker.
+    ///
+    /// * `ops` Marker path, in units of stroke width.
+    /// * `inset` Distance to shorten the stroke, in units of stroke width.
+    pub fn new<T>(ops: T, inset: f32) -> Self
+    where
+        T: IntoIterator,
+        T::Item: Borrow<PathOp>,
+    {
+        let mut marker = Marker { ops: vec![], inset };
+        let mut sub = vec![];
+        for op in ops {
+            match op.borrow() {
+                PathOp::PenWidth(_) | PathOp::PenAttr(_) => (),
+                PathOp::Move(p) => {
+                    marker.add_sub(&sub);
+                    sub.clear();
+                    sub.push(PathOp::Move(*p));
+                }
+                PathOp::Close() => {
+                    marker.add_sub(&sub);
+                    sub.clear();
+                }
+                op => sub.push(*op),
+            }
+        }
+        marker.add_sub(&sub);
+        marker
+    }
+
+    /// Create an arrowhead marker.
+    pub fn arrow() -> Self {
+        let ops = [
+            PathOp::Move(Pt::new(0.0, 0.0)),
+            PathOp::Line(Pt::new(-3.0, 1.5)),
+            PathOp::Line(Pt::new(-3.0, -1.5)),
+            PathOp::Close(),
+        ];
+        Marker::new(ops, 2.0)
+    }
+
+    /// Add a closed sub-path, with consistent orientation.
+    fn add_sub(&mut self, sub: &[PathOp]) {
+        let pts = sub_points(sub);
+        if pts.len() < 3 {
+            return;
+        }
+        let mut area = 0.0;
+        for (i, p0) in pts.iter().enumerate() {
+            let p1 = pts[(i + 1) % pts.len()];
+            area += p0.x() * p1.y() - p1.x() * p0.y();
+        }
+        if area < 0.0 {
+            self.ops.push(PathOp::Move(pts[pts.len() - 1]));
+            let mut p0 = pts[0];
+            let segs: Vec<(Pt<f32>, PathOp)> = sub[1..]
+                .iter()
+                .map(|op| {
+                    let start = p0;
+                    p0 = op_end(op).unwrap_or(p0);
+                    (start, *op)
+                })
+                .collect();
+            for (start, op) in segs.into_iter().rev() {
+                self.ops.push(match op {
+                    PathOp::Quad(b, _) => PathOp::Quad(b, start),
+                    PathOp::Cubic(b, c, _) => PathOp::Cubic(c, b, start),
+                    _ => PathOp::Line(start),
+                });
+            }
+        } else {
+            self.ops.extend_from_slice(sub);
+        }
+        self.ops.push(PathOp::Close());
+    }
+
+    /// Add marker ops at an end point.
+    ///
+    /// * `p` End point (with stroke width).
+    /// * `dir` Unit vector pointing away from the stroke.
+    fn stamp(&self, ops: &mut Vec<PathOp>, p: WidePt, dir: Pt<f32>) {
+        let w = p.w();
+        let map = |pt: Pt<f32>| p.0 + (dir * pt.x() + dir.left() * pt.y()) * w;
+        for op in &self.ops {
+            ops.push(match *op {
+                PathOp::Move(a) => PathOp::Move(map(a)),
+                PathOp::Line(a) => PathOp::Line(map(a)),
+                PathOp::Quad(a, b) => PathOp::Quad(map(a), map(b)),
+                PathOp::Cubic(a, b, c) => PathOp::Cubic(map(a), map(b), map(c)),
+                op => op,
+            });
+        }
+    }
+}
+
+/// Get end point of a path op
+fn op_end(op: &PathOp) -> Option<Pt<f32>> {
+    match *op {
+        PathOp::Move(p) | PathOp::Line(p) => Some(p),
+        PathOp::Quad(_, p) | PathOp::Cubic(_, _, p) => Some(p),
+        _ => None,
+    }
+}
+
+/// Get all points (including control points) of a sub-path
+fn sub_points(sub: &[PathOp]) -> Vec<Pt<f32>> {
+    let mut pts = vec![];
+    for op in sub {
+        match *op {
+            PathOp::Move(a) | PathOp::Line(a) => pts.push(a),
+            PathOp::Quad(a, b) => pts.extend([a, b]),
+            PathOp::Cubic(a, b, c) => pts.extend([a, b, c]),
+            _ => (),
+        }
+    }
+    pts
+}
+
+/// Calculate the cross product (Z component) of two vectors
+fn cross(a: Pt<f32>, b: Pt<f32>) -> f32 {
+    a.x() * b.y() - a.y() * b.x()
+}
+
+/// Get a point on a cubic bézier spline
+fn cubic_point(cub: [Pt<f32>; 4], t: f32) -> Pt<f32> {
+    let [a, b, c, d] = cub;
+    let s = 1.0 - t;
+    a * (s * s * s)
+        + b * (3.0 * s * s * t)
+        + c * (3.0 * s * t * t)
+        + d * (t * t * t)
+}
+
+/// Get first and second derivatives of a cubic bézier spline
+fn cubic_derivs(cub: &[WidePt; 4], t: f32) -> (Pt<f32>, Pt<f32>) {
+    let [a, b, c, d] = cub.map(|p| p.0);
+    let s = 1.0 - t;
+    let d1 = (b - a) * (3.0 * s * s)
+        + (c - b) * (6.0 * s * t)
+        + (d - c) * (3.0 * t * t);
+    let d2 = (c - b * 2.0 + a) * (6.0 * s) + (d - c * 2.0 + b) * (6.0 * t);
+    (d1, d2)
+}
+
+/// Get the half width (and its derivative) of a cubic bézier spline
+fn cubic_half_width(cub: &[WidePt; 4], t: f32) -> (f32, f32) {
+    let [a, b, c, d] = cub.map(|p| p.w() / 2.0);
+    let s = 1.0 - t;
+    let r = a * s * s * s
+        + b * 3.0 * s * s * t
+        + c * 3.0 * s * t * t
+        + d * t * t * t;
+    let dr =
+        (b - a) * 3.0 * s * s + (c - b) * 6.0 * s * t + (d - c) * 3.0 * t * t;
+    (r, dr)
+}
+
+/// Get the direction (unit tangent) of a cubic bézier spline
+fn cubic_dir(cub: &[WidePt; 4], t: f32) -> Pt<f32> {
+    let (d1, _) = cubic_derivs(cub, t);
+    if d1.mag() > 0.0 {
+        return d1.normalize();
+    }
+    // Coincident control points; use chord of the other points
+    let [a, b, c, d] = cub.map(|p| p.0);
+    let v = if t < 0.5 {
+        if c != a {
+            c - a
+        } else {
+            d - a
+        }
+    } else if b != d {
+        d - b
+    } else {
+        d - a
+    };
+    v.normalize()
+}
+
+/// Get a point offset by half width from a cubic bézier spline
+fn cubic_offset(cub: &[WidePt; 4], t: f32) -> Option<Pt<f32>> {
+    let (d1, _) = cubic_derivs(cub, t);
+    let (r, _) = cubic_half_width(cub, t);
+    if t > 0.0 && t < 1.0 && d1.mag() == 0.0 {
+        return None;
+    }
+    let pt = cubic_point(cub.map(|p| p.0), t);
+    Some(pt + cubic_dir(cub, t).right() * r)
+}
+
+/// Get the derivative of a cubic bézier spline offset by half width
+fn cubic_offset_deriv(cub: &[WidePt; 4], t: f32) -> Pt<f32> {
+    let (d1, d2) = cubic_derivs(cub, t);
+    let (r, dr) = cubic_half_width(cub, t);
+    let m = d1.mag();
+    if m > 0.0 {
+        let u = d1 / m;
+        // Derivative of unit tangent
+        let du = (d2 - u * u.dot(d2)) / m;
+        d1 + du.right() * r + u.right() * dr
+    } else {
+        Pt::default()
+    }
+}
+
+/// Snap a centerline coordinate so that stroke edges are on pixel boundaries
+fn snap_coord(v: f32, width: f32) -> f32 {
+    if width.round() as i32 % 2 == 1 {
+        v.floor() + 0.5
+    } else {
+        v.round()
+    }
+}
+
+/// Shorten the start of a polyline by a distance.
+///
+/// If the polyline is not long enough, it is emptied.
+fn trim_start(pts: &mut Vec<WidePt>, mut dist: f32) {
+    while pts.len() > 1 {
+        let (p0, p1) = (pts[0], pts[1]);
+        let len = p0.0.dist(p1.0);
+        if len > dist {
+            pts[0] = p0.lerp(p1, dist / len);
+            return;
+        }
+        dist -= len;
+        pts.remove(0);
+    }
+    pts.clear();
+}
+
+impl Dir {
+    /// Get the opposite direction
+    fn opposite(self) -> Self {
+        match self {
+            Dir::Forward => Dir::Reverse,
+            Dir::Reverse => Dir::Forward,
+        }
+    }
 }
 
 impl SubStroke {
@@ -111,18 +459,72 @@ impl fmt::Debug for Stroke {
 
 impl Stroke {
     /// Create a new stroke.
-    pub fn new(join_style: JoinStyle, tol_sq: f32) -> Stroke {
+    pub fn new(
+        join_style: JoinStyle,
+        cap_style: CapStyle,
+        tol_sq: f32,
+    ) -> Stroke {
         let points = Vec::with_capacity(1024);
         let mut subs = Vec::with_capacity(16);
         subs.push(SubStroke::new(Vid(0)));
         Stroke {
             join_style,
+            cap_style,
             tol_sq,
+            start_marker: None,
+            end_marker: None,
+            curves: false,
             points,
+            ctrl: Vec::with_capacity(1024),
             subs,
+            dropped: 0,
         }
     }
 
+    /// Reset to an empty stroke with new styles, keeping allocated buffers.
+    ///
+    /// Markers and curves are cleared.
+    pub fn reset(
+        &mut self,
+        join_style: JoinStyle,
+        cap_style: CapStyle,
+        tol_sq: f32,
+    ) {
+        self.join_style = join_style;
+        self.cap_style = cap_style;
+        self.tol_sq = tol_sq;
+        self.start_marker = None;
+        self.end_marker = None;
+        self.curves = false;
+        self.points.clear();
+        self.ctrl.clear();
+        self.subs.clear();
+        self.subs.push(SubStroke::new(Vid(0)));
+        self.dropped = 0;
+    }
+
+    /// Set whether curves are kept in stroke offsets.
+    ///
+    /// When set, cubic bézier splines can be added with `add_cubic`, and the
+    /// stroke outline contains offset curves instead of lines.
+    pub fn set_curves(&mut self, curves: bool) {
+        self.curves = curves;
+    }
+
+    /// Check whether curves are kept in stroke offsets.
+    pub fn curves(&self) -> bool {
+        self.curves
+    }
+
+    /// Set markers for the ends of open sub-strokes.
+    ///
+    /// * `start` Marker for start points.
+    /// * `end` Marker for end points.
+    pub fn set_markers(&mut self, start: Option<Marker>, end: Option<Marker>) {
+        self.start_marker = start;
+        self.end_marker = end;
+    }
+
     /// Check if two points are within tolerance threshold.
     fn is_within_tolerance2(&self, a: Pt<f32>, b: Pt<f32>) -> bool {
         assert!(self.tol_sq > 0.0);
@@ -198,6 +600,11 @@ impl Stroke {
         self.points[usize::from(vid)]
     }
 
+    /// Get the number of points dropped because the stroke was full.
+    pub fn dropped_points(&self) -> usize {
+        self.dropped
+    }
+
     /// Add a point.
     ///
     /// * `pt` Point to add (w indicates stroke width).
@@ -210,11 +617,37 @@ impl Stroke {
             }
             if done || !self.coincident(pt) {
                 self.points.push(pt);
+                self.ctrl.push(None);
                 self.sub_add_point();
             }
+        } else {
+            self.dropped += 1;
         }
     }
 
+    /// Add a cubic bézier spline from the previous point.
+    ///
+    /// * `b` First control point.
+    /// * `c` Second control point.
+    /// * `d` End point.
+    ///
+    /// Returns false if the spline must be added as a series of points.
+    pub fn add_cubic(&mut self, b: WidePt, c: WidePt, d: WidePt) -> bool {
+        let sub = &self.subs[self.len() - 1];
+        if !self.curves
+            || sub.done
+            || sub.n_points == Vid(0)
+            || self.coincident(d)
+            || self.points.len() >= usize::from(Vid::MAX)
+        {
+            return false;
+        }
+        self.points.push(d);
+        self.ctrl.push(Some((b, c)));
+        self.sub_add_point();
+        true
+    }
+
     /// Check if a point is coincident with previous point.
     fn coincident(&self, pt: WidePt) -> bool {
         if let Some(p) = self.points.last() {
@@ -235,32 +668,338 @@ impl Stroke {
         }
     }
 
-    /// Create path ops of the stroke
-    pub fn path_ops(&self) -> Vec<PathOp> {
-        // FIXME: this should make a lazy iterator
+    /// Snap axis-aligned segments to pixel boundaries.
+    ///
+    /// Points on horizontal or vertical straight segments are moved so that
+    /// the edges of the stroke land on pixel boundaries.
+    pub fn snap_to_pixels(&mut self) {
+        self.points = (0..self.points.len())
+            .map(|v| self.snap_point(Vid::from(v)))
+            .collect();
+    }
+
+    /// Get a point snapped to pixel boundaries.
+    fn snap_point(&self, vid: Vid) -> WidePt {
+        let sub = self.sub_at(vid);
+        let p = self.point(vid);
+        if sub.n_points < Vid(2) {
+            return p;
+        }
+        let end = sub.start + sub.n_points - 1;
+        let mut neighbors = Vec::with_capacity(2);
+        if vid > sub.start || sub.joined {
+            let v0 = sub.next(vid, Dir::Reverse);
+            if self.segment(v0, vid, Dir::Forward).is_none() {
+                neighbors.push(self.point(v0));
+            }
+        }
+        if vid < end || sub.joined {
+            let v1 = sub.next(vid, Dir::Forward);
+            if self.segment(vid, v1, Dir::Forward).is_none() {
+                neighbors.push(self.point(v1));
+            }
+        }
+        let (mut x, mut y) = (p.0.x(), p.0.y());
+        for n in neighbors {
+            if (n.0.y() - p.0.y()).abs() < SNAP_EPSILON {
+                y = snap_coord(p.0.y(), p.w());
+            }
+            if (n.0.x() - p.0.x()).abs() < SNAP_EPSILON {
+                x = snap_coord(p.0.x(), p.w());
+            }
+        }
+        WidePt(Pt::new(x, y), p.w(), p.a())
+    }
+
+    /// Create path ops for the sweep of a flat (calligraphic) nib.
+    ///
+    /// Each segment is swept into a parallelogram with ends aligned to the
+    /// nib.  Segments parallel to the nib become hairlines.
+    ///
+    /// * `nib` Vector from center to one end of nib.
+    pub fn nib_ops(&self, nib: Pt<f32>) -> Vec<PathOp> {
         let mut ops = vec![];
-        let n_subs = self.len();
-        for i in 0..n_subs {
-            self.stroke_sub(&mut ops, i);
+        for i in 0..self.len() {
+            let mut v0 = self.sub_start(i);
+            for _ in 0..usize::from(self.sub_points(i)) {
+                let v1 = self.next(v0, Dir::Forward);
+                self.stroke_nib(
+                    &mut ops,
+                    self.point(v0).0,
+                    self.point(v1).0,
+                    nib,
+                );
+                v0 = v1;
+            }
         }
         ops
     }
 
+    /// Sweep a nib along one segment.
+    fn stroke_nib(
+        &self,
+        ops: &mut Vec<PathOp>,
+        p0: Pt<f32>,
+        p1: Pt<f32>,
+        nib: Pt<f32>,
+    ) {
+        let vr = (p1 - p0).right().normalize();
+        // Projection of nib onto segment normal (half width)
+        let proj = nib.dot(vr);
+        let extra = NIB_HAIRLINE - proj.abs();
+        let n = if extra > 0.0 {
+            let sign = if proj < 0.0 { -1.0 } else { 1.0 };
+            nib + vr * (extra * sign)
+        } else {
+            nib
+        };
+        let mut pts = [p0 + n, p1 + n, p1 - n, p0 - n];
+        // Keep orientation consistent for filling with NonZero rule
+        if cross(p1 - p0, n) > 0.0 {
+            pts.reverse();
+        }
+        for pt in pts {
+            self.stroke_point(ops, pt);
+        }
+        ops.push(PathOp::Close());
+    }
+
+    /// Apply a width profile to all sub-strokes.
+    ///
+    /// The width of each point is replaced by the result of the profile
+    /// function, called with the fraction of the sub-stroke length at that
+    /// point (from 0 to 1).
+    ///
+    /// * `profile` Width profile function.
+    pub fn apply_profile<F>(&mut self, profile: F)
+    where
+        F: Fn(f32) -> f32,
+    {
+        for sub in &self.subs {
+            let start = usize::from(sub.start);
+            let end = start + usize::from(sub.n_points);
+            let pts = &mut self.points[start..end];
+            let mut lens = Vec::with_capacity(pts.len());
+            let mut total = 0.0;
+            for (i, pt) in pts.iter().enumerate() {
+                if i > 0 {
+                    total += pts[i - 1].0.dist(pt.0);
+                }
+                lens.push(total);
+            }
+            if sub.joined && pts.len() > 1 {
+                total += pts[pts.len() - 1].0.dist(pts[0].0);
+            }
+            for (pt, len) in pts.iter_mut().zip(lens) {
+                let t = if total > 0.0 { len / total } else { 0.0 };
+                pt.1 = profile(t).max(0.0);
+            }
+        }
+    }
+
+    /// Get center line segments.
+    ///
+    /// Closing segments of joined sub-strokes are included, and sub-strokes
+    /// with one point have a zero-length segment.
+    fn center_segments(&self) -> Vec<(WidePt, WidePt)> {
+        let mut segs = Vec::with_capacity(self.points.len());
+        for sub in &self.subs {
+            let start = usize::from(sub.start);
+            let end = start + usize::from(sub.n_points);
+            let pts = &self.points[start..end];
+            if pts.len() == 1 {
+                segs.push((pts[0], pts[0]));
+            }
+            let closing = if sub.joined && pts.len() > 1 {
+                Some((pts[pts.len() - 1], pts[0]))
+            } else {
+                None
+            };
+            segs.extend(pts.windows(2).map(|w| (w[0], w[1])).chain(closing));
+        }
+        segs
+    }
+
+    /// Get center line segments, with arc-length parameters.
+    ///
+    /// Each segment has start and end points, with the fraction of the total
+    /// stroke length at each (from 0 at the start of the first sub-stroke to
+    /// 1 at the end of the last).  Closing segments of joined sub-strokes are
+    /// included, and sub-strokes with one point have a zero-length segment.
+    pub fn arc_segments(&self) -> Vec<(WidePt, WidePt, f32, f32)> {
+        let mut segs = Vec::with_capacity(self.points.len());
+        let mut total = 0.0;
+        for (a, b) in self.center_segments() {
+            let len = a.0.dist(b.0);
+            segs.push((a, b, total, total + len));
+            total += len;
+        }
+        if total > 0.0 {
+            for seg in &mut segs {
+                seg.2 /= total;
+                seg.3 /= total;
+            }
+        }
+        segs
+    }
+
+    /// Get center line segments, with attribute parameters.
+    ///
+    /// This is the same as [arc_segments], except with the pen attribute of
+    /// each point instead of the fraction of stroke length.
+    ///
+    /// [arc_segments]: #method.arc_segments
+    pub fn attr_segments(&self) -> Vec<(WidePt, WidePt, f32, f32)> {
+        self.center_segments()
+            .into_iter()
+            .map(|(a, b)| (a, b, a.a(), b.a()))
+            .collect()
+    }
+
+    /// Append path ops of the stroke
+    pub fn path_ops(&self, ops: &mut Vec<PathOp>) {
+        // FIXME: this should make a lazy iterator
+        if self.start_marker.is_some() || self.end_marker.is_some() {
+            let stroke = self.trimmed();
+            for i in 0..stroke.len() {
+                stroke.stroke_sub(ops, i);
+            }
+            for i in 0..self.len() {
+                self.stroke_markers(ops, i);
+            }
+        } else {
+            for i in 0..self.len() {
+                self.stroke_sub(ops, i);
+            }
+        }
+    }
+
+    /// Check if a sub-stroke has open ends.
+    fn sub_open(&self, i: usize) -> bool {
+        !self.sub_joined(i) && self.subs[i].n_points > Vid(1)
+    }
+
+    /// Get a copy of the stroke with open ends shortened by marker insets.
+    fn trimmed(&self) -> Stroke {
+        let mut stroke =
+            Stroke::new(self.join_style, self.cap_style, self.tol_sq);
+        stroke.subs.clear();
+        for (i, sub) in self.subs.iter().enumerate() {
+            let start = usize::from(sub.start);
+            let end = start + usize::from(sub.n_points);
+            let mut pts = self.points[start..end].to_vec();
+            if self.sub_open(i) {
+                if let Some(m) = &self.start_marker {
+                    let dist = m.inset * pts[0].w();
+                    trim_start(&mut pts, dist);
+                }
+                if let (Some(m), Some(p)) = (&self.end_marker, pts.last()) {
+                    let dist = m.inset * p.w();
+                    pts.reverse();
+                    trim_start(&mut pts, dist);
+                    pts.reverse();
+                }
+            }
+            let mut s = SubStroke::new(Vid::from(stroke.points.len()));
+            s.n_points = Vid::from(pts.len());
+            s.joined = sub.joined;
+            s.done = sub.done;
+            stroke.ctrl.extend(pts.iter().map(|_| None));
+            stroke.points.extend(pts);
+            stroke.subs.push(s);
+        }
+        stroke
+    }
+
+    /// Add markers to the ends of one sub-figure.
+    fn stroke_markers(&self, ops: &mut Vec<PathOp>, i: usize) {
+        if !self.sub_open(i) {
+            return;
+        }
+        if let Some(m) = &self.start_marker {
+            let p = self.point(self.sub_start(i));
+            let p1 = self.point(self.sub_start(i) + 1);
+            m.stamp(ops, p, (p.0 - p1.0).normalize());
+        }
+        if let Some(m) = &self.end_marker {
+            let p = self.point(self.sub_end(i));
+            let p0 = self.point(self.sub_end(i) - 1);
+            m.stamp(ops, p, (p.0 - p0.0).normalize());
+        }
+    }
+
     /// Stroke one sub-figure.
     fn stroke_sub(&self, ops: &mut Vec<PathOp>, i: usize) {
-        if self.sub_points(i) > Vid(0) {
+        if self.subs[i].n_points == Vid(1) {
+            self.stroke_dot(ops, self.sub_start(i));
+        } else if self.sub_points(i) > Vid(0) {
             let start = self.sub_start(i);
             let end = self.sub_end(i);
             let joined = self.sub_joined(i);
             self.stroke_side(ops, i, start, Dir::Forward);
             if joined {
                 ops.push(PathOp::Close());
+            } else {
+                self.stroke_cap(ops, end, Dir::Forward);
             }
             self.stroke_side(ops, i, end, Dir::Reverse);
+            if !joined {
+                self.stroke_cap(ops, start, Dir::Reverse);
+            }
             ops.push(PathOp::Close());
         }
     }
 
+    /// Stroke a dot for a sub-figure with only one point.
+    ///
+    /// * `vid` Vertex ID of point.
+    fn stroke_dot(&self, ops: &mut Vec<PathOp>, vid: Vid) {
+        let p = self.point(vid);
+        let hw = p.w() / 2.0;
+        if hw <= 0.0 {
+            return;
+        }
+        let pt = p.0;
+        match self.cap_style {
+            CapStyle::Butt => return,
+            CapStyle::Round => {
+                let a = pt + Pt::new(hw, 0.0);
+                let b = pt - Pt::new(hw, 0.0);
+                self.stroke_point(ops, a);
+                self.stroke_arc(ops, p, a, b);
+                self.stroke_arc(ops, p, b, a);
+            }
+            CapStyle::Square => {
+                self.stroke_point(ops, pt + Pt::new(hw, hw));
+                self.stroke_point(ops, pt + Pt::new(-hw, hw));
+                self.stroke_point(ops, pt + Pt::new(-hw, -hw));
+                self.stroke_point(ops, pt + Pt::new(hw, -hw));
+            }
+        }
+        ops.push(PathOp::Close());
+    }
+
+    /// Add a cap to the end of one side of an open sub-figure.
+    ///
+    /// * `vid` Vertex ID of end point.
+    /// * `dir` Direction of side ending at `vid`.
+    fn stroke_cap(&self, ops: &mut Vec<PathOp>, vid: Vid, dir: Dir) {
+        let p = self.point(vid);
+        let v0 = self.next(vid, dir.opposite());
+        let a = self.seg_offset(v0, vid, dir);
+        let b = self.seg_offset(vid, v0, dir.opposite());
+        match self.cap_style {
+            CapStyle::Butt => (),
+            CapStyle::Round => self.stroke_arc(ops, p, a.p1, b.p0),
+            CapStyle::Square => {
+                // Half width, in direction of end tangent
+                let v = (a.p1 - b.p0).left() / 2.0;
+                self.stroke_point(ops, a.p1 + v);
+                self.stroke_point(ops, b.p0 + v);
+            }
+        }
+    }
+
     /// Stroke one side of a sub-figure to another figure.
     fn stroke_side(
         &self,
@@ -269,99 +1008,225 @@ impl Stroke {
         start: Vid,
         dir: Dir,
     ) {
-        let mut xr: Option<(Pt<f32>, Pt<f32>)> = None;
+        let mut xr: Option<Offset> = None;
         let mut v0 = start;
         let mut v1 = self.next(v0, dir);
         let joined = self.sub_joined(i);
         for _ in 0..usize::from(self.sub_points(i)) {
             let p0 = self.point(v0);
-            let p1 = self.point(v1);
-            let bounds = self.stroke_offset(p0, p1);
-            let (pr0, pr1) = bounds;
-            if let Some((xr0, xr1)) = xr {
-                self.stroke_join(ops, p0, xr0, xr1, pr0, pr1);
+            let off = self.seg_offset(v0, v1, dir);
+            if let Some(x) = xr {
+                self.stroke_join(ops, p0, &x, &off);
             } else if !joined {
-                self.stroke_point(ops, pr0);
+                self.stroke_point(ops, off.p0);
+            }
+            // First segment of joined sub-figure is completed at the end
+            if xr.is_some() || !joined {
+                if let Some(cub) = self.segment(v0, v1, dir) {
+                    self.offset_cubic(ops, cub, 0);
+                }
             }
-            xr = Some(bounds);
+            xr = Some(off);
             v0 = v1;
             v1 = self.next(v1, dir);
         }
         if !joined {
-            if let Some((_, xr1)) = xr {
-                self.stroke_point(ops, xr1);
+            if let Some(x) = xr {
+                self.stroke_point(ops, x.p1);
+            }
+        }
+    }
+
+    /// Get the cubic bézier spline of a segment.
+    ///
+    /// * `v0` Start vertex.
+    /// * `v1` End vertex.
+    /// * `dir` Direction of segment.
+    ///
+    /// Returns None for straight segments.
+    fn segment(&self, v0: Vid, v1: Vid, dir: Dir) -> Option<[WidePt; 4]> {
+        match dir {
+            Dir::Forward if v1 == v0 + 1 => {
+                let (b, c) = self.ctrl[usize::from(v1)]?;
+                Some([self.point(v0), b, c, self.point(v1)])
+            }
+            Dir::Reverse if v0 == v1 + 1 => {
+                let (b, c) = self.ctrl[usize::from(v0)]?;
+                Some([self.point(v0), c, b, self.point(v1)])
             }
+            _ => None,
         }
     }
 
-    /// Offset segment by half stroke width.
+    /// Offset a segment by half stroke width.
     ///
-    /// * `p0` First point.
-    /// * `p1` Second point.
-    fn stroke_offset(&self, p0: WidePt, p1: WidePt) -> (Pt<f32>, Pt<f32>) {
-        // FIXME: scale offset to allow user units as well as pixel units
-        let pp0 = p0.0;
-        let pp1 = p1.0;
-        let vr = (pp1 - pp0).right().normalize();
-        let pr0 = pp0 + vr * (p0.w() / 2.0);
-        let pr1 = pp1 + vr * (p1.w() / 2.0);
-        (pr0, pr1)
+    /// * `v0` Start vertex.
+    /// * `v1` End vertex.
+    /// * `dir` Direction of segment.
+    fn seg_offset(&self, v0: Vid, v1: Vid, dir: Dir) -> Offset {
+        let p0 = self.point(v0);
+        let p1 = self.point(v1);
+        match self.segment(v0, v1, dir) {
+            Some(cub) => {
+                let c0 = cubic_dir(&cub, 0.0);
+                let c1 = cubic_dir(&cub, 1.0);
+                let d0 = cubic_offset_deriv(&cub, 0.0).normalize();
+                let d1 = cubic_offset_deriv(&cub, 1.0).normalize();
+                Offset {
+                    p0: p0.0 + c0.right() * (p0.w() / 2.0),
+                    d0: if d0.x().is_nan() { c0 } else { d0 },
+                    p1: p1.0 + c1.right() * (p1.w() / 2.0),
+                    d1: if d1.x().is_nan() { c1 } else { d1 },
+                }
+            }
+            None => {
+                let c = (p1.0 - p0.0).normalize();
+                let pr0 = p0.0 + c.right() * (p0.w() / 2.0);
+                let pr1 = p1.0 + c.right() * (p1.w() / 2.0);
+                // Edge direction differs from centerline if width changes
+                let d = (pr1 - pr0).normalize();
+                Offset {
+                    p0: pr0,
+                    d0: d,
+                    p1: pr1,
+                    d1: d,
+                }
+            }
+        }
+    }
+
+    /// Add an offset cubic bézier spline.
+    ///
+    /// The offset is approximated by matching end points and tangents,
+    /// subdividing until within tolerance.
+    ///
+    /// * `cub` Spline points (with stroke width).
+    /// * `depth` Subdivision depth.
+    fn offset_cubic(
+        &self,
+        ops: &mut Vec<PathOp>,
+        cub: [WidePt; 4],
+        depth: u32,
+    ) {
+        let [a, b, c, d] = cub;
+        let q0 = cubic_offset(&cub, 0.0).unwrap_or(a.0);
+        let q3 = cubic_offset(&cub, 1.0).unwrap_or(d.0);
+        let q1 = q0 + cubic_offset_deriv(&cub, 0.0) / 3.0;
+        let q2 = q3 - cubic_offset_deriv(&cub, 1.0) / 3.0;
+        if depth < MAX_OFFSET_DEPTH {
+            let within = [0.25, 0.5, 0.75].iter().all(|t| {
+                match cubic_offset(&cub, *t) {
+                    Some(pt) => {
+                        let q = cubic_point([q0, q1, q2, q3], *t);
+                        self.is_within_tolerance2(pt, q)
+                    }
+                    None => true,
+                }
+            });
+            if !within {
+                let ab = a.midpoint(b);
+                let bc = b.midpoint(c);
+                let cd = c.midpoint(d);
+                let ab_bc = ab.midpoint(bc);
+                let bc_cd = bc.midpoint(cd);
+                let e = ab_bc.midpoint(bc_cd);
+                self.offset_cubic(ops, [a, ab, ab_bc, e], depth + 1);
+                self.offset_cubic(ops, [e, bc_cd, cd, d], depth + 1);
+                return;
+            }
+        }
+        ops.push(PathOp::Cubic(q1, q2, q3));
     }
 
     /// Add a point to stroke figure.
+    ///
+    /// The first point of each closed outline is a move.
     fn stroke_point(&self, ops: &mut Vec<PathOp>, pt: Pt<f32>) {
-        ops.push(PathOp::Line(pt));
+        match ops.last() {
+            None | Some(PathOp::Close()) => ops.push(PathOp::Move(pt)),
+            Some(op) if op_end(op) == Some(pt) => (),
+            _ => ops.push(PathOp::Line(pt)),
+        }
     }
 
     /// Add a stroke join.
     ///
     /// * `p` Join point (with stroke width).
-    /// * `a0` First point of A segment.
-    /// * `a1` Second point of A segment.
-    /// * `b0` First point of B segment.
-    /// * `b1` Second point of B segment.
+    /// * `a` Offset of A segment.
+    /// * `b` Offset of B segment.
     fn stroke_join(
         &self,
         ops: &mut Vec<PathOp>,
         p: WidePt,
-        a0: Pt<f32>,
-        a1: Pt<f32>,
-        b0: Pt<f32>,
-        b1: Pt<f32>,
+        a: &Offset,
+        b: &Offset,
     ) {
+        let (a0, a1) = (a.p1 - a.d1, a.p1);
+        let (b0, b1) = (b.p0, b.p0 + b.d0);
         match self.join_style {
-            JoinStyle::Miter(ml) => self.stroke_miter(ops, a0, a1, b0, b1, ml),
+            JoinStyle::Miter(ml) => self.stroke_miter(ops, p, a, b, ml),
             JoinStyle::Bevel => self.stroke_bevel(ops, a1, b0),
             JoinStyle::Round => self.stroke_round(ops, p, a0, a1, b0, b1),
         }
     }
 
     /// Add a miter join.
+    ///
+    /// The miter point is the intersection of the offset edges, which may
+    /// not be parallel to the segments if the stroke width changes.  Miters
+    /// longer than the limit are clipped at the limit, so that the result
+    /// changes continuously with the join angle.
+    ///
+    /// * `p` Join point (with stroke width).
+    /// * `a` Offset of A segment.
+    /// * `b` Offset of B segment.
+    /// * `ml` Miter limit.
     fn stroke_miter(
         &self,
         ops: &mut Vec<PathOp>,
-        a0: Pt<f32>,
-        a1: Pt<f32>,
-        b0: Pt<f32>,
-        b1: Pt<f32>,
+        p: WidePt,
+        a: &Offset,
+        b: &Offset,
         ml: f32,
     ) {
-        // formula: miter_length / stroke_width = 1 / sin ( theta / 2 )
-        //      so: stroke_width / miter_length = sin ( theta / 2 )
+        let (a1, da) = (a.p1, a.d1);
+        let (b0, db) = (b.p0, b.d0);
+        // Cosine of angle between edges (1 for straight segments)
+        let cs = da.dot(db);
+        if cs.is_nan() || 1.0 - cs <= STRAIGHT_EPSILON {
+            self.stroke_point(ops, a1.midpoint(b0));
+            return;
+        }
         if ml > 0.0 {
-            // Minimum stroke:miter ratio
-            let sm_min = 1.0 / ml;
-            let th = (a1 - a0).angle_rel(b0 - b1);
-            let sm = (th / 2.0).sin().abs();
-            if sm >= sm_min && sm < 1.0 {
-                let lna = Line::new(a0, a1);
-                let lnb = Line::new(b0, b1);
-                // Calculate miter point
-                if let Some(xp) = lna.intersection(lnb) {
+            // formula: miter_length / stroke_width = 1 / sin ( theta / 2 )
+            //   where: miter_length is from inner to outer corner
+            let lim = ml * p.w() / 2.0;
+            // Direction from join point toward outer corner
+            let u = (da - db).normalize();
+            let outer = da.right().dot(db) < 0.0;
+            let cr = cross(da, db);
+            if cr != 0.0 {
+                let xp = a1 + da * (cross(b0 - a1, db) / cr);
+                let within = if outer {
+                    (xp - p.0).dot(u) <= lim
+                } else {
+                    xp.dist(p.0) <= lim
+                };
+                if within {
                     self.stroke_point(ops, xp);
                     return;
                 }
             }
+            // Only clip on the outside of the join
+            if outer {
+                let sa = (lim - (a1 - p.0).dot(u)) / da.dot(u);
+                let sb = (lim - (b0 - p.0).dot(u)) / -db.dot(u);
+                if sa > 0.0 && sb > 0.0 {
+                    self.stroke_point(ops, a1 + da * sa);
+                    self.stroke_point(ops, b0 - db * sb);
+                    return;
+                }
+            }
         }
         self.stroke_bevel(ops, a1, b0);
     }
@@ -396,6 +1261,9 @@ impl Stroke {
     }
 
     /// Add a stroke arc.
+    ///
+    /// Subdivision is iterative, with an explicit stack, so deep subdivision
+    /// cannot overflow the call stack.
     fn stroke_arc(
         &self,
         ops: &mut Vec<PathOp>,
@@ -403,15 +1271,30 @@ impl Stroke {
         a: Pt<f32>,
         b: Pt<f32>,
     ) {
-        let p2 = p.0;
-        let vr = (b - a).right().normalize();
-        let c = p2 + vr * (p.w() / 2.0);
-        let ab = a.midpoint(b);
-        if self.is_within_tolerance2(c, ab) {
+        if p.w() <= 0.0 {
             self.stroke_point(ops, b);
-        } else {
-            self.stroke_arc(ops, p, a, c);
-            self.stroke_arc(ops, p, c, b);
+            return;
+        }
+        let mut stack = [(a, b, 0); MAX_ARC_DEPTH as usize + 1];
+        let mut n = 1;
+        while n > 0 {
+            n -= 1;
+            let (a, b, depth) = stack[n];
+            if a == b || depth >= MAX_ARC_DEPTH {
+                self.stroke_point(ops, b);
+                continue;
+            }
+            let vr = (b - a).right().normalize();
+            let c = p.0 + vr * (p.w() / 2.0);
+            let ab = a.midpoint(b);
+            if self.is_within_tolerance2(c, ab) {
+                self.stroke_point(ops, b);
+            } else {
+                // Second half is pushed first, to be popped last
+                stack[n] = (c, b, depth + 1);
+                stack[n + 1] = (a, c, depth + 1);
+                n += 2;
+            }
         }
     }
 }
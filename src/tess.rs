@@ -0,0 +1,402 @@
+// tess.rs      Tessellation of paths into triangles.
+//
+// Copyright (c) 2026  Douglas P Lau
+//
+//! Tessellation of paths into triangles, for GPU rendering.
+use crate::geom::{flatten_cubic, flatten_quad, tol_sq, WidePt};
+use crate::path::{FillRule, PathOp};
+use pointy::Pt;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+
+/// Edge of a flattened path, from upper to lower point
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    /// Upper point X
+    x0: f64,
+    /// Upper point Y
+    y0: f64,
+    /// Lower point X
+    x1: f64,
+    /// Lower point Y
+    y1: f64,
+    /// Winding direction (1 if path goes down, -1 if up)
+    winding: i32,
+}
+
+/// Triangle list builder, with shared vertices
+#[derive(Default)]
+struct Triangles {
+    /// Vertex positions
+    vertices: Vec<[f32; 2]>,
+    /// Index of each vertex position
+    lookup: HashMap<(u32, u32), u32>,
+    /// Vertex indices, 3 per triangle
+    indices: Vec<u32>,
+}
+
+/// Flattened path contours
+struct Contours {
+    /// Squared tolerance
+    tol_sq: f32,
+    /// Pen position
+    pen: Pt<f32>,
+    /// Start of current contour
+    start: Option<Pt<f32>>,
+    /// All edges
+    edges: Vec<Edge>,
+}
+
+impl Edge {
+    /// Create a new edge, unless it is horizontal
+    fn new(p0: Pt<f32>, p1: Pt<f32>) -> Option<Self> {
+        let (x0, y0) = (f64::from(p0.x()), f64::from(p0.y()));
+        let (x1, y1) = (f64::from(p1.x()), f64::from(p1.y()));
+        if y0 < y1 {
+            Some(Edge {
+                x0,
+                y0,
+                x1,
+                y1,
+                winding: 1,
+            })
+        } else if y0 > y1 {
+            Some(Edge {
+                x0: x1,
+                y0: y1,
+                x1: x0,
+                y1: y0,
+                winding: -1,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Get X at a Y value
+    fn x_at(&self, y: f64) -> f64 {
+        self.x0 + (self.x1 - self.x0) * (y - self.y0) / (self.y1 - self.y0)
+    }
+}
+
+impl Triangles {
+    /// Get index of a vertex, adding it if needed
+    fn vertex(&mut self, x: f64, y: f64) -> u32 {
+        let v = [x as f32, y as f32];
+        let key = (v[0].to_bits(), v[1].to_bits());
+        let vertices = &mut self.vertices;
+        *self.lookup.entry(key).or_insert_with(|| {
+            vertices.push(v);
+            (vertices.len() - 1) as u32
+        })
+    }
+
+    /// Add a triangle, unless it is degenerate
+    fn add_triangle(&mut self, a: u32, b: u32, c: u32) {
+        if a != b && b != c && c != a {
+            self.indices.extend([a, b, c]);
+        }
+    }
+
+    /// Add a trapezoid between two edges, within a slab of rows
+    fn add_trapezoid(&mut self, left: &Edge, right: &Edge, ya: f64, yb: f64) {
+        let tl = self.vertex(left.x_at(ya), ya);
+        let tr = self.vertex(right.x_at(ya), ya);
+        let br = self.vertex(right.x_at(yb), yb);
+        let bl = self.vertex(left.x_at(yb), yb);
+        self.add_triangle(tl, tr, br);
+        self.add_triangle(tl, br, bl);
+    }
+}
+
+impl Contours {
+    /// Create new contours
+    fn new(tolerance: f32) -> Self {
+        Contours {
+            tol_sq: tol_sq(tolerance),
+            pen: Pt::default(),
+            start: None,
+            edges: vec![],
+        }
+    }
+
+    /// Add a path operation
+    fn add_op(&mut self, op: &PathOp) {
+        match *op {
+            PathOp::Close() => {
+                self.close();
+                self.pen = Pt::default();
+            }
+            PathOp::Move(pb) => {
+                self.close();
+                self.start = Some(pb);
+                self.pen = pb;
+            }
+            PathOp::Line(pb) => self.line_to(pb),
+            PathOp::Quad(pb, pc) => {
+                let (a, b, c) = (wide(self.pen), wide(pb), wide(pc));
+                let mut pts = vec![];
+                flatten_quad(a, b, c, self.tol_sq, |p| pts.push(p.0));
+                pts.into_iter().for_each(|p| self.line_to(p));
+            }
+            PathOp::Cubic(pb, pc, pd) => {
+                let (a, b) = (wide(self.pen), wide(pb));
+                let (c, d) = (wide(pc), wide(pd));
+                let mut pts = vec![];
+                flatten_cubic(a, b, c, d, self.tol_sq, |p| pts.push(p.0));
+                pts.into_iter().for_each(|p| self.line_to(p));
+            }
+            PathOp::PenWidth(_) | PathOp::PenAttr(_) => (),
+        }
+    }
+
+    /// Add a line from the pen to a point
+    fn line_to(&mut self, pb: Pt<f32>) {
+        match self.start {
+            Some(_) => self.edges.extend(Edge::new(self.pen, pb)),
+            None => self.start = Some(pb),
+        }
+        self.pen = pb;
+    }
+
+    /// Close the current contour
+    fn close(&mut self) {
+        if let Some(start) = self.start.take() {
+            self.edges.extend(Edge::new(self.pen, start));
+        }
+    }
+}
+
+/// Make a wide point for curve flattening
+fn wide(pt: Pt<f32>) -> WidePt {
+    WidePt(pt, 1.0, 0.0)
+}
+
+/// Check if a winding number is inside, with a fill rule
+fn is_inside(rule: FillRule, winding: i32) -> bool {
+    match rule {
+        FillRule::NonZero => winding != 0,
+        FillRule::EvenOdd => winding % 2 != 0,
+    }
+}
+
+/// Tessellate a filled path into triangles.
+///
+/// The path is flattened, with no transform, and every sub-path is treated
+/// as closed, the same as [Plotter.fill].  The interior is swept from top to
+/// bottom, and split into trapezoids wherever a vertex or a crossing of two
+/// edges starts a new slab.  Each trapezoid becomes one or two triangles.
+///
+/// All triangles have the same winding: clockwise on screen, with Y down.
+/// Vertices are shared between triangles where they are equal, so the
+/// result can be drawn as an indexed triangle list.  There is no
+/// anti-aliasing.
+///
+/// * `ops` PathOp iterator.
+/// * `rule` Fill rule.
+/// * `tolerance` Maximum distance of flattened curves, in path units.
+///
+/// Returns vertex positions and indices (3 per triangle).
+///
+/// ```rust
+/// use footile::{tess, FillRule, Path2D};
+///
+/// let square = Path2D::default()
+///     .absolute()
+///     .move_to(0.0, 0.0)
+///     .line_to(4.0, 0.0)
+///     .line_to(4.0, 4.0)
+///     .line_to(0.0, 4.0)
+///     .close()
+///     .finish();
+/// let (vertices, indices) =
+///     tess::fill_triangles(&square, FillRule::NonZero, 0.25);
+/// assert_eq!(vertices.len(), 4);
+/// assert_eq!(indices.len(), 6);
+/// ```
+///
+/// [Plotter.fill]: ../struct.Plotter.html#method.fill
+pub fn fill_triangles<T>(
+    ops: T,
+    rule: FillRule,
+    tolerance: f32,
+) -> (Vec<[f32; 2]>, Vec<u32>)
+where
+    T: IntoIterator,
+    T::Item: Borrow<PathOp>,
+{
+    let mut contours = Contours::new(tolerance);
+    for op in ops {
+        contours.add_op(op.borrow());
+    }
+    contours.close();
+    let mut edges = contours.edges;
+    edges.retain(|e| [e.x0, e.y0, e.x1, e.y1].iter().all(|v| v.is_finite()));
+    edges.sort_by(|a, b| a.y0.total_cmp(&b.y0));
+    let mut ys: Vec<f64> = edges.iter().flat_map(|e| [e.y0, e.y1]).collect();
+    ys.sort_by(f64::total_cmp);
+    ys.dedup();
+    let mut tris = Triangles::default();
+    let mut active: Vec<Edge> = vec![];
+    let mut next = 0;
+    for slab in ys.windows(2) {
+        let (ya, yb) = (slab[0], slab[1]);
+        active.retain(|e| e.y1 > ya);
+        while next < edges.len() && edges[next].y0 <= ya {
+            active.push(edges[next]);
+            next += 1;
+        }
+        for (ya, yb) in split_crossings(&mut active, ya, yb) {
+            add_slab(&mut tris, &mut active, rule, ya, yb);
+        }
+    }
+    (tris.vertices, tris.indices)
+}
+
+/// Split a slab at Y values where active edges cross.
+///
+/// Active edges are sorted by X at the top of the slab.
+fn split_crossings(active: &mut [Edge], ya: f64, yb: f64) -> Vec<(f64, f64)> {
+    active.sort_by(|a, b| {
+        a.x_at(ya)
+            .total_cmp(&b.x_at(ya))
+            .then(a.x_at(yb).total_cmp(&b.x_at(yb)))
+    });
+    let mut ys = vec![ya, yb];
+    let sorted = active.windows(2).all(|w| w[0].x_at(yb) <= w[1].x_at(yb));
+    if !sorted {
+        for (i, a) in active.iter().enumerate() {
+            for b in &active[i + 1..] {
+                let dt = b.x_at(ya) - a.x_at(ya);
+                let db = b.x_at(yb) - a.x_at(yb);
+                if dt > 0.0 && db < 0.0 {
+                    let y = ya + (yb - ya) * dt / (dt - db);
+                    if y > ya && y < yb {
+                        ys.push(y);
+                    }
+                }
+            }
+        }
+        ys.sort_by(f64::total_cmp);
+        ys.dedup();
+    }
+    ys.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+/// Add trapezoids of the interior within a slab.
+///
+/// No edges cross within the slab, so they are sorted by X at the middle.
+fn add_slab(
+    tris: &mut Triangles,
+    active: &mut [Edge],
+    rule: FillRule,
+    ya: f64,
+    yb: f64,
+) {
+    let ym = (ya + yb) / 2.0;
+    active.sort_by(|a, b| a.x_at(ym).total_cmp(&b.x_at(ym)));
+    let mut winding = 0;
+    for pair in active.windows(2) {
+        winding += pair[0].winding;
+        if is_inside(rule, winding) {
+            tris.add_trapezoid(&pair[0], &pair[1], ya, yb);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Fig, Paint, Path2D, Plotter};
+    use pix::el::Pixel;
+    use pix::matte::Matte8;
+    use pix::Raster;
+
+    /// Fill triangles to a matte raster
+    fn fill_tris(vertices: &[[f32; 2]], indices: &[u32]) -> Raster<Matte8> {
+        let mut m = Raster::with_clear(128, 128);
+        let mut s = vec![0i16; 128];
+        let mut f = Fig::new();
+        for tri in indices.chunks(3) {
+            for i in tri {
+                let [x, y] = vertices[*i as usize];
+                f.add_point((x, y));
+            }
+            f.close();
+        }
+        let paint = Paint::new(Matte8::new(255));
+        f.fill(FillRule::NonZero, &mut m, paint, &mut s);
+        m
+    }
+
+    /// Fish path, with an eye wound like the body and a crossing tail
+    fn fish() -> Vec<PathOp> {
+        Path2D::default()
+            .relative()
+            .move_to(112.0, 24.0)
+            .line_to(-32.0, 24.0)
+            .cubic_to(-96.0, -48.0, -96.0, 80.0, 0.0, 32.0)
+            .line_to(32.0, 24.0)
+            .line_to(-16.0, -40.0)
+            .close()
+            .move_to(44.0, 46.0)
+            .quad_to(4.0, 6.0, 8.0, 0.0)
+            .quad_to(-4.0, -6.0, -8.0, 0.0)
+            .close()
+            .absolute()
+            .move_to(100.0, 70.0)
+            .line_to(124.0, 100.0)
+            .line_to(124.0, 70.0)
+            .line_to(100.0, 100.0)
+            .finish()
+    }
+
+    #[test]
+    fn tess_square() {
+        let square = Path2D::default()
+            .absolute()
+            .move_to(1.0, 1.0)
+            .line_to(5.0, 1.0)
+            .line_to(5.0, 3.0)
+            .line_to(1.0, 3.0)
+            .finish();
+        let (v, i) = fill_triangles(&square, FillRule::NonZero, 0.25);
+        assert_eq!(v.len(), 4);
+        assert_eq!(i.len(), 6);
+        for tri in i.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|j| v[tri[j] as usize]);
+            let cross =
+                (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]);
+            assert!(cross > 0.0, "{v:?} {i:?}");
+        }
+        let (_v, i) =
+            fill_triangles(Vec::<PathOp>::new(), FillRule::NonZero, 0.25);
+        assert!(i.is_empty());
+    }
+
+    #[test]
+    fn tess_fish() {
+        for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let (v, i) = fill_triangles(fish(), rule, 0.25);
+            let tris = fill_tris(&v, &i);
+
+            let mut p = Plotter::new(Raster::with_clear(128, 128));
+            p.set_tolerance(0.25);
+            let r = p.fill(rule, fish(), Matte8::new(255));
+            // Signed area cancels where opposite windings share a pixel,
+            // so the plotter is dimmer where the tail crosses the body
+            let mut n_diff = 0;
+            for (a, b) in tris.pixels().iter().zip(r.pixels()) {
+                let (a, b) = (u8::from(a.one()), u8::from(b.one()));
+                if a.abs_diff(b) > 2 {
+                    assert!(a > b, "{rule:?}: {a} {b}");
+                    n_diff += 1;
+                }
+            }
+            assert!(n_diff <= 4, "{rule:?}: {n_diff}");
+            // Eye is wound like the body, so it is a hole only with even-odd
+            let eye = u8::from(tris.pixel(47, 45).one());
+            assert_eq!(eye == 0, rule == FillRule::EvenOdd, "{rule:?}");
+        }
+    }
+}
@@ -6,26 +6,46 @@ use std::convert::TryFrom;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 /// Vertex ID
-#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
-pub struct Vid(pub u16);
+///
+/// Adding or subtracting panics if the result is outside of `Vid::MIN` to
+/// `Vid::MAX`, since no figure or stroke can have vertices beyond that
+/// limit.  Use `checked_add` or `checked_sub` where that is not a bug.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vid(pub u32);
 
 impl Vid {
     /// Minimum vertex ID
-    pub const MIN: Self = Vid(u16::MIN);
+    pub const MIN: Self = Vid(u32::MIN);
 
-    /// Maximum vertex ID
-    pub const MAX: Self = Vid(u16::MAX);
+    /// Maximum vertex ID.
+    ///
+    /// This limits figures and strokes to about 16 million points, which
+    /// keeps memory use bounded for runaway paths.
+    pub const MAX: Self = Vid(1 << 24);
+
+    /// Add to a vertex ID, or `None` if the result is over `Vid::MAX`.
+    pub fn checked_add<R: Into<Vid>>(self, rhs: R) -> Option<Self> {
+        match self.0.checked_add(rhs.into().0) {
+            Some(v) if v <= Self::MAX.0 => Some(Vid(v)),
+            _ => None,
+        }
+    }
+
+    /// Subtract from a vertex ID, or `None` if the result is below zero.
+    pub fn checked_sub<R: Into<Vid>>(self, rhs: R) -> Option<Self> {
+        self.0.checked_sub(rhs.into().0).map(Vid)
+    }
 }
 
 impl From<usize> for Vid {
     fn from(v: usize) -> Self {
-        Vid(u16::try_from(v).expect("Invalid vertex ID"))
+        Vid(u32::try_from(v).expect("Invalid vertex ID"))
     }
 }
 
 impl From<Vid> for usize {
     fn from(v: Vid) -> Self {
-        usize::from(v.0)
+        v.0 as usize
     }
 }
 
@@ -36,7 +56,7 @@ where
     type Output = Self;
 
     fn add(self, rhs: R) -> Self {
-        Vid(self.0 + rhs.into().0)
+        self.checked_add(rhs).expect("Vertex ID over Vid::MAX")
     }
 }
 
@@ -45,7 +65,7 @@ where
     R: Into<Vid>,
 {
     fn add_assign(&mut self, rhs: R) {
-        self.0 = self.0 + rhs.into().0;
+        *self = *self + rhs;
     }
 }
 
@@ -56,7 +76,7 @@ where
     type Output = Self;
 
     fn sub(self, rhs: R) -> Self {
-        Vid(self.0 - rhs.into().0)
+        self.checked_sub(rhs).expect("Vertex ID below zero")
     }
 }
 
@@ -65,6 +85,66 @@ where
     R: Into<Vid>,
 {
     fn sub_assign(&mut self, rhs: R) {
-        self.0 = self.0 - rhs.into().0;
+        *self = *self - rhs;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::{BTreeSet, HashSet};
+
+    #[test]
+    fn vid_add() {
+        assert_eq!(Vid(3) + 2, Vid(5));
+        assert_eq!(Vid(0) + Vid::MAX, Vid::MAX);
+        let mut v = Vid::MAX - 1;
+        v += 1;
+        assert_eq!(v, Vid::MAX);
+        assert_eq!(Vid::MAX.checked_add(0), Some(Vid::MAX));
+        assert_eq!(Vid::MAX.checked_add(1), None);
+        assert_eq!(Vid(1).checked_add(Vid(u32::MAX)), None);
+    }
+
+    #[test]
+    fn vid_sub() {
+        assert_eq!(Vid(5) - 2, Vid(3));
+        let mut v = Vid(1);
+        v -= 1;
+        assert_eq!(v, Vid::MIN);
+        assert_eq!(Vid::MAX.checked_sub(Vid::MAX), Some(Vid(0)));
+        assert_eq!(Vid(0).checked_sub(1), None);
+        assert_eq!(Vid(2).checked_sub(3), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Vertex ID over Vid::MAX")]
+    fn vid_add_over() {
+        let _ = Vid::MAX + 1;
+    }
+
+    #[test]
+    #[should_panic(expected = "Vertex ID below zero")]
+    fn vid_sub_under() {
+        let mut v = Vid(0);
+        v -= 1;
+    }
+
+    #[test]
+    fn vid_ord() {
+        let vals = [0, 1, 2, 255, 256, 65_535, 65_536, 1 << 24];
+        for a in vals {
+            for b in vals {
+                let (va, vb) = (Vid::from(a), Vid::from(b));
+                assert_eq!(va.cmp(&vb), a.cmp(&b));
+                assert_eq!(usize::from(va).cmp(&usize::from(vb)), a.cmp(&b));
+            }
+        }
+        let set: BTreeSet<Vid> =
+            vals.iter().rev().map(|v| Vid::from(*v)).collect();
+        let sorted: Vec<usize> = set.into_iter().map(usize::from).collect();
+        assert_eq!(sorted, vals);
+        let set: HashSet<Vid> = [Vid(7), Vid::from(7), Vid(8)].into();
+        assert_eq!(set.len(), 2);
     }
 }
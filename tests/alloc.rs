@@ -0,0 +1,62 @@
+// alloc.rs      Allocation counting tests.
+use footile::{FillRule, Path2D, Plotter};
+use pix::rgb::Rgba8p;
+use pix::Raster;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+/// Allocator which counts allocations on the current thread
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCS.try_with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOC: CountingAlloc = CountingAlloc;
+
+/// Get the number of allocations on the current thread
+fn allocs() -> usize {
+    ALLOCS.with(|a| a.get())
+}
+
+#[test]
+fn scratch_reuse() {
+    let tri = Path2D::default()
+        .absolute()
+        .move_to(4.0, 2.0)
+        .line_to(14.0, 30.0)
+        .quad_to(20.0, 4.0, 28.0, 3.0)
+        .close()
+        .finish();
+    let line = Path2D::default()
+        .absolute()
+        .pen_width(2.5)
+        .move_to(2.0, 20.0)
+        .line_to(16.0, 8.0)
+        .line_to(30.0, 28.0)
+        .finish();
+    let clr = Rgba8p::new(40, 20, 0, 64);
+    let mut p = Plotter::new(Raster::with_clear(32, 32));
+    p.fill(FillRule::NonZero, &tri, clr);
+    p.stroke(&line, clr);
+    let before = allocs();
+    std::hint::black_box(vec![0u8; 16]);
+    assert_eq!(allocs() - before, 1);
+    let before = allocs();
+    for _ in 0..1000 {
+        p.fill(FillRule::NonZero, &tri, clr);
+        p.stroke(&line, clr);
+    }
+    assert_eq!(allocs() - before, 0);
+}